@@ -92,6 +92,7 @@ fn setup_fluid(
                 rho: 99.7, // water density in 2D
                 gravity: Vec2::Y * 9.8,
                 size: SIZE,
+                ..default()
             },
             ProjectionMethod::GaussSeidel(GaussSeidelConfig {
                 num_iterations: 100,
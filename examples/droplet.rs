@@ -62,6 +62,9 @@ fn setup_fluid(
                 rho: 99.7, // water density in 2D
                 gravity: Vec2::Y * 9.8,
                 size: SIZE,
+                // exaggerated to make the droplets bead up at this scale
+                surface_tension: 20.0,
+                ..default()
             },
             ProjectionMethod::MultiGrid(MultiGridConfig::default()),
             Mesh2d(mesh),
@@ -61,6 +61,7 @@ fn setup_scene(
                 rho: 99.7, // water density in 2D
                 gravity: Vec2::ZERO,
                 size: SIZE,
+                ..default()
             },
             ProjectionMethod::MultiGrid(MultiGridConfig::default()),
             Mesh2d(meshes.add(Rectangle::from_size(SIZE.as_vec2()))),
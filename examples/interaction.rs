@@ -46,6 +46,7 @@ fn setup_scene(mut commands: Commands) {
                 rho: 99.7, // water density in 2D
                 gravity: Vec2::ZERO,
                 size: SIZE,
+                ..default()
             },
             Transform::default().with_scale(SIZE.as_vec2().extend(1.0)),
         ))
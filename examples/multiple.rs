@@ -53,6 +53,7 @@ fn setup_scene(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
                         rho: 99.7, // water density in 2D
                         gravity: Vec2::ZERO,
                         size: UVec2::splat(size),
+                        ..default()
                     },
                     Transform::default().with_translation(translation),
                     Mesh2d(mesh),
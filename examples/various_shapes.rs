@@ -86,6 +86,7 @@ fn setup_fluid(
                 rho: 99.7, // water density in 2D
                 gravity: Vec2::Y * 9.8,
                 size: SIZE,
+                ..default()
             },
             Mesh2d(meshes.add(fluid_domain_rectangle.clone())),
             Transform::default(),
@@ -57,6 +57,7 @@ fn setup_fluid(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
                 rho: 99.7, // water density in 2D
                 gravity: Vec2::Y * 9.8,
                 size: SIZE,
+                ..default()
             },
             Mesh2d(mesh),
             Transform::default()
@@ -0,0 +1,110 @@
+extern crate bevy_eulerian_fluid;
+
+use bevy::{camera::ScalingMode, color::palettes, prelude::*, time::common_conditions::on_timer};
+
+use bevy_eulerian_fluid::{
+    boundary_condition::{BoundaryCondition, FluidBoundaryConditions},
+    diagnostics::FluidDiagnosticsPlugin,
+    obstacle::{FluidObstacle, FluidObstacleForces},
+    projection::{multi_grid::MultiGridConfig, ProjectionMethod},
+    settings::{FluidMode, FluidSettings, FluidTextures},
+    FluidPlugin,
+};
+use example_utils::{
+    material::{ExampleMaterialsPlugin, VorticityMaterial},
+    ExampleDefaultPlugins,
+};
+use std::time::Duration;
+
+// Runs without avian2d: `cargo run --no-default-features --example wind_tunnel`
+const LENGTH_UNIT: f32 = 50.0;
+const SIZE: UVec2 = UVec2::new(512, 256);
+const CYLINDER_RADIUS: f32 = 12.0;
+
+fn main() {
+    let mut app = App::new();
+
+    app.add_plugins(ExampleDefaultPlugins)
+        .add_plugins(FluidPlugin::new(LENGTH_UNIT))
+        .add_plugins((FluidDiagnosticsPlugin, ExampleMaterialsPlugin))
+        .add_systems(Startup, setup_scene)
+        .add_systems(Update, (on_fluid_setup, oscillate_cylinder))
+        .add_systems(Update, print_drag.run_if(on_timer(Duration::from_secs(1))));
+
+    app.run();
+}
+
+fn setup_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.spawn((
+        Camera2d,
+        Projection::Orthographic(OrthographicProjection {
+            scaling_mode: ScalingMode::AutoMin {
+                min_width: 1.2 * SIZE.x as f32,
+                min_height: 1.2 * SIZE.y as f32,
+            },
+            ..OrthographicProjection::default_2d()
+        }),
+    ));
+
+    commands.spawn((
+        FluidSettings {
+            rho: 1.2, // air density in 2D
+            gravity: Vec2::ZERO,
+            size: SIZE,
+            mode: FluidMode::SinglePhase,
+            ..default()
+        },
+        FluidBoundaryConditions {
+            left: BoundaryCondition::Inflow(Vec2::new(200.0, 0.0)),
+            right: BoundaryCondition::Open,
+            top: BoundaryCondition::FreeSlip,
+            bottom: BoundaryCondition::FreeSlip,
+        },
+        ProjectionMethod::MultiGrid(MultiGridConfig::default()),
+        Mesh2d(meshes.add(Rectangle::from_size(SIZE.as_vec2()))),
+    ));
+
+    let circle = Circle::new(CYLINDER_RADIUS);
+    commands.spawn((
+        Mesh2d(meshes.add(circle)),
+        MeshMaterial2d(materials.add(Color::Srgba(palettes::css::LIGHT_CYAN))),
+        Transform::from_translation(Vec3::new(-0.25 * SIZE.x as f32, 0.0, 1.0)),
+        FluidObstacle::new(circle),
+    ));
+}
+
+/// Obstacles are moved by the application. Their velocities are given to the fluid along with the transform.
+fn oscillate_cylinder(time: Res<Time>, mut query: Query<(&mut Transform, &mut FluidObstacle)>) {
+    let omega = 2.0;
+    let amplitude = 2.0 * CYLINDER_RADIUS;
+    let t = time.elapsed_secs();
+    for (mut transform, mut obstacle) in &mut query {
+        transform.translation.y = amplitude * (omega * t).sin();
+        obstacle.linear_velocity = Vec2::new(0.0, amplitude * omega * (omega * t).cos());
+    }
+}
+
+fn print_drag(query: Query<&FluidObstacleForces>) {
+    for forces in &query {
+        info!("drag: {:.3}, lift: {:.3}", forces.force.x, forces.force.y);
+    }
+}
+
+fn on_fluid_setup(
+    mut commands: Commands,
+    query: Query<(Entity, &FluidTextures), Added<FluidTextures>>,
+    mut materials: ResMut<Assets<VorticityMaterial>>,
+) {
+    for (entity, fluid_texture) in &query {
+        let material = materials.add(VorticityMaterial {
+            u: fluid_texture.u.clone(),
+            v: fluid_texture.v.clone(),
+        });
+
+        commands.entity(entity).insert(MeshMaterial2d(material));
+    }
+}
@@ -7,4 +7,5 @@ pub mod plugin;
 pub mod render_node;
 pub mod ui;
 
+pub use debug_draw_particles::DebugDrawLevelsetParticlesPlugin;
 pub use plugin::FluidDiagnosticsPlugin;
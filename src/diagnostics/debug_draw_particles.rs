@@ -12,7 +12,9 @@ use crate::{
     settings::{FluidSettings, FluidTextures},
 };
 
-pub(crate) struct DebugDrawLevelsetParticlesPlugin;
+/// Draws the particles of the particle level set on top of the fluid.
+/// Only fluids spawned with [`crate::particle_levelset_two_layers::LevelSetCorrection::ParticleLevelSet`] are drawn.
+pub struct DebugDrawLevelsetParticlesPlugin;
 
 impl Plugin for DebugDrawLevelsetParticlesPlugin {
     fn build(&self, app: &mut App) {
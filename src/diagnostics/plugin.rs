@@ -3,7 +3,6 @@ use bevy::{
     prelude::*,
     render::{
         diagnostic::RenderDiagnosticsPlugin,
-        extract_component::ExtractComponentPlugin,
         gpu_readback::{Readback, ReadbackComplete},
         render_graph::RenderGraph,
         storage::ShaderStorageBuffer,
@@ -14,8 +13,7 @@ use bevy::{
 use crate::{
     diagnostics::{
         calculate_volume::{CalculateVolumePass, CalculateVolumeResource},
-        component::{FluidMaxVelocityMagnitude, FluidMinVelocityMagnitude, FluidVolume, GridSize},
-        max_velocity::{MaxVelocityPass, MaxVelocityResource},
+        component::{FluidMinVelocityMagnitude, FluidVolume, GridSize},
         min_velocity::{MinVelocityPass, MinVelocityResource},
         render_node::{DiagnosticsLabel, DiagnosticsNode},
         ui::{setup_diagnostics_ui, update_diagnostics_ui},
@@ -32,8 +30,6 @@ impl Plugin for FluidDiagnosticsPlugin {
         app.add_plugins((
             FluidComputePassPlugin::<CalculateVolumePass>::default(),
             FluidComputePassPlugin::<MinVelocityPass>::default(),
-            FluidComputePassPlugin::<MaxVelocityPass>::default(),
-            ExtractComponentPlugin::<GridSize>::default(),
             FrameTimeDiagnosticsPlugin::default(),
         ))
         .add_systems(Startup, setup_diagnostics_ui)
@@ -83,25 +79,10 @@ fn on_fluid_setup(
             .observe(min_velocity_readback)
             .id();
 
-        let max_velocity_resource =
-            MaxVelocityResource::new(&mut buffers, fluid_textures, settings.size);
-        let max_velocity_entity = commands
-            .spawn((
-                max_velocity_resource.clone(),
-                Readback::buffer(max_velocity_resource.sum.clone()),
-                GridSize(settings.size),
-            ))
-            .observe(max_velocity_readback)
-            .id();
-
         commands
             .entity(entity)
-            .insert((
-                FluidVolume(0.0),
-                FluidMinVelocityMagnitude(0.0),
-                FluidMaxVelocityMagnitude(0.0),
-            ))
-            .add_children(&[volume_entity, min_velocity_entity, max_velocity_entity]);
+            .insert((FluidVolume(0.0), FluidMinVelocityMagnitude(0.0)))
+            .add_children(&[volume_entity, min_velocity_entity]);
     }
 }
 
@@ -136,19 +117,3 @@ fn min_velocity_readback(
     };
     min_velocity.0 = trigger.event().to_shader_type();
 }
-
-fn max_velocity_readback(
-    trigger: On<ReadbackComplete>,
-    mut fluid_query: Query<&mut FluidMaxVelocityMagnitude, With<FluidSettings>>,
-    query: Query<&ChildOf, With<MaxVelocityResource>>,
-) {
-    let child = query.get(trigger.entity);
-    let Ok(child) = child else {
-        return;
-    };
-
-    let Ok(mut max_velocity) = fluid_query.get_mut(child.parent()) else {
-        return;
-    };
-    max_velocity.0 = trigger.event().to_shader_type();
-}
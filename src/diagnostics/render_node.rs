@@ -10,7 +10,6 @@ use bevy::{
 use crate::diagnostics::{
     calculate_volume::{CalculateVolumeBindGroup, CalculateVolumePipeline},
     component::GridSize,
-    max_velocity::{MaxVelocityBindGroup, MaxVelocityPipeline},
     min_velocity::{MinVelocityBindGroup, MinVelocityPipeline},
 };
 
@@ -34,17 +33,10 @@ struct MinVelocityQueryData {
     min_velocity_bind_group: &'static MinVelocityBindGroup,
 }
 
-#[derive(QueryData)]
-struct MaxVelocityQueryData {
-    grid_size: &'static GridSize,
-    max_velocity_bind_group: &'static MaxVelocityBindGroup,
-}
-
 pub(crate) struct DiagnosticsNode {
     state: State,
     query: QueryState<FluidVolumeQueryData>,
     q_min_velocity: QueryState<MinVelocityQueryData>,
-    q_max_velocity: QueryState<MaxVelocityQueryData>,
 }
 
 impl DiagnosticsNode {
@@ -53,7 +45,6 @@ impl DiagnosticsNode {
             state: State::Loading,
             query: world.query_filtered(),
             q_min_velocity: world.query_filtered(),
-            q_max_velocity: world.query_filtered(),
         }
     }
 }
@@ -62,16 +53,13 @@ impl render_graph::Node for DiagnosticsNode {
     fn update(&mut self, world: &mut World) {
         self.query.update_archetypes(world);
         self.q_min_velocity.update_archetypes(world);
-        self.q_max_velocity.update_archetypes(world);
         let pipeline_cache = world.resource::<PipelineCache>();
         match self.state {
             State::Loading => {
                 let volume_pipeline = world.resource::<CalculateVolumePipeline>();
                 let min_velocity_pipeline = world.resource::<MinVelocityPipeline>();
-                let max_velocity_pipeline = world.resource::<MaxVelocityPipeline>();
                 if volume_pipeline.is_ready(pipeline_cache)
                     && min_velocity_pipeline.is_ready(pipeline_cache)
-                    && max_velocity_pipeline.is_ready(pipeline_cache)
                 {
                     self.state = State::Update;
                 }
@@ -124,23 +112,6 @@ impl render_graph::Node for DiagnosticsNode {
                         grid_size,
                     );
                 }
-
-                for q in self.q_max_velocity.iter_manual(world) {
-                    let mut pass = render_context.command_encoder().begin_compute_pass(
-                        &ComputePassDescriptor {
-                            label: Some("Fluid diagnostics - Max velocity"),
-                            ..default()
-                        },
-                    );
-                    let grid_size = q.grid_size.0;
-                    let max_velocity_pipeline = world.resource::<MaxVelocityPipeline>();
-                    max_velocity_pipeline.dispatch(
-                        pipeline_cache,
-                        &mut pass,
-                        q.max_velocity_bind_group,
-                        grid_size,
-                    );
-                }
             }
         }
         Ok(())
@@ -4,10 +4,12 @@ use bevy::{
 };
 
 use crate::{
+    advection::AdvectionMethod,
     diagnostics::component::{FluidMaxVelocityMagnitude, FluidMinVelocityMagnitude, FluidVolume},
-    projection::ProjectionMethod,
+    projection::{diagnostics::ProjectionResidual, ProjectionMethod},
     reinitialize_levelset::ReinitializeMethod,
     settings::{FluidGridLength, FluidSettings},
+    velocity_transfer::VelocityTransferMethod,
 };
 
 #[derive(Component, Clone, Copy)]
@@ -17,10 +19,13 @@ pub(crate) enum ItemMarker {
     Resolution,
     ProjectionMethod,
     ReinitLevelSetMethod,
+    AdvectionMethod,
+    VelocityTransferMethod,
     ComputeShader,
     Volume,
     MinVelocity,
     MaxVelocity,
+    ProjectionResidual,
 }
 
 pub(crate) fn setup_diagnostics_ui(mut commands: Commands) {
@@ -32,10 +37,13 @@ pub(crate) fn setup_diagnostics_ui(mut commands: Commands) {
         ("Resolution: ", ItemMarker::Resolution),
         ("Projection Method: ", ItemMarker::ProjectionMethod),
         ("ReinitLevelSet Method: ", ItemMarker::ReinitLevelSetMethod),
+        ("Advection Method: ", ItemMarker::AdvectionMethod),
+        ("Velocity Transfer: ", ItemMarker::VelocityTransferMethod),
         ("GPU (ms):               ", ItemMarker::ComputeShader),
         ("Approx Volume (m^2):    ", ItemMarker::Volume),
         ("Min Velocity Mag (m/s): ", ItemMarker::MinVelocity),
         ("Max Velocity Mag (m/s): ", ItemMarker::MaxVelocity),
+        ("Residual (L2/Linf):     ", ItemMarker::ProjectionResidual),
     ];
     commands
         .spawn((
@@ -74,14 +82,27 @@ pub(crate) fn update_diagnostics_ui(
         &FluidSettings,
         &ProjectionMethod,
         &ReinitializeMethod,
+        &AdvectionMethod,
+        &VelocityTransferMethod,
         Option<&FluidVolume>,
         Option<&FluidMinVelocityMagnitude>,
         Option<&FluidMaxVelocityMagnitude>,
+        Option<&ProjectionResidual>,
     )>,
     diagnostics: Res<DiagnosticsStore>,
     grid_length: Res<FluidGridLength>,
 ) {
-    let (settings, projection, reinit, volume, min_velocity, max_velocity) = fluid_query
+    let (
+        settings,
+        projection,
+        reinit,
+        advection,
+        velocity_transfer,
+        volume,
+        min_velocity,
+        max_velocity,
+        residual,
+    ) = fluid_query
         .single()
         .expect("FluidDiagnostics can work when there is exactly one fluid compoent.");
     for (mut text, marker) in &mut query {
@@ -115,6 +136,12 @@ pub(crate) fn update_diagnostics_ui(
             ItemMarker::ReinitLevelSetMethod => {
                 **text = format!("{}", reinit);
             }
+            ItemMarker::AdvectionMethod => {
+                **text = format!("{}", advection);
+            }
+            ItemMarker::VelocityTransferMethod => {
+                **text = format!("{}", velocity_transfer);
+            }
             ItemMarker::ComputeShader => {
                 let diagnostics_path = DiagnosticPath::new("render/eulerian_fluid/elapsed_gpu");
                 if let Some(gpu) = diagnostics
@@ -154,6 +181,16 @@ pub(crate) fn update_diagnostics_ui(
                     **text = "N/A".into();
                 }
             }
+            ItemMarker::ProjectionResidual => {
+                if let Some(residual) = residual {
+                    **text = format!(
+                        "{:.2e}/{:.2e} ({} chunks)",
+                        residual.l2, residual.linf, residual.num_chunks
+                    );
+                } else {
+                    **text = "N/A".into();
+                }
+            }
         }
     }
 }
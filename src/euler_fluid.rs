@@ -1,6 +1,7 @@
 pub mod advect_levelset;
 pub mod advection;
 pub mod apply_forces;
+pub mod boundary_condition;
 pub mod common_pass;
 pub mod divergence;
 pub mod extrapolate_velocity;
@@ -16,12 +17,21 @@ pub mod physics_time;
 pub mod projection;
 pub mod reinitialize_levelset;
 pub mod render_node;
+pub mod rigid_body_backend;
+pub mod scalar_field;
 pub mod settings;
 pub mod setup_components;
+pub mod solid_mask;
 pub mod solve_pressure;
 pub mod solve_velocity;
+pub mod substep;
+pub mod surface_tension;
+pub mod terrain;
 pub mod update_area_fraction;
 pub mod update_solid;
+pub mod velocity_transfer;
+pub mod viscosity;
+pub mod vorticity_confinement;
 
 use bevy::{
     prelude::*,
@@ -64,6 +74,7 @@ impl Plugin for FluidPlugin {
                 update_solid::UpdateSolidPlugin,
                 FluidComputePassPlugin::<advection::AdvectionPass>::default(),
                 apply_forces::ApplyForcesPlugin,
+                vorticity_confinement::VorticityConfinementPlugin,
                 FluidComputePassPlugin::<divergence::DivergencePass>::default(),
                 fluid_uniform::SimulationUniformPlugin,
                 PressureProjectionPlugin,
@@ -75,19 +86,27 @@ impl Plugin for FluidPlugin {
                 fluid_to_solid::FluidToSolidForcesPlugin,
                 FluidComputePassPlugin::<levelset_gradient::LevelSetGradientPass>::default(),
                 FluidComputePassPlugin::<update_area_fraction::UpdateAreaFractionPass>::default(),
-                FluidSourcePlugin,
             ))
             .add_plugins((
-                // particle_levelset_two_layers::ParticleLevelsetTwoLayersPlugin,
+                FluidSourcePlugin,
+                particle_levelset_two_layers::ParticleLevelsetTwoLayersPlugin,
                 common_pass::CommonPassPlugin,
+                viscosity::ViscosityPlugin,
+                FluidComputePassPlugin::<surface_tension::CurvaturePass>::default(),
+                scalar_field::ScalarFieldPlugin,
+                advection::high_order::HighOrderAdvectionPlugin,
+                velocity_transfer::VelocityTransferPlugin,
+                substep::SubstepPlugin,
             ))
             .add_plugins(FluidMaterialPlugin)
             .add_plugins((
                 fluid_status::FluidStatusPlugin,
                 physics_time::PhysicsFramePlugin,
+                rigid_body_backend::RigidBodyBackendPlugin,
+                terrain::TerrainPlugin,
+                solid_mask::SolidMaskPlugin,
             ))
             .insert_resource(FluidGridLength(1.0 / self.length_unit))
-            .add_systems(Update, obstacle::construct_rigid_body_buffer_for_gpu)
             .add_systems(Update, watch_fluid_component);
 
         let render_app = app.sub_app_mut(RenderApp);
@@ -101,12 +120,16 @@ impl Plugin for FluidPlugin {
         load_shader_library!(app, "euler_fluid/shaders/fluid_uniform.wgsl");
         load_shader_library!(app, "euler_fluid/shaders/utils/area_fraction.wgsl");
         load_shader_library!(app, "euler_fluid/shaders/utils/coordinate.wgsl");
+        load_shader_library!(app, "euler_fluid/shaders/utils/boundary_condition.wgsl");
         load_shader_library!(app, "euler_fluid/shaders/utils/levelset_utils.wgsl");
         load_shader_library!(app, "euler_fluid/shaders/utils/hash.wgsl");
+        load_shader_library!(app, "euler_fluid/shaders/utils/advection.wgsl");
         load_shader_library!(
             app,
             "euler_fluid/shaders/fluid_to_solid/fixed_point_conversion.wgsl"
         );
         load_shader_library!(app, "euler_fluid/shaders/solid_obstacle.wgsl");
+        load_shader_library!(app, "euler_fluid/fluid_source/fluid_source.wgsl");
+        load_shader_library!(app, "euler_fluid/shaders/scalar_field/scalar_channel.wgsl");
     }
 }
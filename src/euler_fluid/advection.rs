@@ -1,3 +1,7 @@
+pub mod high_order;
+
+use std::fmt::Display;
+
 use bevy::{
     asset::{embedded_asset, load_embedded_asset},
     prelude::*,
@@ -19,6 +23,62 @@ use crate::{
 
 pub(crate) struct AdvectionPass;
 
+/// Scheme to advect the velocity and the level set.
+/// The higher-order schemes reduce the numerical dissipation of semi-Lagrangian advection,
+/// so that vortices and the volume of liquid survive longer. Their error corrections are clamped
+/// to the range of the original field around the backtraced point to avoid overshoots.
+#[derive(Component, ExtractComponent, Clone, Debug, Default)]
+pub enum AdvectionMethod {
+    /// First-order semi-Lagrangian advection. Stable but dissipative.
+    #[default]
+    SemiLagrangian,
+    /// MacCormack method. Corrects the forward advection by the error of the backward advection. Two advections per step.
+    MacCormack,
+    /// Back and Forth Error Compensation and Correction. Advects the field compensated by the error of the back and forth advection.
+    /// Three advections per step.
+    BFECC,
+}
+
+/// Kernel to interpolate the fields between grid points, used by the advection of the velocity, the level set and the scalars.
+/// Insert this component to the entity with [`crate::settings::FluidSettings`] to override the default [`InterpolationMethod::Bilinear`].
+/// The level set is always interpolated with a cubic kernel, which is unclamped Catmull-Rom unless one of the cubic kernels below is selected.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub enum InterpolationMethod {
+    /// Bilinear interpolation. Smooths out the fields most, but never overshoots.
+    #[default]
+    Bilinear,
+    /// Monotone cubic interpolation (Fedkiw et al. 2001). Sharper than bilinear, and no new extrema are created.
+    MonotoneCubic,
+    /// Catmull-Rom spline clamped into the range of the neighboring values.
+    CatmullRom,
+}
+
+impl InterpolationMethod {
+    pub(crate) fn as_u32(&self) -> u32 {
+        match self {
+            InterpolationMethod::Bilinear => 0,
+            InterpolationMethod::MonotoneCubic => 1,
+            InterpolationMethod::CatmullRom => 2,
+        }
+    }
+}
+
+impl Display for AdvectionMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdvectionMethod::SemiLagrangian => {
+                write!(f, "Semi-Lagrangian")
+            }
+            AdvectionMethod::MacCormack => {
+                write!(f, "MacCormack")
+            }
+            AdvectionMethod::BFECC => {
+                write!(f, "BFECC")
+            }
+        }
+    }
+}
+
 impl FluidComputePass for AdvectionPass {
     type Pipeline = AdvectionPipeline;
     type Resource = AdvectionResource;
@@ -0,0 +1,417 @@
+use bevy::{
+    asset::{embedded_asset, embedded_path},
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_asset::RenderAssets,
+        render_resource::{
+            AsBindGroup, BindGroup, BindGroupLayoutDescriptor, CachedComputePipelineId,
+            ComputePass, PipelineCache,
+        },
+        renderer::RenderDevice,
+        storage::GpuShaderStorageBuffer,
+        texture::{FallbackImage, GpuImage},
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+use crate::{
+    advection::AdvectionMethod,
+    fluid_uniform::{uniform_bind_group_layout_desc, SimulationUniformBindGroup},
+    pipeline::{is_pipeline_loaded, queue_compute_pipeline, DispatchFluidPass},
+};
+
+pub(crate) struct HighOrderAdvectionPlugin;
+
+impl Plugin for HighOrderAdvectionPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/advect_velocity_high_order.wgsl");
+        embedded_asset!(app, "shaders/advect_levelset_high_order.wgsl");
+
+        app.add_plugins((
+            ExtractComponentPlugin::<AdvectionMethod>::default(),
+            ExtractComponentPlugin::<HighOrderAdvectionResource>::default(),
+        ));
+
+        let render_app = app.sub_app_mut(RenderApp);
+        render_app.add_systems(
+            Render,
+            prepare_bind_groups.in_set(RenderSystems::PrepareBindGroups),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let render_app = app.sub_app_mut(RenderApp);
+        render_app.init_resource::<HighOrderAdvectionPipelines>();
+    }
+}
+
+/// Steps of MacCormack and BFECC. With A the semi-Lagrangian advection and A^R the one backward in time:
+/// * MacCormack: `forward` (A(q0)), `backward` (A^R(A(q0))) and `maccormack` (corrects A(q0) by the error of the round trip).
+/// * BFECC: `forward`, `backward`, `compensate` (q0 compensated by the error of the round trip) and `bfecc_advect` (advects the compensated field).
+#[derive(Clone)]
+pub(crate) struct HighOrderAdvectionSteps<T> {
+    pub forward: T,
+    pub backward: T,
+    pub maccormack: T,
+    pub compensate: T,
+    pub bfecc_advect: T,
+}
+
+impl<T> HighOrderAdvectionSteps<T> {
+    fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> HighOrderAdvectionSteps<U> {
+        HighOrderAdvectionSteps {
+            forward: f(&self.forward),
+            backward: f(&self.backward),
+            maccormack: f(&self.maccormack),
+            compensate: f(&self.compensate),
+            bfecc_advect: f(&self.bfecc_advect),
+        }
+    }
+
+    fn zip<U>(self, other: HighOrderAdvectionSteps<U>) -> HighOrderAdvectionSteps<(T, U)> {
+        HighOrderAdvectionSteps {
+            forward: (self.forward, other.forward),
+            backward: (self.backward, other.backward),
+            maccormack: (self.maccormack, other.maccormack),
+            compensate: (self.compensate, other.compensate),
+            bfecc_advect: (self.bfecc_advect, other.bfecc_advect),
+        }
+    }
+
+    /// Steps to run in order for the method.
+    fn sequence(&self, method: &AdvectionMethod) -> Vec<&T> {
+        match method {
+            AdvectionMethod::SemiLagrangian => vec![],
+            AdvectionMethod::MacCormack => vec![&self.forward, &self.backward, &self.maccormack],
+            AdvectionMethod::BFECC => vec![
+                &self.forward,
+                &self.backward,
+                &self.compensate,
+                &self.bfecc_advect,
+            ],
+        }
+    }
+}
+
+/// Textures (src, aux, dst) of each step, where `a` and `b` are the temporaries.
+/// q0 -> (forward) -> a -> (backward) -> b, then either
+/// (a, b) -> (maccormack) -> q1, or b -> (compensate) -> a -> (bfecc_advect) -> q1.
+fn step_textures(
+    q0: &Handle<Image>,
+    q1: &Handle<Image>,
+    a: &Handle<Image>,
+    b: &Handle<Image>,
+) -> HighOrderAdvectionSteps<[Handle<Image>; 3]> {
+    HighOrderAdvectionSteps {
+        forward: [q0.clone(), q0.clone(), a.clone()],
+        backward: [a.clone(), a.clone(), b.clone()],
+        maccormack: [a.clone(), b.clone(), q1.clone()],
+        compensate: [b.clone(), b.clone(), a.clone()],
+        bfecc_advect: [a.clone(), a.clone(), q1.clone()],
+    }
+}
+
+#[derive(Clone, AsBindGroup)]
+pub(crate) struct HighOrderAdvectVelocityResource {
+    #[storage_texture(0, image_format = R32Float, access = ReadOnly)]
+    pub u0: Handle<Image>,
+    #[storage_texture(1, image_format = R32Float, access = ReadOnly)]
+    pub v0: Handle<Image>,
+    #[storage_texture(2, image_format = R32Float, access = ReadOnly)]
+    pub src_u: Handle<Image>,
+    #[storage_texture(3, image_format = R32Float, access = ReadOnly)]
+    pub src_v: Handle<Image>,
+    #[storage_texture(4, image_format = R32Float, access = ReadOnly)]
+    pub aux_u: Handle<Image>,
+    #[storage_texture(5, image_format = R32Float, access = ReadOnly)]
+    pub aux_v: Handle<Image>,
+    #[storage_texture(6, image_format = R32Float, access = WriteOnly)]
+    pub dst_u: Handle<Image>,
+    #[storage_texture(7, image_format = R32Float, access = WriteOnly)]
+    pub dst_v: Handle<Image>,
+}
+
+#[derive(Clone, AsBindGroup)]
+pub(crate) struct HighOrderAdvectLevelSetResource {
+    #[storage_texture(0, image_format = R32Float, access = ReadOnly)]
+    pub u0: Handle<Image>,
+    #[storage_texture(1, image_format = R32Float, access = ReadOnly)]
+    pub v0: Handle<Image>,
+    #[storage_texture(2, image_format = R32Float, access = ReadOnly)]
+    pub levelset_air0: Handle<Image>,
+    #[storage_texture(3, image_format = R32Float, access = ReadOnly)]
+    pub src: Handle<Image>,
+    #[storage_texture(4, image_format = R32Float, access = ReadOnly)]
+    pub aux: Handle<Image>,
+    #[storage_texture(5, image_format = R32Float, access = WriteOnly)]
+    pub dst: Handle<Image>,
+}
+
+/// Inserted only if [`AdvectionMethod`] is other than [`AdvectionMethod::SemiLagrangian`].
+#[derive(Component, Clone, ExtractComponent)]
+pub(crate) struct HighOrderAdvectionResource {
+    pub velocity: HighOrderAdvectionSteps<HighOrderAdvectVelocityResource>,
+    pub levelset: HighOrderAdvectionSteps<HighOrderAdvectLevelSetResource>,
+}
+
+impl HighOrderAdvectionResource {
+    pub fn new(
+        u: [&Handle<Image>; 2],
+        v: [&Handle<Image>; 2],
+        levelset_air: [&Handle<Image>; 2],
+        temporary_u: [Handle<Image>; 2],
+        temporary_v: [Handle<Image>; 2],
+        temporary_levelset: [Handle<Image>; 2],
+    ) -> Self {
+        let [u0, u1] = u;
+        let [v0, v1] = v;
+        let [levelset_air0, levelset_air1] = levelset_air;
+        let [a_u, b_u] = &temporary_u;
+        let [a_v, b_v] = &temporary_v;
+        let [a_levelset, b_levelset] = &temporary_levelset;
+
+        let velocity = step_textures(u0, u1, a_u, b_u)
+            .zip(step_textures(v0, v1, a_v, b_v))
+            .map(|([src_u, aux_u, dst_u], [src_v, aux_v, dst_v])| {
+                HighOrderAdvectVelocityResource {
+                    u0: u0.clone(),
+                    v0: v0.clone(),
+                    src_u: src_u.clone(),
+                    src_v: src_v.clone(),
+                    aux_u: aux_u.clone(),
+                    aux_v: aux_v.clone(),
+                    dst_u: dst_u.clone(),
+                    dst_v: dst_v.clone(),
+                }
+            });
+
+        let levelset = step_textures(levelset_air0, levelset_air1, a_levelset, b_levelset).map(
+            |[src, aux, dst]| HighOrderAdvectLevelSetResource {
+                u0: u0.clone(),
+                v0: v0.clone(),
+                levelset_air0: levelset_air0.clone(),
+                src: src.clone(),
+                aux: aux.clone(),
+                dst: dst.clone(),
+            },
+        );
+
+        Self { velocity, levelset }
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct HighOrderAdvectionPipelines {
+    velocity_pipelines: HighOrderAdvectionSteps<[CachedComputePipelineId; 2]>,
+    levelset_pipelines: HighOrderAdvectionSteps<CachedComputePipelineId>,
+    velocity_bind_group_layout: BindGroupLayoutDescriptor,
+    levelset_bind_group_layout: BindGroupLayoutDescriptor,
+}
+
+impl FromWorld for HighOrderAdvectionPipelines {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let velocity_bind_group_layout =
+            HighOrderAdvectVelocityResource::bind_group_layout_descriptor(render_device);
+        let levelset_bind_group_layout =
+            HighOrderAdvectLevelSetResource::bind_group_layout_descriptor(render_device);
+        let velocity_layouts = vec![
+            velocity_bind_group_layout.clone(),
+            uniform_bind_group_layout_desc(),
+        ];
+        let levelset_layouts = vec![
+            levelset_bind_group_layout.clone(),
+            uniform_bind_group_layout_desc(),
+        ];
+
+        let mut queue_velocity = |label: [&'static str; 2], entry_point: [&'static str; 2]| {
+            [0, 1].map(|i| {
+                queue_compute_pipeline(
+                    world,
+                    label[i],
+                    embedded_path!("shaders/advect_velocity_high_order.wgsl"),
+                    entry_point[i],
+                    velocity_layouts.clone(),
+                )
+            })
+        };
+        let velocity_pipelines = HighOrderAdvectionSteps {
+            forward: queue_velocity(
+                ["AdvectForwardUPipeline", "AdvectForwardVPipeline"],
+                ["advect_forward_u", "advect_forward_v"],
+            ),
+            backward: queue_velocity(
+                ["AdvectBackwardUPipeline", "AdvectBackwardVPipeline"],
+                ["advect_backward_u", "advect_backward_v"],
+            ),
+            maccormack: queue_velocity(
+                ["MacCormackUPipeline", "MacCormackVPipeline"],
+                ["maccormack_u", "maccormack_v"],
+            ),
+            compensate: queue_velocity(
+                ["BFECCCompensateUPipeline", "BFECCCompensateVPipeline"],
+                ["bfecc_compensate_u", "bfecc_compensate_v"],
+            ),
+            bfecc_advect: queue_velocity(
+                ["BFECCAdvectUPipeline", "BFECCAdvectVPipeline"],
+                ["bfecc_advect_u", "bfecc_advect_v"],
+            ),
+        };
+
+        let mut queue_levelset = |label: &'static str, entry_point: &'static str| {
+            queue_compute_pipeline(
+                world,
+                label,
+                embedded_path!("shaders/advect_levelset_high_order.wgsl"),
+                entry_point,
+                levelset_layouts.clone(),
+            )
+        };
+        let levelset_pipelines = HighOrderAdvectionSteps {
+            forward: queue_levelset("AdvectLevelSetForwardPipeline", "advect_forward"),
+            backward: queue_levelset("AdvectLevelSetBackwardPipeline", "advect_backward"),
+            maccormack: queue_levelset("MacCormackLevelSetPipeline", "maccormack"),
+            compensate: queue_levelset("BFECCCompensateLevelSetPipeline", "bfecc_compensate"),
+            bfecc_advect: queue_levelset("BFECCAdvectLevelSetPipeline", "bfecc_advect"),
+        };
+
+        Self {
+            velocity_pipelines,
+            levelset_pipelines,
+            velocity_bind_group_layout,
+            levelset_bind_group_layout,
+        }
+    }
+}
+
+impl HighOrderAdvectionPipelines {
+    pub fn is_ready(&self, pipeline_cache: &PipelineCache) -> bool {
+        let velocity = &self.velocity_pipelines;
+        let levelset = &self.levelset_pipelines;
+        [
+            &velocity.forward,
+            &velocity.backward,
+            &velocity.maccormack,
+            &velocity.compensate,
+            &velocity.bfecc_advect,
+        ]
+        .into_iter()
+        .flatten()
+        .chain([
+            &levelset.forward,
+            &levelset.backward,
+            &levelset.maccormack,
+            &levelset.compensate,
+            &levelset.bfecc_advect,
+        ])
+        .all(|pipeline| is_pipeline_loaded(pipeline_cache, *pipeline))
+    }
+
+    pub fn dispatch_velocity(
+        &self,
+        method: &AdvectionMethod,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        bind_groups: &HighOrderAdvectionBindGroups,
+        uniform_bind_group: &SimulationUniformBindGroup,
+        size: UVec2,
+    ) {
+        pass.push_debug_group("Advect velocity (high order)");
+        pass.set_bind_group(
+            1,
+            &uniform_bind_group.bind_group,
+            &[uniform_bind_group.index],
+        );
+        for ([u_pipeline, v_pipeline], bind_group) in self
+            .velocity_pipelines
+            .sequence(method)
+            .into_iter()
+            .zip(bind_groups.velocity.sequence(method))
+        {
+            let u_pipeline = pipeline_cache.get_compute_pipeline(*u_pipeline).unwrap();
+            let v_pipeline = pipeline_cache.get_compute_pipeline(*v_pipeline).unwrap();
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.set_pipeline(u_pipeline);
+            pass.dispatch_x_major(size);
+            pass.set_pipeline(v_pipeline);
+            pass.dispatch_y_major(size);
+        }
+        pass.pop_debug_group();
+    }
+
+    pub fn dispatch_levelset(
+        &self,
+        method: &AdvectionMethod,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        bind_groups: &HighOrderAdvectionBindGroups,
+        uniform_bind_group: &SimulationUniformBindGroup,
+        size: UVec2,
+    ) {
+        pass.push_debug_group("Advect level set (high order)");
+        pass.set_bind_group(
+            1,
+            &uniform_bind_group.bind_group,
+            &[uniform_bind_group.index],
+        );
+        for (pipeline, bind_group) in self
+            .levelset_pipelines
+            .sequence(method)
+            .into_iter()
+            .zip(bind_groups.levelset.sequence(method))
+        {
+            let pipeline = pipeline_cache.get_compute_pipeline(*pipeline).unwrap();
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.set_pipeline(pipeline);
+            pass.dispatch_center(size);
+        }
+        pass.pop_debug_group();
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct HighOrderAdvectionBindGroups {
+    velocity: HighOrderAdvectionSteps<BindGroup>,
+    levelset: HighOrderAdvectionSteps<BindGroup>,
+}
+
+fn prepare_bind_groups<'a>(
+    mut commands: Commands,
+    pipelines: Res<HighOrderAdvectionPipelines>,
+    query: Query<(Entity, &HighOrderAdvectionResource)>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    mut param: (
+        Res<'a, RenderAssets<GpuImage>>,
+        Res<'a, FallbackImage>,
+        Res<'a, RenderAssets<GpuShaderStorageBuffer>>,
+    ),
+) {
+    for (entity, resource) in &query {
+        let velocity = resource.velocity.map(|step| {
+            step.as_bind_group(
+                &pipelines.velocity_bind_group_layout,
+                &render_device,
+                &pipeline_cache,
+                &mut param,
+            )
+            .unwrap()
+            .bind_group
+        });
+        let levelset = resource.levelset.map(|step| {
+            step.as_bind_group(
+                &pipelines.levelset_bind_group_layout,
+                &render_device,
+                &pipeline_cache,
+                &mut param,
+            )
+            .unwrap()
+            .bind_group
+        });
+
+        commands
+            .entity(entity)
+            .insert(HighOrderAdvectionBindGroups { velocity, levelset });
+    }
+}
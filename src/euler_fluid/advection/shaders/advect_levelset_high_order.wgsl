@@ -0,0 +1,71 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::advection::{levelset_interpolation, limit_center, semi_lagrangian_center};
+
+@group(0) @binding(0) var u0: texture_storage_2d<r32float, read>;
+@group(0) @binding(1) var v0: texture_storage_2d<r32float, read>;
+// Level set at the beginning of the step. It bounds the limiters.
+@group(0) @binding(2) var levelset_air0: texture_storage_2d<r32float, read>;
+// Intermediate fields of the step, see `HighOrderAdvectionSteps` for which textures are bound.
+@group(0) @binding(3) var src: texture_storage_2d<r32float, read>;
+@group(0) @binding(4) var aux: texture_storage_2d<r32float, read>;
+@group(0) @binding(5) var dst: texture_storage_2d<r32float, write>;
+
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
+// Semi-Lagrangian step forward in time: dst = A(src).
+@compute @workgroup_size(8, 8, 1)
+fn advect_forward(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let level = semi_lagrangian_center(constants, u0, v0, src, idx, constants.dt, levelset_interpolation(constants));
+    textureStore(dst, idx, vec4<f32>(level, 0.0, 0.0, 0.0));
+}
+
+// Semi-Lagrangian step backward in time: dst = A^R(src).
+@compute @workgroup_size(8, 8, 1)
+fn advect_backward(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let level = semi_lagrangian_center(constants, u0, v0, src, idx, -constants.dt, levelset_interpolation(constants));
+    textureStore(dst, idx, vec4<f32>(level, 0.0, 0.0, 0.0));
+}
+
+// MacCormack correction: dst = src + (phi0 - aux) / 2, where src = A(phi0) and aux = A^R(A(phi0)).
+@compute @workgroup_size(8, 8, 1)
+fn maccormack(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let forward = textureLoad(src, idx).r;
+    let corrected = forward + 0.5 * (textureLoad(levelset_air0, idx).r - textureLoad(aux, idx).r);
+    let level = limit_center(constants, u0, v0, levelset_air0, idx, constants.dt, corrected);
+    if abs(level) < 1000.0 {
+        textureStore(dst, idx, vec4<f32>(level, 0.0, 0.0, 0.0));
+    }
+}
+
+// BFECC error compensation: dst = phi0 + (phi0 - src) / 2, where src = A^R(A(phi0)).
+@compute @workgroup_size(8, 8, 1)
+fn bfecc_compensate(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let phi0 = textureLoad(levelset_air0, idx).r;
+    let level = phi0 + 0.5 * (phi0 - textureLoad(src, idx).r);
+    textureStore(dst, idx, vec4<f32>(level, 0.0, 0.0, 0.0));
+}
+
+// BFECC final step: dst = A(src), where src is the compensated level set.
+@compute @workgroup_size(8, 8, 1)
+fn bfecc_advect(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let advected = semi_lagrangian_center(constants, u0, v0, src, idx, constants.dt, levelset_interpolation(constants));
+    let level = limit_center(constants, u0, v0, levelset_air0, idx, constants.dt, advected);
+    if abs(level) < 1000.0 {
+        textureStore(dst, idx, vec4<f32>(level, 0.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,120 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::advection::{limit_u, limit_v, semi_lagrangian_u, semi_lagrangian_v};
+
+// Velocity at the beginning of the step. It carries the fields and bounds the limiters.
+@group(0) @binding(0) var u0: texture_storage_2d<r32float, read>;
+@group(0) @binding(1) var v0: texture_storage_2d<r32float, read>;
+// Intermediate fields of the step, see `HighOrderAdvectionSteps` for which textures are bound.
+@group(0) @binding(2) var src_u: texture_storage_2d<r32float, read>;
+@group(0) @binding(3) var src_v: texture_storage_2d<r32float, read>;
+@group(0) @binding(4) var aux_u: texture_storage_2d<r32float, read>;
+@group(0) @binding(5) var aux_v: texture_storage_2d<r32float, read>;
+@group(0) @binding(6) var dst_u: texture_storage_2d<r32float, write>;
+@group(0) @binding(7) var dst_v: texture_storage_2d<r32float, write>;
+
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
+// Semi-Lagrangian step forward in time: dst = A(src).
+@compute @workgroup_size(1, 64, 1)
+fn advect_forward_u(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let u = semi_lagrangian_u(constants, u0, v0, src_u, idx, constants.dt);
+    textureStore(dst_u, idx, vec4<f32>(u, 0.0, 0.0, 0.0));
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn advect_forward_v(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let v = semi_lagrangian_v(constants, u0, v0, src_v, idx, constants.dt);
+    textureStore(dst_v, idx, vec4<f32>(v, 0.0, 0.0, 0.0));
+}
+
+// Semi-Lagrangian step backward in time: dst = A^R(src).
+@compute @workgroup_size(1, 64, 1)
+fn advect_backward_u(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let u = semi_lagrangian_u(constants, u0, v0, src_u, idx, -constants.dt);
+    textureStore(dst_u, idx, vec4<f32>(u, 0.0, 0.0, 0.0));
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn advect_backward_v(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let v = semi_lagrangian_v(constants, u0, v0, src_v, idx, -constants.dt);
+    textureStore(dst_v, idx, vec4<f32>(v, 0.0, 0.0, 0.0));
+}
+
+// MacCormack correction: dst = src + (q0 - aux) / 2, where src = A(q0) and aux = A^R(A(q0)).
+@compute @workgroup_size(1, 64, 1)
+fn maccormack_u(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let forward = textureLoad(src_u, idx).r;
+    let corrected = forward + 0.5 * (textureLoad(u0, idx).r - textureLoad(aux_u, idx).r);
+    let u = limit_u(constants, u0, v0, u0, idx, constants.dt, corrected, forward);
+    textureStore(dst_u, idx, vec4<f32>(u, 0.0, 0.0, 0.0));
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn maccormack_v(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let forward = textureLoad(src_v, idx).r;
+    let corrected = forward + 0.5 * (textureLoad(v0, idx).r - textureLoad(aux_v, idx).r);
+    let v = limit_v(constants, u0, v0, v0, idx, constants.dt, corrected, forward);
+    textureStore(dst_v, idx, vec4<f32>(v, 0.0, 0.0, 0.0));
+}
+
+// BFECC error compensation: dst = q0 + (q0 - src) / 2, where src = A^R(A(q0)).
+@compute @workgroup_size(1, 64, 1)
+fn bfecc_compensate_u(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let q0 = textureLoad(u0, idx).r;
+    let u = q0 + 0.5 * (q0 - textureLoad(src_u, idx).r);
+    textureStore(dst_u, idx, vec4<f32>(u, 0.0, 0.0, 0.0));
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn bfecc_compensate_v(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let q0 = textureLoad(v0, idx).r;
+    let v = q0 + 0.5 * (q0 - textureLoad(src_v, idx).r);
+    textureStore(dst_v, idx, vec4<f32>(v, 0.0, 0.0, 0.0));
+}
+
+// BFECC final step: dst = A(src), where src is the compensated field.
+@compute @workgroup_size(1, 64, 1)
+fn bfecc_advect_u(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let advected = semi_lagrangian_u(constants, u0, v0, src_u, idx, constants.dt);
+    let fallback = semi_lagrangian_u(constants, u0, v0, u0, idx, constants.dt);
+    let u = limit_u(constants, u0, v0, u0, idx, constants.dt, advected, fallback);
+    textureStore(dst_u, idx, vec4<f32>(u, 0.0, 0.0, 0.0));
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn bfecc_advect_v(
+    @builtin(global_invocation_id) invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(invocation_id.xy);
+    let advected = semi_lagrangian_v(constants, u0, v0, src_v, idx, constants.dt);
+    let fallback = semi_lagrangian_v(constants, u0, v0, v0, idx, constants.dt);
+    let v = limit_v(constants, u0, v0, v0, idx, constants.dt, advected, fallback);
+    textureStore(dst_v, idx, vec4<f32>(v, 0.0, 0.0, 0.0));
+}
@@ -31,6 +31,8 @@ pub(crate) struct ApplyForcesResource {
     pub forces_to_fluid: Handle<ShaderStorageBuffer>,
     #[storage_texture(4, image_format = Rgba32Float, access = ReadOnly)]
     pub area_fraction_solid: Handle<Image>,
+    #[storage_texture(5, image_format = R32Float, access = ReadOnly)]
+    pub temperature: Handle<Image>,
 }
 
 #[derive(Clone, Copy, Default, ShaderType)]
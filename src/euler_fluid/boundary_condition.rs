@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+
+/// Condition imposed on an edge of the simulation domain. See [`FluidBoundaryConditions`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BoundaryCondition {
+    /// Solid wall. Fluid neither penetrates nor slips along the wall.
+    #[default]
+    NoSlip,
+    /// Solid wall. Fluid does not penetrate the wall but slips along it without friction.
+    FreeSlip,
+    /// Zero pressure outflow. Fluid leaves (or is drawn into) the domain freely.
+    Open,
+    /// Fluid enters the domain with the prescribed velocity in world space.
+    /// The unit is the same as the velocity of rigid bodies.
+    Inflow(Vec2),
+    /// The edge is connected to the opposite edge.
+    /// Setting it to either of the opposite edges makes the axis periodic.
+    Periodic,
+}
+
+impl BoundaryCondition {
+    fn as_u32(&self) -> u32 {
+        match self {
+            BoundaryCondition::NoSlip => 0,
+            BoundaryCondition::FreeSlip => 1,
+            BoundaryCondition::Open => 2,
+            BoundaryCondition::Inflow(_) => 3,
+            BoundaryCondition::Periodic => 4,
+        }
+    }
+}
+
+/// Boundary conditions at the edges of the simulation domain.
+/// Insert this component to the entity with [`crate::settings::FluidSettings`] to override the default,
+/// where the domain is surrounded by no-slip walls.
+/// # Fields
+/// * `left`, `right`, `top`, `bottom`: [`BoundaryCondition`] at each edge of the domain in world space.
+///
+/// # Examples
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_eulerian_fluid::boundary_condition::{BoundaryCondition, FluidBoundaryConditions};
+///
+/// // Wind tunnel blowing from left to right.
+/// let wind_tunnel = FluidBoundaryConditions {
+///     left: BoundaryCondition::Inflow(Vec2::new(50.0, 0.0)),
+///     right: BoundaryCondition::Open,
+///     top: BoundaryCondition::FreeSlip,
+///     bottom: BoundaryCondition::FreeSlip,
+/// };
+/// ```
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct FluidBoundaryConditions {
+    pub left: BoundaryCondition,
+    pub right: BoundaryCondition,
+    pub top: BoundaryCondition,
+    pub bottom: BoundaryCondition,
+}
+
+impl FluidBoundaryConditions {
+    /// Applies the same condition to all edges.
+    pub fn all(condition: BoundaryCondition) -> Self {
+        Self {
+            left: condition,
+            right: condition,
+            top: condition,
+            bottom: condition,
+        }
+    }
+
+    /// Connects the opposite edges along the selected axes, leaving the others no-slip walls.
+    pub fn periodic(x: bool, y: bool) -> Self {
+        let along = |periodic: bool| {
+            if periodic {
+                BoundaryCondition::Periodic
+            } else {
+                BoundaryCondition::NoSlip
+            }
+        };
+        Self {
+            left: along(x),
+            right: along(x),
+            top: along(y),
+            bottom: along(y),
+        }
+    }
+
+    pub fn is_periodic_x(&self) -> bool {
+        self.left == BoundaryCondition::Periodic || self.right == BoundaryCondition::Periodic
+    }
+
+    pub fn is_periodic_y(&self) -> bool {
+        self.top == BoundaryCondition::Periodic || self.bottom == BoundaryCondition::Periodic
+    }
+
+    /// Conditions in the order of -x, +x, -y and +y edges of the grid.
+    /// The y axis of the grid points downward, so -y is the top edge.
+    pub(crate) fn boundary_types(&self) -> UVec4 {
+        let mut types = UVec4::new(
+            self.left.as_u32(),
+            self.right.as_u32(),
+            self.top.as_u32(),
+            self.bottom.as_u32(),
+        );
+        let periodic = BoundaryCondition::Periodic.as_u32();
+        if self.is_periodic_x() {
+            types.x = periodic;
+            types.y = periodic;
+        }
+        if self.is_periodic_y() {
+            types.z = periodic;
+            types.w = periodic;
+        }
+        types
+    }
+
+    /// Weights of the fluid velocity in the tangential velocity of the wall cells, in the same order as [`Self::boundary_types`].
+    /// Free-slip walls copy the tangential velocity of the fluid next to them so that its normal derivative vanishes,
+    /// while no-slip walls and inflows keep their own velocity.
+    pub(crate) fn wall_slip(&self) -> Vec4 {
+        let slip = |condition: &BoundaryCondition| match condition {
+            BoundaryCondition::FreeSlip => 1.0,
+            _ => 0.0,
+        };
+        let mut wall_slip = Vec4::new(
+            slip(&self.left),
+            slip(&self.right),
+            slip(&self.top),
+            slip(&self.bottom),
+        );
+        if self.is_periodic_x() {
+            wall_slip.x = 0.0;
+            wall_slip.y = 0.0;
+        }
+        if self.is_periodic_y() {
+            wall_slip.z = 0.0;
+            wall_slip.w = 0.0;
+        }
+        wall_slip
+    }
+
+    /// Inflow velocities in grid coordinates packed as (-x edge, +x edge) and (-y edge, +y edge).
+    pub(crate) fn inflow_velocities(&self) -> (Vec4, Vec4) {
+        let to_grid = |condition: &BoundaryCondition| match condition {
+            BoundaryCondition::Inflow(velocity) => Vec2::new(velocity.x, -velocity.y),
+            _ => Vec2::ZERO,
+        };
+        let (left, right) = (to_grid(&self.left), to_grid(&self.right));
+        let (top, bottom) = (to_grid(&self.top), to_grid(&self.bottom));
+        (
+            Vec4::new(left.x, left.y, right.x, right.y),
+            Vec4::new(top.x, top.y, bottom.x, bottom.y),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_free_slip_walls_take_tangential_velocity_of_fluid() {
+        let no_slip = FluidBoundaryConditions::all(BoundaryCondition::NoSlip);
+        let free_slip = FluidBoundaryConditions::all(BoundaryCondition::FreeSlip);
+        assert_eq!(no_slip.wall_slip(), Vec4::ZERO);
+        assert_eq!(free_slip.wall_slip(), Vec4::ONE);
+
+        // A periodic axis has no walls even if the opposite edge is set to free-slip.
+        let mixed = FluidBoundaryConditions {
+            left: BoundaryCondition::Periodic,
+            right: BoundaryCondition::FreeSlip,
+            top: BoundaryCondition::FreeSlip,
+            bottom: BoundaryCondition::Inflow(Vec2::X),
+        };
+        assert_eq!(mixed.wall_slip(), Vec4::new(0.0, 0.0, 1.0, 0.0));
+    }
+}
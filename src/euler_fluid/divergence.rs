@@ -34,8 +34,8 @@ pub(crate) struct DivergenceResource {
     pub u_solid: Handle<Image>,
     #[storage_texture(3, image_format = R32Float, access = ReadOnly)]
     pub v_solid: Handle<Image>,
-    #[storage_texture(4, image_format = R32Float, access = ReadOnly)]
-    pub levelset_solid: Handle<Image>,
+    #[storage_texture(4, image_format = Rgba32Float, access = ReadOnly)]
+    pub area_fraction_solid: Handle<Image>,
     #[storage_texture(5, image_format = R32Float, access = WriteOnly)]
     pub div: Handle<Image>,
 }
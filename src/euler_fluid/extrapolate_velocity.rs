@@ -15,7 +15,7 @@ use bevy::{
     },
 };
 
-use crate::pipeline::Pipeline;
+use crate::{fluid_uniform::uniform_bind_group_layout_desc, pipeline::Pipeline};
 
 pub(crate) struct ExtrapolateVelocityPlugin;
 
@@ -117,6 +117,7 @@ impl FromWorld for ExtrapolateVelocityPipeline {
         let pipeline_cache = world.resource::<PipelineCache>();
         let asset_server = world.resource::<AssetServer>();
 
+        let uniform_bind_group_layout = uniform_bind_group_layout_desc();
         let initialize_u_valid_bind_group_layout =
             InitializeUValid::bind_group_layout_descriptor(render_device);
         let initialize_v_valid_bind_group_layout =
@@ -130,7 +131,10 @@ impl FromWorld for ExtrapolateVelocityPipeline {
         let initialize_u_valid_pipeline =
             pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
                 label: Some("InitializeUValid".into()),
-                layout: vec![initialize_u_valid_bind_group_layout.clone()],
+                layout: vec![
+                    initialize_u_valid_bind_group_layout.clone(),
+                    uniform_bind_group_layout.clone(),
+                ],
                 shader: load_embedded_asset!(
                     asset_server,
                     "shaders/extrapolate/initialize_u_valid.wgsl"
@@ -142,7 +146,10 @@ impl FromWorld for ExtrapolateVelocityPipeline {
         let initialize_v_valid_pipeline =
             pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
                 label: Some("InitializeVValid".into()),
-                layout: vec![initialize_v_valid_bind_group_layout.clone()],
+                layout: vec![
+                    initialize_v_valid_bind_group_layout.clone(),
+                    uniform_bind_group_layout.clone(),
+                ],
                 shader: load_embedded_asset!(
                     asset_server,
                     "shaders/extrapolate/initialize_v_valid.wgsl"
@@ -154,7 +161,10 @@ impl FromWorld for ExtrapolateVelocityPipeline {
         let extrapolate_u_pipeline =
             pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
                 label: Some("ExtrapolateU".into()),
-                layout: vec![extrapolate_u_bind_group_layout.clone()],
+                layout: vec![
+                    extrapolate_u_bind_group_layout.clone(),
+                    uniform_bind_group_layout.clone(),
+                ],
                 shader: load_embedded_asset!(
                     asset_server,
                     "shaders/extrapolate/extrapolate_u.wgsl"
@@ -166,7 +176,10 @@ impl FromWorld for ExtrapolateVelocityPipeline {
         let extrapolate_v_pipeline =
             pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
                 label: Some("ExtrapolateV".into()),
-                layout: vec![extrapolate_v_bind_group_layout.clone()],
+                layout: vec![
+                    extrapolate_v_bind_group_layout.clone(),
+                    uniform_bind_group_layout.clone(),
+                ],
                 shader: load_embedded_asset!(
                     asset_server,
                     "shaders/extrapolate/extrapolate_v.wgsl"
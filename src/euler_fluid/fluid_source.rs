@@ -35,10 +35,14 @@ pub struct FluidSource {
     pub mode: FluidSourceMode,
 }
 
+/// With [`crate::settings::FluidMode::SinglePhase`], the domain is always filled with fluid,
+/// so sources only drive the velocity and sinks have no effect.
 #[derive(Default)]
 pub enum FluidSourceMode {
+    /// Fills the shape with fluid moving at [`FluidSourceVelocity`].
     #[default]
     Source,
+    /// Drains the fluid in the shape. Only supported with [`crate::settings::FluidMode::FreeSurface`].
     Sink,
 }
 
@@ -82,6 +86,16 @@ impl Default for FluidSourceShape {
 #[derive(Component, Default)]
 pub struct FluidSourceVelocity(pub Vec2);
 
+/// Values of passive scalar channels written inside the source shape while [`FluidSource::active`] is `true`.
+/// The i-th value is written to the i-th channel of [`crate::scalar_field::FluidScalarFields`]. Channels without a value are left untouched.
+#[derive(Component, Default, Clone, Debug)]
+pub struct FluidSourceScalars(pub Vec<f32>);
+
+/// Temperature written inside the source shape while [`FluidSource::active`] is `true`.
+/// Requires [`crate::scalar_field::FluidTemperature`] on the fluid.
+#[derive(Component, Default, Clone, Debug)]
+pub struct FluidSourceTemperature(pub f32);
+
 /// Spawning a FluidSourceOneshot with [`FluidSource`], [`FluidSource`] will be added only on startup.
 /// Used to setup initial fluid domain.
 #[derive(Component)]
@@ -0,0 +1,60 @@
+#define_import_path bevy_fluid::fluid_source
+
+struct FluidSourceData {
+    center: vec2f,
+    data: vec2f,
+    velocity: vec2f,
+    shape: u32,
+    mode: u32,
+}
+
+struct FluidSourceUniform {
+    data: array<FluidSourceData, 16>,
+    count: u32,
+}
+
+const SHAPE_CIRCLE: u32 = 0;
+const SHAPE_AABB: u32 = 1;
+
+const MODE_SOURCE: u32 = 0;
+const MODE_SINK: u32 = 1;
+
+const LARGE_FLOAT: f32 = 1.0e6;
+
+fn level_source(data: FluidSourceData, position: vec2f) -> f32 {
+    switch data.shape {
+        case SHAPE_CIRCLE: {
+            let radius = data.data.x;
+            return distance(data.center, position) - radius;
+            
+        }
+        case SHAPE_AABB: {
+            let half_size = data.data;
+            return level_aabb(half_size, data.center, position);
+        }
+        default: {
+            return 0.0;
+        }
+    }
+}
+
+fn level_aabb(half_size: vec2f, center: vec2f, x: vec2f) -> f32 {
+    var level = LARGE_FLOAT;
+    let d = abs(center - x) - half_size;
+    let is_inside_x = d.x < 0;
+    let is_inside_y = d.y < 0;
+    if (is_inside_x) {
+        if (is_inside_y) {
+            level = max(d.x, d.y);
+        } else {
+            level = d.y;
+        }
+    } else {
+        if (is_inside_y) {
+            level = d.x;
+        } else {
+            level = length(d);
+        }
+    }
+    return level;
+}
@@ -74,6 +74,16 @@ pub(crate) struct FluidSourceUniformBindGroup {
     pub update_index: u32,
 }
 
+pub(crate) fn fluid_source_uniform_bind_group_layout_desc() -> BindGroupLayoutDescriptor {
+    BindGroupLayoutDescriptor::new(
+        "FluidSourceUniformBindGroupLayout",
+        &BindGroupLayoutEntries::single(
+            ShaderStages::COMPUTE,
+            uniform_buffer::<FluidSourceUniform>(true),
+        ),
+    )
+}
+
 fn update_fluid_source_init_buffer(
     mut q_fluid: Query<(&mut FluidSourceInitUniform, Option<&Children>)>,
     q_source: Query<(&FluidSource, &FluidSourceShape, &Transform), With<FluidSourceOneshot>>,
@@ -171,13 +181,7 @@ fn prepare_bind_groups(
 ) {
     let fluid_source_uniform = fluid_source_uniform.uniforms();
     let fluid_source_init_uniform = fluid_source_init_uniform.uniforms();
-    let bind_group_layout_descriptor = BindGroupLayoutDescriptor::new(
-        "FluidSourceUniformBindGroupLayout",
-        &BindGroupLayoutEntries::single(
-            ShaderStages::COMPUTE,
-            uniform_buffer::<FluidSourceUniform>(true),
-        ),
-    );
+    let bind_group_layout_descriptor = fluid_source_uniform_bind_group_layout_desc();
 
     let update_bind_group = render_device.create_bind_group(
         "FluidSourceUniformBindGroup",
@@ -5,18 +5,20 @@ use bevy::{
     render::{
         extract_component::ExtractComponent,
         render_resource::{
-            binding_types::uniform_buffer, AsBindGroup, BindGroup, BindGroupLayoutDescriptor,
-            BindGroupLayoutEntries, CachedComputePipelineId, ComputePass, PipelineCache,
-            ShaderStages,
+            AsBindGroup, BindGroup, BindGroupLayoutDescriptor, CachedComputePipelineId,
+            ComputePass, PipelineCache,
         },
         renderer::RenderDevice,
     },
 };
 
 use crate::{
-    fluid_source::fluid_source_uniform::{FluidSourceUniform, FluidSourceUniformBindGroup},
+    fluid_source::fluid_source_uniform::{
+        fluid_source_uniform_bind_group_layout_desc, FluidSourceUniformBindGroup,
+    },
     pipeline::{is_pipeline_loaded, queue_compute_pipeline, HasBindGroupLayout},
     plugin::FluidComputePass,
+    settings::FluidMode,
 };
 
 pub(crate) struct UpdateFluidSourcePass;
@@ -34,7 +36,7 @@ impl FluidComputePass for UpdateFluidSourcePass {
 #[derive(QueryData)]
 pub(crate) struct UpdateFluidSourceBindGroupsQuery {
     bind_group: &'static UpdateFluidSourceBindGroup,
-    uniform_bind_group: &'static FluidSourceUniformBindGroup,
+    pub uniform_bind_group: &'static FluidSourceUniformBindGroup,
 }
 
 #[derive(Resource)]
@@ -53,13 +55,7 @@ impl FromWorld for UpdateFluidSourcePipeline {
     fn from_world(world: &mut World) -> Self {
         let render_device = world.resource::<RenderDevice>();
 
-        let fluid_source_uniform_bind_group_layout = BindGroupLayoutDescriptor::new(
-            "FluidSourceUniformBindGroupLayout",
-            &BindGroupLayoutEntries::single(
-                ShaderStages::COMPUTE,
-                uniform_buffer::<FluidSourceUniform>(true),
-            ),
-        );
+        let fluid_source_uniform_bind_group_layout = fluid_source_uniform_bind_group_layout_desc();
 
         let bind_group_layout =
             UpdateFluidSourceResource::bind_group_layout_descriptor(render_device);
@@ -136,14 +132,23 @@ pub(crate) struct UpdateFluidSourceResource {
     pub u: Handle<Image>,
     #[storage_texture(2, image_format = R32Float, access = WriteOnly)]
     pub v: Handle<Image>,
+    /// 1 if [`crate::settings::FluidMode::SinglePhase`], where the level set is left untouched.
+    #[uniform(3)]
+    pub single_phase: u32,
 }
 
 impl UpdateFluidSourceResource {
-    pub fn new(levelset_air: &Handle<Image>, u: &Handle<Image>, v: &Handle<Image>) -> Self {
+    pub fn new(
+        levelset_air: &Handle<Image>,
+        u: &Handle<Image>,
+        v: &Handle<Image>,
+        mode: FluidMode,
+    ) -> Self {
         Self {
             levelset_air: levelset_air.clone(),
             u: u.clone(),
             v: v.clone(),
+            single_phase: (mode == FluidMode::SinglePhase) as u32,
         }
     }
 }
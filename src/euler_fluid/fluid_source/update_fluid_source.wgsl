@@ -1,29 +1,10 @@
 #import bevy_render::view::uv_to_ndc;
-
-struct FluidSourceData {
-    center: vec2f,
-    data: vec2f,
-    velocity: vec2f,
-    shape: u32,
-    mode: u32,
-}
-
-struct FluidSourceUniform {
-    data: array<FluidSourceData, 16>,
-    count: u32,
-}
-
-const SHAPE_CIRCLE: u32 = 0;
-const SHAPE_AABB: u32 = 1;
-
-const MODE_SOURCE: u32 = 0;
-const MODE_SINK: u32 = 1;
-
-const LARGE_FLOAT: f32 = 1.0e6;
+#import bevy_fluid::fluid_source::{FluidSourceUniform, MODE_SOURCE, level_source};
 
 @group(0) @binding(0) var levelset_air: texture_storage_2d<r32float, read_write>;
 @group(0) @binding(1) var u: texture_storage_2d<r32float, write>;
 @group(0) @binding(2) var v: texture_storage_2d<r32float, write>;
+@group(0) @binding(3) var<uniform> single_phase: u32;
 
 @group(1) @binding(0) var<uniform> fluid_source_uniform: FluidSourceUniform;
 
@@ -59,8 +40,12 @@ fn update_fluid_source(
         }
     }
 
-    textureStore(levelset_air, idx, vec4f(new_level, vec3f(0.0)));
-    
+    // The whole domain is filled with fluid in single phase, where the level set is neither advected nor reinitialized.
+    // Sources only drive the velocity there, and sinks have no effect.
+    if single_phase == 0u {
+        textureStore(levelset_air, idx, vec4f(new_level, vec3f(0.0)));
+    }
+
     if velocity_updated {
         textureStore(u, idx, vec4f(velocity.x, vec3f(0.0)));
         textureStore(u, idx + vec2u(1, 0), vec4f(velocity.x, vec3f(0.0)));
@@ -68,41 +53,3 @@ fn update_fluid_source(
         textureStore(v, idx + vec2u(0, 1), vec4f(velocity.y, vec3f(0.0)));
     }
 }
-
-fn level_source(data: FluidSourceData, position: vec2f) -> f32 {
-    switch data.shape {
-        case SHAPE_CIRCLE: {
-            let radius = data.data.x;
-            return distance(data.center, position) - radius;
-            
-        }
-        case SHAPE_AABB: {
-            let half_size = data.data;
-            return level_aabb(half_size, data.center, position);
-        }
-        default: {
-            return 0.0;
-        }
-    }
-}
-
-fn level_aabb(half_size: vec2f, center: vec2f, x: vec2f) -> f32 {
-    var level = LARGE_FLOAT;
-    let d = abs(center - x) - half_size;
-    let is_inside_x = d.x < 0;
-    let is_inside_y = d.y < 0;
-    if (is_inside_x) {
-        if (is_inside_y) {
-            level = max(d.x, d.y);
-        } else {
-            level = d.y;
-        }
-    } else {
-        if (is_inside_y) {
-            level = d.x;
-        } else {
-            level = length(d);
-        }
-    }
-    return level;
-}
\ No newline at end of file
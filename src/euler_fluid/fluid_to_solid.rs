@@ -1,18 +1,15 @@
 use crate::{
     fluid_uniform::uniform_bind_group_layout_desc,
-    obstacle::{SolidEntities, SolidObstaclesBuffer},
-    physics_time::PhysicsFrameInfo,
+    obstacle::{SolidEntities, SolidGeometryBuffers, SolidObstaclesBuffer},
     pipeline::Pipeline,
-    settings::FluidGridLength,
+    projection::solid_coupling::BodyVelocityResource,
+    rigid_body_backend::SolidObstacleSystems,
 };
-use avian2d::prelude::{Forces, RigidBody, WriteRigidBodyForces};
 use bevy::{
     asset::{embedded_asset, load_embedded_asset},
     prelude::*,
     render::{
         extract_component::{ExtractComponent, ExtractComponentPlugin},
-        extract_resource::ExtractResourcePlugin,
-        gpu_readback::ReadbackComplete,
         render_asset::RenderAssets,
         render_resource::{
             AsBindGroup, BindGroup, BindGroupLayoutDescriptor, CachedComputePipelineId,
@@ -25,8 +22,6 @@ use bevy::{
     },
 };
 
-pub const MAX_SOLIDS: usize = 256;
-
 pub(crate) struct FluidToSolidForcesPlugin;
 
 #[derive(Component, Clone, ExtractComponent, AsBindGroup)]
@@ -43,6 +38,12 @@ pub(crate) struct SampleForcesResource {
     pub solid_id: Handle<Image>,
     #[storage_texture(5, image_format = R32Float, access = ReadOnly)]
     pub p0: Handle<Image>,
+    #[storage_texture(6, image_format = R32Float, access = ReadOnly)]
+    pub u0: Handle<Image>,
+    #[storage_texture(7, image_format = R32Float, access = ReadOnly)]
+    pub v0: Handle<Image>,
+    #[storage_texture(8, image_format = R32Float, access = ReadOnly)]
+    pub levelset_air0: Handle<Image>,
 }
 
 #[derive(Component, Clone, ExtractComponent, AsBindGroup)]
@@ -55,6 +56,28 @@ pub(crate) struct AccumulateForcesResource {
     pub bins_torque: Handle<ShaderStorageBuffer>,
     #[storage(3, visibility(compute))]
     pub forces: Handle<ShaderStorageBuffer>,
+    /// Length of the bins, which grows with the number of obstacles in the fluid.
+    pub num_solids: u32,
+}
+
+/// How the pressure of the fluid is coupled with the obstacles.
+/// Insert this component to the entity with [`crate::settings::FluidSettings`] to override the default [`SolidCouplingMethod::AreaFraction`].
+#[derive(Component, ExtractComponent, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SolidCouplingMethod {
+    /// Pressure of each cell is applied to the solid fractions of its faces.
+    /// Cheap, but the boundary is resolved only up to the grid, so forces jitter as the obstacles move across cells.
+    #[default]
+    AreaFraction,
+    /// Pressure is integrated along the exact boundary of the obstacles, at points placed every half a cell.
+    /// Pressure at each point is extrapolated from the fluid along the normal, with the free surface taken into account.
+    BoundaryQuadrature,
+    /// Velocities of the dynamic bodies are solved together with the pressure, as in the variational coupling by Batty et al. (2007),
+    /// so that light bodies stay stable and stacked heavy bodies do not leak fluid.
+    /// The solid faces of the fluid move with the updated velocities of the bodies,
+    /// and the same pressure forces by which the bodies were updated are handed over to [`crate::rigid_body_backend::FluidRigidBodyBackend::apply_force`].
+    /// Only dynamic avian2d bodies take part, whose densities are regarded as in the same units as [`crate::settings::FluidSettings::rho`].
+    /// Requires [`crate::projection::ProjectionMethod::ConjugateGradient`], and falls back to [`SolidCouplingMethod::AreaFraction`] otherwise.
+    Monolithic,
 }
 
 #[derive(Clone, Copy, Default, ShaderType)]
@@ -66,19 +89,28 @@ pub struct FluidToSolidForce {
 #[derive(Resource)]
 pub(crate) struct FluidToSolidForcesPipeline {
     pub sample_forces_pipeline: CachedComputePipelineId,
+    pub sample_shear_pipeline: CachedComputePipelineId,
+    pub sample_boundary_forces_pipeline: CachedComputePipelineId,
     pub accumulate_forces_pipeline: CachedComputePipelineId,
+    pub accumulate_coupled_forces_pipeline: CachedComputePipelineId,
     sample_forces_bind_group_layout: BindGroupLayoutDescriptor,
     accumulate_forces_bind_group_layout: BindGroupLayoutDescriptor,
     solid_obstacles_bind_group_layout: BindGroupLayoutDescriptor,
+    body_velocity_bind_group_layout: BindGroupLayoutDescriptor,
 }
 
 #[derive(Component)]
 pub(crate) struct FluidToSolidForcesBindGroups {
     pub sample_forces_bind_group: BindGroup,
     pub accumulate_forces_bind_group: BindGroup,
+    pub num_solids: u32,
+    pub method: SolidCouplingMethod,
+    pub num_boundary_points: u32,
+    /// Forces of the monolithic coupling, only with the conjugate gradient solver.
+    pub coupled_forces_bind_group: Option<BindGroup>,
 }
 
-#[derive(Resource)]
+#[derive(Component)]
 pub(crate) struct SolidObstaclesBindGroups {
     pub solid_obstacles_bind_group: BindGroup,
 }
@@ -91,9 +123,13 @@ impl Plugin for FluidToSolidForcesPlugin {
         app.add_plugins((
             ExtractComponentPlugin::<SampleForcesResource>::default(),
             ExtractComponentPlugin::<AccumulateForcesResource>::default(),
-            ExtractResourcePlugin::<SolidObstaclesBuffer>::default(),
+            ExtractComponentPlugin::<SolidObstaclesBuffer>::default(),
+            ExtractComponentPlugin::<SolidCouplingMethod>::default(),
         ))
-        .add_systems(Update, initialize_buffer);
+        .add_systems(
+            Update,
+            initialize_buffer.after(SolidObstacleSystems::Upload),
+        );
 
         let render_app = app.sub_app_mut(RenderApp);
         render_app.add_systems(
@@ -103,25 +139,20 @@ impl Plugin for FluidToSolidForcesPlugin {
     }
 
     fn finish(&self, app: &mut App) {
-        app.init_resource::<SolidObstaclesBuffer>();
+        app.init_resource::<SolidGeometryBuffers>();
 
         let render_app = app.sub_app_mut(RenderApp);
         render_app.init_resource::<FluidToSolidForcesPipeline>();
     }
 }
 
-impl FromWorld for SolidObstaclesBuffer {
-    fn from_world(world: &mut World) -> Self {
-        let mut buffers = world.resource_mut::<Assets<ShaderStorageBuffer>>();
-        let obstacles = buffers.add(ShaderStorageBuffer::from(vec![0; 0]));
-        Self { obstacles }
-    }
-}
-
 impl Pipeline for FluidToSolidForcesPipeline {
     fn is_pipeline_state_ready(&self, pipeline_cache: &PipelineCache) -> bool {
         Self::is_pipeline_loaded(pipeline_cache, self.sample_forces_pipeline)
+            && Self::is_pipeline_loaded(pipeline_cache, self.sample_shear_pipeline)
+            && Self::is_pipeline_loaded(pipeline_cache, self.sample_boundary_forces_pipeline)
             && Self::is_pipeline_loaded(pipeline_cache, self.accumulate_forces_pipeline)
+            && Self::is_pipeline_loaded(pipeline_cache, self.accumulate_coupled_forces_pipeline)
     }
 }
 
@@ -138,101 +169,109 @@ impl FromWorld for FluidToSolidForcesPipeline {
             AccumulateForcesResource::bind_group_layout_descriptor(render_device);
         let solid_obstacles_bind_group_layout =
             SolidObstaclesBuffer::bind_group_layout_descriptor(render_device);
+        let body_velocity_bind_group_layout =
+            BodyVelocityResource::bind_group_layout_descriptor(render_device);
 
-        let sample_forces_pipeline =
+        let sample_forces_shader =
+            load_embedded_asset!(asset_server, "shaders/fluid_to_solid/sample_forces.wgsl");
+        let queue_sample_pipeline = |label: &'static str, entry_point: &'static str| {
             pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
-                label: Some("SampleForcesPipeline".into()),
+                label: Some(label.into()),
                 layout: vec![
                     sample_forces_bind_group_layout.clone(),
                     solid_obstacles_bind_group_layout.clone(),
                     uniform_bind_group_layout.clone(),
                 ],
-                shader: load_embedded_asset!(
-                    asset_server,
-                    "shaders/fluid_to_solid/sample_forces.wgsl"
-                ),
-                entry_point: Some("sample_forces_to_solid".into()),
+                shader: sample_forces_shader.clone(),
+                entry_point: Some(entry_point.into()),
                 ..default()
-            });
+            })
+        };
+        let sample_forces_pipeline =
+            queue_sample_pipeline("SampleForcesPipeline", "sample_forces_to_solid");
+        let sample_shear_pipeline =
+            queue_sample_pipeline("SampleShearPipeline", "sample_shear_to_solid");
+        let sample_boundary_forces_pipeline = queue_sample_pipeline(
+            "SampleBoundaryForcesPipeline",
+            "sample_boundary_forces_to_solid",
+        );
 
+        let accumulate_forces_shader = load_embedded_asset!(
+            asset_server,
+            "shaders/fluid_to_solid/accumulate_forces.wgsl"
+        );
         let accumulate_forces_pipeline =
             pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
                 label: Some("AccumulateForcesPipeline".into()),
                 layout: vec![accumulate_forces_bind_group_layout.clone()],
-                shader: load_embedded_asset!(
-                    asset_server,
-                    "shaders/fluid_to_solid/accumulate_forces.wgsl"
-                ),
+                shader: accumulate_forces_shader.clone(),
                 entry_point: Some("accumulate_forces".into()),
                 ..default()
             });
+        // The forces of the monolithic coupling are read from the bind group of BodyVelocityResource.
+        let accumulate_coupled_forces_pipeline =
+            pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                label: Some("AccumulateCoupledForcesPipeline".into()),
+                layout: vec![
+                    accumulate_forces_bind_group_layout.clone(),
+                    body_velocity_bind_group_layout.clone(),
+                    uniform_bind_group_layout.clone(),
+                ],
+                shader: accumulate_forces_shader,
+                entry_point: Some("accumulate_coupled_forces".into()),
+                ..default()
+            });
 
         FluidToSolidForcesPipeline {
             sample_forces_pipeline,
+            sample_shear_pipeline,
+            sample_boundary_forces_pipeline,
             accumulate_forces_pipeline,
+            accumulate_coupled_forces_pipeline,
             sample_forces_bind_group_layout,
             accumulate_forces_bind_group_layout,
             solid_obstacles_bind_group_layout,
-        }
-    }
-}
-
-pub(crate) fn forces_to_solid_readback(
-    trigger: On<ReadbackComplete>,
-    mut query: Query<(Forces, &RigidBody)>,
-    query_solidentities: Query<&SolidEntities>,
-    grid_length: Res<FluidGridLength>,
-    physics_frame_info: Res<PhysicsFrameInfo>,
-    mut last_physics_step: Local<u64>,
-) {
-    if physics_frame_info.step_number == *last_physics_step {
-        // info!("Skipping forces to solid readback for physics step {}. GPU readback has already been performed for this step.", *last_physics_step);
-        return;
-    }
-    *last_physics_step = physics_frame_info.step_number;
-
-    let data: Vec<FluidToSolidForce> = trigger.event().to_shader_type();
-    for fluids in &query_solidentities {
-        for (idx, entity) in fluids.entities.iter().enumerate() {
-            let rigid_body = query.get_mut(*entity);
-            if let Ok((mut forces, rigid_body)) = rigid_body {
-                if *rigid_body == RigidBody::Dynamic {
-                    let mut force = data[idx].force * physics_frame_info.delta_secs / grid_length.0;
-                    force.y *= -1.0;
-                    let torque = data[idx].torque * physics_frame_info.delta_secs / grid_length.0;
-                    forces.apply_force(force);
-                    forces.apply_torque(-torque);
-                }
-            }
+            body_velocity_bind_group_layout,
         }
     }
 }
 
 fn initialize_buffer(
-    query: Query<&AccumulateForcesResource>,
+    mut query: Query<(&mut AccumulateForcesResource, &SolidEntities)>,
     mut buffers: ResMut<Assets<ShaderStorageBuffer>>,
 ) {
-    // info!("Initializing forces to solid buffers");
-    for bins in query.iter() {
+    for (mut bins, solid_entities) in &mut query {
+        // Storage buffers can not be empty.
+        let num_solids = solid_entities.entities.len().max(1);
+        bins.num_solids = num_solids as u32;
+
         let bins_force_x = buffers.get_mut(&bins.bins_force_x).unwrap();
-        bins_force_x.set_data(vec![0.0; MAX_SOLIDS]);
+        bins_force_x.set_data(vec![0.0; num_solids]);
 
         let bins_force_y = buffers.get_mut(&bins.bins_force_y).unwrap();
-        bins_force_y.set_data(vec![0.0; MAX_SOLIDS]);
+        bins_force_y.set_data(vec![0.0; num_solids]);
 
         let bins_torque = buffers.get_mut(&bins.bins_torque).unwrap();
-        bins_torque.set_data(vec![0.0; MAX_SOLIDS]);
+        bins_torque.set_data(vec![0.0; num_solids]);
 
         let forces_buffer = buffers.get_mut(&bins.forces).unwrap();
-        forces_buffer.set_data(vec![FluidToSolidForce::default(); MAX_SOLIDS]);
+        forces_buffer.set_data(vec![FluidToSolidForce::default(); num_solids]);
     }
 }
 
+type FluidToSolidForcesResourcesQuery = (
+    Entity,
+    &'static SampleForcesResource,
+    &'static AccumulateForcesResource,
+    &'static SolidObstaclesBuffer,
+    &'static SolidCouplingMethod,
+    Option<&'static BodyVelocityResource>,
+);
+
 fn prepare_bind_groups<'a>(
     mut commands: Commands,
     pipeline: Res<FluidToSolidForcesPipeline>,
-    query: Query<(Entity, &SampleForcesResource, &AccumulateForcesResource)>,
-    solid_obstacles: Res<SolidObstaclesBuffer>,
+    query: Query<FluidToSolidForcesResourcesQuery>,
     render_device: Res<RenderDevice>,
     pipeline_cache: Res<PipelineCache>,
     mut param: (
@@ -241,7 +280,15 @@ fn prepare_bind_groups<'a>(
         Res<'a, RenderAssets<GpuShaderStorageBuffer>>,
     ),
 ) {
-    for (entity, sample_forces_resource, accumulate_forces_resource) in &query {
+    for (
+        entity,
+        sample_forces_resource,
+        accumulate_forces_resource,
+        solid_obstacles,
+        method,
+        body_velocity_resource,
+    ) in &query
+    {
         let sample_forces_bind_group = sample_forces_resource
             .as_bind_group(
                 &pipeline.sample_forces_bind_group_layout,
@@ -262,25 +309,40 @@ fn prepare_bind_groups<'a>(
             .unwrap()
             .bind_group;
 
-        commands
-            .entity(entity)
-            .insert(FluidToSolidForcesBindGroups {
+        let solid_obstacles_bind_group = solid_obstacles
+            .as_bind_group(
+                &pipeline.solid_obstacles_bind_group_layout,
+                &render_device,
+                &pipeline_cache,
+                &mut param,
+            )
+            .unwrap()
+            .bind_group;
+
+        let coupled_forces_bind_group = body_velocity_resource.map(|body_velocity_resource| {
+            body_velocity_resource
+                .as_bind_group(
+                    &pipeline.body_velocity_bind_group_layout,
+                    &render_device,
+                    &pipeline_cache,
+                    &mut param,
+                )
+                .unwrap()
+                .bind_group
+        });
+
+        commands.entity(entity).insert((
+            FluidToSolidForcesBindGroups {
                 sample_forces_bind_group,
                 accumulate_forces_bind_group,
-            });
+                num_solids: accumulate_forces_resource.num_solids,
+                method: *method,
+                num_boundary_points: solid_obstacles.num_boundary_points,
+                coupled_forces_bind_group,
+            },
+            SolidObstaclesBindGroups {
+                solid_obstacles_bind_group,
+            },
+        ));
     }
-
-    let solid_obstacles_bind_group = solid_obstacles
-        .as_bind_group(
-            &pipeline.solid_obstacles_bind_group_layout,
-            &render_device,
-            &pipeline_cache,
-            &mut param,
-        )
-        .unwrap()
-        .bind_group;
-
-    commands.insert_resource(SolidObstaclesBindGroups {
-        solid_obstacles_bind_group,
-    });
 }
@@ -1,3 +1,4 @@
+#[cfg(feature = "avian2d")]
 use avian2d::physics_transform::PhysicsTransformSystems;
 use bevy::{
     prelude::*,
@@ -16,8 +17,15 @@ use bevy::{
 };
 
 use crate::{
+    advection::InterpolationMethod,
+    boundary_condition::FluidBoundaryConditions,
+    diagnostics::component::FluidMaxVelocityMagnitude,
     physics_time::FluidTimeStep,
+    scalar_field::FluidTemperature,
     settings::{FluidGridLength, FluidSettings},
+    substep::FluidSubsteps,
+    velocity_transfer::VelocityTransferMethod,
+    vorticity_confinement::VorticityConfinement,
 };
 
 pub(crate) struct SimulationUniformPlugin;
@@ -27,9 +35,22 @@ pub struct SimulationUniform {
     pub dx: f32,
     pub dt: f32,
     pub rho: f32,
+    pub viscosity: f32,
+    pub surface_tension: f32,
+    pub vorticity_confinement: f32,
+    pub ambient_temperature: f32,
+    pub thermal_expansion: f32,
     pub gravity: Vec2,
     pub fluid_transform: Mat4,
     pub size: Vec2,
+    pub boundary_types: UVec4,
+    /// Weight of the fluid velocity in the tangential velocity of the wall cells at each edge. 1 for free-slip walls.
+    pub wall_slip: Vec4,
+    pub inflow_velocity_x: Vec4,
+    pub inflow_velocity_y: Vec4,
+    pub interpolation: u32,
+    pub velocity_transfer: u32,
+    pub flip_ratio: f32,
 }
 
 #[derive(Resource)]
@@ -46,11 +67,18 @@ impl Plugin for SimulationUniformPlugin {
         app.add_plugins((
             ExtractComponentPlugin::<SimulationUniform>::default(),
             UniformComponentPlugin::<SimulationUniform>::default(),
-        ))
-        .add_systems(
+        ));
+
+        #[cfg(feature = "avian2d")]
+        app.add_systems(
             FixedPostUpdate,
             update_simulation_uniform.after(PhysicsTransformSystems::Propagate),
         );
+        #[cfg(not(feature = "avian2d"))]
+        app.add_systems(
+            FixedPostUpdate,
+            update_simulation_uniform.after(crate::physics_time::update_physics_frame_info),
+        );
 
         let render_app = app.sub_app_mut(RenderApp);
         render_app.add_systems(
@@ -105,17 +133,57 @@ pub(crate) fn prepare_bind_groups(
     }
 }
 
+type SimulationUniformQuery = (
+    &'static mut SimulationUniform,
+    &'static FluidSettings,
+    &'static Transform,
+    Option<&'static VorticityConfinement>,
+    Option<&'static FluidTemperature>,
+    Option<&'static FluidBoundaryConditions>,
+    Option<&'static InterpolationMethod>,
+    &'static VelocityTransferMethod,
+    &'static mut FluidSubsteps,
+    Option<&'static FluidMaxVelocityMagnitude>,
+);
+
 fn update_simulation_uniform(
-    mut query: Query<(&mut SimulationUniform, &FluidSettings, &Transform)>,
+    mut query: Query<SimulationUniformQuery>,
     time_step: Res<FluidTimeStep>,
     grid_length: Res<FluidGridLength>,
 ) {
-    for (mut uniform, settings, transform) in &mut query {
+    for (
+        mut uniform,
+        settings,
+        transform,
+        vorticity_confinement,
+        temperature,
+        boundary_conditions,
+        interpolation,
+        velocity_transfer,
+        mut substeps,
+        max_velocity,
+    ) in &mut query
+    {
+        *substeps =
+            FluidSubsteps::from_cfl(max_velocity.map_or(0.0, |v| v.0), time_step.0, settings.cfl);
         uniform.dx = grid_length.0;
-        uniform.dt = time_step.0;
+        uniform.dt = time_step.0 / substeps.0 as f32;
         uniform.rho = settings.rho;
+        uniform.viscosity = settings.viscosity;
+        uniform.surface_tension = settings.surface_tension;
+        uniform.vorticity_confinement = vorticity_confinement.map_or(0.0, |v| v.strength);
+        uniform.ambient_temperature = temperature.map_or(0.0, |t| t.ambient);
+        uniform.thermal_expansion = temperature.map_or(0.0, |t| t.expansion_coefficient);
         uniform.gravity = settings.gravity;
         uniform.fluid_transform = transform.to_matrix();
         uniform.size = settings.size.as_vec2();
+        let boundary_conditions = boundary_conditions.copied().unwrap_or_default();
+        uniform.boundary_types = boundary_conditions.boundary_types();
+        uniform.wall_slip = boundary_conditions.wall_slip();
+        (uniform.inflow_velocity_x, uniform.inflow_velocity_y) =
+            boundary_conditions.inflow_velocities();
+        uniform.interpolation = interpolation.copied().unwrap_or_default().as_u32();
+        uniform.velocity_transfer = velocity_transfer.as_u32();
+        uniform.flip_ratio = velocity_transfer.flip_ratio();
     }
 }
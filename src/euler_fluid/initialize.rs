@@ -56,6 +56,9 @@ pub(crate) struct InitializeGridCenterResource {
     pub levelset_air1: Handle<Image>,
     #[storage_texture(2, image_format = Rg32Float, access = WriteOnly)]
     pub grad_levelset_air: Handle<Image>,
+    /// 1 if [`crate::settings::FluidMode::SinglePhase`], otherwise 0.
+    #[uniform(3)]
+    pub single_phase: u32,
 }
 
 #[derive(Resource)]
@@ -1,18 +1,25 @@
-use avian2d::{
-    parry::shape::{Ball, Capsule, Cuboid, ShapeType, Triangle},
-    prelude::*,
-};
+pub mod boundary_quadrature;
+
+#[cfg(feature = "avian2d")]
+use avian2d::parry::shape::{Ball, Capsule, Cuboid, RoundCuboid, Triangle};
 use bevy::{
     prelude::*,
     render::{
         extract_component::ExtractComponent,
-        extract_resource::ExtractResource,
         render_resource::{AsBindGroup, ShaderType},
         storage::ShaderStorageBuffer,
     },
 };
 
-use crate::settings::FluidSettings;
+// Corresponds to parry2d::shape::shape::ShapeType, as well as the constants in solid_obstacle.wgsl.
+const SHAPE_CIRCLE: u32 = 0;
+const SHAPE_RECTANGLE: u32 = 1;
+const SHAPE_CAPSULE: u32 = 2;
+const SHAPE_TRIANGLE: u32 = 4;
+const SHAPE_POLYLINE: u32 = 7;
+const SHAPE_COMPOUND: u32 = 10;
+const SHAPE_CONVEX_POLYGON: u32 = 11;
+const SHAPE_ROUND_RECTANGLE: u32 = 12;
 
 #[derive(ShaderType, Default, Copy, Clone)]
 pub struct ShapeVariant {
@@ -29,12 +36,166 @@ pub struct SolidObstacle {
     pub inverse_transform: Mat4,
     pub linear_velocity: Vec2,
     pub angular_velocity: f32,
+    /// Center of mass in the local space, around which the obstacle rotates.
+    pub center_of_mass: Vec2,
+    /// Scale of the viscous shear exerted by the fluid. See [`FluidDragCoefficient`].
+    pub drag_coefficient: f32,
+    /// Inverse mass and moment of inertia, for [`crate::fluid_to_solid::SolidCouplingMethod::Monolithic`].
+    /// Zero for bodies whose motion is not affected by the fluid, such as static and kinematic ones.
+    pub inverse_mass: f32,
+    pub inverse_inertia: f32,
+}
+
+impl SolidObstacle {
+    /// `entity_id` is assigned when the obstacles are uploaded to GPU.
+    pub fn new(
+        shape: ShapeVariant,
+        transform: &GlobalTransform,
+        center_of_mass: Vec2,
+        linear_velocity: Vec2,
+        angular_velocity: f32,
+    ) -> Self {
+        let transform = transform.to_matrix();
+        Self {
+            entity_id: 0,
+            shape,
+            transform,
+            inverse_transform: transform.inverse(),
+            linear_velocity,
+            angular_velocity,
+            center_of_mass,
+            drag_coefficient: 1.0,
+            inverse_mass: 0.0,
+            inverse_inertia: 0.0,
+        }
+    }
+}
+
+/// A part of a compound shape, placed relative to the obstacle.
+#[derive(ShaderType, Default, Copy, Clone)]
+pub(crate) struct SolidObstaclePart {
+    pub shape: ShapeVariant,
+    pub transform: Mat4,
+    pub inverse_transform: Mat4,
 }
 
-#[derive(Resource, Clone, ExtractResource, AsBindGroup)]
+/// Variable-length shape data of the obstacles, such as polygon vertices and compound parts.
+/// Shapes built here refer to the data by offset, so they are only valid in the frame they are built.
+#[derive(Default)]
+pub struct SolidGeometry {
+    pub(crate) vertices: Vec<Vec2>,
+    pub(crate) parts: Vec<SolidObstaclePart>,
+}
+
+impl SolidGeometry {
+    /// Convex polygon with vertices in counter-clockwise order.
+    pub fn convex_polygon(&mut self, vertices: &[Vec2]) -> ShapeVariant {
+        let offset = self.vertices.len();
+        self.vertices.extend_from_slice(vertices);
+        ShapeVariant {
+            shape: SHAPE_CONVEX_POLYGON,
+            values: [offset as f32, vertices.len() as f32, 0.0, 0.0, 0.0, 0.0],
+        }
+    }
+
+    /// Set of line segments. The segments have no interior, so they are rasterized as walls of one cell thickness.
+    pub fn polyline(&mut self, segments: impl IntoIterator<Item = [Vec2; 2]>) -> ShapeVariant {
+        let offset = self.vertices.len();
+        self.vertices.extend(segments.into_iter().flatten());
+        ShapeVariant {
+            shape: SHAPE_POLYLINE,
+            values: [
+                offset as f32,
+                (self.vertices.len() - offset) as f32,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+            ],
+        }
+    }
+
+    /// Union of shapes placed relative to the obstacle. Nested compound shapes are not supported.
+    pub fn compound(
+        &mut self,
+        parts: impl IntoIterator<Item = (Isometry2d, ShapeVariant)>,
+    ) -> ShapeVariant {
+        let offset = self.parts.len();
+        self.parts
+            .extend(parts.into_iter().map(|(isometry, shape)| {
+                let transform = Mat4::from_rotation_translation(
+                    Quat::from_rotation_z(isometry.rotation.as_radians()),
+                    isometry.translation.extend(0.0),
+                );
+                SolidObstaclePart {
+                    shape,
+                    transform,
+                    inverse_transform: transform.inverse(),
+                }
+            }));
+        ShapeVariant {
+            shape: SHAPE_COMPOUND,
+            values: [
+                offset as f32,
+                (self.parts.len() - offset) as f32,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+            ],
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.vertices.clear();
+        self.parts.clear();
+    }
+}
+
+/// Obstacles overlapping the domain of a fluid. Entity ids of the obstacles are the indices in this buffer.
+#[derive(Component, Clone, ExtractComponent, AsBindGroup)]
 pub(crate) struct SolidObstaclesBuffer {
     #[storage(0, read_only, visibility(compute))]
     pub obstacles: Handle<ShaderStorageBuffer>,
+    #[storage(1, read_only, visibility(compute))]
+    pub vertices: Handle<ShaderStorageBuffer>,
+    #[storage(2, read_only, visibility(compute))]
+    pub parts: Handle<ShaderStorageBuffer>,
+    /// Filled only for [`crate::fluid_to_solid::SolidCouplingMethod::BoundaryQuadrature`].
+    #[storage(3, read_only, visibility(compute))]
+    pub boundary_points: Handle<ShaderStorageBuffer>,
+    pub num_boundary_points: u32,
+}
+
+/// [`SolidGeometry`] of all the obstacles, shared by the [`SolidObstaclesBuffer`] of every fluid.
+#[derive(Resource)]
+pub(crate) struct SolidGeometryBuffers {
+    pub vertices: Handle<ShaderStorageBuffer>,
+    pub parts: Handle<ShaderStorageBuffer>,
+}
+
+impl FromWorld for SolidGeometryBuffers {
+    fn from_world(world: &mut World) -> Self {
+        let mut buffers = world.resource_mut::<Assets<ShaderStorageBuffer>>();
+        let vertices = buffers.add(ShaderStorageBuffer::from(vec![0; 0]));
+        let parts = buffers.add(ShaderStorageBuffer::from(vec![0; 0]));
+        Self { vertices, parts }
+    }
+}
+
+impl SolidGeometryBuffers {
+    pub fn new_obstacles_buffer(
+        &self,
+        buffers: &mut Assets<ShaderStorageBuffer>,
+    ) -> SolidObstaclesBuffer {
+        SolidObstaclesBuffer {
+            obstacles: buffers.add(ShaderStorageBuffer::from(vec![0; 0])),
+            vertices: self.vertices.clone(),
+            parts: self.parts.clone(),
+            boundary_points: buffers.add(ShaderStorageBuffer::from(vec![0; 0])),
+            num_boundary_points: 0,
+        }
+    }
 }
 
 #[derive(Component, Clone, ExtractComponent)]
@@ -42,24 +203,204 @@ pub struct SolidEntities {
     pub entities: Vec<Entity>,
 }
 
+/// Excludes a body from the coupling with fluids, so that it neither moves the fluid nor receives forces from it.
+/// Also prevents a trimesh or heightfield `Collider` from being inserted as [`crate::terrain::FluidTerrain`].
+///
+/// With the `avian2d` feature, bodies can also be filtered per fluid by inserting `CollisionLayers` to the fluid.
+/// A body is coupled with the fluid only if their layers interact, as if the fluid were a collider.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct FluidIgnore;
+
+/// Scales the viscous shear, or skin friction, exerted by the fluid on a body. Defaults to 1.0 without this component.
+/// The shear is proportional to the `viscosity` in [`crate::settings::FluidSettings`], so inviscid fluids exert none.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct FluidDragCoefficient(pub f32);
+
+impl Default for FluidDragCoefficient {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Solid obstacle owned by this crate, which interacts with the fluid without a physics engine.
+/// The obstacle is placed by its [`GlobalTransform`] and moves the fluid around it with the given velocities.
+/// Forces exerted by the fluid are written to [`FluidObstacleForces`].
+///
+/// With the `avian2d` feature, rigid bodies with an avian2d `Collider` are coupled as well,
+/// so do not insert both to the same entity.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(Transform, FluidObstacleForces)]
+pub struct FluidObstacle {
+    pub shape: FluidObstacleShape,
+    pub linear_velocity: Vec2,
+    pub angular_velocity: f32,
+}
+
+impl FluidObstacle {
+    pub fn new(shape: impl Into<FluidObstacleShape>) -> Self {
+        Self {
+            shape: shape.into(),
+            linear_velocity: Vec2::ZERO,
+            angular_velocity: 0.0,
+        }
+    }
+}
+
+/// Shape of a [`FluidObstacle`] in its local space.
+#[derive(Clone, Copy, Debug)]
+pub enum FluidObstacleShape {
+    Circle(Circle),
+    Rectangle(Rectangle),
+    Capsule(Capsule2d),
+    Triangle(Triangle2d),
+}
+
+impl FluidObstacleShape {
+    /// Center of mass in the local space, assuming uniform density.
+    pub fn center_of_mass(&self) -> Vec2 {
+        match self {
+            FluidObstacleShape::Triangle(triangle) => {
+                let [a, b, c] = triangle.vertices;
+                (a + b + c) / 3.0
+            }
+            _ => Vec2::ZERO,
+        }
+    }
+}
+
+impl From<Circle> for FluidObstacleShape {
+    fn from(circle: Circle) -> Self {
+        Self::Circle(circle)
+    }
+}
+
+impl From<Rectangle> for FluidObstacleShape {
+    fn from(rectangle: Rectangle) -> Self {
+        Self::Rectangle(rectangle)
+    }
+}
+
+impl From<Capsule2d> for FluidObstacleShape {
+    fn from(capsule: Capsule2d) -> Self {
+        Self::Capsule(capsule)
+    }
+}
+
+impl From<Triangle2d> for FluidObstacleShape {
+    fn from(triangle: Triangle2d) -> Self {
+        Self::Triangle(triangle)
+    }
+}
+
+/// Force and torque exerted by the fluid on a [`FluidObstacle`] in the latest step.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct FluidObstacleForces {
+    pub force: Vec2,
+    pub torque: f32,
+}
+
+impl From<&FluidObstacleShape> for ShapeVariant {
+    fn from(shape: &FluidObstacleShape) -> Self {
+        match shape {
+            FluidObstacleShape::Circle(circle) => Self {
+                shape: SHAPE_CIRCLE,
+                values: [circle.radius, 0.0, 0.0, 0.0, 0.0, 0.0],
+            },
+            FluidObstacleShape::Rectangle(rectangle) => Self {
+                shape: SHAPE_RECTANGLE,
+                values: [
+                    rectangle.half_size.x,
+                    rectangle.half_size.y,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                ],
+            },
+            FluidObstacleShape::Capsule(capsule) => Self {
+                shape: SHAPE_CAPSULE,
+                values: [
+                    0.0,
+                    -capsule.half_length,
+                    0.0,
+                    capsule.half_length,
+                    capsule.radius,
+                    0.0,
+                ],
+            },
+            FluidObstacleShape::Triangle(triangle) => {
+                let [a, b, c] = triangle.vertices;
+                Self {
+                    shape: SHAPE_TRIANGLE,
+                    values: [a.x, a.y, b.x, b.y, c.x, c.y],
+                }
+            }
+        }
+    }
+}
+
+impl ShapeVariant {
+    /// Radius of a circle around the origin of the local space which contains the shape.
+    /// Shapes referring to `geometry` must be built with it in the same frame.
+    pub(crate) fn bounding_radius(&self, geometry: &SolidGeometry) -> f32 {
+        let [v0, v1, v2, v3, v4, v5] = self.values;
+        match self.shape {
+            SHAPE_CIRCLE => v0,
+            SHAPE_RECTANGLE => Vec2::new(v0, v1).length(),
+            SHAPE_CAPSULE => Vec2::new(v0, v1).length().max(Vec2::new(v2, v3).length()) + v4,
+            SHAPE_TRIANGLE => [Vec2::new(v0, v1), Vec2::new(v2, v3), Vec2::new(v4, v5)]
+                .map(Vec2::length)
+                .into_iter()
+                .fold(0.0, f32::max),
+            SHAPE_POLYLINE | SHAPE_CONVEX_POLYGON => {
+                let offset = v0 as usize;
+                geometry.vertices[offset..offset + v1 as usize]
+                    .iter()
+                    .map(|vertex| vertex.length())
+                    .fold(0.0, f32::max)
+            }
+            SHAPE_COMPOUND => {
+                let offset = v0 as usize;
+                geometry.parts[offset..offset + v1 as usize]
+                    .iter()
+                    .map(|part| {
+                        part.transform.w_axis.xy().length() + part.shape.bounding_radius(geometry)
+                    })
+                    .fold(0.0, f32::max)
+            }
+            SHAPE_ROUND_RECTANGLE => Vec2::new(v0, v1).length() + v2,
+            _ => f32::INFINITY,
+        }
+    }
+
+    /// Rectangle with rounded corners. `half_size` is of the rectangle before rounding.
+    pub fn round_rectangle(half_size: Vec2, border_radius: f32) -> Self {
+        Self {
+            shape: SHAPE_ROUND_RECTANGLE,
+            values: [half_size.x, half_size.y, border_radius, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+#[cfg(feature = "avian2d")]
 impl ShapeVariant {
     pub fn from_ball(ball: &Ball) -> Self {
         Self {
-            shape: ShapeType::Ball as u32,
+            shape: SHAPE_CIRCLE,
             values: [ball.radius, 0.0, 0.0, 0.0, 0.0, 0.0],
         }
     }
 
     pub fn from_rectangle(rect: &Cuboid) -> Self {
         Self {
-            shape: ShapeType::Cuboid as u32,
+            shape: SHAPE_RECTANGLE,
             values: [rect.half_extents.x, rect.half_extents.y, 0.0, 0.0, 0.0, 0.0],
         }
     }
 
     pub fn from_triangle(triangle: &Triangle) -> Self {
         Self {
-            shape: ShapeType::Triangle as u32,
+            shape: SHAPE_TRIANGLE,
             values: [
                 triangle.a.x,
                 triangle.a.y,
@@ -73,7 +414,7 @@ impl ShapeVariant {
 
     pub fn from_capsule(capsule: &Capsule) -> Self {
         Self {
-            shape: ShapeType::Capsule as u32,
+            shape: SHAPE_CAPSULE,
             values: [
                 capsule.segment.a.x,
                 capsule.segment.a.y,
@@ -84,77 +425,11 @@ impl ShapeVariant {
             ],
         }
     }
-}
 
-pub(crate) fn construct_rigid_body_buffer_for_gpu(
-    query: Query<(
-        Entity,
-        &GlobalTransform,
-        &Collider,
-        &LinearVelocity,
-        &AngularVelocity,
-        &RigidBody,
-    )>,
-    obstacles_buffer: Res<SolidObstaclesBuffer>,
-    mut query_fluid: Query<&mut SolidEntities, With<FluidSettings>>,
-    mut buffers: ResMut<Assets<ShaderStorageBuffer>>,
-) {
-    let solid_obstacles = query
-        .iter()
-        .enumerate()
-        .filter_map(
-            |(idx, (_, transform, collider, linear_velocity, angular_velocity, _rigid_body))| {
-                // match rigid_body {
-                //     RigidBody::Static | RigidBody::Kinematic => return None,
-                //     RigidBody::Dynamic => {}
-                // }
-
-                let shape_type = collider.shape().shape_type();
-                let shape = match shape_type {
-                    ShapeType::Ball => {
-                        Some(ShapeVariant::from_ball(collider.shape().as_ball().unwrap()))
-                    }
-                    ShapeType::Cuboid => Some(ShapeVariant::from_rectangle(
-                        collider.shape().as_cuboid().unwrap(),
-                    )),
-                    ShapeType::Triangle => Some(ShapeVariant::from_triangle(
-                        collider.shape().as_triangle().unwrap(),
-                    )),
-                    ShapeType::Capsule => Some(ShapeVariant::from_capsule(
-                        collider.shape().as_capsule().unwrap(),
-                    )),
-                    _ => {
-                        warn!("Unsupported shape type for solid: {:?}", shape_type);
-                        None
-                    }
-                };
-
-                match shape {
-                    Some(shape) => {
-                        let transform = transform.to_matrix();
-                        Some(SolidObstacle {
-                            entity_id: idx as u32,
-                            shape,
-                            transform,
-                            inverse_transform: transform.inverse(),
-                            linear_velocity: linear_velocity.0,
-                            angular_velocity: angular_velocity.0,
-                        })
-                    }
-                    None => None,
-                }
-            },
+    pub fn from_round_rectangle(round_rect: &RoundCuboid) -> Self {
+        Self::round_rectangle(
+            round_rect.inner_shape.half_extents,
+            round_rect.border_radius,
         )
-        .collect::<Vec<_>>();
-
-    for mut fluids in &mut query_fluid {
-        let solid_entities = query
-            .iter()
-            .map(|(entity, _, _, _, _, _)| entity)
-            .collect::<Vec<_>>();
-        fluids.entities = solid_entities;
     }
-
-    let obstacles_buffer = buffers.get_mut(&obstacles_buffer.obstacles).unwrap();
-    obstacles_buffer.set_data(solid_obstacles);
 }
@@ -0,0 +1,407 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use bevy::{prelude::*, render::render_resource::ShaderType};
+
+use super::{
+    ShapeVariant, SolidGeometry, SolidObstacle, SHAPE_CAPSULE, SHAPE_CIRCLE, SHAPE_COMPOUND,
+    SHAPE_CONVEX_POLYGON, SHAPE_POLYLINE, SHAPE_RECTANGLE, SHAPE_ROUND_RECTANGLE, SHAPE_TRIANGLE,
+};
+
+/// Maximum distance between the quadrature points along the boundary, in units of cells.
+const QUADRATURE_SPACING: f32 = 0.5;
+
+/// Point on the boundary of an obstacle at which the pressure is sampled,
+/// for [`crate::fluid_to_solid::SolidCouplingMethod::BoundaryQuadrature`].
+#[derive(ShaderType, Default, Copy, Clone, Debug)]
+pub(crate) struct BoundaryQuadraturePoint {
+    /// Position in the local space of the fluid, in units of cells.
+    pub position: Vec2,
+    /// Outward unit normal of the boundary.
+    pub normal: Vec2,
+    /// Length of the boundary which the point stands for, in units of cells.
+    pub weight: f32,
+    pub solid_id: u32,
+}
+
+/// Quadrature points on the boundaries of `obstacles`, whose entity ids are used as the solid ids.
+/// `world_to_fluid` transforms the world space into the local space of the fluid.
+pub(crate) fn boundary_quadrature_points(
+    obstacles: &[SolidObstacle],
+    geometry: &SolidGeometry,
+    world_to_fluid: Mat4,
+) -> Vec<BoundaryQuadraturePoint> {
+    let mut points = Vec::new();
+    for obstacle in obstacles {
+        let start = points.len();
+        obstacle.shape.push_boundary_quadrature(
+            geometry,
+            world_to_fluid * obstacle.transform,
+            &mut points,
+        );
+        for point in &mut points[start..] {
+            point.solid_id = obstacle.entity_id;
+        }
+    }
+    points
+}
+
+impl ShapeVariant {
+    /// Pushes midpoint quadrature points of the boundary, at most [`QUADRATURE_SPACING`] apart in the local space of the fluid.
+    /// `to_fluid` transforms the local space of the shape into the local space of the fluid, and may have a non-uniform scale.
+    /// Polylines have no interior, so both sides of the segments are pushed.
+    fn push_boundary_quadrature(
+        &self,
+        geometry: &SolidGeometry,
+        to_fluid: Mat4,
+        points: &mut Vec<BoundaryQuadraturePoint>,
+    ) {
+        let [v0, v1, v2, v3, v4, v5] = self.values;
+        match self.shape {
+            SHAPE_CIRCLE => push_arc(Vec2::ZERO, v0, 0.0, TAU, to_fluid, points),
+            SHAPE_RECTANGLE => {
+                let vertices = [
+                    Vec2::new(-v0, -v1),
+                    Vec2::new(v0, -v1),
+                    Vec2::new(v0, v1),
+                    Vec2::new(-v0, v1),
+                ];
+                push_polygon(&vertices, to_fluid, points);
+            }
+            SHAPE_CAPSULE => {
+                let (a, b, radius) = (Vec2::new(v0, v1), Vec2::new(v2, v3), v4);
+                // Outward normal of the side on the right of the segment from a to b.
+                let normal = -(b - a).try_normalize().unwrap_or(Vec2::X).perp();
+                let angle = normal.to_angle();
+                push_segment(a + radius * normal, b + radius * normal, to_fluid, points);
+                push_arc(b, radius, angle, PI, to_fluid, points);
+                push_segment(b - radius * normal, a - radius * normal, to_fluid, points);
+                push_arc(a, radius, angle + PI, PI, to_fluid, points);
+            }
+            SHAPE_TRIANGLE => {
+                let (a, b, c) = (Vec2::new(v0, v1), Vec2::new(v2, v3), Vec2::new(v4, v5));
+                if (b - a).perp_dot(c - a) < 0.0 {
+                    push_polygon(&[a, c, b], to_fluid, points);
+                } else {
+                    push_polygon(&[a, b, c], to_fluid, points);
+                }
+            }
+            SHAPE_POLYLINE => {
+                let offset = v0 as usize;
+                for segment in geometry.vertices[offset..offset + v1 as usize].chunks_exact(2) {
+                    push_segment(segment[0], segment[1], to_fluid, points);
+                    push_segment(segment[1], segment[0], to_fluid, points);
+                }
+            }
+            SHAPE_COMPOUND => {
+                let offset = v0 as usize;
+                for part in &geometry.parts[offset..offset + v1 as usize] {
+                    part.shape.push_boundary_quadrature(
+                        geometry,
+                        to_fluid * part.transform,
+                        points,
+                    );
+                }
+            }
+            SHAPE_CONVEX_POLYGON => {
+                let offset = v0 as usize;
+                push_polygon(
+                    &geometry.vertices[offset..offset + v1 as usize],
+                    to_fluid,
+                    points,
+                );
+            }
+            SHAPE_ROUND_RECTANGLE => {
+                let (half_size, radius) = (Vec2::new(v0, v1), v2);
+                let corners = [
+                    Vec2::new(half_size.x, -half_size.y),
+                    half_size,
+                    Vec2::new(-half_size.x, half_size.y),
+                    -half_size,
+                ];
+                // Each corner is followed by the side on its counter-clockwise side.
+                for (i, corner) in corners.iter().enumerate() {
+                    let angle = -FRAC_PI_2 + i as f32 * FRAC_PI_2;
+                    push_arc(*corner, radius, angle, FRAC_PI_2, to_fluid, points);
+                    let normal = Vec2::from_angle(angle + FRAC_PI_2);
+                    let next = corners[(i + 1) % 4];
+                    push_segment(
+                        corner + radius * normal,
+                        next + radius * normal,
+                        to_fluid,
+                        points,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Linear part of `to_fluid` in the xy-plane.
+fn linear_part(to_fluid: Mat4) -> Mat2 {
+    Mat2::from_cols(to_fluid.x_axis.xy(), to_fluid.y_axis.xy())
+}
+
+/// Outward unit normal on the right of `tangent` in the local space of the fluid.
+/// A mirroring `to_fluid` swaps the sides, so the normal is flipped to keep it outward.
+fn outward_normal(tangent: Vec2, to_fluid: Mat4) -> Vec2 {
+    let normal = -tangent.perp().normalize_or_zero();
+    if linear_part(to_fluid).determinant() < 0.0 {
+        -normal
+    } else {
+        normal
+    }
+}
+
+/// Polygon with vertices in counter-clockwise order.
+fn push_polygon(vertices: &[Vec2], to_fluid: Mat4, points: &mut Vec<BoundaryQuadraturePoint>) {
+    for (i, a) in vertices.iter().enumerate() {
+        push_segment(*a, vertices[(i + 1) % vertices.len()], to_fluid, points);
+    }
+}
+
+/// Segment from `a` to `b`, with the outward normal on its right.
+/// The end points are transformed first, so the weights and the normal follow the segment in the fluid.
+fn push_segment(a: Vec2, b: Vec2, to_fluid: Mat4, points: &mut Vec<BoundaryQuadraturePoint>) {
+    let a = to_fluid.transform_point3(a.extend(0.0)).xy();
+    let b = to_fluid.transform_point3(b.extend(0.0)).xy();
+    let length = a.distance(b);
+    if length == 0.0 {
+        return;
+    }
+    let normal = outward_normal(b - a, to_fluid);
+    let n = (length / QUADRATURE_SPACING).ceil();
+    for i in 0..n as u32 {
+        points.push(BoundaryQuadraturePoint {
+            position: a.lerp(b, (i as f32 + 0.5) / n),
+            normal,
+            weight: length / n,
+            solid_id: 0,
+        });
+    }
+}
+
+/// Arc around `center`, from `start_angle` to `start_angle + sweep` counter-clockwise.
+/// A non-uniform scale turns the arc into an elliptic one, so each point is weighted by the length of its transformed tangent.
+fn push_arc(
+    center: Vec2,
+    radius: f32,
+    start_angle: f32,
+    sweep: f32,
+    to_fluid: Mat4,
+    points: &mut Vec<BoundaryQuadraturePoint>,
+) {
+    let linear = linear_part(to_fluid);
+    // Largest stretch by the transform, i.e. the largest singular value, so that the points are at most the spacing apart.
+    let frobenius = linear.x_axis.length_squared() + linear.y_axis.length_squared();
+    let discriminant = (frobenius * frobenius - 4.0 * linear.determinant().powi(2)).max(0.0);
+    let max_scale = (0.5 * (frobenius + discriminant.sqrt())).sqrt();
+    let length = radius * sweep * max_scale;
+    if length == 0.0 {
+        return;
+    }
+    let n = (length / QUADRATURE_SPACING).ceil();
+    for i in 0..n as u32 {
+        let direction = Vec2::from_angle(start_angle + sweep * (i as f32 + 0.5) / n);
+        let tangent = linear * (radius * sweep / n * direction.perp());
+        points.push(BoundaryQuadraturePoint {
+            position: to_fluid
+                .transform_point3((center + radius * direction).extend(0.0))
+                .xy(),
+            normal: outward_normal(tangent, to_fluid),
+            weight: tangent.length(),
+            solid_id: 0,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obstacle::FluidObstacleShape;
+
+    const RHO: f32 = 1.0;
+    const GRAVITY: f32 = 9.8;
+
+    fn quadrature_points(
+        shape: FluidObstacleShape,
+        transform: Transform,
+    ) -> Vec<BoundaryQuadraturePoint> {
+        let obstacle = SolidObstacle::new(
+            ShapeVariant::from(&shape),
+            &GlobalTransform::from(transform),
+            Vec2::ZERO,
+            Vec2::ZERO,
+            0.0,
+        );
+        boundary_quadrature_points(&[obstacle], &SolidGeometry::default(), Mat4::IDENTITY)
+    }
+
+    /// Force of the hydrostatic pressure integrated over the points, with the free surface at `surface`.
+    fn buoyancy(points: &[BoundaryQuadraturePoint], surface: f32) -> Vec2 {
+        points
+            .iter()
+            .map(|point| {
+                let p = RHO * GRAVITY * (surface - point.position.y).max(0.0);
+                -p * point.normal * point.weight
+            })
+            .sum()
+    }
+
+    fn assert_relative_eq(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (actual - expected).abs() <= tolerance * expected.abs(),
+            "actual: {actual}, expected: {expected}"
+        );
+    }
+
+    #[test]
+    fn immersed_shapes_get_buoyancy_of_their_area() {
+        let (a, b, c) = (
+            Vec2::new(-4.0, -2.0),
+            Vec2::new(5.0, -1.0),
+            Vec2::new(0.5, 6.0),
+        );
+        let shapes = [
+            (FluidObstacleShape::from(Circle::new(5.0)), PI * 25.0),
+            (Rectangle::new(12.0, 6.0).into(), 72.0),
+            (Capsule2d::new(2.0, 8.0).into(), PI * 4.0 + 32.0),
+            (
+                Triangle2d::new(a, b, c).into(),
+                0.5 * (b - a).perp_dot(c - a),
+            ),
+            // Clockwise vertices.
+            (
+                Triangle2d::new(a, c, b).into(),
+                0.5 * (b - a).perp_dot(c - a),
+            ),
+        ];
+        let transform =
+            Transform::from_xyz(3.3, -2.7, 0.0).with_rotation(Quat::from_rotation_z(0.4));
+
+        for (shape, area) in shapes {
+            let points = quadrature_points(shape, transform);
+            let closure: Vec2 = points.iter().map(|point| point.normal * point.weight).sum();
+            assert!(closure.length() < 1.0e-3, "{shape:?}: {closure}");
+
+            let force = buoyancy(&points, 20.0);
+            assert!(force.x.abs() < 1.0e-2 * area, "{shape:?}: {force}");
+            assert_relative_eq(force.y, RHO * GRAVITY * area, 5.0e-3);
+        }
+    }
+
+    #[test]
+    fn partly_immersed_circle_gets_buoyancy_of_the_submerged_segment() {
+        let radius = 5.0;
+        // The free surface at various offsets from the quadrature points.
+        for surface in [-4.0, -1.3, 0.0, 0.25, 2.8] {
+            let points = quadrature_points(Circle::new(radius).into(), Transform::IDENTITY);
+            let d = -surface;
+            let submerged =
+                radius * radius * (d / radius).acos() - d * (radius * radius - d * d).sqrt();
+            assert_relative_eq(
+                buoyancy(&points, surface).y,
+                RHO * GRAVITY * submerged,
+                1.0e-2,
+            );
+        }
+    }
+
+    #[test]
+    fn box_of_half_density_floats_at_half_immersion() {
+        let half_size = Vec2::new(6.0, 4.0);
+        let transform = Transform::from_xyz(2.0, -1.5, 0.0);
+        let points = quadrature_points(Rectangle { half_size }.into(), transform);
+
+        // The free surface at the center of the box, i.e. the draft of half its height.
+        let force = buoyancy(&points, transform.translation.y);
+        let weight = 0.5 * RHO * GRAVITY * 4.0 * half_size.x * half_size.y;
+        assert!(force.x.abs() < 1.0e-3 * weight, "{force}");
+        assert_relative_eq(force.y, weight, 1.0e-3);
+    }
+
+    #[test]
+    fn points_follow_the_transform_into_the_fluid() {
+        let shape = FluidObstacleShape::from(Rectangle::new(4.0, 2.0));
+        let points = quadrature_points(shape, Transform::IDENTITY);
+
+        // The fluid is scaled down as much as the obstacle is scaled up.
+        let rotation = Quat::from_rotation_z(FRAC_PI_2);
+        let translation = Vec3::new(1.0, -3.0, 0.0);
+        let world_to_fluid =
+            Mat4::from_scale_rotation_translation(Vec3::splat(0.5), rotation, translation);
+        let obstacle = SolidObstacle::new(
+            ShapeVariant::from(&shape),
+            &GlobalTransform::from_scale(Vec3::splat(2.0)),
+            Vec2::ZERO,
+            Vec2::ZERO,
+            0.0,
+        );
+        let fluid_points =
+            boundary_quadrature_points(&[obstacle], &SolidGeometry::default(), world_to_fluid);
+
+        assert_eq!(points.len(), fluid_points.len());
+        for (point, fluid_point) in points.iter().zip(&fluid_points) {
+            let position = (rotation * point.position.extend(0.0) + translation).xy();
+            assert!(position.distance(fluid_point.position) < 1.0e-5);
+            assert!(point.normal.perp().distance(fluid_point.normal) < 1.0e-5);
+            assert_relative_eq(fluid_point.weight, point.weight, 1.0e-5);
+            assert!(fluid_point.weight <= QUADRATURE_SPACING);
+        }
+    }
+
+    #[test]
+    fn points_follow_non_uniform_scale() {
+        let variant = |shape: FluidObstacleShape| ShapeVariant::from(&shape);
+        let mut geometry = SolidGeometry::default();
+        let compound = geometry.compound([
+            (
+                Isometry2d::new(Vec2::new(-3.0, 0.0), Rot2::radians(0.3)),
+                variant(Rectangle::new(2.0, 1.0).into()),
+            ),
+            (
+                Isometry2d::from_translation(Vec2::new(3.0, 1.0)),
+                variant(Circle::new(1.5).into()),
+            ),
+        ]);
+        let shapes = [
+            (variant(Circle::new(3.0).into()), PI * 9.0),
+            (variant(Rectangle::new(4.0, 2.0).into()), 8.0),
+            (variant(Capsule2d::new(1.0, 3.0).into()), PI + 6.0),
+            (compound, 2.0 + PI * 2.25),
+        ];
+
+        // Non-uniform scales of both the obstacle and the fluid, one of which mirrors the x-axis.
+        let transform = Transform::from_xyz(1.0, -2.0, 0.0)
+            .with_rotation(Quat::from_rotation_z(0.7))
+            .with_scale(Vec3::new(2.0, 0.5, 1.0));
+        let world_to_fluid = Mat4::from_scale_rotation_translation(
+            Vec3::new(-1.5, 0.8, 1.0),
+            Quat::from_rotation_z(-0.2),
+            Vec3::new(4.0, 3.0, 0.0),
+        );
+        let to_fluid = world_to_fluid * transform.to_matrix();
+        let scale = linear_part(to_fluid).determinant().abs();
+
+        for (shape, area) in shapes {
+            let obstacle = SolidObstacle::new(
+                shape,
+                &GlobalTransform::from(transform),
+                Vec2::ZERO,
+                Vec2::ZERO,
+                0.0,
+            );
+            let points = boundary_quadrature_points(&[obstacle], &geometry, world_to_fluid);
+            for point in &points {
+                assert_relative_eq(point.normal.length(), 1.0, 1.0e-5);
+                assert!(point.weight <= QUADRATURE_SPACING * (1.0 + 1.0e-5));
+            }
+            let closure: Vec2 = points.iter().map(|point| point.normal * point.weight).sum();
+            assert!(closure.length() < 1.0e-3, "{closure}");
+
+            let force = buoyancy(&points, 40.0);
+            assert!(force.x.abs() < 1.0e-2 * area * scale, "{force}");
+            assert_relative_eq(force.y, RHO * GRAVITY * area * scale, 5.0e-3);
+        }
+    }
+}
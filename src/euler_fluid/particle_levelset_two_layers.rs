@@ -6,4 +6,4 @@ pub mod plugin;
 pub mod reseed;
 pub mod update_interface_band_mask;
 
-pub use plugin::ParticleLevelsetTwoLayersPlugin;
+pub use plugin::{LevelSetCorrection, ParticleLevelsetTwoLayersPlugin};
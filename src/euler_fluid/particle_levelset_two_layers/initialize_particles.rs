@@ -42,6 +42,8 @@ pub(crate) struct InitializeParticlesResource {
     pub grad_levelset_air: Handle<Image>,
     #[storage_texture(6, image_format = R32Uint, access = ReadOnly)]
     pub interface_band_mask: Handle<Image>,
+    #[uniform(7)]
+    pub particles_per_cell: u32,
 }
 
 impl InitializeParticlesResource {
@@ -64,6 +66,7 @@ impl InitializeParticlesResource {
             levelset_air: levelset_air.clone(),
             grad_levelset_air: grad_levelset_air.clone(),
             interface_band_mask,
+            particles_per_cell: pls_resources.particles_per_cell,
         }
     }
 }
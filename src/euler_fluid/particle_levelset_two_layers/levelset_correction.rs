@@ -81,6 +81,7 @@ impl Plugin for LevelsetCorrectionPlugin {
 }
 
 #[derive(QueryData)]
+#[query_data(derive(Clone, Copy))]
 pub(crate) struct PLSLevelsetCorrectionQuery {
     pub mark_escaped_particles_bind_group: &'static MarkEscapedParticlesBindGroup,
     pub reset_levelset_correction_bind_group: &'static ResetLevelSetCorrectionBindGroup,
@@ -92,6 +93,7 @@ pub(crate) struct PLSLevelsetCorrectionQuery {
 }
 
 #[derive(QueryData)]
+#[query_data(derive(Clone, Copy))]
 pub(crate) struct PLSLevelsetCorrectionSecondQuery {
     pub mark_escaped_particles_bind_group: &'static MarkEscapedParticlesSecondBindGroup,
     pub reset_levelset_correction_bind_group: &'static ResetLevelSetCorrectionSecondBindGroup,
@@ -5,14 +5,12 @@ use bevy::{
     render::{
         render_resource::{ComputePass, PipelineCache, TextureFormat},
         storage::ShaderStorageBuffer,
-        RenderApp,
     },
     shader::load_shader_library,
 };
 
 use crate::{
     common_pass::prefix_sum::{PrefixSumPipeline, PREFIX_SUM_BLOCK_SIZE},
-    diagnostics::debug_draw_particles::DebugDrawLevelsetParticlesPlugin,
     fluid_uniform::SimulationUniformBindGroup,
     particle_levelset_two_layers::{
         advect_particles::{
@@ -92,8 +90,20 @@ pub(crate) const WORKGROUP_SIZE_PARTICLE: u32 = 256;
 
 pub struct ParticleLevelsetTwoLayersPlugin;
 
-#[derive(Resource)]
-struct PluginEnabledMarker;
+/// Correction of the level set after it is advected, to reduce the volume loss of the liquid.
+/// Insert this component to the entity with [`crate::settings::FluidSettings`]. The resources are allocated only if
+/// [`LevelSetCorrection::ParticleLevelSet`] is selected when a fluid with a free surface is spawned.
+/// The particles can be visualized with [`crate::diagnostics::DebugDrawLevelsetParticlesPlugin`].
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub enum LevelSetCorrection {
+    /// The level set is only advected and reinitialized.
+    #[default]
+    None,
+    /// Particle level set method (Enright et al. 2002). Particles are seeded on both sides of the interface
+    /// and correct the level set where they escape to the other side.
+    /// `particles_per_cell` is the number of particles of each sign seeded in a cell near the interface, up to 16.
+    ParticleLevelSet { particles_per_cell: u32 },
+}
 
 impl Plugin for ParticleLevelsetTwoLayersPlugin {
     fn build(&self, app: &mut App) {
@@ -101,20 +111,13 @@ impl Plugin for ParticleLevelsetTwoLayersPlugin {
         load_shader_library!(app, "shaders/constants.wgsl");
         load_shader_library!(app, "shaders/fixed_point.wgsl");
 
-        app.add_plugins((
-            LevelsetCorrectionPlugin,
-            ReseedPlugin,
-            DebugDrawLevelsetParticlesPlugin,
-        ))
-        .add_plugins((
-            FluidComputePassPlugin::<UpdateInterfaceBandMaskPass>::default(),
-            FluidComputePassPlugin::<InitializeParticlesPass>::default(),
-            FluidComputePassPlugin::<AdvectParticlesPass>::default(),
-        ))
-        .add_systems(Update, reset_buffers);
-
-        let render_app = app.sub_app_mut(RenderApp);
-        render_app.insert_resource(PluginEnabledMarker);
+        app.add_plugins((LevelsetCorrectionPlugin, ReseedPlugin))
+            .add_plugins((
+                FluidComputePassPlugin::<UpdateInterfaceBandMaskPass>::default(),
+                FluidComputePassPlugin::<InitializeParticlesPass>::default(),
+                FluidComputePassPlugin::<AdvectParticlesPass>::default(),
+            ))
+            .add_systems(Update, reset_buffers);
     }
 }
 
@@ -125,6 +128,7 @@ pub(crate) struct PLSInitializeBindGroupsQuery {
 }
 
 #[derive(QueryData)]
+#[query_data(derive(Clone, Copy))]
 pub(crate) struct PLSAdvectionBindGroupsQuery {
     pub advect_particles_bind_group: &'static AdvectParticlesBindGroup,
 }
@@ -210,6 +214,7 @@ pub(crate) struct PLSResources {
     pub negative_cell_cursor: Handle<ShaderStorageBuffer>,
     pub positive_particles_to_be_added: Handle<ShaderStorageBuffer>,
     pub negative_particles_to_be_added: Handle<ShaderStorageBuffer>,
+    pub particles_per_cell: u32,
 }
 
 impl PLSResources {
@@ -217,6 +222,7 @@ impl PLSResources {
         images: &mut ResMut<Assets<Image>>,
         buffers: &mut ResMut<Assets<ShaderStorageBuffer>>,
         grid_size: UVec2,
+        particles_per_cell: u32,
     ) -> Self {
         let grid_length = grid_size.element_product() as usize;
         let particle_buffer_length = grid_length * MAX_PARTICLES_PER_CELL;
@@ -281,23 +287,34 @@ impl PLSResources {
             negative_cell_cursor,
             positive_particles_to_be_added,
             negative_particles_to_be_added,
+            particles_per_cell: particles_per_cell.min(MAX_PARTICLES_PER_CELL as u32),
         }
     }
 }
 
+/// Textures of the fluid the particle level set reads and corrects.
+pub(crate) struct PLSFluidTextures<'a> {
+    pub u0: &'a Handle<Image>,
+    pub v0: &'a Handle<Image>,
+    pub levelset_air0: &'a Handle<Image>,
+    pub levelset_air1: &'a Handle<Image>,
+    pub grad_levelset_air: &'a Handle<Image>,
+}
+
 pub(crate) fn setup(
     commands: &mut Commands,
     entity: Entity,
-    images: &mut ResMut<Assets<Image>>,
-    buffers: &mut ResMut<Assets<ShaderStorageBuffer>>,
+    pls_resources: PLSResources,
     grid_size: UVec2,
-    u0: &Handle<Image>,
-    v0: &Handle<Image>,
-    levelset_air0: &Handle<Image>,
-    levelset_air1: &Handle<Image>,
-    grad_levelset_air: &Handle<Image>,
+    textures: PLSFluidTextures,
 ) {
-    let pls_resources = PLSResources::new(images, buffers, grid_size);
+    let PLSFluidTextures {
+        u0,
+        v0,
+        levelset_air0,
+        levelset_air1,
+        grad_levelset_air,
+    } = textures;
 
     let update_interface_band_mask =
         UpdateInterfaceBandMaskResource::new(&pls_resources, levelset_air0);
@@ -433,11 +450,6 @@ fn reset_buffers(
 }
 
 pub(crate) fn are_pls_pipelines_ready(world: &World, pipeline_cache: &PipelineCache) -> bool {
-    let is_pls_enabled = world.get_resource::<PluginEnabledMarker>();
-    if is_pls_enabled.is_none() {
-        return true;
-    }
-
     let initialize_particles = world.resource::<InitializeParticlesPipeline>();
     let advect_particles = world.resource::<AdvectParticlesPipeline>();
 
@@ -100,6 +100,7 @@ impl Plugin for ReseedPlugin {
 }
 
 #[derive(QueryData)]
+#[query_data(derive(Clone, Copy))]
 pub(crate) struct PLSReseedBindGroupsQuery {
     pub count_positive_particles_in_cell: &'static CountPositiveParticlesInCellBindGroup,
     pub count_negative_particles_in_cell: &'static CountNegativeParticlesInCellBindGroup,
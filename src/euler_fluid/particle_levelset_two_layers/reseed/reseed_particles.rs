@@ -53,6 +53,8 @@ pub(crate) struct ReseedPositiveParticlesResource {
     pub levelset_air: Handle<Image>,
     #[uniform(6)]
     pub grid_size: UVec2,
+    #[uniform(7)]
+    pub particles_per_cell: u32,
 }
 
 impl ReseedPositiveParticlesResource {
@@ -75,6 +77,7 @@ impl ReseedPositiveParticlesResource {
             cell_offsets,
             levelset_air: levelset_air.clone(),
             grid_size,
+            particles_per_cell: pls_rersources.particles_per_cell,
         }
     }
 }
@@ -95,6 +98,8 @@ pub(crate) struct ReseedNegativeParticlesResource {
     pub levelset_air: Handle<Image>,
     #[uniform(6)]
     pub grid_size: UVec2,
+    #[uniform(7)]
+    pub particles_per_cell: u32,
 }
 
 impl ReseedNegativeParticlesResource {
@@ -117,6 +122,7 @@ impl ReseedNegativeParticlesResource {
             cell_offsets,
             levelset_air: levelset_air.clone(),
             grid_size,
+            particles_per_cell: pls_resources.particles_per_cell,
         }
     }
 }
@@ -9,6 +9,7 @@
 @group(0) @binding(4) var<storage, read_write> particles_to_be_added: array<u32>;
 @group(0) @binding(5) var levelset_air: texture_storage_2d<r32float, read>;
 @group(0) @binding(6) var<uniform> grid_size: vec2<u32>;
+@group(0) @binding(7) var<uniform> particles_per_cell: u32;
 
 @compute @workgroup_size(8, 8, 1)
 fn reseed_particles(
@@ -20,9 +21,9 @@ fn reseed_particles(
     let cell_offset = cell_offsets[cell_id_1d];
 
     let cell_center = vec2<f32>(cell_id) + vec2<f32>(0.0);
-    let is_near_interface = abs(interp2d_center(levelset_air, cell_center)) < BAND_WIDTH;
+    let is_near_interface = abs(interp2d_center(levelset_air, cell_center, vec2<i32>(0))) < BAND_WIDTH;
     var heap: array<Node, MAX_PARTICLES_PER_CELL>;
-    var heap_capacity = MAX_PARTICLES_PER_CELL;
+    var heap_capacity = min(particles_per_cell, MAX_PARTICLES_PER_CELL);
     var heap_idx = 0u;
     for (var i = 0u; i < n; i++) {
         let p_idx = cell_offset + i;
@@ -81,7 +82,7 @@ fn reseed_particles(
 }
 
 fn eval_priority(p: Particle, levelset_air: texture_storage_2d<r32float, read>) -> f32 {
-    return p.sign * interp2d_center(levelset_air, p.position) - p.radius;
+    return p.sign * interp2d_center(levelset_air, p.position, vec2<i32>(0)) - p.radius;
 }
 
 struct Node {
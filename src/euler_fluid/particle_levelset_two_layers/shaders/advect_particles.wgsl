@@ -1,4 +1,5 @@
 #import bevy_fluid::coordinate::{tvd_rk3, interp2d_center};
+#import bevy_fluid::boundary_condition::{grid_period, wrap_periodic_position};
 #import bevy_fluid::fluid_uniform::SimulationUniform;
 #import bevy_fluid::particle_levelset::particle::Particle;
 #import bevy_fluid::particle_levelset::constants::PARTICLE_WORKWGROUP_SIZE;
@@ -18,16 +19,23 @@ fn advect_particles(
     @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
 ) {
     let idx = global_invocation_id.x;
+    let period = grid_period(constants, vec2<i32>(constants.size));
     if (idx < positive_particles_count) {
         let particle_position = positive_particles[idx].position;
-        let new_particle_position = tvd_rk3(u0, v0, particle_position, constants.dt);
+        let new_particle_position = wrap_periodic_position(
+            constants,
+            tvd_rk3(u0, v0, particle_position, constants.dt, period),
+        );
 
         positive_particles[idx].position = new_particle_position;
     }
 
     if (idx < negative_particles_count) {
         let particle_position = negative_particles[idx].position;
-        let new_particle_position = tvd_rk3(u0, v0, particle_position, constants.dt);
+        let new_particle_position = wrap_periodic_position(
+            constants,
+            tvd_rk3(u0, v0, particle_position, constants.dt, period),
+        );
 
         negative_particles[idx].position = new_particle_position;
     }
@@ -10,6 +10,7 @@
 @group(0) @binding(4) var levelset_air: texture_storage_2d<r32float, read>;
 @group(0) @binding(5) var grad_levelset_air: texture_storage_2d<rg32float, read>;
 @group(0) @binding(6) var interface_band_mask: texture_storage_2d<r32uint, read>;
+@group(0) @binding(7) var<uniform> particles_per_cell: u32;
 
 // const MAX_ITER: u32 = 15;
 
@@ -34,7 +35,7 @@ fn initialize_particles(
     let bmax_neg = -bmin;
 
     let cell_id = idx.x + dim.x * idx.y;
-    for (var i = 0u; i < MAX_PARTICLES_PER_CELL; i++) {
+    for (var i = 0u; i < min(particles_per_cell, MAX_PARTICLES_PER_CELL); i++) {
         let seed = cell_id + i;
         let x = x_base + hash22(x_base + vec2<f32>(seed));
         let phi_goal = hash11(f32(seed)) * (bmax - bmin) + bmin;
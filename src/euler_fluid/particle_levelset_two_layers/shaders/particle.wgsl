@@ -20,7 +20,7 @@ fn is_particle_escaped_u32(
     particle: Particle,
     levelset_air: texture_storage_2d<r32float, read>
 ) -> u32 {
-    let phi = interp2d_center(levelset_air, particle.position);
+    let phi = interp2d_center(levelset_air, particle.position, vec2<i32>(0));
     if particle.sign * phi + particle.radius < 0.0 {
         return 1;
     } else {
@@ -39,8 +39,8 @@ fn attract(
     levelset_air: texture_storage_2d<r32float, read>,
     grad_levelset_air: texture_storage_2d<rg32float, read>,
 ) -> vec2<f32> {
-    let phi = interp2d_center(levelset_air, x);
-    let grad_phi = interp2d_center_rg32float(grad_levelset_air, x);
+    let phi = interp2d_center(levelset_air, x, vec2<i32>(0));
+    let grad_phi = interp2d_center_rg32float(grad_levelset_air, x, vec2<i32>(0));
     let grad_phi_norm = normalize(grad_phi);
 
     return x + lambda * (phi_goal - phi) * grad_phi_norm;
@@ -69,7 +69,7 @@ fn spawn_and_attract_particle(
     x_new = attract(x_new, lambda, phi_goal, levelset_air, grad_levelset_air);
     var acceptable = false;
     if all(vec2<f32>(0.0) < x_new) && all(x_new < fdim - vec2<f32>(1.0)) {
-        phi_new = interp2d_center(levelset_air, x_new);
+        phi_new = interp2d_center(levelset_air, x_new, vec2<i32>(0));
         if bmin <= phi_new && phi_new <= bmax {
             acceptable = true;
         }
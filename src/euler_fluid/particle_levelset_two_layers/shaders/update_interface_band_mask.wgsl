@@ -2,12 +2,19 @@
 
 @group(0) @binding(0) var levelset_air: texture_storage_2d<r32float, read>;
 @group(0) @binding(1) var interface_band_mask: texture_storage_2d<r32uint, write>;
+@group(0) @binding(2) var<storage, read_write> positive_particles_count: u32;
+@group(0) @binding(3) var<storage, read_write> negative_particles_count: u32;
 
 @compute @workgroup_size(8, 8, 1)
 fn update_interface_band_mask(
     @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
 ) {
     let idx = global_invocation_id.xy;
+    // Particles are seeded from scratch, including after a reset.
+    if all(idx == vec2<u32>(0)) {
+        positive_particles_count = 0u;
+        negative_particles_count = 0u;
+    }
 
     let level = textureLoad(levelset_air, idx).r;
     if (abs(level) < BAND_WIDTH) {
@@ -4,6 +4,7 @@ use bevy::{
     render::{
         extract_component::ExtractComponent,
         render_resource::{AsBindGroup, BindGroup},
+        storage::ShaderStorageBuffer,
     },
 };
 
@@ -31,6 +32,10 @@ pub(crate) struct UpdateInterfaceBandMaskResource {
     pub levelset_air: Handle<Image>,
     #[storage_texture(1, image_format = R32Uint, access = WriteOnly)]
     pub interface_band_mask: Handle<Image>,
+    #[storage(2, visibility(compute))]
+    pub positive_particles_count: Handle<ShaderStorageBuffer>,
+    #[storage(3, visibility(compute))]
+    pub negative_particles_count: Handle<ShaderStorageBuffer>,
 }
 
 impl UpdateInterfaceBandMaskResource {
@@ -40,6 +45,8 @@ impl UpdateInterfaceBandMaskResource {
         Self {
             levelset_air: levelset_air.clone(),
             interface_band_mask,
+            positive_particles_count: pls_resources.positive_particles_count.clone(),
+            negative_particles_count: pls_resources.negative_particles_count.clone(),
         }
     }
 }
@@ -1,3 +1,4 @@
+#[cfg(feature = "avian2d")]
 use avian2d::prelude::{Physics, PhysicsSchedule, PhysicsStepSystems};
 use bevy::{
     prelude::*,
@@ -9,12 +10,19 @@ use bevy::{
 
 pub struct PhysicsFramePlugin;
 
+/// Clock the fluid is stepped with. The fluid follows avian2d physics steps if the `avian2d` feature is enabled,
+/// otherwise it runs on its own fixed timestep.
+#[cfg(feature = "avian2d")]
+pub type FluidClock = Physics;
+#[cfg(not(feature = "avian2d"))]
+pub type FluidClock = Fixed;
+
 #[derive(Resource, Clone, Copy, ExtractResource)]
 pub struct FluidTimeStep(pub f32);
 
 impl FromWorld for FluidTimeStep {
     fn from_world(world: &mut World) -> Self {
-        let physics_time = world.resource::<Time<Physics>>();
+        let physics_time = world.resource::<Time<FluidClock>>();
         Self(physics_time.delta_secs())
     }
 }
@@ -22,11 +30,15 @@ impl FromWorld for FluidTimeStep {
 impl Plugin for PhysicsFramePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<PhysicsFrameInfo>()
-            .add_plugins(ExtractResourcePlugin::<PhysicsFrameInfo>::default())
-            .add_systems(
-                PhysicsSchedule,
-                update_physics_frame_info.after(PhysicsStepSystems::Last),
-            );
+            .add_plugins(ExtractResourcePlugin::<PhysicsFrameInfo>::default());
+
+        #[cfg(feature = "avian2d")]
+        app.add_systems(
+            PhysicsSchedule,
+            update_physics_frame_info.after(PhysicsStepSystems::Last),
+        );
+        #[cfg(not(feature = "avian2d"))]
+        app.add_systems(FixedPostUpdate, update_physics_frame_info);
 
         let render_app = app.sub_app_mut(RenderApp);
         render_app.init_resource::<CurrentPhysicsStepNumberRenderWorld>();
@@ -48,7 +60,7 @@ pub struct PhysicsFrameInfo {
 pub struct CurrentPhysicsStepNumberRenderWorld(pub u64);
 
 pub(crate) fn update_physics_frame_info(
-    time: Res<Time<Physics>>,
+    time: Res<Time<FluidClock>>,
     mut step: ResMut<PhysicsFrameInfo>,
     mut time_step: ResMut<FluidTimeStep>,
 ) {
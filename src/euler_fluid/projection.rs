@@ -1,5 +1,8 @@
+pub mod conjugate_gradient;
+pub mod diagnostics;
 pub mod gauss_seidel;
 pub mod multi_grid;
+pub mod solid_coupling;
 
 use std::fmt::Display;
 
@@ -8,22 +11,33 @@ use bevy::{
     prelude::*,
     render::{
         extract_component::{ExtractComponent, ExtractComponentPlugin},
-        render_resource::{ComputePass, PipelineCache},
+        render_asset::RenderAssets,
+        render_resource::{Buffer, ComputePass, PipelineCache},
+        storage::GpuShaderStorageBuffer,
     },
 };
 
 use crate::{
     fluid_uniform::SimulationUniformBindGroup,
-    pipeline::{DispatchFluidPass, WORKGROUP_SIZE},
+    pipeline::WORKGROUP_SIZE,
     plugin::FluidComputePassPlugin,
     projection::{
+        conjugate_gradient::{
+            ConjugateGradientBindGroupsQuery, ConjugateGradientConfig, ConjugateGradientPass,
+            ConjugateGradientPreconditioner,
+        },
+        diagnostics::{
+            level_num_workgroups, ProjectionDiagnostics, ProjectionResidualBindGroup,
+            ProjectionResidualPass, ProjectionResidualPipelines, ProjectionResidualResource,
+        },
         gauss_seidel::{
             GaussSeidelBindGroup, GaussSeidelConfig, GaussSeidelPass, GaussSeidelPipeline,
         },
         multi_grid::{
-            MultiGridBindGroups, MultiGridConfig, MultiGridNumLevels, MultiGridPassPlugin,
-            MultiGridPipelines,
+            MultiGridBindGroups, MultiGridConfig, MultiGridDispatchParams, MultiGridNumLevels,
+            MultiGridPassPlugin, MultiGridPipelines,
         },
+        solid_coupling::{BodyVelocityPass, SolidCouplingPass},
     },
     solve_pressure::{SolvePressureBindGroups, SolvePressurePipeline},
 };
@@ -34,7 +48,12 @@ impl Plugin for PressureProjectionPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
             FluidComputePassPlugin::<GaussSeidelPass>::default(),
+            FluidComputePassPlugin::<ConjugateGradientPass>::default(),
+            FluidComputePassPlugin::<SolidCouplingPass>::default(),
+            FluidComputePassPlugin::<BodyVelocityPass>::default(),
+            FluidComputePassPlugin::<ProjectionResidualPass>::default(),
             ExtractComponentPlugin::<ProjectionMethod>::default(),
+            ExtractComponentPlugin::<ProjectionDiagnostics>::default(),
             MultiGridPassPlugin,
         ));
     }
@@ -49,6 +68,9 @@ pub enum ProjectionMethod {
     GaussSeidel(GaussSeidelConfig),
     /// Multigrid solver. Most performant.
     MultiGrid(MultiGridConfig),
+    /// Preconditioned conjugate gradient solver. Iterates until the residual reaches the tolerance,
+    /// so the velocity is divergence-free even on large domains.
+    ConjugateGradient(ConjugateGradientConfig),
 }
 
 impl Default for ProjectionMethod {
@@ -75,15 +97,45 @@ impl Display for ProjectionMethod {
                     multi_grid_config.post_smooth_config.num_iterations
                 )
             }
+            ProjectionMethod::ConjugateGradient(config) => {
+                let preconditioner = match config.preconditioner {
+                    ConjugateGradientPreconditioner::Jacobi => "PCG",
+                    ConjugateGradientPreconditioner::MultiGrid(_) => "MGPCG",
+                };
+                write!(
+                    f,
+                    "{}(tol={:e}, max={})",
+                    preconditioner, config.tolerance, config.max_iterations
+                )
+            }
         }
     }
 }
 
+/// Textures of a pressure Poisson problem, shared by the solvers and the residual diagnostics.
+#[derive(Clone)]
+pub(crate) struct PressurePoissonTextures {
+    /// Solution of the problem.
+    pub p: Handle<Image>,
+    /// Right-hand side of the problem.
+    pub div: Handle<Image>,
+    pub levelset_air: Handle<Image>,
+    pub area_fraction_solid: Handle<Image>,
+    pub curvature: Handle<Image>,
+}
+
 #[derive(QueryData)]
+#[query_data(derive(Clone, Copy))]
 pub(crate) struct ProjectionBindGroupsQuery {
     pub gauss_seidel_bind_group: Option<&'static GaussSeidelBindGroup>,
     pub jacobi_bind_groups: Option<&'static SolvePressureBindGroups>,
     pub multi_grid_bind_groups: Option<(&'static MultiGridBindGroups, &'static MultiGridNumLevels)>,
+    pub conjugate_gradient_bind_groups: Option<ConjugateGradientBindGroupsQuery>,
+    pub diagnostics: Option<(
+        &'static ProjectionDiagnostics,
+        &'static ProjectionResidualResource,
+        &'static ProjectionResidualBindGroup,
+    )>,
 }
 
 pub(crate) fn dispatch(
@@ -95,12 +147,95 @@ pub(crate) fn dispatch(
     uniform_bind_group: &SimulationUniformBindGroup,
     size: UVec2,
 ) {
+    let Some((diagnostics, residual_resource, residual_bind_group)) =
+        projection_bind_groups.diagnostics
+    else {
+        dispatch_method(
+            world,
+            method,
+            pass,
+            &projection_bind_groups,
+            uniform_bind_group,
+            size,
+            None,
+        );
+        return;
+    };
+
+    let residual_pipelines = world.resource::<ProjectionResidualPipelines>();
+    residual_pipelines.dispatch_reset(
+        pipeline_cache,
+        pass,
+        residual_bind_group,
+        uniform_bind_group,
+    );
+
+    let indirect_args = world
+        .resource::<RenderAssets<GpuShaderStorageBuffer>>()
+        .get(&residual_resource.indirect_args);
+    // The conjugate gradient solver iterates until its own tolerance, so it is not chunked.
+    let chunked = !matches!(method, ProjectionMethod::ConjugateGradient(_));
+    match (&diagnostics.tolerance, indirect_args) {
+        (Some(tolerance), Some(indirect_args)) if chunked => {
+            for _ in 0..tolerance.max_chunks {
+                dispatch_method(
+                    world,
+                    method,
+                    pass,
+                    &projection_bind_groups,
+                    uniform_bind_group,
+                    size,
+                    Some(&indirect_args.buffer),
+                );
+                residual_pipelines.dispatch(
+                    pipeline_cache,
+                    pass,
+                    residual_bind_group,
+                    uniform_bind_group,
+                    size,
+                );
+            }
+        }
+        _ => {
+            dispatch_method(
+                world,
+                method,
+                pass,
+                &projection_bind_groups,
+                uniform_bind_group,
+                size,
+                None,
+            );
+            residual_pipelines.dispatch(
+                pipeline_cache,
+                pass,
+                residual_bind_group,
+                uniform_bind_group,
+                size,
+            );
+        }
+    }
+}
+
+/// Runs the pressure solve once. With `indirect_args`, the workgroup counts are read from GPU,
+/// which are zeroed by [`ProjectionResidualPipelines::dispatch`] once the tolerance is met.
+/// The conjugate gradient solver has its own tolerance, and is dispatched only once without them.
+fn dispatch_method(
+    world: &World,
+    method: &ProjectionMethod,
+    pass: &mut ComputePass,
+    projection_bind_groups: &ProjectionBindGroupsQueryItem,
+    uniform_bind_group: &SimulationUniformBindGroup,
+    size: UVec2,
+    indirect_args: Option<&Buffer>,
+) {
+    let pipeline_cache = world.resource::<PipelineCache>();
+    let num_workgroups = level_num_workgroups(indirect_args, 0, (size / WORKGROUP_SIZE).extend(1));
     match method {
         ProjectionMethod::GaussSeidel(config) => {
             pass.push_debug_group("Projection (Gauss-Seidel)");
 
             let pipeline = world.resource::<GaussSeidelPipeline>();
-            let num_workgroups = (size / WORKGROUP_SIZE).extend(1);
             pipeline.dispatch(
                 pipeline_cache,
                 pass,
@@ -135,11 +270,11 @@ pub(crate) fn dispatch(
             for _ in 0..50 {
                 pass.set_pipeline(&jacobi_iteration_pipeline);
                 pass.set_bind_group(0, &bind_groups.jacobi_iteration_bind_group, &[]);
-                pass.dispatch_center(size);
+                num_workgroups.dispatch(pass);
 
                 pass.set_pipeline(&jacobi_iteration_reverse_pipeline);
                 pass.set_bind_group(0, &bind_groups.jacobi_iteration_reverse_bind_group, &[]);
-                pass.dispatch_center(size);
+                num_workgroups.dispatch(pass);
             }
             pass.pop_debug_group();
         }
@@ -154,9 +289,29 @@ pub(crate) fn dispatch(
                 pass,
                 bind_groups,
                 uniform_bind_group,
-                size,
+                &MultiGridDispatchParams {
+                    grid_size: size,
+                    config,
+                    levels: num_levels,
+                    indirect_args,
+                    symmetric: false,
+                },
+            );
+            pass.pop_debug_group();
+        }
+        ProjectionMethod::ConjugateGradient(config) => {
+            let bind_groups = projection_bind_groups
+                .conjugate_gradient_bind_groups
+                .expect("ConjugateGradientBindGroup component is missing in RenderWorld.");
+            pass.push_debug_group("Projection (Conjugate Gradient)");
+            conjugate_gradient::dispatch(
+                world,
                 config,
-                num_levels,
+                pipeline_cache,
+                pass,
+                bind_groups,
+                uniform_bind_group,
+                size,
             );
             pass.pop_debug_group();
         }
@@ -0,0 +1,465 @@
+use bevy::{
+    asset::{embedded_asset, embedded_path},
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::ExtractComponent,
+        render_asset::RenderAssets,
+        render_resource::{
+            AsBindGroup, BindGroup, BindGroupLayoutDescriptor, CachedComputePipelineId,
+            ComputePass, PipelineCache, ShaderType,
+        },
+        renderer::RenderDevice,
+        storage::{GpuShaderStorageBuffer, ShaderStorageBuffer},
+    },
+};
+
+use crate::{
+    fluid_uniform::{uniform_bind_group_layout_desc, SimulationUniformBindGroup},
+    pipeline::{
+        is_pipeline_loaded, queue_compute_pipeline, HasBindGroupLayout, NumWorkgroups,
+        WORKGROUP_SIZE,
+    },
+    plugin::FluidComputePass,
+    projection::{
+        diagnostics::{level_num_workgroups, new_indirect_args},
+        gauss_seidel::GaussSeidelConfig,
+        multi_grid::{
+            MultiGridConfig, MultiGridDispatchParams, MultiGridPipelines,
+            MultiGridPreconditionerBindGroups,
+        },
+        solid_coupling::{
+            BodyVelocityBindGroup, BodyVelocityPipelines, SolidCouplingBindGroup,
+            SolidCouplingPipelines,
+        },
+    },
+};
+
+pub(crate) struct ConjugateGradientPass;
+
+impl FluidComputePass for ConjugateGradientPass {
+    type Pipeline = ConjugateGradientPipelines;
+    type Resource = ConjugateGradientResource;
+    type BG = ConjugateGradientBindGroup;
+
+    fn register_assets(app: &mut App) {
+        embedded_asset!(app, "shaders/conjugate_gradient.wgsl");
+    }
+}
+
+/// Configuration of the preconditioned conjugate gradient solver.
+/// Unlike the other methods, the solver iterates until the residual satisfies the tolerance.
+/// # Fields
+/// * `tolerance`: The solver stops once the norm of the residual gets smaller than `tolerance` times the norm of the right hand side.
+/// * `max_iterations`: Upper limit of the iterations per step. Iterations after convergence are skipped on GPU.
+/// * `preconditioner`: See [`ConjugateGradientPreconditioner`].
+#[derive(Clone, Debug)]
+pub struct ConjugateGradientConfig {
+    pub tolerance: f32,
+    pub max_iterations: u32,
+    pub preconditioner: ConjugateGradientPreconditioner,
+}
+
+impl Default for ConjugateGradientConfig {
+    fn default() -> Self {
+        Self {
+            tolerance: 1.0e-4,
+            max_iterations: 30,
+            preconditioner: ConjugateGradientPreconditioner::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ConjugateGradientPreconditioner {
+    /// Diagonal preconditioner. Cheap per iteration, but needs many iterations on large domains.
+    Jacobi,
+    /// A multigrid V-cycle per iteration (MGPCG). Converges in a few iterations regardless of the domain size.
+    /// The post smoother sweeps in the reverse order of the pre smoother, so both should have the same
+    /// number of iterations to keep the preconditioner symmetric.
+    MultiGrid(MultiGridConfig),
+}
+
+impl Default for ConjugateGradientPreconditioner {
+    fn default() -> Self {
+        ConjugateGradientPreconditioner::MultiGrid(MultiGridConfig {
+            coarsest_config: GaussSeidelConfig { num_iterations: 10 },
+            ..default()
+        })
+    }
+}
+
+/// Scalars of the solver shared between the passes on GPU.
+#[derive(ShaderType, Clone, Copy, Default)]
+pub(crate) struct ConjugateGradientState {
+    pub rz: f32,
+    pub rr: f32,
+    pub bb: f32,
+    pub alpha: f32,
+    pub beta: f32,
+    pub converged: u32,
+    pub num_iterations: u32,
+}
+
+#[derive(Component, ExtractComponent, Clone, AsBindGroup)]
+pub(crate) struct ConjugateGradientResource {
+    #[storage_texture(0, image_format = R32Float, access = ReadWrite)]
+    pub p: Handle<Image>,
+    #[storage_texture(1, image_format = R32Float, access = ReadOnly)]
+    pub div: Handle<Image>,
+    #[storage_texture(2, image_format = R32Float, access = ReadOnly)]
+    pub levelset_air: Handle<Image>,
+    #[storage_texture(3, image_format = Rgba32Float, access = ReadOnly)]
+    pub area_fraction_solid: Handle<Image>,
+    #[storage_texture(4, image_format = R32Float, access = ReadOnly)]
+    pub curvature: Handle<Image>,
+    #[storage_texture(5, image_format = R32Float, access = ReadWrite)]
+    pub r: Handle<Image>,
+    #[storage_texture(6, image_format = R32Float, access = ReadWrite)]
+    pub z: Handle<Image>,
+    #[storage(7, visibility(compute))]
+    pub d: Handle<ShaderStorageBuffer>,
+    #[storage(8, visibility(compute))]
+    pub q: Handle<ShaderStorageBuffer>,
+    #[storage(9, visibility(compute))]
+    pub partial_sums: Handle<ShaderStorageBuffer>,
+    #[storage(10, visibility(compute))]
+    pub state: Handle<ShaderStorageBuffer>,
+    #[uniform(11)]
+    pub tolerance: f32,
+    /// Dispatch arguments of the preconditioner per multigrid level, zeroed once the solver converges.
+    #[storage(12, visibility(compute))]
+    pub indirect_args: Handle<ShaderStorageBuffer>,
+}
+
+/// Creates the buffers of the search direction `d`, `q = Ad`, partial sums of the reductions, the solver state
+/// and the dispatch arguments of the preconditioner.
+pub(crate) fn new_buffers(
+    buffers: &mut Assets<ShaderStorageBuffer>,
+    grid_size: UVec2,
+) -> [Handle<ShaderStorageBuffer>; 5] {
+    let num_cells = grid_size.element_product() as usize;
+    let num_workgroups = (grid_size / WORKGROUP_SIZE).element_product();
+
+    [
+        buffers.add(ShaderStorageBuffer::from(vec![0.0f32; num_cells])),
+        buffers.add(ShaderStorageBuffer::from(vec![0.0f32; num_cells])),
+        buffers.add(ShaderStorageBuffer::from(vec![
+            Vec4::ZERO;
+            num_workgroups as usize
+        ])),
+        buffers.add(ShaderStorageBuffer::from(ConjugateGradientState::default())),
+        new_indirect_args(buffers, grid_size),
+    ]
+}
+
+#[derive(Resource)]
+pub(crate) struct ConjugateGradientPipelines {
+    initialize_pipeline: CachedComputePipelineId,
+    clear_preconditioned_pipeline: CachedComputePipelineId,
+    precondition_jacobi_pipeline: CachedComputePipelineId,
+    initialize_direction_pipeline: CachedComputePipelineId,
+    apply_operator_pipeline: CachedComputePipelineId,
+    update_solution_pipeline: CachedComputePipelineId,
+    update_direction_pipeline: CachedComputePipelineId,
+    dot_residual_pipeline: CachedComputePipelineId,
+    dot_direction_pipeline: CachedComputePipelineId,
+    finalize_initial_pipeline: CachedComputePipelineId,
+    finalize_alpha_pipeline: CachedComputePipelineId,
+    finalize_beta_pipeline: CachedComputePipelineId,
+    pub bind_group_layout: BindGroupLayoutDescriptor,
+}
+
+impl FromWorld for ConjugateGradientPipelines {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let bind_group_layout =
+            ConjugateGradientResource::bind_group_layout_descriptor(render_device);
+        let layouts = vec![bind_group_layout.clone(), uniform_bind_group_layout_desc()];
+
+        let mut queue = |label: &'static str, entry_point: &'static str| {
+            queue_compute_pipeline(
+                world,
+                label,
+                embedded_path!("shaders/conjugate_gradient.wgsl"),
+                entry_point,
+                layouts.clone(),
+            )
+        };
+
+        Self {
+            initialize_pipeline: queue("ConjugateGradientInitializePipeline", "initialize"),
+            clear_preconditioned_pipeline: queue(
+                "ConjugateGradientClearPreconditionedPipeline",
+                "clear_preconditioned",
+            ),
+            precondition_jacobi_pipeline: queue(
+                "ConjugateGradientPreconditionJacobiPipeline",
+                "precondition_jacobi",
+            ),
+            initialize_direction_pipeline: queue(
+                "ConjugateGradientInitializeDirectionPipeline",
+                "initialize_direction",
+            ),
+            apply_operator_pipeline: queue(
+                "ConjugateGradientApplyOperatorPipeline",
+                "apply_operator",
+            ),
+            update_solution_pipeline: queue(
+                "ConjugateGradientUpdateSolutionPipeline",
+                "update_solution",
+            ),
+            update_direction_pipeline: queue(
+                "ConjugateGradientUpdateDirectionPipeline",
+                "update_direction",
+            ),
+            dot_residual_pipeline: queue("ConjugateGradientDotResidualPipeline", "dot_residual"),
+            dot_direction_pipeline: queue("ConjugateGradientDotDirectionPipeline", "dot_direction"),
+            finalize_initial_pipeline: queue(
+                "ConjugateGradientFinalizeInitialPipeline",
+                "finalize_initial",
+            ),
+            finalize_alpha_pipeline: queue(
+                "ConjugateGradientFinalizeAlphaPipeline",
+                "finalize_alpha",
+            ),
+            finalize_beta_pipeline: queue("ConjugateGradientFinalizeBetaPipeline", "finalize_beta"),
+            bind_group_layout,
+        }
+    }
+}
+
+impl HasBindGroupLayout for ConjugateGradientPipelines {
+    fn bind_group_layout(&self) -> &BindGroupLayoutDescriptor {
+        &self.bind_group_layout
+    }
+}
+
+impl ConjugateGradientPipelines {
+    pub fn is_ready(&self, pipeline_cache: &PipelineCache) -> bool {
+        [
+            self.initialize_pipeline,
+            self.clear_preconditioned_pipeline,
+            self.precondition_jacobi_pipeline,
+            self.initialize_direction_pipeline,
+            self.apply_operator_pipeline,
+            self.update_solution_pipeline,
+            self.update_direction_pipeline,
+            self.dot_residual_pipeline,
+            self.dot_direction_pipeline,
+            self.finalize_initial_pipeline,
+            self.finalize_alpha_pipeline,
+            self.finalize_beta_pipeline,
+        ]
+        .into_iter()
+        .all(|pipeline| is_pipeline_loaded(pipeline_cache, pipeline))
+    }
+
+    fn run(
+        &self,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        pipeline: CachedComputePipelineId,
+        bind_group: &BindGroup,
+        uniform_bind_group: &SimulationUniformBindGroup,
+        num_workgroups: NumWorkgroups,
+    ) {
+        let pipeline = pipeline_cache.get_compute_pipeline(pipeline).unwrap();
+        pass.set_pipeline(pipeline);
+        // The multigrid preconditioner replaces the bind groups, so they are set every time.
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.set_bind_group(
+            1,
+            &uniform_bind_group.bind_group,
+            &[uniform_bind_group.index],
+        );
+        num_workgroups.dispatch(pass);
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct ConjugateGradientBindGroup {
+    pub bind_group: BindGroup,
+}
+
+impl From<BindGroup> for ConjugateGradientBindGroup {
+    fn from(bind_group: BindGroup) -> Self {
+        Self { bind_group }
+    }
+}
+
+pub(crate) type ConjugateGradientBindGroupsQuery = (
+    &'static ConjugateGradientBindGroup,
+    &'static ConjugateGradientResource,
+    Option<&'static MultiGridPreconditionerBindGroups>,
+    Option<(
+        &'static SolidCouplingBindGroup,
+        &'static BodyVelocityBindGroup,
+    )>,
+);
+
+pub(crate) fn dispatch(
+    world: &World,
+    config: &ConjugateGradientConfig,
+    pipeline_cache: &PipelineCache,
+    pass: &mut ComputePass,
+    bind_groups: QueryItem<ConjugateGradientBindGroupsQuery>,
+    uniform_bind_group: &SimulationUniformBindGroup,
+    size: UVec2,
+) {
+    let pipelines = world.resource::<ConjugateGradientPipelines>();
+    let solid_coupling_pipelines = world.resource::<SolidCouplingPipelines>();
+    let (bind_group, resource, preconditioner_bind_groups, solid_coupling_bind_groups) =
+        bind_groups;
+    let solid_coupling_bind_group = solid_coupling_bind_groups.map(|(bind_group, _)| bind_group);
+    let bind_group = &bind_group.bind_group;
+    let num_workgroups_grid = (size / WORKGROUP_SIZE).extend(1);
+    let num_workgroups_single = UVec3::ONE;
+    // Written by the solver after each reduction and zeroed once it converges,
+    // so that the remaining iterations skip the multigrid preconditioner.
+    let indirect_args = world
+        .resource::<RenderAssets<GpuShaderStorageBuffer>>()
+        .get(&resource.indirect_args)
+        .map(|indirect_args| &indirect_args.buffer);
+
+    let run = |pass: &mut ComputePass, pipeline: CachedComputePipelineId, num_workgroups| {
+        pipelines.run(
+            pipeline_cache,
+            pass,
+            pipeline,
+            bind_group,
+            uniform_bind_group,
+            NumWorkgroups::Direct(num_workgroups),
+        );
+    };
+
+    // z = M^-1 r
+    let preconditioner = &config.preconditioner;
+    let precondition = |pass: &mut ComputePass, indirect_args| match preconditioner {
+        ConjugateGradientPreconditioner::Jacobi => {
+            run(
+                pass,
+                pipelines.precondition_jacobi_pipeline,
+                num_workgroups_grid,
+            );
+        }
+        ConjugateGradientPreconditioner::MultiGrid(multi_grid_config) => {
+            let preconditioner_bind_groups = preconditioner_bind_groups
+                .expect("MultiGridPreconditionerBindGroups component is missing in RenderWorld.");
+            pipelines.run(
+                pipeline_cache,
+                pass,
+                pipelines.clear_preconditioned_pipeline,
+                bind_group,
+                uniform_bind_group,
+                level_num_workgroups(indirect_args, 0, num_workgroups_grid),
+            );
+            world.resource::<MultiGridPipelines>().dispatch(
+                pipeline_cache,
+                pass,
+                &preconditioner_bind_groups.bind_groups,
+                uniform_bind_group,
+                &MultiGridDispatchParams {
+                    grid_size: size,
+                    config: multi_grid_config,
+                    levels: &preconditioner_bind_groups.num_levels,
+                    indirect_args,
+                    symmetric: true,
+                },
+            );
+        }
+    };
+
+    // With the monolithic coupling, J^T x is accumulated to the bodies before the operator is applied to x.
+    let run_coupled = |pass: &mut ComputePass,
+                       solid_coupling_bind_group: &SolidCouplingBindGroup,
+                       accumulate_pipeline: CachedComputePipelineId,
+                       operator_pipeline: CachedComputePipelineId| {
+        for pipeline in [
+            solid_coupling_pipelines.clear_body_forces_pipeline,
+            accumulate_pipeline,
+            operator_pipeline,
+        ] {
+            pass.set_bind_group(2, &solid_coupling_bind_group.bind_group, &[]);
+            run(pass, pipeline, num_workgroups_grid);
+        }
+    };
+    let apply_operator = |pass: &mut ComputePass| match solid_coupling_bind_group {
+        Some(solid_coupling_bind_group) => run_coupled(
+            pass,
+            solid_coupling_bind_group,
+            solid_coupling_pipelines.accumulate_direction_pipeline,
+            solid_coupling_pipelines.apply_operator_pipeline,
+        ),
+        None => run(pass, pipelines.apply_operator_pipeline, num_workgroups_grid),
+    };
+
+    match solid_coupling_bind_group {
+        Some(solid_coupling_bind_group) => run_coupled(
+            pass,
+            solid_coupling_bind_group,
+            solid_coupling_pipelines.accumulate_pressure_pipeline,
+            solid_coupling_pipelines.initialize_pipeline,
+        ),
+        None => run(pass, pipelines.initialize_pipeline, num_workgroups_grid),
+    }
+    // The dispatch arguments are not written yet in this step.
+    precondition(pass, None);
+    run(pass, pipelines.dot_residual_pipeline, num_workgroups_grid);
+    run(
+        pass,
+        pipelines.finalize_initial_pipeline,
+        num_workgroups_single,
+    );
+    run(
+        pass,
+        pipelines.initialize_direction_pipeline,
+        num_workgroups_grid,
+    );
+
+    for _ in 0..config.max_iterations {
+        apply_operator(pass);
+        run(pass, pipelines.dot_direction_pipeline, num_workgroups_grid);
+        run(
+            pass,
+            pipelines.finalize_alpha_pipeline,
+            num_workgroups_single,
+        );
+        run(
+            pass,
+            pipelines.update_solution_pipeline,
+            num_workgroups_grid,
+        );
+        precondition(pass, indirect_args);
+        run(pass, pipelines.dot_residual_pipeline, num_workgroups_grid);
+        run(
+            pass,
+            pipelines.finalize_beta_pipeline,
+            num_workgroups_single,
+        );
+        run(
+            pass,
+            pipelines.update_direction_pipeline,
+            num_workgroups_grid,
+        );
+    }
+
+    // The bodies are updated by the forces of the final pressure, and the faces covered by them move accordingly.
+    if let Some((solid_coupling_bind_group, body_velocity_bind_group)) = solid_coupling_bind_groups
+    {
+        for pipeline in [
+            solid_coupling_pipelines.clear_body_forces_pipeline,
+            solid_coupling_pipelines.accumulate_pressure_pipeline,
+        ] {
+            pass.set_bind_group(2, &solid_coupling_bind_group.bind_group, &[]);
+            run(pass, pipeline, num_workgroups_grid);
+        }
+        world.resource::<BodyVelocityPipelines>().dispatch(
+            pipeline_cache,
+            pass,
+            body_velocity_bind_group,
+            uniform_bind_group,
+            size,
+        );
+    }
+}
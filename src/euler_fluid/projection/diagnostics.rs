@@ -0,0 +1,337 @@
+use bevy::{
+    asset::{embedded_asset, embedded_path},
+    prelude::*,
+    render::{
+        extract_component::ExtractComponent,
+        gpu_readback::{Readback, ReadbackComplete},
+        render_resource::{
+            AsBindGroup, BindGroup, BindGroupLayoutDescriptor, Buffer, BufferUsages,
+            CachedComputePipelineId, ComputePass, PipelineCache, ShaderType,
+        },
+        renderer::RenderDevice,
+        storage::ShaderStorageBuffer,
+    },
+};
+
+use crate::{
+    fluid_uniform::{uniform_bind_group_layout_desc, SimulationUniformBindGroup},
+    pipeline::{
+        is_pipeline_loaded, queue_compute_pipeline, HasBindGroupLayout, NumWorkgroups,
+        WORKGROUP_SIZE,
+    },
+    plugin::FluidComputePass,
+    projection::PressurePoissonTextures,
+    settings::FluidSettings,
+};
+
+pub(crate) struct ProjectionResidualPass;
+
+impl FluidComputePass for ProjectionResidualPass {
+    type Pipeline = ProjectionResidualPipelines;
+    type Resource = ProjectionResidualResource;
+    type BG = ProjectionResidualBindGroup;
+
+    fn register_assets(app: &mut App) {
+        embedded_asset!(app, "shaders/residual_norm.wgsl");
+    }
+}
+
+/// Reads back the residual of the pressure Poisson equation after each step.
+/// Insert this component together with [`crate::settings::FluidSettings`] to enable it.
+/// The result can be found on [`ProjectionResidual`].
+/// # Fields
+/// * `tolerance`: If set, the pressure solve is repeated in chunks until the residual satisfies the tolerance.
+///   Otherwise, the pressure solve runs once as configured and the residual is only reported.
+#[derive(Component, ExtractComponent, Clone, Debug, Default)]
+pub struct ProjectionDiagnostics {
+    pub tolerance: Option<ProjectionTolerance>,
+}
+
+/// Termination criteria of the chunked pressure solve.
+/// A chunk is a single run of the [`super::ProjectionMethod`], e.g. `num_iterations` sweeps of Gauss-Seidel or a V-cycle of multigrid.
+/// The chunks after convergence are skipped on GPU, so that no readback is needed in the middle of the step.
+/// # Fields
+/// * `tolerance`: The chunks stop once the L2 norm of the residual gets smaller than `tolerance` times the L2 norm of the divergence.
+/// * `max_chunks`: Upper limit of the chunks per step.
+#[derive(Clone, Debug)]
+pub struct ProjectionTolerance {
+    pub tolerance: f32,
+    pub max_chunks: u32,
+}
+
+impl Default for ProjectionTolerance {
+    fn default() -> Self {
+        Self {
+            tolerance: 1.0e-3,
+            max_chunks: 8,
+        }
+    }
+}
+
+/// Residual of the pressure solve read back from GPU. The values arrive a few frames after the step.
+/// # Fields
+/// * `l2`: L2 norm of the residual in unit of [1/s].
+/// * `linf`: L-infinity norm of the residual in unit of [1/s].
+/// * `num_chunks`: Number of chunks taken in the step. Always 1 without [`ProjectionDiagnostics::tolerance`].
+/// * `converged`: Whether the residual satisfies the tolerance.
+#[derive(Component, Clone, Debug, Default)]
+pub struct ProjectionResidual {
+    pub l2: f32,
+    pub linf: f32,
+    pub num_chunks: u32,
+    pub converged: bool,
+}
+
+#[derive(ShaderType, Clone, Copy, Default)]
+struct ProjectionResidualState {
+    l2: f32,
+    linf: f32,
+    b_l2: f32,
+    num_chunks: u32,
+    converged: u32,
+}
+
+#[derive(Component, ExtractComponent, Clone, AsBindGroup)]
+pub(crate) struct ProjectionResidualResource {
+    #[storage_texture(0, image_format = R32Float, access = ReadOnly)]
+    pub p: Handle<Image>,
+    #[storage_texture(1, image_format = R32Float, access = ReadOnly)]
+    pub div: Handle<Image>,
+    #[storage_texture(2, image_format = R32Float, access = ReadOnly)]
+    pub levelset_air: Handle<Image>,
+    #[storage_texture(3, image_format = Rgba32Float, access = ReadOnly)]
+    pub area_fraction_solid: Handle<Image>,
+    #[storage_texture(4, image_format = R32Float, access = ReadOnly)]
+    pub curvature: Handle<Image>,
+    #[storage(5, visibility(compute))]
+    pub partial_sums: Handle<ShaderStorageBuffer>,
+    #[storage(6, visibility(compute))]
+    pub state: Handle<ShaderStorageBuffer>,
+    #[storage(7, visibility(compute))]
+    pub indirect_args: Handle<ShaderStorageBuffer>,
+    #[uniform(8)]
+    pub tolerance: f32,
+}
+
+/// Marks the entity reading back the residual of its parent fluid.
+#[derive(Component)]
+pub(crate) struct ProjectionResidualReadback;
+
+pub(crate) fn setup(
+    commands: &mut Commands,
+    entity: Entity,
+    buffers: &mut ResMut<Assets<ShaderStorageBuffer>>,
+    grid_size: UVec2,
+    diagnostics: &ProjectionDiagnostics,
+    textures: &PressurePoissonTextures,
+) {
+    let size_partial_sums = (grid_size / WORKGROUP_SIZE).element_product() as usize;
+    let partial_sums = buffers.add(ShaderStorageBuffer::from(vec![
+        Vec4::ZERO;
+        size_partial_sums
+    ]));
+
+    let mut state = ShaderStorageBuffer::from(ProjectionResidualState::default());
+    state.buffer_description.usage |= BufferUsages::COPY_SRC;
+    let state = buffers.add(state);
+
+    let indirect_args = new_indirect_args(buffers, grid_size);
+
+    let readback = commands
+        .spawn((ProjectionResidualReadback, Readback::buffer(state.clone())))
+        .observe(projection_residual_readback)
+        .id();
+
+    commands
+        .entity(entity)
+        .insert((
+            ProjectionResidualResource {
+                p: textures.p.clone(),
+                div: textures.div.clone(),
+                levelset_air: textures.levelset_air.clone(),
+                area_fraction_solid: textures.area_fraction_solid.clone(),
+                curvature: textures.curvature.clone(),
+                partial_sums,
+                state,
+                indirect_args,
+                tolerance: diagnostics
+                    .tolerance
+                    .as_ref()
+                    .map_or(0.0, |tolerance| tolerance.tolerance),
+            },
+            ProjectionResidual::default(),
+        ))
+        .add_child(readback);
+}
+
+fn projection_residual_readback(
+    trigger: On<ReadbackComplete>,
+    mut fluid_query: Query<&mut ProjectionResidual, With<FluidSettings>>,
+    query: Query<&ChildOf, With<ProjectionResidualReadback>>,
+) {
+    let Ok(child) = query.get(trigger.entity) else {
+        return;
+    };
+
+    let Ok(mut residual) = fluid_query.get_mut(child.parent()) else {
+        return;
+    };
+    let state: ProjectionResidualState = trigger.event().to_shader_type();
+    *residual = ProjectionResidual {
+        l2: state.l2,
+        linf: state.linf,
+        num_chunks: state.num_chunks,
+        converged: state.converged != 0,
+    };
+}
+
+/// Creates the dispatch arguments of the pressure solve, 3 u32 for every level of the multigrid hierarchy.
+pub(crate) fn new_indirect_args(
+    buffers: &mut Assets<ShaderStorageBuffer>,
+    grid_size: UVec2,
+) -> Handle<ShaderStorageBuffer> {
+    let num_levels = grid_size.min_element().ilog2().max(1) as usize;
+    let mut indirect_args = ShaderStorageBuffer::from(vec![0u32; 3 * num_levels]);
+    indirect_args.buffer_description.usage |= BufferUsages::INDIRECT;
+    buffers.add(indirect_args)
+}
+
+/// Workgroups of the pressure solve at the multigrid level.
+/// With `indirect_args`, the counts are read from GPU so that the dispatch is skipped once the tolerance is met.
+pub(crate) fn level_num_workgroups(
+    indirect_args: Option<&Buffer>,
+    level: usize,
+    num_workgroups: UVec3,
+) -> NumWorkgroups<'_> {
+    match indirect_args {
+        Some(buffer) => NumWorkgroups::Indirect(buffer, (3 * size_of::<u32>() * level) as u64),
+        None => NumWorkgroups::Direct(num_workgroups),
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct ProjectionResidualPipelines {
+    reset_pipeline: CachedComputePipelineId,
+    partial_reduction_pipeline: CachedComputePipelineId,
+    reduction_pipeline: CachedComputePipelineId,
+    pub bind_group_layout: BindGroupLayoutDescriptor,
+}
+
+impl FromWorld for ProjectionResidualPipelines {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let bind_group_layout =
+            ProjectionResidualResource::bind_group_layout_descriptor(render_device);
+        let layouts = vec![bind_group_layout.clone(), uniform_bind_group_layout_desc()];
+
+        let reset_pipeline = queue_compute_pipeline(
+            world,
+            "ProjectionResidualResetPipeline",
+            embedded_path!("shaders/residual_norm.wgsl"),
+            "reset",
+            layouts.clone(),
+        );
+
+        let partial_reduction_pipeline = queue_compute_pipeline(
+            world,
+            "ProjectionResidualPartialReductionPipeline",
+            embedded_path!("shaders/residual_norm.wgsl"),
+            "partial_reduction",
+            layouts.clone(),
+        );
+
+        let reduction_pipeline = queue_compute_pipeline(
+            world,
+            "ProjectionResidualReductionPipeline",
+            embedded_path!("shaders/residual_norm.wgsl"),
+            "reduction",
+            layouts,
+        );
+
+        Self {
+            reset_pipeline,
+            partial_reduction_pipeline,
+            reduction_pipeline,
+            bind_group_layout,
+        }
+    }
+}
+
+impl HasBindGroupLayout for ProjectionResidualPipelines {
+    fn bind_group_layout(&self) -> &BindGroupLayoutDescriptor {
+        &self.bind_group_layout
+    }
+}
+
+impl ProjectionResidualPipelines {
+    pub fn is_ready(&self, pipeline_cache: &PipelineCache) -> bool {
+        is_pipeline_loaded(pipeline_cache, self.reset_pipeline)
+            && is_pipeline_loaded(pipeline_cache, self.partial_reduction_pipeline)
+            && is_pipeline_loaded(pipeline_cache, self.reduction_pipeline)
+    }
+
+    /// Clears the number of chunks and enables the dispatches of the pressure solve.
+    pub fn dispatch_reset(
+        &self,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        bind_group: &ProjectionResidualBindGroup,
+        uniform_bind_group: &SimulationUniformBindGroup,
+    ) {
+        let reset_pipeline = pipeline_cache
+            .get_compute_pipeline(self.reset_pipeline)
+            .unwrap();
+
+        pass.set_pipeline(reset_pipeline);
+        pass.set_bind_group(0, &bind_group.bind_group, &[]);
+        pass.set_bind_group(
+            1,
+            &uniform_bind_group.bind_group,
+            &[uniform_bind_group.index],
+        );
+        pass.dispatch_workgroups(1, 1, 1);
+    }
+
+    /// Computes the norms of the residual and disables the dispatches of the pressure solve once the tolerance is met.
+    pub fn dispatch(
+        &self,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        bind_group: &ProjectionResidualBindGroup,
+        uniform_bind_group: &SimulationUniformBindGroup,
+        grid_size: UVec2,
+    ) {
+        pass.push_debug_group("Projection residual");
+        let partial_reduction_pipeline = pipeline_cache
+            .get_compute_pipeline(self.partial_reduction_pipeline)
+            .unwrap();
+        let reduction_pipeline = pipeline_cache
+            .get_compute_pipeline(self.reduction_pipeline)
+            .unwrap();
+
+        pass.set_pipeline(partial_reduction_pipeline);
+        pass.set_bind_group(0, &bind_group.bind_group, &[]);
+        pass.set_bind_group(
+            1,
+            &uniform_bind_group.bind_group,
+            &[uniform_bind_group.index],
+        );
+        let num_workgroups = grid_size / WORKGROUP_SIZE;
+        pass.dispatch_workgroups(num_workgroups.x, num_workgroups.y, 1);
+
+        pass.set_pipeline(reduction_pipeline);
+        pass.dispatch_workgroups(1, 1, 1);
+        pass.pop_debug_group();
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct ProjectionResidualBindGroup {
+    pub bind_group: BindGroup,
+}
+
+impl From<BindGroup> for ProjectionResidualBindGroup {
+    fn from(bind_group: BindGroup) -> Self {
+        Self { bind_group }
+    }
+}
@@ -5,7 +5,7 @@ use bevy::{
         extract_component::ExtractComponent,
         render_resource::{
             AsBindGroup, BindGroup, BindGroupLayoutDescriptor, CachedComputePipelineId,
-            ComputePass, PipelineCache,
+            ComputePass, ComputePipeline, PipelineCache,
         },
         renderer::RenderDevice,
     },
@@ -13,7 +13,7 @@ use bevy::{
 
 use crate::{
     fluid_uniform::{uniform_bind_group_layout_desc, SimulationUniformBindGroup},
-    pipeline::{is_pipeline_loaded, queue_compute_pipeline, HasBindGroupLayout},
+    pipeline::{is_pipeline_loaded, queue_compute_pipeline, HasBindGroupLayout, NumWorkgroups},
     plugin::FluidComputePass,
 };
 
@@ -55,6 +55,10 @@ pub(crate) struct GaussSeidelResource {
     weight: f32,
     #[uniform(5)]
     resolution_scale: f32,
+    #[storage_texture(6, image_format = R32Float, access = ReadOnly)]
+    curvature: Handle<Image>,
+    #[uniform(7)]
+    pressure_jump_scale: f32,
 }
 
 impl GaussSeidelResource {
@@ -63,6 +67,7 @@ impl GaussSeidelResource {
         div: &Handle<Image>,
         levelset_air: &Handle<Image>,
         area_fraction_solid: &Handle<Image>,
+        curvature: &Handle<Image>,
     ) -> Self {
         Self {
             p: p.clone(),
@@ -71,6 +76,8 @@ impl GaussSeidelResource {
             area_fraction_solid: area_fraction_solid.clone(),
             weight: 1.9,
             resolution_scale: 1.0,
+            curvature: curvature.clone(),
+            pressure_jump_scale: 1.0,
         }
     }
 }
@@ -123,28 +130,56 @@ impl GaussSeidelPipeline {
         pass: &mut ComputePass,
         bind_group: &BindGroup,
         uniform_bind_group: &SimulationUniformBindGroup,
-        num_workgroups: UVec3,
+        num_workgroups: NumWorkgroups,
         config: &GaussSeidelConfig,
     ) {
-        let pipeline_red = pipeline_cache
-            .get_compute_pipeline(self.pipelines[0])
-            .unwrap();
-        let pipeline_black = pipeline_cache
-            .get_compute_pipeline(self.pipelines[1])
-            .unwrap();
+        let [pipeline_red, pipeline_black] =
+            self.bind(pipeline_cache, pass, bind_group, uniform_bind_group);
+        for _ in 0..config.num_iterations {
+            pass.set_pipeline(pipeline_red);
+            num_workgroups.dispatch(pass);
+            pass.set_pipeline(pipeline_black);
+            num_workgroups.dispatch(pass);
+        }
+    }
+
+    /// Same as [`Self::dispatch`], but sweeps black then red, i.e. the adjoint of [`Self::dispatch`].
+    /// A forward sweep followed by a reversed one is a symmetric smoother.
+    pub fn dispatch_reversed(
+        &self,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        bind_group: &BindGroup,
+        uniform_bind_group: &SimulationUniformBindGroup,
+        num_workgroups: NumWorkgroups,
+        config: &GaussSeidelConfig,
+    ) {
+        let [pipeline_red, pipeline_black] =
+            self.bind(pipeline_cache, pass, bind_group, uniform_bind_group);
+        for _ in 0..config.num_iterations {
+            pass.set_pipeline(pipeline_black);
+            num_workgroups.dispatch(pass);
+            pass.set_pipeline(pipeline_red);
+            num_workgroups.dispatch(pass);
+        }
+    }
 
+    /// Sets the bind groups and returns the red and black pipelines.
+    fn bind<'a>(
+        &self,
+        pipeline_cache: &'a PipelineCache,
+        pass: &mut ComputePass,
+        bind_group: &BindGroup,
+        uniform_bind_group: &SimulationUniformBindGroup,
+    ) -> [&'a ComputePipeline; 2] {
         pass.set_bind_group(0, bind_group, &[]);
         pass.set_bind_group(
             1,
             &uniform_bind_group.bind_group,
             &[uniform_bind_group.index],
         );
-        for _ in 0..config.num_iterations {
-            pass.set_pipeline(pipeline_red);
-            pass.dispatch_workgroups(num_workgroups.x, num_workgroups.y, num_workgroups.z);
-            pass.set_pipeline(pipeline_black);
-            pass.dispatch_workgroups(num_workgroups.x, num_workgroups.y, num_workgroups.z);
-        }
+        self.pipelines
+            .map(|pipeline| pipeline_cache.get_compute_pipeline(pipeline).unwrap())
     }
 }
 
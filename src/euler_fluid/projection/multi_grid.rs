@@ -6,7 +6,7 @@ use bevy::{
         render_asset::RenderAssets,
         render_resource::{
             binding_types::{texture_storage_2d, uniform_buffer},
-            BindGroup, BindGroupEntries, BindGroupLayoutDescriptor, BindGroupLayoutEntries,
+            BindGroup, BindGroupEntries, BindGroupLayoutDescriptor, BindGroupLayoutEntries, Buffer,
             CachedComputePipelineId, ComputePass, ComputePipeline, ComputePipelineDescriptor,
             PipelineCache, ShaderStages, StorageTextureAccess, TextureFormat, UniformBuffer,
         },
@@ -19,7 +19,11 @@ use bevy::{
 use crate::{
     fluid_uniform::{uniform_bind_group_layout_desc, SimulationUniformBindGroup},
     pipeline::{is_pipeline_loaded, WORKGROUP_SIZE},
-    projection::gauss_seidel::{GaussSeidelConfig, GaussSeidelPipeline},
+    projection::{
+        diagnostics::level_num_workgroups,
+        gauss_seidel::{GaussSeidelConfig, GaussSeidelPipeline},
+        PressurePoissonTextures,
+    },
     texture::NewTexture,
 };
 
@@ -33,6 +37,7 @@ impl Plugin for MultiGridPassPlugin {
         app.add_plugins((
             ExtractComponentPlugin::<MultiGridResources>::default(),
             ExtractComponentPlugin::<MultiGridNumLevels>::default(),
+            ExtractComponentPlugin::<MultiGridPreconditionerResources>::default(),
         ));
 
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
@@ -41,7 +46,8 @@ impl Plugin for MultiGridPassPlugin {
 
         render_app.add_systems(
             Render,
-            prepare_bind_groups.in_set(RenderSystems::PrepareBindGroups),
+            (prepare_bind_groups, prepare_preconditioner_bind_groups)
+                .in_set(RenderSystems::PrepareBindGroups),
         );
     }
 
@@ -80,18 +86,56 @@ pub(crate) struct MultiGridResources {
     levelset: Vec<Handle<Image>>,
     area_fraction_solid: Vec<Handle<Image>>,
     r: Vec<Handle<Image>>,
+    curvature: Handle<Image>,
+    // Whether the finest level imposes the pressure jump by surface tension.
+    pressure_jump: bool,
+}
+
+/// Multigrid hierarchy solving for the preconditioned residual of the conjugate gradient solver.
+/// It is separated from [`MultiGridResources`] so that both can live on the same entity.
+#[derive(Component, ExtractComponent, Clone)]
+pub(crate) struct MultiGridPreconditionerResources {
+    resources: MultiGridResources,
+    num_levels: MultiGridNumLevels,
 }
 
 pub(crate) fn setup_multigrid_resources(
     commands: &mut Commands,
     entity: Entity,
     grid_size: UVec2,
-    div: &Handle<Image>,
-    p: &Handle<Image>,
-    levelset_air: &Handle<Image>,
-    area_fraction_solid: &Handle<Image>,
+    textures: &PressurePoissonTextures,
+    images: &mut ResMut<Assets<Image>>,
+) {
+    let (resources, num_levels) = new_multigrid_resources(grid_size, textures, true, images);
+
+    commands.entity(entity).insert((resources, num_levels));
+}
+
+/// Sets up the multigrid hierarchy for the preconditioner, whose `textures` solve for `z` with the residual `r` as the right hand side.
+/// The preconditioner has to be a linear operator, so the pressure jump by surface tension is not imposed.
+pub(crate) fn setup_multigrid_preconditioner_resources(
+    commands: &mut Commands,
+    entity: Entity,
+    grid_size: UVec2,
+    textures: &PressurePoissonTextures,
     images: &mut ResMut<Assets<Image>>,
 ) {
+    let (resources, num_levels) = new_multigrid_resources(grid_size, textures, false, images);
+
+    commands
+        .entity(entity)
+        .insert(MultiGridPreconditionerResources {
+            resources,
+            num_levels,
+        });
+}
+
+fn new_multigrid_resources(
+    grid_size: UVec2,
+    textures: &PressurePoissonTextures,
+    pressure_jump: bool,
+    images: &mut ResMut<Assets<Image>>,
+) -> (MultiGridResources, MultiGridNumLevels) {
     let num_levels = ((grid_size.min_element() as f32).log2() as usize)
         .saturating_sub(2)
         .max(1);
@@ -101,10 +145,10 @@ pub(crate) fn setup_multigrid_resources(
     let mut levelset = Vec::<Handle<Image>>::with_capacity(num_levels);
     let mut area_fraction_solids = Vec::<Handle<Image>>::with_capacity(num_levels);
     let mut r = Vec::<Handle<Image>>::with_capacity(num_levels);
-    x.push(p.clone());
-    b.push(div.clone());
-    levelset.push(levelset_air.clone());
-    area_fraction_solids.push(area_fraction_solid.clone());
+    x.push(textures.p.clone());
+    b.push(textures.div.clone());
+    levelset.push(textures.levelset_air.clone());
+    area_fraction_solids.push(textures.area_fraction_solid.clone());
     r.push(images.new_texture_storage(grid_size, TextureFormat::R32Float));
     let mut grid_size = grid_size;
     for _ in 1..num_levels {
@@ -123,11 +167,11 @@ pub(crate) fn setup_multigrid_resources(
         levelset,
         area_fraction_solid: area_fraction_solids,
         r,
+        curvature: textures.curvature.clone(),
+        pressure_jump,
     };
 
-    commands
-        .entity(entity)
-        .insert((resources, MultiGridNumLevels(num_levels)));
+    (resources, MultiGridNumLevels(num_levels))
 }
 
 #[derive(Resource)]
@@ -158,6 +202,8 @@ impl FromWorld for MultiGridPipelines {
                     // Workaround: Insert resource barrier by changing to read-and-write access to surpress simulation instability on DX12 and NVIDIA GPU while the only write access should be enough.
                     texture_storage_2d(TextureFormat::R32Float, StorageTextureAccess::ReadWrite),
                     uniform_buffer::<f32>(false),
+                    texture_storage_2d(TextureFormat::R32Float, StorageTextureAccess::ReadOnly),
+                    uniform_buffer::<f32>(false),
                 ),
             ),
         );
@@ -168,7 +214,7 @@ impl FromWorld for MultiGridPipelines {
             label: Some("ResidualPipeline".into()),
             layout: vec![
                 residual_bind_group_layout.clone(),
-                uniform_bind_group_layout,
+                uniform_bind_group_layout.clone(),
             ],
             shader: load_embedded_asset!(world, "shaders/residual.wgsl"),
             entry_point: Some("residual".into()),
@@ -194,7 +240,10 @@ impl FromWorld for MultiGridPipelines {
         let restriction_pipeline =
             pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
                 label: Some("RestrictionPipeline".into()),
-                layout: vec![restriction_bind_group_layout.clone()],
+                layout: vec![
+                    restriction_bind_group_layout.clone(),
+                    uniform_bind_group_layout,
+                ],
                 shader: load_embedded_asset!(world, "shaders/restriction.wgsl"),
                 entry_point: Some("restriction".into()),
                 ..default()
@@ -250,6 +299,12 @@ pub(crate) struct MultiGridBindGroups {
     prolongation_bind_groups: Box<[BindGroup]>,
 }
 
+#[derive(Component)]
+pub(crate) struct MultiGridPreconditionerBindGroups {
+    pub bind_groups: MultiGridBindGroups,
+    pub num_levels: MultiGridNumLevels,
+}
+
 fn prepare_bind_groups(
     mut commands: Commands,
     render_device: Res<RenderDevice>,
@@ -260,96 +315,177 @@ fn prepare_bind_groups(
     pipelines: Res<MultiGridPipelines>,
 ) {
     for (entity, resources, num_levels) in &query {
-        let mut smoother_bind_groups = Vec::with_capacity(num_levels.0);
-        let mut residual_bind_groups = Vec::with_capacity(num_levels.0 - 1);
-        let mut restriction_bind_groups = Vec::with_capacity(num_levels.0 - 1);
-        let mut prolongation_bind_groups = Vec::with_capacity(num_levels.0 - 1);
-        for i in 0..num_levels.0 {
-            let mut resolution_scale_buffer = UniformBuffer::from((1 << i) as f32);
-            resolution_scale_buffer.write_buffer(&render_device, &render_queue);
-            let mut gs_weight_buffer = UniformBuffer::from(1.0);
-            gs_weight_buffer.write_buffer(&render_device, &render_queue);
-
-            let x = gpu_images.get(&resources.x[i]).unwrap();
-            let b = gpu_images.get(&resources.b[i]).unwrap();
-            let levelset = gpu_images.get(&resources.levelset[i]).unwrap();
-            let area_fraction_solid = gpu_images.get(&resources.area_fraction_solid[i]).unwrap();
-            let r = gpu_images.get(&resources.r[i]).unwrap();
-
-            smoother_bind_groups.push(
-                render_device.create_bind_group(
-                    format!("SmootherBindGroup_Level{}", i).as_str(),
-                    &pipeline_cache
-                        .get_bind_group_layout(&pipelines.smoother_pipeline.bind_group_layout),
-                    &BindGroupEntries::sequential((
-                        &x.texture_view,
-                        &b.texture_view,
-                        &levelset.texture_view,
-                        &area_fraction_solid.texture_view,
-                        gs_weight_buffer.binding().unwrap(),
-                        resolution_scale_buffer.binding().unwrap(),
-                    )),
-                ),
-            );
-
-            if i == num_levels.0 - 1 {
-                continue;
-            }
-
-            residual_bind_groups.push(render_device.create_bind_group(
-                format!("ResidualBindGroup_Level{i}").as_str(),
-                &pipeline_cache.get_bind_group_layout(&pipelines.residual_bind_group_layout),
-                &BindGroupEntries::sequential((
-                    &x.texture_view,
-                    &b.texture_view,
-                    &levelset.texture_view,
-                    &area_fraction_solid.texture_view,
-                    &r.texture_view,
-                    resolution_scale_buffer.binding().unwrap(),
-                )),
-            ));
-
-            let x_plus = gpu_images.get(&resources.x[i + 1]).unwrap();
-            let b_plus = gpu_images.get(&resources.b[i + 1]).unwrap();
-            let levelset_plus = gpu_images.get(&resources.levelset[i + 1]).unwrap();
-            let area_fraction_solid_plus = gpu_images
-                .get(&resources.area_fraction_solid[i + 1])
-                .unwrap();
-
-            restriction_bind_groups.push(render_device.create_bind_group(
-                format!("Restriction_Level{}_to_Level{}", i, i + 1).as_str(),
-                &pipeline_cache.get_bind_group_layout(&pipelines.restriction_bind_group_layout),
-                &BindGroupEntries::sequential((
-                    &r.texture_view,
-                    &levelset.texture_view,
-                    &area_fraction_solid.texture_view,
-                    &b_plus.texture_view,
-                    &levelset_plus.texture_view,
-                    &area_fraction_solid_plus.texture_view,
-                    &x_plus.texture_view,
-                )),
-            ));
-
-            prolongation_bind_groups.push(render_device.create_bind_group(
-                format!("Prolongation_Level{}_to_Level{}", i + 1, i).as_str(),
-                &pipeline_cache.get_bind_group_layout(&pipelines.prolongation_bind_group_layout),
-                &BindGroupEntries::sequential((
-                    &x.texture_view,
-                    &x_plus.texture_view,
-                    &levelset.texture_view,
-                )),
-            ));
+        let bind_groups = create_bind_groups(
+            &render_device,
+            &render_queue,
+            &pipeline_cache,
+            &gpu_images,
+            &pipelines,
+            resources,
+            num_levels,
+        );
+        commands.entity(entity).insert(bind_groups);
+    }
+}
+
+fn prepare_preconditioner_bind_groups(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    pipeline_cache: Res<PipelineCache>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    query: Query<(Entity, &MultiGridPreconditionerResources)>,
+    pipelines: Res<MultiGridPipelines>,
+) {
+    for (entity, preconditioner) in &query {
+        let bind_groups = create_bind_groups(
+            &render_device,
+            &render_queue,
+            &pipeline_cache,
+            &gpu_images,
+            &pipelines,
+            &preconditioner.resources,
+            &preconditioner.num_levels,
+        );
+        commands
+            .entity(entity)
+            .insert(MultiGridPreconditionerBindGroups {
+                bind_groups,
+                num_levels: preconditioner.num_levels.clone(),
+            });
+    }
+}
+
+fn create_bind_groups(
+    render_device: &RenderDevice,
+    render_queue: &RenderQueue,
+    pipeline_cache: &PipelineCache,
+    gpu_images: &RenderAssets<GpuImage>,
+    pipelines: &MultiGridPipelines,
+    resources: &MultiGridResources,
+    num_levels: &MultiGridNumLevels,
+) -> MultiGridBindGroups {
+    let curvature = gpu_images.get(&resources.curvature).unwrap();
+    let mut smoother_bind_groups = Vec::with_capacity(num_levels.0);
+    let mut residual_bind_groups = Vec::with_capacity(num_levels.0 - 1);
+    let mut restriction_bind_groups = Vec::with_capacity(num_levels.0 - 1);
+    let mut prolongation_bind_groups = Vec::with_capacity(num_levels.0 - 1);
+    for i in 0..num_levels.0 {
+        let mut resolution_scale_buffer = UniformBuffer::from((1 << i) as f32);
+        resolution_scale_buffer.write_buffer(render_device, render_queue);
+        let mut gs_weight_buffer = UniformBuffer::from(1.0);
+        gs_weight_buffer.write_buffer(render_device, render_queue);
+        // Pressure jump by surface tension appears only on the finest level.
+        let mut pressure_jump_scale_buffer =
+            UniformBuffer::from(if i == 0 && resources.pressure_jump {
+                1.0f32
+            } else {
+                0.0
+            });
+        pressure_jump_scale_buffer.write_buffer(render_device, render_queue);
+
+        let x = gpu_images.get(&resources.x[i]).unwrap();
+        let b = gpu_images.get(&resources.b[i]).unwrap();
+        let levelset = gpu_images.get(&resources.levelset[i]).unwrap();
+        let area_fraction_solid = gpu_images.get(&resources.area_fraction_solid[i]).unwrap();
+        let r = gpu_images.get(&resources.r[i]).unwrap();
+
+        smoother_bind_groups.push(render_device.create_bind_group(
+            format!("SmootherBindGroup_Level{}", i).as_str(),
+            &pipeline_cache.get_bind_group_layout(&pipelines.smoother_pipeline.bind_group_layout),
+            &BindGroupEntries::sequential((
+                &x.texture_view,
+                &b.texture_view,
+                &levelset.texture_view,
+                &area_fraction_solid.texture_view,
+                gs_weight_buffer.binding().unwrap(),
+                resolution_scale_buffer.binding().unwrap(),
+                &curvature.texture_view,
+                pressure_jump_scale_buffer.binding().unwrap(),
+            )),
+        ));
+
+        if i == num_levels.0 - 1 {
+            continue;
         }
 
-        commands.entity(entity).insert(MultiGridBindGroups {
-            smoother_bind_groups: smoother_bind_groups.into_boxed_slice(),
-            residual_bind_groups: residual_bind_groups.into_boxed_slice(),
-            restriction_bind_groups: restriction_bind_groups.into_boxed_slice(),
-            prolongation_bind_groups: prolongation_bind_groups.into_boxed_slice(),
-        });
+        residual_bind_groups.push(render_device.create_bind_group(
+            format!("ResidualBindGroup_Level{i}").as_str(),
+            &pipeline_cache.get_bind_group_layout(&pipelines.residual_bind_group_layout),
+            &BindGroupEntries::sequential((
+                &x.texture_view,
+                &b.texture_view,
+                &levelset.texture_view,
+                &area_fraction_solid.texture_view,
+                &r.texture_view,
+                resolution_scale_buffer.binding().unwrap(),
+                &curvature.texture_view,
+                pressure_jump_scale_buffer.binding().unwrap(),
+            )),
+        ));
+
+        let x_plus = gpu_images.get(&resources.x[i + 1]).unwrap();
+        let b_plus = gpu_images.get(&resources.b[i + 1]).unwrap();
+        let levelset_plus = gpu_images.get(&resources.levelset[i + 1]).unwrap();
+        let area_fraction_solid_plus = gpu_images
+            .get(&resources.area_fraction_solid[i + 1])
+            .unwrap();
+
+        restriction_bind_groups.push(render_device.create_bind_group(
+            format!("Restriction_Level{}_to_Level{}", i, i + 1).as_str(),
+            &pipeline_cache.get_bind_group_layout(&pipelines.restriction_bind_group_layout),
+            &BindGroupEntries::sequential((
+                &r.texture_view,
+                &levelset.texture_view,
+                &area_fraction_solid.texture_view,
+                &b_plus.texture_view,
+                &levelset_plus.texture_view,
+                &area_fraction_solid_plus.texture_view,
+                &x_plus.texture_view,
+            )),
+        ));
+
+        prolongation_bind_groups.push(render_device.create_bind_group(
+            format!("Prolongation_Level{}_to_Level{}", i + 1, i).as_str(),
+            &pipeline_cache.get_bind_group_layout(&pipelines.prolongation_bind_group_layout),
+            &BindGroupEntries::sequential((
+                &x.texture_view,
+                &x_plus.texture_view,
+                &levelset.texture_view,
+            )),
+        ));
+    }
+
+    MultiGridBindGroups {
+        smoother_bind_groups: smoother_bind_groups.into_boxed_slice(),
+        residual_bind_groups: residual_bind_groups.into_boxed_slice(),
+        restriction_bind_groups: restriction_bind_groups.into_boxed_slice(),
+        prolongation_bind_groups: prolongation_bind_groups.into_boxed_slice(),
     }
 }
 
+/// Parameters of a V-cycle of the multigrid solver.
+pub(crate) struct MultiGridDispatchParams<'a> {
+    /// Size of the finest level.
+    pub grid_size: UVec2,
+    pub config: &'a MultiGridConfig,
+    pub levels: &'a MultiGridNumLevels,
+    /// Indirect dispatch args per level, zeroed once the residual tolerance is met.
+    pub indirect_args: Option<&'a Buffer>,
+    /// Whether the post smoother sweeps in the reverse order of the pre smoother,
+    /// so that the V-cycle is a symmetric operator as required for a preconditioner of the conjugate gradient solver.
+    pub symmetric: bool,
+}
+
+/// Pipelines of a V-cycle, resolved from the cache once per dispatch.
+struct VCyclePipelines<'a> {
+    pipeline_cache: &'a PipelineCache,
+    smoother: &'a GaussSeidelPipeline,
+    residual: &'a ComputePipeline,
+    restriction: &'a ComputePipeline,
+    prolongation: &'a ComputePipeline,
+}
+
 impl MultiGridPipelines {
     pub(crate) fn dispatch(
         &self,
@@ -357,119 +493,104 @@ impl MultiGridPipelines {
         pass: &mut ComputePass,
         bind_groups: &MultiGridBindGroups,
         uniform_bind_group: &SimulationUniformBindGroup,
-        grid_size: UVec2,
-        config: &MultiGridConfig,
-        levels: &MultiGridNumLevels,
+        params: &MultiGridDispatchParams,
     ) {
-        let residual_pipeline = pipeline_cache
-            .get_compute_pipeline(self.residual_pipeline)
-            .unwrap();
-        let restriction_pipeline = pipeline_cache
-            .get_compute_pipeline(self.restriction_pipeline)
-            .unwrap();
-        let prolongation_pipeline = pipeline_cache
-            .get_compute_pipeline(self.prolongation_pipeline)
-            .unwrap();
-        v_cycle(
-            0,
+        let pipelines = VCyclePipelines {
             pipeline_cache,
-            &self.smoother_pipeline,
-            residual_pipeline,
-            restriction_pipeline,
-            prolongation_pipeline,
-            pass,
-            bind_groups,
-            uniform_bind_group,
-            grid_size,
-            config,
-            levels,
-        );
+            smoother: &self.smoother_pipeline,
+            residual: pipeline_cache
+                .get_compute_pipeline(self.residual_pipeline)
+                .unwrap(),
+            restriction: pipeline_cache
+                .get_compute_pipeline(self.restriction_pipeline)
+                .unwrap(),
+            prolongation: pipeline_cache
+                .get_compute_pipeline(self.prolongation_pipeline)
+                .unwrap(),
+        };
+        v_cycle(0, &pipelines, pass, bind_groups, uniform_bind_group, params);
     }
 }
 
 fn v_cycle(
     i: usize,
-    pipeline_cache: &PipelineCache,
-    smoother_pipeline: &GaussSeidelPipeline,
-    residual_pipeline: &ComputePipeline,
-    restriction_pipeline: &ComputePipeline,
-    prolongation_pipeline: &ComputePipeline,
+    pipelines: &VCyclePipelines,
     pass: &mut ComputePass,
     bind_groups: &MultiGridBindGroups,
     uniform_bind_group: &SimulationUniformBindGroup,
-    grid_size: UVec2,
-    config: &MultiGridConfig,
-    levels: &MultiGridNumLevels,
+    params: &MultiGridDispatchParams,
 ) {
     pass.push_debug_group(format!("V-Cycle (Level {i})").as_str());
-    let num_workgroups = ((grid_size + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE).extend(1);
-
-    if i == levels.0 - 1 {
-        pass.push_debug_group("Solve");
-        smoother_pipeline.dispatch(
-            pipeline_cache,
+    let grid_size = params.grid_size >> i as u32;
+    let num_workgroups = level_num_workgroups(
+        params.indirect_args,
+        i,
+        ((grid_size + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE).extend(1),
+    );
+    let smooth = |pass: &mut ComputePass, config: &GaussSeidelConfig, reversed: bool| {
+        let dispatch = if reversed {
+            GaussSeidelPipeline::dispatch_reversed
+        } else {
+            GaussSeidelPipeline::dispatch
+        };
+        dispatch(
+            pipelines.smoother,
+            pipelines.pipeline_cache,
             pass,
             &bind_groups.smoother_bind_groups[i],
             uniform_bind_group,
             num_workgroups,
-            &config.coarsest_config,
+            config,
         );
+    };
+
+    if i == params.levels.0 - 1 {
+        pass.push_debug_group("Solve");
+        smooth(pass, &params.config.coarsest_config, false);
+        if params.symmetric {
+            smooth(pass, &params.config.coarsest_config, true);
+        }
         pass.pop_debug_group();
         pass.pop_debug_group();
         return;
     }
     pass.push_debug_group("Pre smooth");
-    smoother_pipeline.dispatch(
-        pipeline_cache,
-        pass,
-        &bind_groups.smoother_bind_groups[i],
-        uniform_bind_group,
-        num_workgroups,
-        &config.pre_smooth_config,
-    );
+    smooth(pass, &params.config.pre_smooth_config, false);
     pass.pop_debug_group();
 
-    pass.set_pipeline(residual_pipeline);
+    pass.set_pipeline(pipelines.residual);
     pass.set_bind_group(0, &bind_groups.residual_bind_groups[i], &[]);
     pass.set_bind_group(
         1,
         &uniform_bind_group.bind_group,
         &[uniform_bind_group.index],
     );
-    pass.dispatch_workgroups(num_workgroups.x, num_workgroups.y, num_workgroups.z);
+    num_workgroups.dispatch(pass);
 
-    pass.set_pipeline(restriction_pipeline);
+    pass.set_pipeline(pipelines.restriction);
     pass.set_bind_group(0, &bind_groups.restriction_bind_groups[i], &[]);
-    pass.dispatch_workgroups(num_workgroups.x, num_workgroups.y, num_workgroups.z);
+    pass.set_bind_group(
+        1,
+        &uniform_bind_group.bind_group,
+        &[uniform_bind_group.index],
+    );
+    num_workgroups.dispatch(pass);
 
     v_cycle(
         i + 1,
-        pipeline_cache,
-        smoother_pipeline,
-        residual_pipeline,
-        restriction_pipeline,
-        prolongation_pipeline,
+        pipelines,
         pass,
         bind_groups,
         uniform_bind_group,
-        grid_size / 2,
-        config,
-        levels,
+        params,
     );
 
-    pass.set_pipeline(prolongation_pipeline);
+    pass.set_pipeline(pipelines.prolongation);
     pass.set_bind_group(0, &bind_groups.prolongation_bind_groups[i], &[]);
-    pass.dispatch_workgroups(num_workgroups.x, num_workgroups.y, num_workgroups.z);
+    num_workgroups.dispatch(pass);
 
     pass.push_debug_group("Post smooth");
-    smoother_pipeline.dispatch(
-        pipeline_cache,
-        pass,
-        &bind_groups.smoother_bind_groups[i],
-        uniform_bind_group,
-        num_workgroups,
-        &config.post_smooth_config,
-    );
+    smooth(pass, &params.config.post_smooth_config, params.symmetric);
     pass.pop_debug_group();
     pass.pop_debug_group();
 }
@@ -0,0 +1,600 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::levelset_utils::{interface_curvature, interface_pressure_jump};
+#import bevy_fluid::boundary_condition::{is_open_outside, wrap_periodic};
+#import bevy_fluid::coupled_body::{CoupledBody, is_coupled, generalized_inverse_mass};
+
+struct ConjugateGradientState {
+    // r.z of the current iteration
+    rz: f32,
+    // r.r of the current iteration
+    rr: f32,
+    // b.b to measure the relative residual
+    bb: f32,
+    alpha: f32,
+    beta: f32,
+    converged: u32,
+    num_iterations: u32,
+}
+
+@group(0) @binding(0) var p: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(1) var div: texture_storage_2d<r32float, read>;
+@group(0) @binding(2) var levelset_air: texture_storage_2d<r32float, read>;
+@group(0) @binding(3) var area_fraction_solid: texture_storage_2d<rgba32float, read>;
+@group(0) @binding(4) var curvature: texture_storage_2d<r32float, read>;
+// residual
+@group(0) @binding(5) var r: texture_storage_2d<r32float, read_write>;
+// preconditioned residual
+@group(0) @binding(6) var z: texture_storage_2d<r32float, read_write>;
+// search direction
+@group(0) @binding(7) var<storage, read_write> d: array<f32>;
+// operator applied to the search direction
+@group(0) @binding(8) var<storage, read_write> q: array<f32>;
+@group(0) @binding(9) var<storage, read_write> partial_sums: array<vec4<f32>>;
+@group(0) @binding(10) var<storage, read_write> state: ConjugateGradientState;
+@group(0) @binding(11) var<uniform> tolerance: f32;
+// Dispatch arguments of the multigrid preconditioner for each level, 3 u32 per level.
+@group(0) @binding(12) var<storage, read_write> indirect_args: array<u32>;
+
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
+// Bound only to the *_coupled and *_body_forces entry points.
+// Bodies covering the -x and -y faces of each cell, or -1.
+@group(2) @binding(0) var solid_id_face: texture_storage_2d<rg32sint, read>;
+@group(2) @binding(1) var<storage, read> bodies: array<CoupledBody>;
+// Generalized forces (x, y, torque) of the bodies as bits of f32, since WGSL has no atomic floats.
+@group(2) @binding(2) var<storage, read_write> body_forces: array<atomic<u32>>;
+
+const SIZE_X = 8;
+const SIZE_Y = 8;
+const WG_SIZE = SIZE_X * SIZE_Y;
+const WG_SIZE_2 = 256;
+
+// Discretized pressure Poisson equation around a cell, in the same form as the Gauss-Seidel smoother.
+struct Stencil {
+    // Coefficients of the neighbouring liquid cells (iminusj, iplusj, ijminus, ijplus).
+    off_diagonal: vec4<f32>,
+    neighbors: array<vec2<i32>, 4>,
+    // Zero for cells without liquid, which are excluded from the system.
+    diagonal: f32,
+    // Pressure jump at the free surface moved to the right hand side.
+    jump: f32,
+}
+
+fn stencil(idx: vec2<i32>) -> Stencil {
+    var s = Stencil(vec4<f32>(0.0), array<vec2<i32>, 4>(idx, idx, idx, idx), 0.0, 0.0);
+    let level = textureLoad(levelset_air, idx).r;
+    if level >= 0.0 {
+        return s;
+    }
+    let f = textureLoad(area_fraction_solid, idx);
+    if all(f == vec4<f32>(0.0)) {
+        return s;
+    }
+
+    let offsets = array<vec2<i32>, 4>(
+        vec2<i32>(-1, 0),
+        vec2<i32>(1, 0),
+        vec2<i32>(0, -1),
+        vec2<i32>(0, 1),
+    );
+    let f_vec = array<f32, 4>(f.x, f.y, f.z, f.w);
+    let dim = vec2<i32>(textureDimensions(levelset_air));
+    for (var i = 0; i < 4; i++) {
+        let idx_nb_unwrapped = idx + offsets[i];
+        if is_open_outside(constants, idx_nb_unwrapped, dim) {
+            // Zero pressure outside of the open boundary.
+            s.diagonal += f_vec[i];
+            continue;
+        }
+        let idx_nb = wrap_periodic(constants, idx_nb_unwrapped, dim);
+        if any(idx_nb < vec2<i32>(0)) || any(idx_nb >= dim) {
+            continue;
+        }
+        let level_nb = textureLoad(levelset_air, idx_nb).r;
+        if level_nb < 0.0 {
+            s.diagonal += f_vec[i];
+            s.off_diagonal[i] = f_vec[i];
+            s.neighbors[i] = idx_nb;
+        } else {
+            let coef = f_vec[i] * (1.0 - level_nb / level);
+            s.diagonal += coef;
+            let kappa = interface_curvature(
+                level,
+                level_nb,
+                textureLoad(curvature, idx).r,
+                textureLoad(curvature, idx_nb).r,
+            );
+            s.jump += coef * interface_pressure_jump(constants, kappa);
+        }
+    }
+
+    return s;
+}
+
+fn factor() -> f32 {
+    return constants.dt / constants.rho / (constants.dx * constants.dx);
+}
+
+fn linear_index(idx: vec2<i32>) -> i32 {
+    return idx.y * i32(textureDimensions(r).x) + idx.x;
+}
+
+fn is_converged() -> bool {
+    return state.converged != 0u;
+}
+
+// r = b - Ax, where x is the pressure of the previous step. Cells without liquid are cleared.
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn initialize(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if any(idx >= vec2<i32>(textureDimensions(r))) {
+        return;
+    }
+
+    initialize_residual(idx, stencil(idx), 0.0);
+}
+
+// `ax_solid` is the part of Ax from the coupled bodies.
+fn initialize_residual(idx: vec2<i32>, s: Stencil, ax_solid: f32) {
+    var residual = 0.0;
+    if s.diagonal > 0.0 {
+        var ax = s.diagonal * textureLoad(p, idx).r - s.jump;
+        for (var i = 0; i < 4; i++) {
+            ax -= s.off_diagonal[i] * textureLoad(p, s.neighbors[i]).r;
+        }
+        residual = textureLoad(div, idx).r - factor() * ax - ax_solid;
+    } else {
+        textureStore(p, idx, vec4<f32>(0.0));
+    }
+
+    textureStore(r, idx, vec4<f32>(residual, 0.0, 0.0, 0.0));
+    textureStore(z, idx, vec4<f32>(0.0));
+    d[linear_index(idx)] = 0.0;
+    q[linear_index(idx)] = 0.0;
+}
+
+// z = 0 as the initial guess of the multigrid preconditioner.
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn clear_preconditioned(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if any(idx >= vec2<i32>(textureDimensions(r))) {
+        return;
+    }
+
+    textureStore(z, idx, vec4<f32>(0.0));
+}
+
+// z = r / diag(A)
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn precondition_jacobi(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if any(idx >= vec2<i32>(textureDimensions(r))) || is_converged() {
+        return;
+    }
+
+    let diagonal = factor() * stencil(idx).diagonal;
+    var value = 0.0;
+    if diagonal > 0.0 {
+        value = textureLoad(r, idx).r / diagonal;
+    }
+    textureStore(z, idx, vec4<f32>(value, 0.0, 0.0, 0.0));
+}
+
+// d = z
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn initialize_direction(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if any(idx >= vec2<i32>(textureDimensions(r))) {
+        return;
+    }
+
+    d[linear_index(idx)] = textureLoad(z, idx).r;
+}
+
+// q = Ad
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn apply_operator(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if any(idx >= vec2<i32>(textureDimensions(r))) || is_converged() {
+        return;
+    }
+
+    apply_stencil(idx, stencil(idx), 0.0);
+}
+
+// `ad_solid` is the part of Ad from the coupled bodies.
+fn apply_stencil(idx: vec2<i32>, s: Stencil, ad_solid: f32) {
+    var ad = s.diagonal * d[linear_index(idx)];
+    for (var i = 0; i < 4; i++) {
+        ad -= s.off_diagonal[i] * d[linear_index(s.neighbors[i])];
+    }
+    q[linear_index(idx)] = factor() * ad + ad_solid;
+}
+
+// x += alpha * d, r -= alpha * q
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn update_solution(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if any(idx >= vec2<i32>(textureDimensions(r))) || is_converged() {
+        return;
+    }
+
+    let i = linear_index(idx);
+    let x_new = textureLoad(p, idx).r + state.alpha * d[i];
+    let r_new = textureLoad(r, idx).r - state.alpha * q[i];
+    textureStore(p, idx, vec4<f32>(x_new, 0.0, 0.0, 0.0));
+    textureStore(r, idx, vec4<f32>(r_new, 0.0, 0.0, 0.0));
+}
+
+// d = z + beta * d
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn update_direction(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if any(idx >= vec2<i32>(textureDimensions(r))) || is_converged() {
+        return;
+    }
+
+    let i = linear_index(idx);
+    d[i] = textureLoad(z, idx).r + state.beta * d[i];
+}
+
+// Dot products are reduced here rather than by common_pass::prefix_sum, which is an exclusive scan of u32
+// over a buffer padded to its block size. Only the f32 totals are needed, and the first stage is fused
+// into the kernels computing the products, so the operands are not written to a separate buffer.
+var<workgroup> workgroup_sum: array<vec4<f32>, WG_SIZE>;
+
+fn partial_reduction(
+    value: vec4<f32>,
+    lid: u32,
+    workgroup_id: vec3<u32>,
+    num_workgroups: vec3<u32>,
+) {
+    workgroup_sum[lid] = value;
+    workgroupBarrier();
+
+    for (var stride: u32 = WG_SIZE / 2; stride > 0; stride /= 2) {
+        if lid < stride {
+            workgroup_sum[lid] += workgroup_sum[lid + stride];
+        }
+        workgroupBarrier();
+    }
+
+    if lid == 0 {
+        let wid = workgroup_id.x + workgroup_id.y * num_workgroups.x;
+        partial_sums[wid] = workgroup_sum[0];
+    }
+}
+
+// Partial sums of (r.z, r.r, b.b).
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn dot_residual(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+    @builtin(local_invocation_index) lid: u32,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let idx = global_invocation_id.xy;
+    var value = vec4<f32>(0.0);
+    if all(idx < textureDimensions(r)) {
+        let r_ij = textureLoad(r, idx).r;
+        var b_ij = 0.0;
+        if textureLoad(levelset_air, idx).r < 0.0 {
+            b_ij = textureLoad(div, idx).r;
+        }
+        value = vec4<f32>(r_ij * textureLoad(z, idx).r, r_ij * r_ij, b_ij * b_ij, 0.0);
+    }
+
+    partial_reduction(value, lid, workgroup_id, num_workgroups);
+}
+
+// Partial sums of d.q.
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn dot_direction(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+    @builtin(local_invocation_index) lid: u32,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    var value = vec4<f32>(0.0);
+    if all(idx < vec2<i32>(textureDimensions(r))) {
+        let i = linear_index(idx);
+        value.x = d[i] * q[i];
+    }
+
+    partial_reduction(value, lid, workgroup_id, num_workgroups);
+}
+
+var<workgroup> partials2: array<vec4<f32>, WG_SIZE_2>;
+
+fn reduction(lid: u32) -> vec4<f32> {
+    let n = arrayLength(&partial_sums);
+
+    var tmp_sum = vec4<f32>(0.0);
+    for (var i = lid; i < n; i += WG_SIZE_2) {
+        tmp_sum += partial_sums[i];
+    }
+    partials2[lid] = tmp_sum;
+
+    workgroupBarrier();
+
+    for (var stride: u32 = WG_SIZE_2 / 2; stride > 0; stride /= 2) {
+        if lid < stride {
+            partials2[lid] += partials2[lid + stride];
+        }
+        workgroupBarrier();
+    }
+
+    return partials2[0];
+}
+
+fn has_converged(rr: f32, bb: f32) -> bool {
+    return rr <= tolerance * tolerance * bb;
+}
+
+// Same as write_indirect_args of residual_norm.wgsl, so that the preconditioner is skipped after convergence.
+fn write_indirect_args(enabled: bool) {
+    let num_levels = arrayLength(&indirect_args) / 3u;
+    let dim = textureDimensions(r);
+    for (var level = 0u; level < num_levels; level++) {
+        var num_workgroups = vec2<u32>(0u);
+        if enabled {
+            let size = max(dim >> vec2<u32>(level), vec2<u32>(1u));
+            num_workgroups = (size + vec2<u32>(SIZE_X - 1, SIZE_Y - 1)) / vec2<u32>(SIZE_X, SIZE_Y);
+        }
+        indirect_args[3u * level] = num_workgroups.x;
+        indirect_args[3u * level + 1u] = num_workgroups.y;
+        indirect_args[3u * level + 2u] = select(0u, 1u, enabled);
+    }
+}
+
+@compute @workgroup_size(WG_SIZE_2, 1, 1)
+fn finalize_initial(
+    @builtin(local_invocation_index) lid: u32,
+) {
+    let sum = reduction(lid);
+    if lid == 0 {
+        state.rz = sum.x;
+        state.rr = sum.y;
+        state.bb = sum.z;
+        state.alpha = 0.0;
+        state.beta = 0.0;
+        state.converged = u32(has_converged(sum.y, sum.z));
+        state.num_iterations = 0u;
+        write_indirect_args(!is_converged());
+    }
+}
+
+// alpha = r.z / d.q
+@compute @workgroup_size(WG_SIZE_2, 1, 1)
+fn finalize_alpha(
+    @builtin(local_invocation_index) lid: u32,
+) {
+    let dq = reduction(lid).x;
+    if lid == 0 && !is_converged() {
+        if dq > 0.0 {
+            state.alpha = state.rz / dq;
+        } else {
+            // Break down. The search direction vanished before reaching the tolerance.
+            state.alpha = 0.0;
+            state.converged = 1u;
+            write_indirect_args(false);
+        }
+    }
+}
+
+// beta = r.z / (r.z of the previous iteration)
+@compute @workgroup_size(WG_SIZE_2, 1, 1)
+fn finalize_beta(
+    @builtin(local_invocation_index) lid: u32,
+) {
+    let sum = reduction(lid);
+    if lid == 0 && !is_converged() {
+        if state.rz != 0.0 {
+            state.beta = sum.x / state.rz;
+        } else {
+            state.beta = 0.0;
+        }
+        state.rz = sum.x;
+        state.rr = sum.y;
+        state.num_iterations += 1u;
+        state.converged = u32(has_converged(sum.y, state.bb));
+        write_indirect_args(!is_converged());
+    }
+}
+
+// Monolithic coupling (Batty et al. 2007). The velocities of the bodies respond to the pressure within the solve,
+// which adds dt J M^-1 J^T to the operator, where a row of J maps the velocity of a body to the divergence of a cell.
+
+// Each face of a cell covered by a body contributes to the row of J, so a cell touching two bodies is coupled with both.
+struct FaceJacobian {
+    // -1 if the face is not covered by any body.
+    id: i32,
+    // Divergence of the cell times dx per the velocity (x, y) and the angular velocity of the body.
+    row: vec3<f32>,
+}
+
+// Body covering the face (iminusj, iplusj, ijminus, ijplus) of the cell, or -1.
+// The -x and -y faces are stored on the cell, and the +x and +y faces on the next cells.
+fn face_owner(idx: vec2<i32>, face: i32) -> i32 {
+    var idx_face = idx;
+    if face == 1 {
+        idx_face.x += 1;
+    } else if face == 3 {
+        idx_face.y += 1;
+    }
+    let dim = vec2<i32>(textureDimensions(solid_id_face));
+    idx_face = wrap_periodic(constants, idx_face, dim);
+    if any(idx_face < vec2<i32>(0)) || any(idx_face >= dim) {
+        return -1;
+    }
+    let owners = textureLoad(solid_id_face, idx_face).xy;
+    if face < 2 {
+        return owners.x;
+    }
+    return owners.y;
+}
+
+fn face_jacobian(idx: vec2<i32>, s: Stencil, face: i32) -> FaceJacobian {
+    var jacobian = FaceJacobian(-1, vec3<f32>(0.0));
+    if s.diagonal == 0.0 {
+        return jacobian;
+    }
+    let f = textureLoad(area_fraction_solid, idx);
+    let f_vec = array<f32, 4>(f.x, f.y, f.z, f.w);
+    let solid_fraction = 1.0 - f_vec[face];
+    if solid_fraction <= 0.0 {
+        return jacobian;
+    }
+    let id = face_owner(idx, face);
+    if id < 0 || u32(id) >= arrayLength(&bodies) {
+        return jacobian;
+    }
+
+    // Outward normals of the faces.
+    let normals = array<vec2<f32>, 4>(
+        vec2<f32>(-1.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(0.0, -1.0),
+        vec2<f32>(0.0, 1.0),
+    );
+    let n = normals[face];
+    let r = (vec2<f32>(idx) + 0.5 * n - bodies[id].center) * constants.dx;
+    jacobian.id = id;
+    jacobian.row = solid_fraction * vec3<f32>(n, r.x * n.y - r.y * n.x);
+    return jacobian;
+}
+
+fn atomic_add_body_force(i: u32, value: f32) {
+    var old = atomicLoad(&body_forces[i]);
+    loop {
+        let result = atomicCompareExchangeWeak(&body_forces[i], old, bitcast<u32>(bitcast<f32>(old) + value));
+        if result.exchanged {
+            break;
+        }
+        old = result.old_value;
+    }
+}
+
+fn body_force(id: i32) -> vec3<f32> {
+    let i = 3u * u32(id);
+    return vec3<f32>(
+        bitcast<f32>(atomicLoad(&body_forces[i])),
+        bitcast<f32>(atomicLoad(&body_forces[i + 1u])),
+        bitcast<f32>(atomicLoad(&body_forces[i + 2u])),
+    );
+}
+
+// J^T x, i.e. the generalized forces on the bodies by x.
+// Bodies which do not respond to the pressure are skipped unless `include_uncoupled`.
+fn accumulate_body_forces(idx: vec2<i32>, x: f32, include_uncoupled: bool) {
+    if x == 0.0 {
+        return;
+    }
+    let s = stencil(idx);
+    for (var face = 0; face < 4; face++) {
+        let jacobian = face_jacobian(idx, s, face);
+        if jacobian.id < 0 || !(include_uncoupled || is_coupled(bodies[jacobian.id])) {
+            continue;
+        }
+        for (var i = 0u; i < 3u; i++) {
+            if jacobian.row[i] != 0.0 {
+                atomic_add_body_force(3u * u32(jacobian.id) + i, jacobian.row[i] * x);
+            }
+        }
+    }
+}
+
+// dt J M^-1 (J^T x) of the cell, after the forces are accumulated.
+fn body_response(idx: vec2<i32>, s: Stencil) -> f32 {
+    var response = 0.0;
+    for (var face = 0; face < 4; face++) {
+        let jacobian = face_jacobian(idx, s, face);
+        if jacobian.id < 0 {
+            continue;
+        }
+        let inverse_mass = generalized_inverse_mass(bodies[jacobian.id]);
+        response += constants.dt * dot(jacobian.row, inverse_mass * body_force(jacobian.id));
+    }
+    return response;
+}
+
+// Dispatched over the grid, so that any number of bodies is covered.
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn clear_body_forces(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let width = num_workgroups.x * u32(SIZE_X);
+    let stride = width * num_workgroups.y * u32(SIZE_Y);
+    for (var i = global_invocation_id.y * width + global_invocation_id.x; i < arrayLength(&body_forces); i += stride) {
+        atomicStore(&body_forces[i], 0u);
+    }
+}
+
+// Forces of the pressure on all the bodies, which are also handed over to the rigid body backend after the solve.
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn accumulate_body_forces_pressure(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if any(idx >= vec2<i32>(textureDimensions(r))) {
+        return;
+    }
+
+    accumulate_body_forces(idx, textureLoad(p, idx).r, true);
+}
+
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn accumulate_body_forces_direction(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if any(idx >= vec2<i32>(textureDimensions(r))) || is_converged() {
+        return;
+    }
+
+    accumulate_body_forces(idx, d[linear_index(idx)], false);
+}
+
+// Same as initialize, with the forces of the previous pressure accumulated.
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn initialize_coupled(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if any(idx >= vec2<i32>(textureDimensions(r))) {
+        return;
+    }
+
+    let s = stencil(idx);
+    initialize_residual(idx, s, body_response(idx, s));
+}
+
+// Same as apply_operator, with the forces of the search direction accumulated.
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn apply_operator_coupled(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if any(idx >= vec2<i32>(textureDimensions(r))) || is_converged() {
+        return;
+    }
+
+    let s = stencil(idx);
+    apply_stencil(idx, s, body_response(idx, s));
+}
@@ -0,0 +1,24 @@
+#define_import_path bevy_fluid::coupled_body
+
+// Dynamic body solved together with the pressure, for the monolithic coupling only.
+struct CoupledBody {
+    // Center of mass in the grid coordinates, in units of cells.
+    center: vec2<f32>,
+    inverse_mass: f32,
+    inverse_inertia: f32,
+}
+
+// Static and kinematic bodies exert forces, but do not respond to the pressure.
+fn is_coupled(body: CoupledBody) -> bool {
+    return body.inverse_mass != 0.0 || body.inverse_inertia != 0.0;
+}
+
+// Inverse mass per generalized coordinate (x, y, angle).
+fn generalized_inverse_mass(body: CoupledBody) -> vec3<f32> {
+    return vec3<f32>(body.inverse_mass, body.inverse_mass, body.inverse_inertia);
+}
+
+// Velocity at `r` from the center of mass in meters, given the velocity (x, y) and the angular velocity of the body.
+fn rigid_velocity(velocity: vec3<f32>, r: vec2<f32>) -> vec2<f32> {
+    return velocity.xy + velocity.z * vec2<f32>(-r.y, r.x);
+}
@@ -1,4 +1,6 @@
 #import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::levelset_utils::{interface_curvature, interface_pressure_jump};
+#import bevy_fluid::boundary_condition::{is_open_outside, wrap_periodic};
 
 @group(0) @binding(0) var p: texture_storage_2d<r32float, read_write>;
 @group(0) @binding(1) var div: texture_storage_2d<r32float, read>;
@@ -6,6 +8,9 @@
 @group(0) @binding(3) var area_fraction_solid: texture_storage_2d<rgba32float, read>;
 @group(0) @binding(4) var<uniform> weight: f32;
 @group(0) @binding(5) var<uniform> resolution_scale: f32;
+@group(0) @binding(6) var curvature: texture_storage_2d<r32float, read>;
+// 1.0 on the finest level and 0.0 on the coarser levels of multigrid, where the error equation has no pressure jump.
+@group(0) @binding(7) var<uniform> pressure_jump_scale: f32;
 
 @group(1) @binding(0) var<uniform> constants: SimulationUniform;
 
@@ -66,14 +71,24 @@ fn update_pressure(idx: vec2<i32>) -> f32 {
 
     let dim = vec2<i32>(textureDimensions(levelset_air0));
     for (var i = 0; i < 4; i++) {
-        var j = idx + offsets[i];
+        let j_unwrapped = idx + offsets[i];
+        if is_open_outside(constants, j_unwrapped, dim) {
+            // Zero pressure outside of the open boundary.
+            denom += f_vec[i];
+            continue;
+        }
+        let j = wrap_periodic(constants, j_unwrapped, dim);
         if all(vec2<i32>(0) <= j) && all(j < dim) {
             let level = textureLoad(levelset_air0, j).r;
             if level < 0.0 {
                 denom += f_vec[i];
                 nume += f_vec[i] * textureLoad(p, j).r;
             } else {
-                denom += f_vec[i] * (1.0 - level / level_air_ij);
+                let coef = f_vec[i] * (1.0 - level / level_air_ij);
+                denom += coef;
+                if (pressure_jump_scale > 0.0) {
+                    nume += coef * pressure_jump_scale * interface_pressure(idx, j, level_air_ij, level);
+                }
             }
         }
     }
@@ -86,3 +101,14 @@ fn update_pressure(idx: vec2<i32>) -> f32 {
 
     return  weight * p_new + (1.0 - weight) * p_old;
 }
+
+// Pressure at the free surface due to surface tension, sigma * kappa.
+fn interface_pressure(idx: vec2<i32>, idx_air: vec2<i32>, level_liquid: f32, level_air: f32) -> f32 {
+    let kappa = interface_curvature(
+        level_liquid,
+        level_air,
+        textureLoad(curvature, idx).r,
+        textureLoad(curvature, idx_air).r,
+    );
+    return interface_pressure_jump(constants, kappa);
+}
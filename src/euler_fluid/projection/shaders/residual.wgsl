@@ -1,4 +1,6 @@
 #import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::levelset_utils::{interface_curvature, interface_pressure_jump};
+#import bevy_fluid::boundary_condition::{is_open_outside, wrap_periodic};
 
 @group(0) @binding(0) var x: texture_storage_2d<r32float, read>;
 @group(0) @binding(1) var b: texture_storage_2d<r32float, read>;
@@ -6,6 +8,8 @@
 @group(0) @binding(3) var area_fraction_solid: texture_storage_2d<rgba32float, read>;
 @group(0) @binding(4) var r: texture_storage_2d<r32float, read_write>;
 @group(0) @binding(5) var<uniform> resolution_scale: f32;
+@group(0) @binding(6) var curvature: texture_storage_2d<r32float, read>;
+@group(0) @binding(7) var<uniform> pressure_jump_scale: f32;
 
 @group(1) @binding(0) var<uniform> constants: SimulationUniform;
 
@@ -42,13 +46,29 @@ fn residual(
     );
     let f_vec = array<f32, 4>(f.x, f.y, f.z, f.w);
     for (var i = 0; i < 4; i++) {
-        let idx_nb = idx + offsets[i];
+        let idx_nb_unwrapped = idx + offsets[i];
+        if is_open_outside(constants, idx_nb_unwrapped, dim) {
+            // Zero pressure outside of the open boundary.
+            residual -= f_vec[i] * x_center * factor;
+            continue;
+        }
+        let idx_nb = wrap_periodic(constants, idx_nb_unwrapped, dim);
         if all(vec2i(0) <= idx_nb) && all(idx_nb < dim) {
             let phi_nb = textureLoad(levelset_air, idx_nb).r;
             if phi_nb < 0.0 {
                 residual -= f_vec[i] * (x_center - textureLoad(x, idx_nb).r) * factor;
             } else {
-                residual -= f_vec[i] * (1.0 - phi_nb / phi) * x_center * factor;
+                var p_interface = 0.0;
+                if (pressure_jump_scale > 0.0) {
+                    let kappa = interface_curvature(
+                        phi,
+                        phi_nb,
+                        textureLoad(curvature, idx).r,
+                        textureLoad(curvature, idx_nb).r,
+                    );
+                    p_interface = pressure_jump_scale * interface_pressure_jump(constants, kappa);
+                }
+                residual -= f_vec[i] * (1.0 - phi_nb / phi) * (x_center - p_interface) * factor;
             }
         }
     }
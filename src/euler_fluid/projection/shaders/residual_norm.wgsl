@@ -0,0 +1,181 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::levelset_utils::{interface_curvature, interface_pressure_jump};
+#import bevy_fluid::boundary_condition::{is_open_outside, wrap_periodic};
+
+struct ProjectionResidualState {
+    // L2 norm of the residual
+    l2: f32,
+    // L-infinity norm of the residual
+    linf: f32,
+    // L2 norm of the right hand side to measure the relative residual
+    b_l2: f32,
+    num_chunks: u32,
+    converged: u32,
+}
+
+@group(0) @binding(0) var p: texture_storage_2d<r32float, read>;
+@group(0) @binding(1) var div: texture_storage_2d<r32float, read>;
+@group(0) @binding(2) var levelset_air: texture_storage_2d<r32float, read>;
+@group(0) @binding(3) var area_fraction_solid: texture_storage_2d<rgba32float, read>;
+@group(0) @binding(4) var curvature: texture_storage_2d<r32float, read>;
+@group(0) @binding(5) var<storage, read_write> partial_sums: array<vec4<f32>>;
+@group(0) @binding(6) var<storage, read_write> state: ProjectionResidualState;
+// Dispatch arguments of the pressure solve for each multigrid level, 3 u32 per level.
+@group(0) @binding(7) var<storage, read_write> indirect_args: array<u32>;
+@group(0) @binding(8) var<uniform> tolerance: f32;
+
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
+const SIZE_X = 8;
+const SIZE_Y = 8;
+const WG_SIZE = SIZE_X * SIZE_Y;
+const WG_SIZE_2 = 256;
+
+// (r^2, |r|, b^2) of the pressure Poisson equation at the cell.
+fn residual(idx: vec2<i32>, dim: vec2<i32>) -> vec3<f32> {
+    let phi = textureLoad(levelset_air, idx).r;
+    if phi >= 0.0 {
+        return vec3<f32>(0.0);
+    }
+    let f = textureLoad(area_fraction_solid, idx);
+    if all(f == vec4<f32>(0.0)) {
+        return vec3<f32>(0.0);
+    }
+    let p_center = textureLoad(p, idx).r;
+    let b = textureLoad(div, idx).r;
+    let factor = constants.dt / constants.rho / (constants.dx * constants.dx);
+
+    var r = b;
+    let offsets = array<vec2<i32>, 4>(
+        vec2<i32>(-1, 0),
+        vec2<i32>(1, 0),
+        vec2<i32>(0, -1),
+        vec2<i32>(0, 1),
+    );
+    let f_vec = array<f32, 4>(f.x, f.y, f.z, f.w);
+    for (var i = 0; i < 4; i++) {
+        let idx_nb_unwrapped = idx + offsets[i];
+        if is_open_outside(constants, idx_nb_unwrapped, dim) {
+            // Zero pressure outside of the open boundary.
+            r -= f_vec[i] * p_center * factor;
+            continue;
+        }
+        let idx_nb = wrap_periodic(constants, idx_nb_unwrapped, dim);
+        if any(idx_nb < vec2<i32>(0)) || any(idx_nb >= dim) {
+            continue;
+        }
+        let phi_nb = textureLoad(levelset_air, idx_nb).r;
+        if phi_nb < 0.0 {
+            r -= f_vec[i] * (p_center - textureLoad(p, idx_nb).r) * factor;
+        } else {
+            let kappa = interface_curvature(
+                phi,
+                phi_nb,
+                textureLoad(curvature, idx).r,
+                textureLoad(curvature, idx_nb).r,
+            );
+            let p_interface = interface_pressure_jump(constants, kappa);
+            r -= f_vec[i] * (1.0 - phi_nb / phi) * (p_center - p_interface) * factor;
+        }
+    }
+
+    return vec3<f32>(r * r, abs(r), b * b);
+}
+
+fn write_indirect_args(enabled: bool) {
+    let num_levels = arrayLength(&indirect_args) / 3u;
+    let dim = textureDimensions(p);
+    for (var level = 0u; level < num_levels; level++) {
+        var num_workgroups = vec2<u32>(0u);
+        if enabled {
+            let size = max(dim >> vec2<u32>(level), vec2<u32>(1u));
+            num_workgroups = (size + vec2<u32>(SIZE_X - 1, SIZE_Y - 1)) / vec2<u32>(SIZE_X, SIZE_Y);
+        }
+        indirect_args[3u * level] = num_workgroups.x;
+        indirect_args[3u * level + 1u] = num_workgroups.y;
+        indirect_args[3u * level + 2u] = select(0u, 1u, enabled);
+    }
+}
+
+// Sums for the L2 norms and maximum for the L-infinity norm.
+fn combine(a: vec4<f32>, b: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(a.x + b.x, max(a.y, b.y), a.z + b.z, 0.0);
+}
+
+@compute @workgroup_size(1, 1, 1)
+fn reset() {
+    state.num_chunks = 0u;
+    state.converged = 0u;
+    write_indirect_args(true);
+}
+
+var<workgroup> workgroup_sum: array<vec4<f32>, WG_SIZE>;
+
+@compute @workgroup_size(SIZE_X, SIZE_Y, 1)
+fn partial_reduction(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+    @builtin(local_invocation_index) lid: u32,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    let dim = vec2<i32>(textureDimensions(p));
+    var value = vec4<f32>(0.0);
+    if all(idx < dim) {
+        value = vec4<f32>(residual(idx, dim), 0.0);
+    }
+
+    workgroup_sum[lid] = value;
+    workgroupBarrier();
+
+    for (var stride: u32 = WG_SIZE / 2; stride > 0; stride /= 2) {
+        if lid < stride {
+            workgroup_sum[lid] = combine(workgroup_sum[lid], workgroup_sum[lid + stride]);
+        }
+        workgroupBarrier();
+    }
+
+    if lid == 0 {
+        let wid = workgroup_id.x + workgroup_id.y * num_workgroups.x;
+        partial_sums[wid] = workgroup_sum[0];
+    }
+}
+
+var<workgroup> partials2: array<vec4<f32>, WG_SIZE_2>;
+
+@compute @workgroup_size(WG_SIZE_2, 1, 1)
+fn reduction(
+    @builtin(local_invocation_index) lid: u32,
+) {
+    let n = arrayLength(&partial_sums);
+
+    var tmp = vec4<f32>(0.0);
+    for (var i = lid; i < n; i += WG_SIZE_2) {
+        tmp = combine(tmp, partial_sums[i]);
+    }
+    partials2[lid] = tmp;
+
+    workgroupBarrier();
+
+    for (var stride: u32 = WG_SIZE_2 / 2; stride > 0; stride /= 2) {
+        if lid < stride {
+            partials2[lid] = combine(partials2[lid], partials2[lid + stride]);
+        }
+        workgroupBarrier();
+    }
+
+    if lid == 0 {
+        let sum = partials2[0];
+        state.l2 = sqrt(sum.x);
+        state.linf = sum.y;
+        state.b_l2 = sqrt(sum.z);
+        if state.converged == 0u {
+            state.num_chunks += 1u;
+            if state.l2 <= tolerance * state.b_l2 {
+                state.converged = 1u;
+                // Skip the remaining chunks of the pressure solve.
+                write_indirect_args(false);
+            }
+        }
+    }
+}
@@ -1,3 +1,6 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::boundary_condition::wrap_periodic;
+
 @group(0) @binding(0) var r: texture_storage_2d<r32float, read>;
 @group(0) @binding(1) var levelset_air: texture_storage_2d<r32float, read>;
 @group(0) @binding(2) var area_fraction_solid: texture_storage_2d<rgba32float, read>;
@@ -7,6 +10,8 @@
 @group(0) @binding(5) var area_fraction_solid_low: texture_storage_2d<rgba32float, write>;
 @group(0) @binding(6) var x_low: texture_storage_2d<r32float, write>;
 
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
 @compute @workgroup_size(8, 8, 1)
 fn restriction(
     @builtin(global_invocation_id) global_invocation_id: vec3u,
@@ -17,15 +22,24 @@ fn restriction(
         return;
     }
 
-    let b_r = 0.25 * (textureLoad(r, 2 * idx).r
-        + textureLoad(r, 2 * idx + vec2u(0, 1)).r
-        + textureLoad(r, 2 * idx + vec2u(1, 0)).r
-        + textureLoad(r, 2 * idx + vec2u(1, 1)).r);
+    // A fine grid with odd number of cells along a periodic axis wraps around to its first cell.
+    let fine_dim = vec2i(textureDimensions(r));
+    let fine_idx = array<vec2i, 4>(
+        wrap_periodic(constants, vec2i(2 * idx), fine_dim),
+        wrap_periodic(constants, vec2i(2 * idx + vec2u(0, 1)), fine_dim),
+        wrap_periodic(constants, vec2i(2 * idx + vec2u(1, 0)), fine_dim),
+        wrap_periodic(constants, vec2i(2 * idx + vec2u(1, 1)), fine_dim),
+    );
+
+    let b_r = 0.25 * (textureLoad(r, fine_idx[0]).r
+        + textureLoad(r, fine_idx[1]).r
+        + textureLoad(r, fine_idx[2]).r
+        + textureLoad(r, fine_idx[3]).r);
         
-    var phi0 = textureLoad(levelset_air, 2 * idx).r;
-    let phi1 = textureLoad(levelset_air, 2 * idx + vec2u(0, 1)).r;
-    let phi2 = textureLoad(levelset_air, 2 * idx + vec2u(1, 0)).r;
-    let phi3 = textureLoad(levelset_air, 2 * idx + vec2u(1, 1)).r;
+    var phi0 = textureLoad(levelset_air, fine_idx[0]).r;
+    let phi1 = textureLoad(levelset_air, fine_idx[1]).r;
+    let phi2 = textureLoad(levelset_air, fine_idx[2]).r;
+    let phi3 = textureLoad(levelset_air, fine_idx[3]).r;
 
     var phi_r = phi0;
     if abs(phi1) < abs(phi_r) {
@@ -40,10 +54,10 @@ fn restriction(
     // let phi_r = 0.25 * (phi0 + phi1 + phi2 + phi3);
 
     let fractions = array<vec4f, 4>(
-        textureLoad(area_fraction_solid, 2 * idx),
-        textureLoad(area_fraction_solid, 2 * idx + vec2u(1, 0)),
-        textureLoad(area_fraction_solid, 2 * idx + vec2u(0, 1)),
-        textureLoad(area_fraction_solid, 2 * idx + vec2u(1, 1)),
+        textureLoad(area_fraction_solid, fine_idx[0]),
+        textureLoad(area_fraction_solid, fine_idx[2]),
+        textureLoad(area_fraction_solid, fine_idx[1]),
+        textureLoad(area_fraction_solid, fine_idx[3]),
     );
 
     let f_r = vec4f(
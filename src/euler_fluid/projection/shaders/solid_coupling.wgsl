@@ -0,0 +1,64 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::coupled_body::{CoupledBody, is_coupled, generalized_inverse_mass, rigid_velocity};
+
+// Velocities of the coupled bodies after the conjugate gradient solve, V' = V + dt M^-1 J^T p.
+// They are kept apart from the other coupling bindings, as the storage textures of the solver are at the limit of WebGPU.
+
+@group(0) @binding(0) var u_solid: texture_storage_2d<r32float, write>;
+@group(0) @binding(1) var v_solid: texture_storage_2d<r32float, write>;
+@group(0) @binding(2) var solid_id_face: texture_storage_2d<rg32sint, read>;
+@group(0) @binding(3) var<storage, read> bodies: array<CoupledBody>;
+// J^T p of the final pressure as bits of f32, accumulated by accumulate_body_forces_pressure.
+@group(0) @binding(4) var<storage, read> body_forces: array<u32>;
+// Velocities (x, y, angular) of the bodies, uploaded every physics step and updated every substep.
+@group(0) @binding(5) var<storage, read_write> body_velocities: array<vec3<f32>>;
+
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
+// Dispatched over the grid as clear_body_forces, so that any number of bodies is covered.
+@compute @workgroup_size(8, 8, 1)
+fn update_body_velocities(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let width = num_workgroups.x * 8u;
+    let stride = width * num_workgroups.y * 8u;
+    let num_bodies = min(arrayLength(&body_velocities), arrayLength(&body_forces) / 3u);
+    for (var i = global_invocation_id.y * width + global_invocation_id.x; i < num_bodies; i += stride) {
+        let force = vec3<f32>(
+            bitcast<f32>(body_forces[3u * i]),
+            bitcast<f32>(body_forces[3u * i + 1u]),
+            bitcast<f32>(body_forces[3u * i + 2u]),
+        );
+        // J omits the length of the faces dx, which cancels within the solve.
+        let impulse = constants.dt * constants.dx * force;
+        body_velocities[i] += generalized_inverse_mass(bodies[i]) * impulse;
+    }
+}
+
+// The faces covered by the coupled bodies move with V', which solve_velocity imposes on the fully solid faces.
+@compute @workgroup_size(8, 8, 1)
+fn apply_body_velocities(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if any(idx >= vec2<i32>(textureDimensions(solid_id_face))) {
+        return;
+    }
+
+    let owners = textureLoad(solid_id_face, idx).xy;
+    let offsets = array<vec2<f32>, 2>(vec2<f32>(-0.5, 0.0), vec2<f32>(0.0, -0.5));
+    for (var i = 0; i < 2; i++) {
+        let id = owners[i];
+        if id < 0 || u32(id) >= arrayLength(&bodies) || !is_coupled(bodies[id]) {
+            continue;
+        }
+        let r = (vec2<f32>(idx) + offsets[i] - bodies[id].center) * constants.dx;
+        let velocity = rigid_velocity(body_velocities[id], r);
+        if i == 0 {
+            textureStore(u_solid, idx, vec4<f32>(velocity.x, 0.0, 0.0, 0.0));
+        } else {
+            textureStore(v_solid, idx, vec4<f32>(velocity.y, 0.0, 0.0, 0.0));
+        }
+    }
+}
@@ -0,0 +1,442 @@
+use bevy::{
+    asset::{embedded_asset, embedded_path},
+    prelude::*,
+    render::{
+        extract_component::ExtractComponent,
+        render_resource::{
+            AsBindGroup, BindGroup, BindGroupLayoutDescriptor, CachedComputePipelineId,
+            ComputePass, PipelineCache, ShaderType,
+        },
+        renderer::RenderDevice,
+        storage::ShaderStorageBuffer,
+    },
+    shader::load_shader_library,
+};
+
+use crate::{
+    fluid_uniform::{uniform_bind_group_layout_desc, SimulationUniformBindGroup},
+    obstacle::SolidObstacle,
+    pipeline::{is_pipeline_loaded, queue_compute_pipeline, DispatchFluidPass, HasBindGroupLayout},
+    plugin::FluidComputePass,
+    projection::conjugate_gradient::ConjugateGradientResource,
+};
+
+/// Monolithic coupling of dynamic bodies with the conjugate gradient solver,
+/// inserted for [`crate::fluid_to_solid::SolidCouplingMethod::Monolithic`].
+/// The entry points live in conjugate_gradient.wgsl, as they extend its operator.
+pub(crate) struct SolidCouplingPass;
+
+impl FluidComputePass for SolidCouplingPass {
+    type Pipeline = SolidCouplingPipelines;
+    type Resource = SolidCouplingResource;
+    type BG = SolidCouplingBindGroup;
+
+    fn register_assets(app: &mut App) {
+        load_shader_library!(app, "shaders/coupled_body.wgsl");
+    }
+}
+
+/// Updates the velocities of the coupled bodies by the solved pressure,
+/// and imposes them on the faces covered by the bodies before the velocity of the fluid is solved.
+pub(crate) struct BodyVelocityPass;
+
+impl FluidComputePass for BodyVelocityPass {
+    type Pipeline = BodyVelocityPipelines;
+    type Resource = BodyVelocityResource;
+    type BG = BodyVelocityBindGroup;
+
+    fn register_assets(app: &mut App) {
+        embedded_asset!(app, "shaders/solid_coupling.wgsl");
+    }
+}
+
+/// Dynamic body solved together with the pressure.
+#[derive(ShaderType, Default, Copy, Clone)]
+pub(crate) struct CoupledBody {
+    /// Center of mass in the grid coordinates of the fluid, in units of cells.
+    pub center: Vec2,
+    /// Inverse mass in the physical units of the fluid, i.e. [1/(kg/m)] in 2D.
+    pub inverse_mass: f32,
+    /// Inverse moment of inertia in the physical units of the fluid.
+    pub inverse_inertia: f32,
+}
+
+impl CoupledBody {
+    /// Body of `obstacle` for the fluid at `fluid_transform`, with its velocity (x, y, angular)
+    /// in the same units as the velocities of the solid faces.
+    pub fn from_obstacle(
+        obstacle: &SolidObstacle,
+        fluid_transform: &Transform,
+        grid_size: UVec2,
+        grid_length: f32,
+    ) -> (Self, Vec3) {
+        let world_to_fluid = fluid_transform.to_matrix().inverse();
+        let half_size = 0.5 * grid_size.as_vec2();
+        // Meters per unit length of the world.
+        let length_scale = grid_length / fluid_transform.scale.x;
+        let center = obstacle
+            .transform
+            .transform_point3(obstacle.center_of_mass.extend(0.0));
+        let center = world_to_fluid.transform_point3(center).xy();
+        let body = Self {
+            center: Vec2::new(center.x + half_size.x, half_size.y - center.y),
+            inverse_mass: obstacle.inverse_mass / length_scale.powi(2),
+            inverse_inertia: obstacle.inverse_inertia / length_scale.powi(4),
+        };
+        // Same as the velocities of the solid faces, with y-axis pointing down
+        // and the angular velocity per lever arm in meters.
+        let velocity = Vec3::new(
+            obstacle.linear_velocity.x,
+            -obstacle.linear_velocity.y,
+            -obstacle.angular_velocity / length_scale,
+        );
+        (body, velocity)
+    }
+}
+
+#[derive(Component, ExtractComponent, Clone, AsBindGroup)]
+pub(crate) struct SolidCouplingResource {
+    /// Bodies covering the -x and -y faces of each cell, written by update_solid.
+    #[storage_texture(0, image_format = Rg32Sint, access = ReadOnly)]
+    pub solid_id_face: Handle<Image>,
+    #[storage(1, read_only, visibility(compute))]
+    pub bodies: Handle<ShaderStorageBuffer>,
+    /// Generalized forces (x, y, torque) on the bodies, accumulated per iteration.
+    #[storage(2, visibility(compute))]
+    pub body_forces: Handle<ShaderStorageBuffer>,
+    /// Velocities (x, y, angular) of the bodies, bound to [`BodyVelocityResource`].
+    pub body_velocities: Handle<ShaderStorageBuffer>,
+}
+
+impl SolidCouplingResource {
+    pub fn new(buffers: &mut Assets<ShaderStorageBuffer>, solid_id_face: &Handle<Image>) -> Self {
+        Self {
+            solid_id_face: solid_id_face.clone(),
+            bodies: buffers.add(ShaderStorageBuffer::from(vec![CoupledBody::default()])),
+            body_forces: buffers.add(ShaderStorageBuffer::from(vec![0u32; 3])),
+            body_velocities: buffers.add(ShaderStorageBuffer::from(vec![Vec3::ZERO])),
+        }
+    }
+
+    /// Uploads the bodies overlapping the fluid, in the order of their solid ids,
+    /// with their velocities (x, y, angular) in the same units as the fluid.
+    pub fn set_bodies(
+        &self,
+        buffers: &mut Assets<ShaderStorageBuffer>,
+        mut bodies: Vec<CoupledBody>,
+        mut velocities: Vec<Vec3>,
+    ) {
+        // Storage buffers can not be empty.
+        if bodies.is_empty() {
+            bodies.push(CoupledBody::default());
+            velocities.push(Vec3::ZERO);
+        }
+        let num_bodies = bodies.len();
+        buffers.get_mut(&self.bodies).unwrap().set_data(bodies);
+        buffers
+            .get_mut(&self.body_forces)
+            .unwrap()
+            .set_data(vec![0u32; 3 * num_bodies]);
+        buffers
+            .get_mut(&self.body_velocities)
+            .unwrap()
+            .set_data(velocities);
+    }
+}
+
+#[derive(Component, ExtractComponent, Clone, AsBindGroup)]
+pub(crate) struct BodyVelocityResource {
+    #[storage_texture(0, image_format = R32Float, access = WriteOnly)]
+    pub u_solid: Handle<Image>,
+    #[storage_texture(1, image_format = R32Float, access = WriteOnly)]
+    pub v_solid: Handle<Image>,
+    #[storage_texture(2, image_format = Rg32Sint, access = ReadOnly)]
+    pub solid_id_face: Handle<Image>,
+    #[storage(3, read_only, visibility(compute))]
+    pub bodies: Handle<ShaderStorageBuffer>,
+    #[storage(4, read_only, visibility(compute))]
+    pub body_forces: Handle<ShaderStorageBuffer>,
+    #[storage(5, visibility(compute))]
+    pub body_velocities: Handle<ShaderStorageBuffer>,
+}
+
+impl BodyVelocityResource {
+    pub fn new(
+        solid_coupling: &SolidCouplingResource,
+        u_solid: &Handle<Image>,
+        v_solid: &Handle<Image>,
+    ) -> Self {
+        Self {
+            u_solid: u_solid.clone(),
+            v_solid: v_solid.clone(),
+            solid_id_face: solid_coupling.solid_id_face.clone(),
+            bodies: solid_coupling.bodies.clone(),
+            body_forces: solid_coupling.body_forces.clone(),
+            body_velocities: solid_coupling.body_velocities.clone(),
+        }
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct SolidCouplingBindGroup {
+    pub bind_group: BindGroup,
+}
+
+impl From<BindGroup> for SolidCouplingBindGroup {
+    fn from(bind_group: BindGroup) -> Self {
+        Self { bind_group }
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct SolidCouplingPipelines {
+    pub clear_body_forces_pipeline: CachedComputePipelineId,
+    pub accumulate_pressure_pipeline: CachedComputePipelineId,
+    pub accumulate_direction_pipeline: CachedComputePipelineId,
+    pub initialize_pipeline: CachedComputePipelineId,
+    pub apply_operator_pipeline: CachedComputePipelineId,
+    bind_group_layout: BindGroupLayoutDescriptor,
+}
+
+impl FromWorld for SolidCouplingPipelines {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let bind_group_layout = SolidCouplingResource::bind_group_layout_descriptor(render_device);
+        let layouts = vec![
+            ConjugateGradientResource::bind_group_layout_descriptor(render_device),
+            uniform_bind_group_layout_desc(),
+            bind_group_layout.clone(),
+        ];
+
+        let mut queue = |label: &'static str, entry_point: &'static str| {
+            queue_compute_pipeline(
+                world,
+                label,
+                embedded_path!("shaders/conjugate_gradient.wgsl"),
+                entry_point,
+                layouts.clone(),
+            )
+        };
+
+        Self {
+            clear_body_forces_pipeline: queue(
+                "SolidCouplingClearBodyForcesPipeline",
+                "clear_body_forces",
+            ),
+            accumulate_pressure_pipeline: queue(
+                "SolidCouplingAccumulatePressurePipeline",
+                "accumulate_body_forces_pressure",
+            ),
+            accumulate_direction_pipeline: queue(
+                "SolidCouplingAccumulateDirectionPipeline",
+                "accumulate_body_forces_direction",
+            ),
+            initialize_pipeline: queue("SolidCouplingInitializePipeline", "initialize_coupled"),
+            apply_operator_pipeline: queue(
+                "SolidCouplingApplyOperatorPipeline",
+                "apply_operator_coupled",
+            ),
+            bind_group_layout,
+        }
+    }
+}
+
+impl HasBindGroupLayout for SolidCouplingPipelines {
+    fn bind_group_layout(&self) -> &BindGroupLayoutDescriptor {
+        &self.bind_group_layout
+    }
+}
+
+impl SolidCouplingPipelines {
+    pub fn is_ready(&self, pipeline_cache: &PipelineCache) -> bool {
+        [
+            self.clear_body_forces_pipeline,
+            self.accumulate_pressure_pipeline,
+            self.accumulate_direction_pipeline,
+            self.initialize_pipeline,
+            self.apply_operator_pipeline,
+        ]
+        .into_iter()
+        .all(|pipeline| is_pipeline_loaded(pipeline_cache, pipeline))
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct BodyVelocityBindGroup {
+    pub bind_group: BindGroup,
+}
+
+impl From<BindGroup> for BodyVelocityBindGroup {
+    fn from(bind_group: BindGroup) -> Self {
+        Self { bind_group }
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct BodyVelocityPipelines {
+    update_body_velocities_pipeline: CachedComputePipelineId,
+    apply_body_velocities_pipeline: CachedComputePipelineId,
+    pub bind_group_layout: BindGroupLayoutDescriptor,
+}
+
+impl FromWorld for BodyVelocityPipelines {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let bind_group_layout = BodyVelocityResource::bind_group_layout_descriptor(render_device);
+        let layouts = vec![bind_group_layout.clone(), uniform_bind_group_layout_desc()];
+
+        let mut queue = |label: &'static str, entry_point: &'static str| {
+            queue_compute_pipeline(
+                world,
+                label,
+                embedded_path!("shaders/solid_coupling.wgsl"),
+                entry_point,
+                layouts.clone(),
+            )
+        };
+
+        Self {
+            update_body_velocities_pipeline: queue(
+                "BodyVelocityUpdatePipeline",
+                "update_body_velocities",
+            ),
+            apply_body_velocities_pipeline: queue(
+                "BodyVelocityApplyPipeline",
+                "apply_body_velocities",
+            ),
+            bind_group_layout,
+        }
+    }
+}
+
+impl HasBindGroupLayout for BodyVelocityPipelines {
+    fn bind_group_layout(&self) -> &BindGroupLayoutDescriptor {
+        &self.bind_group_layout
+    }
+}
+
+impl BodyVelocityPipelines {
+    pub fn is_ready(&self, pipeline_cache: &PipelineCache) -> bool {
+        is_pipeline_loaded(pipeline_cache, self.update_body_velocities_pipeline)
+            && is_pipeline_loaded(pipeline_cache, self.apply_body_velocities_pipeline)
+    }
+
+    /// V' = V + dt M^-1 J^T p, after the forces of the final pressure are accumulated.
+    pub fn dispatch(
+        &self,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        bind_group: &BodyVelocityBindGroup,
+        uniform_bind_group: &SimulationUniformBindGroup,
+        size: UVec2,
+    ) {
+        let update_pipeline = pipeline_cache
+            .get_compute_pipeline(self.update_body_velocities_pipeline)
+            .unwrap();
+        let apply_pipeline = pipeline_cache
+            .get_compute_pipeline(self.apply_body_velocities_pipeline)
+            .unwrap();
+
+        pass.set_bind_group(0, &bind_group.bind_group, &[]);
+        pass.set_bind_group(
+            1,
+            &uniform_bind_group.bind_group,
+            &[uniform_bind_group.index],
+        );
+        pass.set_pipeline(update_pipeline);
+        pass.dispatch_center(size);
+        pass.set_pipeline(apply_pipeline);
+        pass.dispatch_center(size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+    use crate::obstacle::{FluidObstacleShape, ShapeVariant};
+
+    const GRID_SIZE: UVec2 = UVec2::new(64, 48);
+    const GRID_LENGTH: f32 = 0.05;
+
+    /// A uniformly scaled fluid away from the origin, 0.025 m per unit length of the world.
+    fn fluid_transform() -> Transform {
+        Transform::from_xyz(-30.0, 12.0, 0.0).with_scale(Vec3::new(2.0, 2.0, 1.0))
+    }
+
+    fn disk(radius: f32, density: f32, transform: Transform) -> SolidObstacle {
+        let mut obstacle = SolidObstacle::new(
+            ShapeVariant::from(&FluidObstacleShape::from(Circle::new(radius))),
+            &GlobalTransform::from(transform),
+            Vec2::new(1.0, 0.5),
+            Vec2::new(3.0, -2.0),
+            0.7,
+        );
+        // Mass properties in the units of the world, as given by the rigid body backend.
+        let mass = density * PI * radius * radius;
+        obstacle.inverse_mass = 1.0 / mass;
+        obstacle.inverse_inertia = 1.0 / (0.5 * mass * radius * radius);
+        obstacle
+    }
+
+    fn assert_relative_eq(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (actual - expected).abs() <= tolerance * expected.abs(),
+            "actual: {actual}, expected: {expected}"
+        );
+    }
+
+    #[test]
+    fn body_has_the_mass_of_its_density_in_meters() {
+        let (radius, density) = (8.0, 99.7);
+        let obstacle = disk(radius, density, Transform::from_xyz(4.0, -3.0, 0.0));
+        let (body, _) =
+            CoupledBody::from_obstacle(&obstacle, &fluid_transform(), GRID_SIZE, GRID_LENGTH);
+
+        let radius = radius * GRID_LENGTH / fluid_transform().scale.x;
+        let mass = density * PI * radius * radius;
+        assert_relative_eq(body.inverse_mass, 1.0 / mass, 1.0e-5);
+        assert_relative_eq(
+            body.inverse_inertia,
+            1.0 / (0.5 * mass * radius * radius),
+            1.0e-5,
+        );
+    }
+
+    #[test]
+    fn body_velocity_matches_the_solid_faces() {
+        let transform =
+            Transform::from_xyz(4.0, -3.0, 0.0).with_rotation(Quat::from_rotation_z(0.5));
+        let obstacle = disk(8.0, 1.0, transform);
+        let fluid_transform = fluid_transform();
+        let (body, velocity) =
+            CoupledBody::from_obstacle(&obstacle, &fluid_transform, GRID_SIZE, GRID_LENGTH);
+
+        let center = transform
+            .transform_point(obstacle.center_of_mass.extend(0.0))
+            .xy();
+        let half_size = 0.5 * GRID_SIZE.as_vec2();
+        for face in [
+            Vec2::new(10.0, 7.5),
+            Vec2::new(3.5, 20.0),
+            Vec2::new(40.0, 31.5),
+        ] {
+            // Velocity of the obstacle in the world, stored with y-axis pointing down as update_solid does.
+            let position = fluid_transform
+                .transform_point(Vec3::new(face.x - half_size.x, half_size.y - face.y, 0.0))
+                .xy();
+            let r = position - center;
+            let expected = obstacle.linear_velocity + obstacle.angular_velocity * r.perp();
+            let expected = Vec2::new(expected.x, -expected.y);
+
+            // rigid_velocity of coupled_body.wgsl, with the lever arm in meters.
+            let r = (face - body.center) * GRID_LENGTH;
+            let actual = velocity.xy() + velocity.z * r.perp();
+            assert!(
+                actual.distance(expected) < 1.0e-4 * expected.length(),
+                "face: {face}, actual: {actual}, expected: {expected}"
+            );
+        }
+    }
+}
@@ -13,7 +13,8 @@ use bevy::{
 };
 
 use crate::{
-    pipeline::{is_pipeline_loaded, DispatchFluidPass, WORKGROUP_SIZE},
+    fluid_uniform::SimulationUniformBindGroup,
+    pipeline::{is_pipeline_loaded, WORKGROUP_SIZE},
     plugin::FluidComputePassPlugin,
     reinitialize_levelset::{
         fast_iterative_method::{
@@ -63,6 +64,7 @@ impl Display for ReinitializeMethod {
 }
 
 #[derive(QueryData)]
+#[query_data(derive(Clone, Copy))]
 pub(crate) struct ReinitializeLevelSetBindGroupQuery {
     pub jump_flooding_bind_groups: Option<&'static JumpFloodingBindGroups>,
     pub fast_iterative_bind_groups: Option<(
@@ -165,75 +167,51 @@ pub(crate) fn dispatch(
     pipeline_cache: &PipelineCache,
     pass: &mut ComputePass,
     bind_groups: ReinitializeLevelSetBindGroupQueryItem,
+    uniform_bind_group: &SimulationUniformBindGroup,
     size: UVec2,
 ) {
     match method {
         ReinitializeMethod::JumpFlooding => {
             pass.push_debug_group("Reinitialize levelset (JFA)");
-
-            let pipeline = world.resource::<JumpFloodingPipeline>();
-            let bind_groups = bind_groups.jump_flooding_bind_groups.unwrap();
-            let init_seeds_pipeline = pipeline_cache
-                .get_compute_pipeline(pipeline.init_seeds_pipeline)
-                .unwrap();
-            let iterate_pipeline = pipeline_cache
-                .get_compute_pipeline(pipeline.iterate_pipeline)
-                .unwrap();
-            let sdf_pipeline = pipeline_cache
-                .get_compute_pipeline(pipeline.sdf_pipeline)
-                .unwrap();
-
-            pass.set_pipeline(init_seeds_pipeline);
-            pass.set_bind_group(0, &bind_groups.init_seeds_bind_group, &[]);
-            pass.set_bind_group(1, &bind_groups.write_only_seeds_bind_groups[0], &[]);
-            pass.dispatch_center(size);
-
-            pass.set_pipeline(&iterate_pipeline);
-            let mut src_idx = 0;
-            let mut dst_idx = 1;
-
-            for bind_group in &bind_groups.jump_flooding_step_bind_groups {
-                pass.set_bind_group(0, &bind_groups.read_only_seeds_bind_groups[src_idx], &[]);
-                pass.set_bind_group(1, &bind_groups.write_only_seeds_bind_groups[dst_idx], &[]);
-                pass.set_bind_group(2, bind_group, &[]);
-                pass.dispatch_center(size);
-
-                std::mem::swap(&mut src_idx, &mut dst_idx);
-            }
-
-            pass.set_pipeline(&sdf_pipeline);
-            pass.set_bind_group(0, &bind_groups.sdf_bind_group, &[]);
-            pass.set_bind_group(1, &bind_groups.read_only_seeds_bind_groups[src_idx], &[]);
-
-            pass.dispatch_center(size);
+            jump_flooding::dispatch(
+                world,
+                pipeline_cache,
+                pass,
+                bind_groups.jump_flooding_bind_groups.unwrap(),
+                uniform_bind_group,
+                size,
+            );
             pass.pop_debug_group();
         }
         ReinitializeMethod::FastIterative(config) => {
             pass.push_debug_group("Reinitialize levelset (FIM)");
             let num_workgroups_grid = (size / WORKGROUP_SIZE).extend(1);
             let initialize_pipeline = world.resource::<FastIterativeInitializePipeline>();
-            initialize_pipeline.pipeline.dispatch(
+            initialize_pipeline.pipeline.dispatch_with_uniform(
                 pipeline_cache,
                 pass,
                 &bind_groups.fast_iterative_bind_groups.unwrap().0.bind_group,
+                uniform_bind_group,
                 num_workgroups_grid,
             );
 
             let initialize_active_label =
                 world.resource::<FastIterativeInitializeActiveLabelPipeline>();
-            initialize_active_label.pipeline.dispatch(
+            initialize_active_label.pipeline.dispatch_with_uniform(
                 pipeline_cache,
                 pass,
                 &bind_groups.fast_iterative_bind_groups.unwrap().1.bind_group,
+                uniform_bind_group,
                 num_workgroups_grid,
             );
 
             let update_pipeline = world.resource::<FastIterativeUpdatePipeline>();
             for _ in 0..config.num_iterations {
-                update_pipeline.pipeline.dispatch(
+                update_pipeline.pipeline.dispatch_with_uniform(
                     pipeline_cache,
                     pass,
                     &bind_groups.fast_iterative_bind_groups.unwrap().2.bind_group,
+                    uniform_bind_group,
                     num_workgroups_grid,
                 );
             }
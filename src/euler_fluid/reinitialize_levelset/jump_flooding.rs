@@ -7,9 +7,9 @@ use bevy::{
         render_resource::{
             binding_types::{texture_storage_2d, uniform_buffer},
             AsBindGroup, BindGroup, BindGroupEntries, BindGroupLayoutDescriptor,
-            BindGroupLayoutEntries, CachedComputePipelineId, ComputePipelineDescriptor,
-            PipelineCache, ShaderStages, ShaderType, StorageTextureAccess, TextureFormat,
-            UniformBuffer,
+            BindGroupLayoutEntries, CachedComputePipelineId, ComputePass,
+            ComputePipelineDescriptor, PipelineCache, ShaderStages, ShaderType,
+            StorageTextureAccess, TextureFormat, UniformBuffer,
         },
         renderer::{RenderDevice, RenderQueue},
         storage::GpuShaderStorageBuffer,
@@ -18,7 +18,10 @@ use bevy::{
     },
 };
 
-use crate::{pipeline::Pipeline, settings::FluidSettings};
+use crate::{
+    fluid_uniform::{uniform_bind_group_layout_desc, SimulationUniformBindGroup},
+    pipeline::{DispatchFluidPass, Pipeline},
+};
 
 pub(crate) struct JumpFloodingPlugin;
 
@@ -129,6 +132,7 @@ impl FromWorld for JumpFloodingPipeline {
             JumpFloodingInitializeSeedsResource::bind_group_layout_descriptor(render_device);
         let sdf_bind_group_layout =
             JumpFloodingCalculateSdfResource::bind_group_layout_descriptor(render_device);
+        let uniform_bind_group_layout = uniform_bind_group_layout_desc();
 
         let init_seeds_pipeline =
             pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
@@ -136,6 +140,7 @@ impl FromWorld for JumpFloodingPipeline {
                 layout: vec![
                     init_seeds_bind_group_layout.clone(),
                     write_only_seeds_bind_group_layout.clone(),
+                    uniform_bind_group_layout.clone(),
                 ],
                 shader: load_embedded_asset!(asset_server, "shaders/jump_flooding/initialize.wgsl"),
                 entry_point: Some("initialize".into()),
@@ -148,6 +153,7 @@ impl FromWorld for JumpFloodingPipeline {
                 read_only_seeds_bind_group_layout.clone(),
                 write_only_seeds_bind_group_layout.clone(),
                 jump_flooding_step_bind_group_layout.clone(),
+                uniform_bind_group_layout.clone(),
             ],
             shader: load_embedded_asset!(asset_server, "shaders/jump_flooding/iterate.wgsl"),
             entry_point: Some("iterate".into()),
@@ -159,6 +165,7 @@ impl FromWorld for JumpFloodingPipeline {
             layout: vec![
                 sdf_bind_group_layout.clone(),
                 read_only_seeds_bind_group_layout.clone(),
+                uniform_bind_group_layout,
             ],
             shader: load_embedded_asset!(asset_server, "shaders/jump_flooding/calculate_sdf.wgsl"),
             entry_point: Some("calculate_sdf".into()),
@@ -186,7 +193,6 @@ fn prepare_bind_groups<'a>(
     pipeline_cache: Res<PipelineCache>,
     query: Query<(
         Entity,
-        &FluidSettings,
         &JumpFloodingInitializeSeedsResource,
         &JumpFloodingCalculateSdfResource,
         &JumpFloodingSeedsTextures,
@@ -197,9 +203,10 @@ fn prepare_bind_groups<'a>(
         Res<'a, RenderAssets<GpuShaderStorageBuffer>>,
     ),
 ) {
-    for (entity, settings, init_seeds_resource, sdf_resource, seeds_textures) in &query {
+    for (entity, init_seeds_resource, sdf_resource, seeds_textures) in &query {
+        let size = param.0.get(&seeds_textures.0[0]).unwrap().size_2d();
         // steps for jump flooding algorithm: 1, 2, ..., 2^k, where: 2^k < max(size.0, size.1) <= 2^(k+1)
-        let max_power = ((settings.size.max_element() as f32).log2() - 1.0).floor() as usize;
+        let max_power = ((size.max_element() as f32).log2() - 1.0).floor() as usize;
         let mut step = 2_u32.pow((max_power + 1) as u32);
         let mut jump_flooding_buffer =
             Vec::<UniformBuffer<JumpFloodingUniform>>::with_capacity(max_power + 1);
@@ -280,3 +287,63 @@ fn prepare_bind_groups<'a>(
         });
     }
 }
+
+/// Computes the signed distance from the zero level of `levelset_air1` into `levelset_air0`.
+pub(crate) fn dispatch(
+    world: &World,
+    pipeline_cache: &PipelineCache,
+    pass: &mut ComputePass,
+    bind_groups: &JumpFloodingBindGroups,
+    uniform_bind_group: &SimulationUniformBindGroup,
+    size: UVec2,
+) {
+    let pipeline = world.resource::<JumpFloodingPipeline>();
+    let init_seeds_pipeline = pipeline_cache
+        .get_compute_pipeline(pipeline.init_seeds_pipeline)
+        .unwrap();
+    let iterate_pipeline = pipeline_cache
+        .get_compute_pipeline(pipeline.iterate_pipeline)
+        .unwrap();
+    let sdf_pipeline = pipeline_cache
+        .get_compute_pipeline(pipeline.sdf_pipeline)
+        .unwrap();
+
+    pass.set_pipeline(init_seeds_pipeline);
+    pass.set_bind_group(0, &bind_groups.init_seeds_bind_group, &[]);
+    pass.set_bind_group(1, &bind_groups.write_only_seeds_bind_groups[0], &[]);
+    pass.set_bind_group(
+        2,
+        &uniform_bind_group.bind_group,
+        &[uniform_bind_group.index],
+    );
+    pass.dispatch_center(size);
+
+    pass.set_pipeline(&iterate_pipeline);
+    pass.set_bind_group(
+        3,
+        &uniform_bind_group.bind_group,
+        &[uniform_bind_group.index],
+    );
+    let mut src_idx = 0;
+    let mut dst_idx = 1;
+
+    for bind_group in &bind_groups.jump_flooding_step_bind_groups {
+        pass.set_bind_group(0, &bind_groups.read_only_seeds_bind_groups[src_idx], &[]);
+        pass.set_bind_group(1, &bind_groups.write_only_seeds_bind_groups[dst_idx], &[]);
+        pass.set_bind_group(2, bind_group, &[]);
+        pass.dispatch_center(size);
+
+        std::mem::swap(&mut src_idx, &mut dst_idx);
+    }
+
+    pass.set_pipeline(&sdf_pipeline);
+    pass.set_bind_group(0, &bind_groups.sdf_bind_group, &[]);
+    pass.set_bind_group(1, &bind_groups.read_only_seeds_bind_groups[src_idx], &[]);
+    pass.set_bind_group(
+        2,
+        &uniform_bind_group.bind_group,
+        &[uniform_bind_group.index],
+    );
+
+    pass.dispatch_center(size);
+}
@@ -1,7 +1,12 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::boundary_condition::wrap_periodic;
+
 @group(0) @binding(0) var levelset_air: texture_storage_2d<r32float, read>;
 @group(0) @binding(1) var phi: texture_storage_2d<r32float, write>;
 @group(0) @binding(2) var label: texture_storage_2d<r32uint, write>;
 
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
 const LARGE_FLOAT: f32 = 1e30;
 const LABEL_NONE: u32 = 0;
 const LABEL_SOURCE: u32 = 1;
@@ -24,7 +29,7 @@ fn initialize(
     let level = textureLoad(levelset_air, idx).r;
 
     for (var i = 0; i < 4; i++) {
-        let idx_nb = neighbors[i];
+        let idx_nb = wrap_periodic(constants, neighbors[i], dim);
         if (all(vec2i(0) <= idx_nb) && all(idx_nb < dim)) {
             let level_nb = textureLoad(levelset_air, idx_nb).r;
             if (level * level_nb <= 0.0) {
@@ -1,6 +1,11 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::boundary_condition::wrap_periodic;
+
 @group(0) @binding(0) var labels_in: texture_storage_2d<r32uint, read>;
 @group(0) @binding(1) var labels_out: texture_storage_2d<r32uint, write>;
 
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
 const LABEL_NONE: u32 = 0;
 const LABEL_SOURCE: u32 = 1;
 const LABEL_ACTIVE: u32 = 2;
@@ -26,7 +31,7 @@ fn initialize_active_label(
     }
 
     for (var i = 0; i < 4; i++) {
-        let idx_nb = neighbors[i];
+        let idx_nb = wrap_periodic(constants, neighbors[i], dim);
         if (all(vec2i(0) <= idx_nb) && all(idx_nb < dim)) {
             let label_nb = textureLoad(labels_in, idx_nb).r;
             if (label_nb == LABEL_SOURCE) {
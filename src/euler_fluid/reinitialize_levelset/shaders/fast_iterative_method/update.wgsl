@@ -1,6 +1,11 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::boundary_condition::wrap_periodic;
+
 @group(0) @binding(0) var labels: texture_storage_2d<r32uint, read_write>;
 @group(0) @binding(1) var phi: texture_storage_2d<r32float, read_write>;
 
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
 
 const LABEL_NONE: u32 = 0;
 const LABEL_SOURCE: u32 = 1;
@@ -38,7 +43,7 @@ fn update(
 
     textureStore(labels, idx, vec4u(LABEL_NONE, 0, 0, 0));
     for (var i = 0; i < 4; i++) {
-        let idx_nb = neighbors[i];
+        let idx_nb = wrap_periodic(constants, neighbors[i], dim);
         if (all(vec2i(0) <= idx_nb) && all(idx_nb < dim)) {
             let label_nb = textureLoad(labels, idx_nb).r;
             if label_nb != LABEL_ACTIVE && label_nb != LABEL_SOURCE {
@@ -75,9 +80,10 @@ fn abs_get_phi(
     idx: vec2i,
 ) -> f32 {
     let dim = vec2i(textureDimensions(phi));
-    if any(idx < vec2i(0)) || any(dim <= idx) {
+    let idx_wrapped = wrap_periodic(constants, idx, dim);
+    if any(idx_wrapped < vec2i(0)) || any(dim <= idx_wrapped) {
         return LARGE_FLOAT;
     }
 
-    return abs(textureLoad(phi, idx).r);
+    return abs(textureLoad(phi, idx_wrapped).r);
 }
\ No newline at end of file
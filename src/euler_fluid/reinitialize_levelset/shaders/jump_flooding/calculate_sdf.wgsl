@@ -1,7 +1,13 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::boundary_condition::grid_period;
+#import bevy_fluid::coordinate::minimum_image;
+
 @group(0) @binding(0) var levelset_air0: texture_storage_2d<r32float, write>;
 @group(0) @binding(1) var levelset_air1: texture_storage_2d<r32float, read>;
 @group(1) @binding(0) var seeds: texture_storage_2d<rg32float, read>;
 
+@group(2) @binding(0) var<uniform> constants: SimulationUniform;
+
 fn get_seed(x: vec2<i32>) -> vec2<f32> {
     return textureLoad(seeds, x).rg;
 }
@@ -11,7 +17,8 @@ fn calculate_sdf(
     @builtin(global_invocation_id) invocation_id: vec3<u32>,
 ) {
     let x = vec2<i32>(i32(invocation_id.x), i32(invocation_id.y));
-    let sdf = distance(get_seed(x), vec2<f32>(x));
+    let period = grid_period(constants, vec2<i32>(textureDimensions(levelset_air1)));
+    let sdf = length(minimum_image(get_seed(x) - vec2<f32>(x), period));
     let level = textureLoad(levelset_air1, x).r;
     var levelset_sign = 1.0;
     if (level < 0.0) {
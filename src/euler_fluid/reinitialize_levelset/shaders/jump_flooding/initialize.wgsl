@@ -1,6 +1,11 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::boundary_condition::wrap_periodic;
+
 @group(0) @binding(0) var levelset_air1: texture_storage_2d<r32float, read>;
 @group(1) @binding(0) var seeds: texture_storage_2d<rg32float, write>;
 
+@group(2) @binding(0) var<uniform> constants: SimulationUniform;
+
 fn set_seed(x: vec2<i32>, seed: vec2<f32>) {
     textureStore(seeds, x, vec4<f32>(seed, 0.0, 0.0));
 }
@@ -21,7 +26,7 @@ fn initialize(
             if (i == 0 && j == 0) {
                 continue;
             }
-            let neighbor = x + vec2<i32>(i, j);
+            let neighbor = wrap_periodic(constants, x + vec2<i32>(i, j), dim);
             if (any(neighbor < vec2<i32>(0)) || any(neighbor >= dim)) {
                 continue;
             }
@@ -1,8 +1,14 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::boundary_condition::{grid_period, wrap_periodic};
+#import bevy_fluid::coordinate::minimum_image;
+
 @group(0) @binding(0) var seeds_in: texture_storage_2d<rg32float, read>;
 @group(1) @binding(0) var seeds_out: texture_storage_2d<rg32float, write>;
 
 @group(2) @binding(0) var<uniform> step: i32;
 
+@group(3) @binding(0) var<uniform> constants: SimulationUniform;
+
 fn set_seed(x: vec2<i32>, seed: vec2<f32>) {
     textureStore(seeds_out, x, vec4<f32>(seed, 0.0, 0.0));
 }
@@ -22,13 +28,14 @@ fn iterate(
 ) {
     let x = vec2<i32>(invocation_id.xy);
     let size = vec2<i32>(textureDimensions(seeds_in));
+    let period = grid_period(constants, size);
 
     let current_seed = get_seed(x);
     var best_seed = get_seed(x);
     var best_dist = 1e10;
 
     if (is_valid_seed(current_seed)) {
-        best_dist = distance(current_seed, vec2<f32>(x));
+        best_dist = length(minimum_image(current_seed - vec2<f32>(x), period));
     }
     
     for (var i: i32 = -1; i <= 1; i++) {
@@ -36,7 +43,7 @@ fn iterate(
             if (i == 0 && j == 0) {
                 continue;
             }
-            let neighbor = vec2<i32>(x.x + i * step, x.y + j * step);
+            let neighbor = wrap_periodic(constants, vec2<i32>(x.x + i * step, x.y + j * step), size);
             if (any(neighbor < vec2<i32>(0)) || any(size <= neighbor)) {
                 continue;
             }
@@ -44,7 +51,7 @@ fn iterate(
             if (!is_valid_seed(neighbor_seed)) {
                 continue;
             }
-            let candidate_dist = distance(neighbor_seed, vec2<f32>(x));
+            let candidate_dist = length(minimum_image(neighbor_seed - vec2<f32>(x), period));
             if (candidate_dist < best_dist) {
                 best_seed = neighbor_seed;
                 best_dist = candidate_dist;
@@ -1,5 +1,6 @@
 use bevy::{
     ecs::query::QueryData,
+    platform::collections::HashSet,
     prelude::*,
     render::{
         diagnostic::RecordDiagnostics,
@@ -10,8 +11,16 @@ use bevy::{
 
 use crate::{
     advect_levelset::{AdvectLevelSetBindGroups, AdvectLevelSetPipeline},
-    advection::{self, AdvectionBindGroup, AdvectionPipeline},
+    advection::{
+        self,
+        high_order::{HighOrderAdvectionBindGroups, HighOrderAdvectionPipelines},
+        AdvectionBindGroup, AdvectionMethod, AdvectionPipeline,
+    },
     apply_forces::{ApplyForcesBindGroups, ApplyForcesPipeline},
+    diagnostics::{
+        component::GridSize,
+        max_velocity::{MaxVelocityBindGroup, MaxVelocityPipeline},
+    },
     divergence::{DivergenceBindGroup, DivergencePipeline},
     extrapolate_velocity::{ExtrapolateVelocityBindGroups, ExtrapolateVelocityPipeline},
     fluid_source::update_fluid_source::{
@@ -19,8 +28,8 @@ use crate::{
     },
     fluid_status::FluidStatus,
     fluid_to_solid::{
-        FluidToSolidForcesBindGroups, FluidToSolidForcesPipeline, SolidObstaclesBindGroups,
-        MAX_SOLIDS,
+        FluidToSolidForcesBindGroups, FluidToSolidForcesPipeline, SolidCouplingMethod,
+        SolidObstaclesBindGroups,
     },
     fluid_uniform::SimulationUniformBindGroup,
     initialize::{
@@ -39,15 +48,34 @@ use crate::{
     physics_time::{CurrentPhysicsStepNumberRenderWorld, PhysicsFrameInfo},
     pipeline::{DispatchFluidPass, Pipeline, WORKGROUP_SIZE},
     projection::{
-        self, gauss_seidel::GaussSeidelPipeline, multi_grid::MultiGridPipelines,
+        self,
+        conjugate_gradient::ConjugateGradientPipelines,
+        diagnostics::ProjectionResidualPipelines,
+        gauss_seidel::GaussSeidelPipeline,
+        multi_grid::MultiGridPipelines,
+        solid_coupling::{BodyVelocityPipelines, SolidCouplingPipelines},
         ProjectionBindGroupsQuery, ProjectionMethod,
     },
     reinitialize_levelset::{self, ReinitializeLevelSetBindGroupQuery, ReinitializeMethod},
-    settings::FluidSettings,
+    scalar_field::{ScalarFieldBindGroups, ScalarFieldPipelines},
+    settings::{FluidMode, FluidSettings},
+    solid_mask::{
+        self, SolidMaskInitializePipeline, SolidMaskMergeBindGroup, SolidMaskMergePipeline,
+        SolidMaskMergeResource, SolidMaskSdfQuery,
+    },
     solve_pressure::SolvePressurePipeline,
     solve_velocity::{SolveVelocityBindGroups, SolveVelocityPipeline},
+    substep::FluidSubsteps,
+    surface_tension::{CurvatureBindGroup, CurvaturePipeline},
     update_area_fraction::{UpdateAreaFractionBindGroup, UpdateAreaFractionPipeline},
-    update_solid::{UpdateSolidBindGroups, UpdateSolidPipeline},
+    update_solid::{UpdateSolidBindGroups, UpdateSolidPipeline, UpdateSolidResource},
+    velocity_transfer::{
+        VelocityTransferBindGroup, VelocityTransferMethod, VelocityTransferPipelines,
+    },
+    viscosity::{ViscosityBindGroup, ViscosityPipeline, ViscositySolver},
+    vorticity_confinement::{
+        VorticityConfinement, VorticityConfinementBindGroup, VorticityConfinementPipeline,
+    },
 };
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
@@ -65,20 +93,28 @@ enum State {
 struct FluidBindGroupsQueryData {
     initialize_center_bind_group: &'static InitializeGridCenterBindGroup,
     initialize_edge_bind_group: &'static InitializeGridEdgeBindGroup,
+    update_solid_resource: &'static UpdateSolidResource,
     update_solid_bind_groups: &'static UpdateSolidBindGroups,
     update_area_fraction_bind_group: &'static UpdateAreaFractionBindGroup,
     advection_bind_groups: &'static AdvectionBindGroup,
+    high_order_advection_bind_groups: Option<&'static HighOrderAdvectionBindGroups>,
     apply_forces_bind_groups: &'static ApplyForcesBindGroups,
+    vorticity_confinement_bind_group: &'static VorticityConfinementBindGroup,
+    viscosity_bind_group: &'static ViscosityBindGroup,
     divergence_bind_groups: &'static DivergenceBindGroup,
+    curvature_bind_group: &'static CurvatureBindGroup,
     solve_velocity_bind_groups: &'static SolveVelocityBindGroups,
     extrapolate_velocity_bind_groups: &'static ExtrapolateVelocityBindGroups,
     advect_levelset_bind_groups: &'static AdvectLevelSetBindGroups,
     reinit_levelset_bind_groups: ReinitializeLevelSetBindGroupQuery,
     fluid_to_solid_bind_groups: &'static FluidToSolidForcesBindGroups,
+    solid_obstacles_bind_groups: &'static SolidObstaclesBindGroups,
     simulation_uniform: &'static SimulationUniformBindGroup,
     levelset_gradient_bind_group: &'static LevelSetGradientBindGroup,
     projection_bind_groups: ProjectionBindGroupsQuery,
     update_fluid_source_bind_groups: UpdateFluidSourceBindGroupsQuery,
+    scalar_field_bind_groups: Option<&'static ScalarFieldBindGroups>,
+    velocity_transfer_bind_group: Option<&'static VelocityTransferBindGroup>,
 }
 
 pub(crate) struct EulerFluidNode {
@@ -96,11 +132,26 @@ pub(crate) struct EulerFluidNode {
         &'static FluidSettings,
         &'static ProjectionMethod,
         &'static ReinitializeMethod,
+        &'static AdvectionMethod,
+        &'static VelocityTransferMethod,
+        &'static FluidSubsteps,
+        &'static ViscositySolver,
+        Option<&'static VorticityConfinement>,
     )>,
     query_fluid_status: QueryState<
         (Entity, Option<&'static mut FluidStatus>),
         (With<FluidSettings>, With<InitializeGridCenterResource>),
     >,
+    max_velocity_query: QueryState<(&'static GridSize, &'static MaxVelocityBindGroup)>,
+    solid_mask_query: QueryState<(
+        &'static SolidMaskMergeResource,
+        &'static SolidMaskMergeBindGroup,
+    )>,
+    solid_mask_sdf_query: QueryState<SolidMaskSdfQuery>,
+    /// Binary masks which have been converted into signed distance fields.
+    converted_solid_masks: HashSet<AssetId<Image>>,
+    /// Binary masks to be converted in this frame.
+    converting_solid_masks: Vec<Entity>,
 }
 
 impl EulerFluidNode {
@@ -109,6 +160,11 @@ impl EulerFluidNode {
             state: State::Loading,
             fluid_query: world.query_filtered(),
             query_fluid_status: world.query_filtered(),
+            max_velocity_query: world.query_filtered(),
+            solid_mask_query: world.query_filtered(),
+            solid_mask_sdf_query: world.query_filtered(),
+            converted_solid_masks: HashSet::default(),
+            converting_solid_masks: Vec::new(),
         }
     }
 }
@@ -116,6 +172,9 @@ impl EulerFluidNode {
 impl render_graph::Node for EulerFluidNode {
     fn update(&mut self, world: &mut World) {
         self.fluid_query.update_archetypes(world);
+        self.max_velocity_query.update_archetypes(world);
+        self.solid_mask_query.update_archetypes(world);
+        self.solid_mask_sdf_query.update_archetypes(world);
         let pipeline_cache = world.resource::<PipelineCache>();
         match self.state {
             State::Loading => {
@@ -125,16 +184,32 @@ impl render_graph::Node for EulerFluidNode {
                 let update_solid_pipeline = world.resource::<UpdateSolidPipeline>();
                 let update_area_fraction_pipeline = world.resource::<UpdateAreaFractionPipeline>();
                 let advection_pipeline = world.resource::<AdvectionPipeline>();
+                let high_order_advection_pipelines =
+                    world.resource::<HighOrderAdvectionPipelines>();
                 let divergence_pipeline = world.resource::<DivergencePipeline>();
                 let apply_forces_pipeline = world.resource::<ApplyForcesPipeline>();
                 let solve_pressure_pipeline = world.resource::<SolvePressurePipeline>();
                 let gauss_seidel_pipeline = world.resource::<GaussSeidelPipeline>();
                 let multi_grid_pipeline = world.resource::<MultiGridPipelines>();
+                let conjugate_gradient_pipelines = world.resource::<ConjugateGradientPipelines>();
+                let solid_coupling_pipelines = world.resource::<SolidCouplingPipelines>();
+                let body_velocity_pipelines = world.resource::<BodyVelocityPipelines>();
+                let projection_residual_pipelines = world.resource::<ProjectionResidualPipelines>();
                 let solve_velocity_pipeline = world.resource::<SolveVelocityPipeline>();
                 let extrapolate_velocity_pipeline = world.resource::<ExtrapolateVelocityPipeline>();
                 let advect_levelset_pipeline = world.resource::<AdvectLevelSetPipeline>();
                 let fluid_to_solid_forces_pipeline = world.resource::<FluidToSolidForcesPipeline>();
                 let update_fluid_source_pipeline = world.resource::<UpdateFluidSourcePipeline>();
+                let viscosity_pipeline = world.resource::<ViscosityPipeline>();
+                let curvature_pipeline = world.resource::<CurvaturePipeline>();
+                let vorticity_confinement_pipeline =
+                    world.resource::<VorticityConfinementPipeline>();
+                let scalar_field_pipelines = world.resource::<ScalarFieldPipelines>();
+                let velocity_transfer_pipelines = world.resource::<VelocityTransferPipelines>();
+                let max_velocity_pipeline = world.resource::<MaxVelocityPipeline>();
+                let solid_mask_initialize_pipeline =
+                    world.resource::<SolidMaskInitializePipeline>();
+                let solid_mask_merge_pipeline = world.resource::<SolidMaskMergePipeline>();
 
                 if initialize_center_pipeline.pipeline.is_ready(pipeline_cache)
                     && initialize_edge_pipeline.pipeline.is_ready(pipeline_cache)
@@ -143,11 +218,16 @@ impl render_graph::Node for EulerFluidNode {
                         .pipeline
                         .is_ready(pipeline_cache)
                     && advection_pipeline.is_pipeline_state_ready(pipeline_cache)
+                    && high_order_advection_pipelines.is_ready(pipeline_cache)
                     && apply_forces_pipeline.is_pipeline_state_ready(pipeline_cache)
                     && divergence_pipeline.pipeline.is_ready(pipeline_cache)
                     && solve_pressure_pipeline.is_pipeline_state_ready(pipeline_cache)
                     && gauss_seidel_pipeline.is_ready(pipeline_cache)
                     && multi_grid_pipeline.ready(pipeline_cache)
+                    && conjugate_gradient_pipelines.is_ready(pipeline_cache)
+                    && solid_coupling_pipelines.is_ready(pipeline_cache)
+                    && body_velocity_pipelines.is_ready(pipeline_cache)
+                    && projection_residual_pipelines.is_ready(pipeline_cache)
                     && solve_velocity_pipeline.is_pipeline_state_ready(pipeline_cache)
                     && extrapolate_velocity_pipeline.is_pipeline_state_ready(pipeline_cache)
                     && advect_levelset_pipeline.pipeline.is_ready(pipeline_cache)
@@ -155,6 +235,16 @@ impl render_graph::Node for EulerFluidNode {
                     && fluid_to_solid_forces_pipeline.is_pipeline_state_ready(pipeline_cache)
                     && are_pls_pipelines_ready(world, pipeline_cache)
                     && update_fluid_source_pipeline.is_ready(pipeline_cache)
+                    && viscosity_pipeline.is_ready(pipeline_cache)
+                    && curvature_pipeline.pipeline.is_ready(pipeline_cache)
+                    && vorticity_confinement_pipeline.is_ready(pipeline_cache)
+                    && scalar_field_pipelines.is_ready(pipeline_cache)
+                    && velocity_transfer_pipelines.is_ready(pipeline_cache)
+                    && max_velocity_pipeline.is_ready(pipeline_cache)
+                    && solid_mask_initialize_pipeline
+                        .pipeline
+                        .is_ready(pipeline_cache)
+                    && solid_mask_merge_pipeline.pipeline.is_ready(pipeline_cache)
                 {
                     self.state = State::Init;
                 }
@@ -163,6 +253,18 @@ impl render_graph::Node for EulerFluidNode {
                 self.state = State::Update;
             }
             State::Update | State::Idle => {
+                // Binary masks are converted once, whether or not the fluid is stepped.
+                self.converting_solid_masks.clear();
+                let mut converted_solid_masks = HashSet::default();
+                for mask in self.solid_mask_sdf_query.iter_manual(world) {
+                    let id = mask.resource.levelset_binary.id();
+                    if !self.converted_solid_masks.contains(&id) {
+                        self.converting_solid_masks.push(mask.entity);
+                    }
+                    converted_solid_masks.insert(id);
+                }
+                self.converted_solid_masks = converted_solid_masks;
+
                 let current_step = world.resource::<CurrentPhysicsStepNumberRenderWorld>();
                 let physics_step_numper = world.resource::<PhysicsFrameInfo>().step_number;
                 if current_step.0 == physics_step_numper {
@@ -198,6 +300,21 @@ impl render_graph::Node for EulerFluidNode {
     ) -> Result<(), render_graph::NodeRunError> {
         let pipeline_cache = world.resource::<PipelineCache>();
 
+        if !self.converting_solid_masks.is_empty() {
+            let mut pass =
+                render_context
+                    .command_encoder()
+                    .begin_compute_pass(&ComputePassDescriptor {
+                        label: Some("Convert solid masks"),
+                        ..default()
+                    });
+            for entity in &self.converting_solid_masks {
+                if let Ok(mask) = self.solid_mask_sdf_query.get_manual(world, *entity) {
+                    solid_mask::dispatch_sdf_conversion(world, pipeline_cache, &mut pass, mask);
+                }
+            }
+        }
+
         match self.state {
             State::Loading => {}
             State::Init => {}
@@ -213,8 +330,16 @@ impl render_graph::Node for EulerFluidNode {
                     fluid_settings,
                     projection_method,
                     reinitialize_method,
+                    advection_method,
+                    velocity_transfer_method,
+                    fluid_substeps,
+                    viscosity_solver,
+                    vorticity_confinement,
                 ) in self.fluid_query.iter_manual(world)
                 {
+                    let velocity_transfer_bind_group = bind_groups
+                        .velocity_transfer_bind_group
+                        .filter(|_| *velocity_transfer_method != VelocityTransferMethod::Grid);
                     match fluid_status {
                         FluidStatus::Uninitialized => {
                             let mut pass = render_context.command_encoder().begin_compute_pass(
@@ -247,7 +372,11 @@ impl render_graph::Node for EulerFluidNode {
                                 num_workgroups_x_edge,
                             );
 
-                            if let Some(pls_init_bind_groups) = pls_init_bind_groups {
+                            let has_free_surface = fluid_settings.mode == FluidMode::FreeSurface;
+
+                            if let Some(pls_init_bind_groups) =
+                                pls_init_bind_groups.filter(|_| has_free_surface)
+                            {
                                 particle_levelset_two_layers::plugin::dispatch_initialize(
                                     world,
                                     pipeline_cache,
@@ -266,14 +395,45 @@ impl render_graph::Node for EulerFluidNode {
                                 num_workgroups_grid,
                             );
 
-                            reinitialize_levelset::dispatch(
-                                world,
-                                reinitialize_method,
-                                pipeline_cache,
-                                &mut pass,
-                                bind_groups.reinit_levelset_bind_groups,
-                                fluid_settings.size,
-                            );
+                            if let Some(scalar_field_bind_groups) =
+                                bind_groups.scalar_field_bind_groups
+                            {
+                                let scalar_field_pipelines =
+                                    world.resource::<ScalarFieldPipelines>();
+                                scalar_field_pipelines.dispatch_initialize(
+                                    pipeline_cache,
+                                    &mut pass,
+                                    scalar_field_bind_groups,
+                                    bind_groups
+                                        .update_fluid_source_bind_groups
+                                        .uniform_bind_group,
+                                    fluid_settings.size,
+                                );
+                            }
+
+                            if has_free_surface {
+                                reinitialize_levelset::dispatch(
+                                    world,
+                                    reinitialize_method,
+                                    pipeline_cache,
+                                    &mut pass,
+                                    bind_groups.reinit_levelset_bind_groups,
+                                    bind_groups.simulation_uniform,
+                                    fluid_settings.size,
+                                );
+                            }
+
+                            if let Some(velocity_transfer_bind_group) = velocity_transfer_bind_group
+                            {
+                                let velocity_transfer_pipelines =
+                                    world.resource::<VelocityTransferPipelines>();
+                                velocity_transfer_pipelines.dispatch_initialize(
+                                    pipeline_cache,
+                                    &mut pass,
+                                    velocity_transfer_bind_group,
+                                    bind_groups.simulation_uniform,
+                                );
+                            }
                         }
                         FluidStatus::Initialized => {
                             let diagnostics = render_context.diagnostic_recorder();
@@ -286,169 +446,309 @@ impl render_graph::Node for EulerFluidNode {
                             let pass_span = diagnostics.pass_span(&mut pass, "eulerian_fluid");
                             let num_workgroups_grid =
                                 (fluid_settings.size / WORKGROUP_SIZE).extend(1);
+                            let has_free_surface = fluid_settings.mode == FluidMode::FreeSurface;
 
                             let update_solid_pipeline = world.resource::<UpdateSolidPipeline>();
-                            let obstacles_bind_groups =
-                                world.resource::<SolidObstaclesBindGroups>();
                             update_solid(
                                 pipeline_cache,
                                 &mut pass,
                                 bind_groups.update_solid_bind_groups,
-                                obstacles_bind_groups,
+                                bind_groups.solid_obstacles_bind_groups,
                                 bind_groups.simulation_uniform,
                                 update_solid_pipeline,
                                 fluid_settings.size,
                             );
 
+                            let solid_mask_merge_pipeline =
+                                world.resource::<SolidMaskMergePipeline>();
+                            for (solid_mask_resource, solid_mask_bind_group) in
+                                self.solid_mask_query.iter_manual(world)
+                            {
+                                if solid_mask_resource.levelset_solid
+                                    == bind_groups.update_solid_resource.levelset_solid
+                                {
+                                    solid_mask_merge_pipeline.pipeline.dispatch(
+                                        pipeline_cache,
+                                        &mut pass,
+                                        &solid_mask_bind_group.bind_group,
+                                        num_workgroups_grid,
+                                    );
+                                }
+                            }
+
                             let update_area_fraction_pipeline =
                                 world.resource::<UpdateAreaFractionPipeline>();
-                            update_area_fraction_pipeline.pipeline.dispatch(
-                                pipeline_cache,
-                                &mut pass,
-                                &bind_groups.update_area_fraction_bind_group.bind_group,
-                                num_workgroups_grid,
-                            );
-
-                            let advection_pipeline = world.resource::<AdvectionPipeline>();
-                            advection::dispatch(
-                                pipeline_cache,
-                                &mut pass,
-                                bind_groups.advection_bind_groups,
-                                bind_groups.simulation_uniform,
-                                advection_pipeline,
-                                fluid_settings.size,
-                            );
-
-                            let apply_forces_pipeline = world.resource::<ApplyForcesPipeline>();
-                            apply_forces(
-                                pipeline_cache,
-                                &mut pass,
-                                bind_groups.apply_forces_bind_groups,
-                                bind_groups.simulation_uniform,
-                                apply_forces_pipeline,
-                                fluid_settings.size,
-                            );
-
-                            let divergence_pipeline = world.resource::<DivergencePipeline>();
-                            divergence_pipeline.pipeline.dispatch_with_uniform(
-                                pipeline_cache,
-                                &mut pass,
-                                &bind_groups.divergence_bind_groups.bind_group,
-                                bind_groups.simulation_uniform,
-                                num_workgroups_grid,
-                            );
-
-                            projection::dispatch(
-                                world,
-                                projection_method,
-                                pipeline_cache,
-                                &mut pass,
-                                bind_groups.projection_bind_groups,
-                                bind_groups.simulation_uniform,
-                                fluid_settings.size,
-                            );
-
-                            let solve_velocity_pipeline = world.resource::<SolveVelocityPipeline>();
-                            solve_velocity(
-                                pipeline_cache,
-                                &mut pass,
-                                bind_groups.solve_velocity_bind_groups,
-                                bind_groups.simulation_uniform,
-                                solve_velocity_pipeline,
-                                fluid_settings.size,
-                            );
-
-                            let extrapolate_velocity_pipeline =
-                                world.resource::<ExtrapolateVelocityPipeline>();
-                            extrapolate_velocity(
-                                pipeline_cache,
-                                &mut pass,
-                                bind_groups.extrapolate_velocity_bind_groups,
-                                extrapolate_velocity_pipeline,
-                                fluid_settings.size,
-                            );
+                            update_area_fraction_pipeline
+                                .pipeline
+                                .dispatch_with_uniform(
+                                    pipeline_cache,
+                                    &mut pass,
+                                    &bind_groups.update_area_fraction_bind_group.bind_group,
+                                    bind_groups.simulation_uniform,
+                                    num_workgroups_grid,
+                                );
 
-                            let advect_levelset_pipeline =
-                                world.resource::<AdvectLevelSetPipeline>();
-                            advect_levelset_pipeline.pipeline.dispatch(
-                                pipeline_cache,
-                                &mut pass,
-                                &bind_groups.advect_levelset_bind_groups.bind_group,
-                                num_workgroups_grid,
-                            );
+                            // Solids are rasterized once per physics step,
+                            // and the forces on them are sampled after the last substep.
+                            for _ in 0..fluid_substeps.0 {
+                                if let Some(high_order_advection_bind_groups) =
+                                    bind_groups.high_order_advection_bind_groups
+                                {
+                                    let high_order_advection_pipelines =
+                                        world.resource::<HighOrderAdvectionPipelines>();
+                                    high_order_advection_pipelines.dispatch_velocity(
+                                        advection_method,
+                                        pipeline_cache,
+                                        &mut pass,
+                                        high_order_advection_bind_groups,
+                                        bind_groups.simulation_uniform,
+                                        fluid_settings.size,
+                                    );
+                                } else {
+                                    let advection_pipeline = world.resource::<AdvectionPipeline>();
+                                    advection::dispatch(
+                                        pipeline_cache,
+                                        &mut pass,
+                                        bind_groups.advection_bind_groups,
+                                        bind_groups.simulation_uniform,
+                                        advection_pipeline,
+                                        fluid_settings.size,
+                                    );
+                                }
 
-                            let update_fluid_source_pipeline =
-                                world.resource::<UpdateFluidSourcePipeline>();
-                            update_fluid_source_pipeline.dispatch(
-                                pipeline_cache,
-                                &mut pass,
-                                &bind_groups.update_fluid_source_bind_groups,
-                                num_workgroups_grid,
-                            );
+                                if let Some(velocity_transfer_bind_group) =
+                                    velocity_transfer_bind_group
+                                {
+                                    let velocity_transfer_pipelines =
+                                        world.resource::<VelocityTransferPipelines>();
+                                    velocity_transfer_pipelines.dispatch_particle_to_grid(
+                                        pipeline_cache,
+                                        &mut pass,
+                                        velocity_transfer_bind_group,
+                                        bind_groups.simulation_uniform,
+                                        fluid_settings.size,
+                                    );
+                                }
 
-                            if let Some(pls_update_bind_groups) = pls_update_bind_groups {
-                                particle_levelset_two_layers::plugin::dispatch_update(
-                                    world,
+                                let apply_forces_pipeline = world.resource::<ApplyForcesPipeline>();
+                                apply_forces(
                                     pipeline_cache,
                                     &mut pass,
-                                    pls_update_bind_groups,
+                                    bind_groups.apply_forces_bind_groups,
                                     bind_groups.simulation_uniform,
+                                    apply_forces_pipeline,
                                     fluid_settings.size,
                                 );
-                            }
 
-                            if let Some(correct_levelset_bind_groups) =
-                                pls_correct_levelset_bind_groups
-                            {
-                                particle_levelset_two_layers::levelset_correction::dispatch(
-                                    world,
+                                if vorticity_confinement.is_some_and(|v| v.strength > 0.0) {
+                                    let vorticity_confinement_pipeline =
+                                        world.resource::<VorticityConfinementPipeline>();
+                                    vorticity_confinement_pipeline.dispatch(
+                                        pipeline_cache,
+                                        &mut pass,
+                                        &bind_groups.vorticity_confinement_bind_group.bind_group,
+                                        bind_groups.simulation_uniform,
+                                        fluid_settings.size,
+                                    );
+                                }
+
+                                if fluid_settings.viscosity > 0.0 {
+                                    let viscosity_pipeline = world.resource::<ViscosityPipeline>();
+                                    viscosity_pipeline.dispatch(
+                                        pipeline_cache,
+                                        &mut pass,
+                                        &bind_groups.viscosity_bind_group.bind_group,
+                                        bind_groups.simulation_uniform,
+                                        fluid_settings.size,
+                                        viscosity_solver,
+                                    );
+                                }
+
+                                let divergence_pipeline = world.resource::<DivergencePipeline>();
+                                divergence_pipeline.pipeline.dispatch_with_uniform(
                                     pipeline_cache,
                                     &mut pass,
-                                    correct_levelset_bind_groups,
-                                    fluid_settings.size,
+                                    &bind_groups.divergence_bind_groups.bind_group,
+                                    bind_groups.simulation_uniform,
+                                    num_workgroups_grid,
                                 );
-                            }
 
-                            reinitialize_levelset::dispatch(
-                                world,
-                                reinitialize_method,
-                                pipeline_cache,
-                                &mut pass,
-                                bind_groups.reinit_levelset_bind_groups,
-                                fluid_settings.size,
-                            );
+                                if fluid_settings.surface_tension > 0.0 {
+                                    let curvature_pipeline = world.resource::<CurvaturePipeline>();
+                                    curvature_pipeline.pipeline.dispatch_with_uniform(
+                                        pipeline_cache,
+                                        &mut pass,
+                                        &bind_groups.curvature_bind_group.bind_group,
+                                        bind_groups.simulation_uniform,
+                                        num_workgroups_grid,
+                                    );
+                                }
 
-                            if let Some(correct_levelset_second_bind_groups) =
-                                pls_correct_levelset_second_bind_groups
-                            {
-                                particle_levelset_two_layers::levelset_correction::dispatch_second(
+                                projection::dispatch(
                                     world,
+                                    projection_method,
                                     pipeline_cache,
                                     &mut pass,
-                                    correct_levelset_second_bind_groups,
+                                    bind_groups.projection_bind_groups,
+                                    bind_groups.simulation_uniform,
                                     fluid_settings.size,
                                 );
-                            }
 
-                            if let Some(reseed_bind_groups) = pls_reseed_bind_groups {
-                                particle_levelset_two_layers::reseed::dispatch(
-                                    world,
+                                let solve_velocity_pipeline =
+                                    world.resource::<SolveVelocityPipeline>();
+                                solve_velocity(
                                     pipeline_cache,
                                     &mut pass,
-                                    reseed_bind_groups,
+                                    bind_groups.solve_velocity_bind_groups,
                                     bind_groups.simulation_uniform,
+                                    solve_velocity_pipeline,
                                     fluid_settings.size,
                                 );
-                            }
 
-                            let levelset_gradient_pipeline =
-                                world.resource::<LevelSetGradientPipeline>();
-                            levelset_gradient_pipeline.pipeline.dispatch(
-                                pipeline_cache,
-                                &mut pass,
-                                &bind_groups.levelset_gradient_bind_group.bind_group,
-                                num_workgroups_grid,
-                            );
+                                if has_free_surface {
+                                    let extrapolate_velocity_pipeline =
+                                        world.resource::<ExtrapolateVelocityPipeline>();
+                                    extrapolate_velocity(
+                                        pipeline_cache,
+                                        &mut pass,
+                                        bind_groups.extrapolate_velocity_bind_groups,
+                                        bind_groups.simulation_uniform,
+                                        extrapolate_velocity_pipeline,
+                                        fluid_settings.size,
+                                    );
+
+                                    if let Some(high_order_advection_bind_groups) =
+                                        bind_groups.high_order_advection_bind_groups
+                                    {
+                                        let high_order_advection_pipelines =
+                                            world.resource::<HighOrderAdvectionPipelines>();
+                                        high_order_advection_pipelines.dispatch_levelset(
+                                            advection_method,
+                                            pipeline_cache,
+                                            &mut pass,
+                                            high_order_advection_bind_groups,
+                                            bind_groups.simulation_uniform,
+                                            fluid_settings.size,
+                                        );
+                                    } else {
+                                        let advect_levelset_pipeline =
+                                            world.resource::<AdvectLevelSetPipeline>();
+                                        advect_levelset_pipeline.pipeline.dispatch(
+                                            pipeline_cache,
+                                            &mut pass,
+                                            &bind_groups.advect_levelset_bind_groups.bind_group,
+                                            num_workgroups_grid,
+                                        );
+                                    }
+                                }
+
+                                let update_fluid_source_pipeline =
+                                    world.resource::<UpdateFluidSourcePipeline>();
+                                update_fluid_source_pipeline.dispatch(
+                                    pipeline_cache,
+                                    &mut pass,
+                                    &bind_groups.update_fluid_source_bind_groups,
+                                    num_workgroups_grid,
+                                );
+
+                                if let Some(scalar_field_bind_groups) =
+                                    bind_groups.scalar_field_bind_groups
+                                {
+                                    let scalar_field_pipelines =
+                                        world.resource::<ScalarFieldPipelines>();
+                                    scalar_field_pipelines.dispatch(
+                                        pipeline_cache,
+                                        &mut pass,
+                                        scalar_field_bind_groups,
+                                        bind_groups.simulation_uniform,
+                                        bind_groups
+                                            .update_fluid_source_bind_groups
+                                            .uniform_bind_group,
+                                        fluid_settings.size,
+                                    );
+                                }
+
+                                if has_free_surface {
+                                    if let Some(pls_update_bind_groups) = pls_update_bind_groups {
+                                        particle_levelset_two_layers::plugin::dispatch_update(
+                                            world,
+                                            pipeline_cache,
+                                            &mut pass,
+                                            pls_update_bind_groups,
+                                            bind_groups.simulation_uniform,
+                                            fluid_settings.size,
+                                        );
+                                    }
+
+                                    if let Some(correct_levelset_bind_groups) =
+                                        pls_correct_levelset_bind_groups
+                                    {
+                                        particle_levelset_two_layers::levelset_correction::dispatch(
+                                            world,
+                                            pipeline_cache,
+                                            &mut pass,
+                                            correct_levelset_bind_groups,
+                                            fluid_settings.size,
+                                        );
+                                    }
+
+                                    reinitialize_levelset::dispatch(
+                                        world,
+                                        reinitialize_method,
+                                        pipeline_cache,
+                                        &mut pass,
+                                        bind_groups.reinit_levelset_bind_groups,
+                                        bind_groups.simulation_uniform,
+                                        fluid_settings.size,
+                                    );
+
+                                    if let Some(correct_levelset_second_bind_groups) =
+                                        pls_correct_levelset_second_bind_groups
+                                    {
+                                        particle_levelset_two_layers::levelset_correction::dispatch_second(
+                                            world,
+                                            pipeline_cache,
+                                            &mut pass,
+                                            correct_levelset_second_bind_groups,
+                                            fluid_settings.size,
+                                        );
+                                    }
+
+                                    if let Some(reseed_bind_groups) = pls_reseed_bind_groups {
+                                        particle_levelset_two_layers::reseed::dispatch(
+                                            world,
+                                            pipeline_cache,
+                                            &mut pass,
+                                            reseed_bind_groups,
+                                            bind_groups.simulation_uniform,
+                                            fluid_settings.size,
+                                        );
+                                    }
+
+                                    let levelset_gradient_pipeline =
+                                        world.resource::<LevelSetGradientPipeline>();
+                                    levelset_gradient_pipeline.pipeline.dispatch(
+                                        pipeline_cache,
+                                        &mut pass,
+                                        &bind_groups.levelset_gradient_bind_group.bind_group,
+                                        num_workgroups_grid,
+                                    );
+                                }
+
+                                if let Some(velocity_transfer_bind_group) =
+                                    velocity_transfer_bind_group
+                                {
+                                    let velocity_transfer_pipelines =
+                                        world.resource::<VelocityTransferPipelines>();
+                                    velocity_transfer_pipelines.dispatch_grid_to_particle(
+                                        pipeline_cache,
+                                        &mut pass,
+                                        velocity_transfer_bind_group,
+                                        bind_groups.simulation_uniform,
+                                        fluid_settings.size,
+                                    );
+                                }
+                            }
 
                             let fluid_to_solid_forces_pipeline =
                                 world.resource::<FluidToSolidForcesPipeline>();
@@ -456,7 +756,7 @@ impl render_graph::Node for EulerFluidNode {
                                 pipeline_cache,
                                 &mut pass,
                                 bind_groups.fluid_to_solid_bind_groups,
-                                obstacles_bind_groups,
+                                bind_groups.solid_obstacles_bind_groups,
                                 bind_groups.simulation_uniform,
                                 fluid_to_solid_forces_pipeline,
                                 fluid_settings.size,
@@ -467,6 +767,25 @@ impl render_graph::Node for EulerFluidNode {
                         _ => {}
                     }
                 }
+
+                // The maximum velocity determines the number of substeps of the next physics step.
+                for (grid_size, max_velocity_bind_group) in
+                    self.max_velocity_query.iter_manual(world)
+                {
+                    let mut pass = render_context.command_encoder().begin_compute_pass(
+                        &ComputePassDescriptor {
+                            label: Some("Fluid max velocity"),
+                            ..default()
+                        },
+                    );
+                    let max_velocity_pipeline = world.resource::<MaxVelocityPipeline>();
+                    max_velocity_pipeline.dispatch(
+                        pipeline_cache,
+                        &mut pass,
+                        max_velocity_bind_group,
+                        grid_size.0,
+                    );
+                }
             }
             State::Idle => {}
         }
@@ -541,6 +860,7 @@ fn extrapolate_velocity(
     pipeline_cache: &PipelineCache,
     pass: &mut ComputePass,
     extrapolate_velocity_bind_groups: &ExtrapolateVelocityBindGroups,
+    uniform_bind_group: &SimulationUniformBindGroup,
     extrapolate_velocity_pipeline: &ExtrapolateVelocityPipeline,
     size: UVec2,
 ) {
@@ -564,6 +884,11 @@ fn extrapolate_velocity(
         &extrapolate_velocity_bind_groups.initialize_u_valid_bind_group,
         &[],
     );
+    pass.set_bind_group(
+        1,
+        &uniform_bind_group.bind_group,
+        &[uniform_bind_group.index],
+    );
     pass.dispatch_x_major(size);
 
     pass.set_pipeline(&initialize_v_valid_pipeline);
@@ -617,8 +942,22 @@ fn fluid_to_solid_forces(
     size: UVec2,
 ) {
     pass.push_debug_group("Fluid to solid forces");
+    // Without the conjugate gradient solver, the monolithic coupling falls back to the area fraction.
+    let coupled_forces_bind_group = bind_groups
+        .coupled_forces_bind_group
+        .as_ref()
+        .filter(|_| bind_groups.method == SolidCouplingMethod::Monolithic);
+    let sample_forces_pipeline = match bind_groups.method {
+        SolidCouplingMethod::AreaFraction => pipeline.sample_forces_pipeline,
+        SolidCouplingMethod::BoundaryQuadrature => pipeline.sample_shear_pipeline,
+        // The pressure forces are J^T p of the solve.
+        SolidCouplingMethod::Monolithic if coupled_forces_bind_group.is_some() => {
+            pipeline.sample_shear_pipeline
+        }
+        SolidCouplingMethod::Monolithic => pipeline.sample_forces_pipeline,
+    };
     let sample_forces_pipeline = pipeline_cache
-        .get_compute_pipeline(pipeline.sample_forces_pipeline)
+        .get_compute_pipeline(sample_forces_pipeline)
         .unwrap();
     let accumulate_forces_pipeline = pipeline_cache
         .get_compute_pipeline(pipeline.accumulate_forces_pipeline)
@@ -634,9 +973,30 @@ fn fluid_to_solid_forces(
     );
     pass.dispatch_center(size);
 
-    pass.set_pipeline(&accumulate_forces_pipeline);
+    if bind_groups.method == SolidCouplingMethod::BoundaryQuadrature {
+        let sample_boundary_forces_pipeline = pipeline_cache
+            .get_compute_pipeline(pipeline.sample_boundary_forces_pipeline)
+            .unwrap();
+        pass.set_pipeline(sample_boundary_forces_pipeline);
+        pass.dispatch_workgroups(bind_groups.num_boundary_points.div_ceil(64), 1, 1);
+    }
+
+    if let Some(coupled_forces_bind_group) = coupled_forces_bind_group {
+        let accumulate_coupled_forces_pipeline = pipeline_cache
+            .get_compute_pipeline(pipeline.accumulate_coupled_forces_pipeline)
+            .unwrap();
+        pass.set_pipeline(accumulate_coupled_forces_pipeline);
+        pass.set_bind_group(1, coupled_forces_bind_group, &[]);
+        pass.set_bind_group(
+            2,
+            &uniform_bind_group.bind_group,
+            &[uniform_bind_group.index],
+        );
+    } else {
+        pass.set_pipeline(&accumulate_forces_pipeline);
+    }
     pass.set_bind_group(0, &bind_groups.accumulate_forces_bind_group, &[]);
-    pass.dispatch_workgroups(MAX_SOLIDS as u32, 1, 1);
+    pass.dispatch_workgroups(bind_groups.num_solids, 1, 1);
     pass.pop_debug_group();
 }
 
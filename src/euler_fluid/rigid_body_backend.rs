@@ -0,0 +1,312 @@
+#[cfg(feature = "avian2d")]
+mod avian;
+
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::query::{QueryData, QueryItem, ROQueryItem, ReadOnlyQueryData},
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+    render::{gpu_readback::ReadbackComplete, storage::ShaderStorageBuffer},
+};
+
+#[cfg(feature = "avian2d")]
+pub use avian::AvianBackend;
+
+use crate::{
+    fluid_to_solid::{FluidToSolidForce, SolidCouplingMethod},
+    obstacle::{
+        boundary_quadrature::{boundary_quadrature_points, BoundaryQuadraturePoint},
+        FluidDragCoefficient, FluidIgnore, FluidObstacle, FluidObstacleForces, ShapeVariant,
+        SolidEntities, SolidGeometry, SolidGeometryBuffers, SolidObstacle, SolidObstaclesBuffer,
+    },
+    physics_time::PhysicsFrameInfo,
+    projection::solid_coupling::{CoupledBody, SolidCouplingResource},
+    settings::{FluidGridLength, FluidSettings},
+};
+
+/// Bridge between a rigid body physics engine and the two-way coupling of fluids and solids.
+/// Every frame, the bodies matching [`FluidRigidBodyBackend::Body`] are collected into [`SolidObstacle`]s
+/// to be rasterized into the fluid, except for the ones with [`FluidIgnore`] or rejected by [`FluidRigidBodyBackend::interacts_with_fluid`].
+/// After each physics step, the forces exerted by the fluid are handed over
+/// to [`FluidRigidBodyBackend::apply_force`].
+///
+/// Register an implementation with [`FluidRigidBodyBackendPlugin`].
+/// [`FluidObstacleBackend`] is always registered, and [`AvianBackend`] is registered with the `avian2d` feature.
+pub trait FluidRigidBodyBackend: Send + Sync + 'static {
+    /// Components read from a body to rasterize it into the fluid.
+    type Body: ReadOnlyQueryData;
+    /// Components written to apply the forces exerted by the fluid.
+    type Forces: QueryData;
+    /// Components read from both bodies and fluids to decide whether they are coupled.
+    type Filter: ReadOnlyQueryData;
+
+    /// Collects the shape, transform and velocities of a body.
+    /// Variable-length shapes such as polygons are built with `geometry`.
+    /// Returns `None` if the body can not be coupled with the fluid, e.g. its shape is not supported.
+    fn solid_obstacle(
+        body: ROQueryItem<'_, '_, Self::Body>,
+        geometry: &mut SolidGeometry,
+    ) -> Option<SolidObstacle>;
+
+    /// Whether the body is coupled with the fluid, given the [`Self::Filter`] components of each entity.
+    /// All bodies are coupled with all fluids by default.
+    fn interacts_with_fluid(
+        _body: &ROQueryItem<'_, '_, Self::Filter>,
+        _fluid: &ROQueryItem<'_, '_, Self::Filter>,
+    ) -> bool {
+        true
+    }
+
+    /// Applies the force and torque exerted by the fluid in the latest physics step.
+    /// `force` is in world units with y-axis pointing up.
+    fn apply_force(body: QueryItem<'_, '_, Self::Forces>, force: &FluidToSolidForce);
+}
+
+/// Couples the bodies of a [`FluidRigidBodyBackend`] with fluids.
+pub struct FluidRigidBodyBackendPlugin<B: FluidRigidBodyBackend>(PhantomData<B>);
+
+impl<B: FluidRigidBodyBackend> Default for FluidRigidBodyBackendPlugin<B> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<B: FluidRigidBodyBackend> Plugin for FluidRigidBodyBackendPlugin<B> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            collect_solid_obstacles::<B>.in_set(SolidObstacleSystems::Collect),
+        )
+        .add_observer(apply_fluid_forces::<B>);
+    }
+}
+
+/// Backend of the crate-owned [`FluidObstacle`], which does not depend on any physics engine.
+pub struct FluidObstacleBackend;
+
+impl FluidRigidBodyBackend for FluidObstacleBackend {
+    type Body = (&'static GlobalTransform, &'static FluidObstacle);
+    type Forces = &'static mut FluidObstacleForces;
+    type Filter = ();
+
+    fn solid_obstacle(
+        (transform, obstacle): ROQueryItem<'_, '_, Self::Body>,
+        _geometry: &mut SolidGeometry,
+    ) -> Option<SolidObstacle> {
+        Some(SolidObstacle::new(
+            ShapeVariant::from(&obstacle.shape),
+            transform,
+            obstacle.shape.center_of_mass(),
+            obstacle.linear_velocity,
+            obstacle.angular_velocity,
+        ))
+    }
+
+    fn apply_force(mut body: QueryItem<'_, '_, Self::Forces>, force: &FluidToSolidForce) {
+        body.force = force.force;
+        body.torque = force.torque;
+    }
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum SolidObstacleSystems {
+    Collect,
+    Upload,
+}
+
+/// Obstacles collected from all the backends in the current frame.
+#[derive(Resource, Default)]
+struct CollectedSolidObstacles {
+    entities: Vec<Entity>,
+    obstacles: Vec<SolidObstacle>,
+    geometry: SolidGeometry,
+    /// Pairs of a body and a fluid which are not coupled.
+    excluded: HashSet<(Entity, Entity)>,
+}
+
+pub(crate) struct RigidBodyBackendPlugin;
+
+impl Plugin for RigidBodyBackendPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CollectedSolidObstacles>()
+            .add_systems(
+                Update,
+                (
+                    clear_solid_obstacles.before(SolidObstacleSystems::Collect),
+                    upload_solid_obstacles
+                        .after(SolidObstacleSystems::Collect)
+                        .in_set(SolidObstacleSystems::Upload),
+                ),
+            )
+            .add_plugins(FluidRigidBodyBackendPlugin::<FluidObstacleBackend>::default());
+
+        #[cfg(feature = "avian2d")]
+        app.add_plugins(FluidRigidBodyBackendPlugin::<AvianBackend>::default());
+    }
+}
+
+fn clear_solid_obstacles(mut collected: ResMut<CollectedSolidObstacles>) {
+    collected.entities.clear();
+    collected.obstacles.clear();
+    collected.geometry.clear();
+    collected.excluded.clear();
+}
+
+fn collect_solid_obstacles<B: FluidRigidBodyBackend>(
+    query: Query<(Entity, B::Body, B::Filter), Without<FluidIgnore>>,
+    query_fluid: Query<(Entity, B::Filter), With<FluidSettings>>,
+    query_drag_coefficient: Query<&FluidDragCoefficient>,
+    mut collected: ResMut<CollectedSolidObstacles>,
+) {
+    let collected = collected.as_mut();
+    for (entity, body, filter) in &query {
+        if let Some(mut obstacle) = B::solid_obstacle(body, &mut collected.geometry) {
+            if let Ok(drag_coefficient) = query_drag_coefficient.get(entity) {
+                obstacle.drag_coefficient = drag_coefficient.0;
+            }
+            collected.entities.push(entity);
+            collected.obstacles.push(obstacle);
+            for (fluid_entity, fluid_filter) in &query_fluid {
+                if !B::interacts_with_fluid(&filter, &fluid_filter) {
+                    collected.excluded.insert((entity, fluid_entity));
+                }
+            }
+        }
+    }
+}
+
+fn upload_solid_obstacles(
+    collected: Res<CollectedSolidObstacles>,
+    geometry_buffers: Res<SolidGeometryBuffers>,
+    mut query_fluid: Query<(
+        Entity,
+        &FluidSettings,
+        &Transform,
+        &SolidCouplingMethod,
+        &mut SolidObstaclesBuffer,
+        &mut SolidEntities,
+    )>,
+    query_solid_coupling: Query<&SolidCouplingResource>,
+    grid_length: Res<FluidGridLength>,
+    mut buffers: ResMut<Assets<ShaderStorageBuffer>>,
+) {
+    // Bounding circles of the obstacles in world space.
+    let bounds: Vec<Rect> = collected
+        .obstacles
+        .iter()
+        .map(|obstacle| {
+            let transform = obstacle.transform;
+            let scale = transform
+                .x_axis
+                .xy()
+                .length()
+                .max(transform.y_axis.xy().length());
+            let radius = obstacle.shape.bounding_radius(&collected.geometry) * scale;
+            Rect::from_center_half_size(transform.w_axis.xy(), Vec2::splat(radius))
+        })
+        .collect();
+
+    for (fluid_entity, settings, transform, method, mut obstacles_buffer, mut solid_entities) in
+        &mut query_fluid
+    {
+        // Domain of the fluid in world space, with a margin of a cell.
+        let half_size = 0.5 * settings.size.as_vec2() + 1.0;
+        let domain = [
+            Vec2::new(-half_size.x, -half_size.y),
+            Vec2::new(half_size.x, -half_size.y),
+            Vec2::new(-half_size.x, half_size.y),
+            half_size,
+        ]
+        .map(|corner| transform.transform_point(corner.extend(0.0)).xy())
+        .into_iter()
+        .fold(Rect::EMPTY, |domain, corner| domain.union_point(corner));
+
+        solid_entities.entities.clear();
+        let mut obstacles = Vec::new();
+        for ((entity, obstacle), bounds) in collected
+            .entities
+            .iter()
+            .zip(&collected.obstacles)
+            .zip(&bounds)
+        {
+            if domain.intersect(*bounds).is_empty()
+                || collected.excluded.contains(&(*entity, fluid_entity))
+            {
+                continue;
+            }
+            obstacles.push(SolidObstacle {
+                entity_id: obstacles.len() as u32,
+                ..*obstacle
+            });
+            solid_entities.entities.push(*entity);
+        }
+
+        if *method == SolidCouplingMethod::BoundaryQuadrature {
+            let mut boundary_points = boundary_quadrature_points(
+                &obstacles,
+                &collected.geometry,
+                transform.to_matrix().inverse(),
+            );
+            obstacles_buffer.num_boundary_points = boundary_points.len() as u32;
+            // Storage buffers can not be empty.
+            if boundary_points.is_empty() {
+                boundary_points.push(BoundaryQuadraturePoint::default());
+            }
+            let boundary_points_buffer =
+                buffers.get_mut(&obstacles_buffer.boundary_points).unwrap();
+            boundary_points_buffer.set_data(boundary_points);
+        }
+
+        if let Ok(solid_coupling) = query_solid_coupling.get(fluid_entity) {
+            let (bodies, velocities) = obstacles
+                .iter()
+                .map(|obstacle| {
+                    CoupledBody::from_obstacle(obstacle, transform, settings.size, grid_length.0)
+                })
+                .unzip();
+            solid_coupling.set_bodies(&mut buffers, bodies, velocities);
+        }
+
+        let obstacles_buffer = buffers.get_mut(&obstacles_buffer.obstacles).unwrap();
+        obstacles_buffer.set_data(obstacles);
+    }
+
+    let vertices = buffers.get_mut(&geometry_buffers.vertices).unwrap();
+    vertices.set_data(collected.geometry.vertices.clone());
+
+    let parts = buffers.get_mut(&geometry_buffers.parts).unwrap();
+    parts.set_data(collected.geometry.parts.clone());
+}
+
+fn apply_fluid_forces<B: FluidRigidBodyBackend>(
+    trigger: On<ReadbackComplete>,
+    query_fluid: Query<&SolidEntities>,
+    mut query: Query<B::Forces>,
+    grid_length: Res<FluidGridLength>,
+    physics_frame_info: Res<PhysicsFrameInfo>,
+    mut last_physics_steps: Local<HashMap<Entity, u64>>,
+) {
+    let Ok(solid_entities) = query_fluid.get(trigger.entity) else {
+        return;
+    };
+
+    // GPU readback can complete more than once in a physics step.
+    let last_physics_step = last_physics_steps.entry(trigger.entity).or_default();
+    if *last_physics_step == physics_frame_info.step_number {
+        return;
+    }
+    *last_physics_step = physics_frame_info.step_number;
+
+    let data: Vec<FluidToSolidForce> = trigger.event().to_shader_type();
+    let scale = physics_frame_info.delta_secs / grid_length.0;
+    for (entity, force) in solid_entities.entities.iter().zip(&data) {
+        if let Ok(body) = query.get_mut(*entity) {
+            let force = FluidToSolidForce {
+                force: Vec2::new(force.force.x, -force.force.y) * scale,
+                torque: -force.torque * scale,
+            };
+            B::apply_force(body, &force);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use avian2d::{
+    parry::shape::{Shape, ShapeType},
+    prelude::{
+        AngularVelocity, Collider, CollisionLayers, ComputedAngularInertia, ComputedCenterOfMass,
+        ComputedMass, Forces, LinearVelocity, RigidBody, WriteRigidBodyForces,
+    },
+};
+use bevy::{
+    ecs::query::{QueryItem, ROQueryItem},
+    prelude::*,
+};
+
+use crate::{
+    fluid_to_solid::FluidToSolidForce,
+    obstacle::{ShapeVariant, SolidGeometry, SolidObstacle},
+    rigid_body_backend::FluidRigidBodyBackend,
+};
+
+/// Backend of avian2d rigid bodies with a [`Collider`].
+/// Forces are applied only to [`RigidBody::Dynamic`] bodies.
+/// A body is coupled with a fluid only if their [`CollisionLayers`] interact, as if the fluid were a collider.
+pub struct AvianBackend;
+
+impl FluidRigidBodyBackend for AvianBackend {
+    type Body = (
+        &'static GlobalTransform,
+        &'static Collider,
+        &'static ComputedCenterOfMass,
+        &'static LinearVelocity,
+        &'static AngularVelocity,
+        &'static RigidBody,
+        &'static ComputedMass,
+        &'static ComputedAngularInertia,
+    );
+    type Forces = (Forces, &'static RigidBody);
+    type Filter = Option<&'static CollisionLayers>;
+
+    fn solid_obstacle(
+        (
+            transform,
+            collider,
+            center_of_mass,
+            linear_velocity,
+            angular_velocity,
+            rigid_body,
+            mass,
+            angular_inertia,
+        ): ROQueryItem<'_, '_, Self::Body>,
+        geometry: &mut SolidGeometry,
+    ) -> Option<SolidObstacle> {
+        let shape = shape_variant(collider.shape().as_ref(), geometry)?;
+
+        let mut obstacle = SolidObstacle::new(
+            shape,
+            transform,
+            center_of_mass.0,
+            linear_velocity.0,
+            angular_velocity.0,
+        );
+        if *rigid_body == RigidBody::Dynamic {
+            obstacle.inverse_mass = mass.inverse();
+            obstacle.inverse_inertia = angular_inertia.inverse();
+        }
+        Some(obstacle)
+    }
+
+    fn interacts_with_fluid(
+        body: &ROQueryItem<'_, '_, Self::Filter>,
+        fluid: &ROQueryItem<'_, '_, Self::Filter>,
+    ) -> bool {
+        let body = body.copied().unwrap_or_default();
+        let fluid = fluid.copied().unwrap_or_default();
+        fluid.interacts_with(body)
+    }
+
+    fn apply_force(
+        (mut forces, rigid_body): QueryItem<'_, '_, Self::Forces>,
+        force: &FluidToSolidForce,
+    ) {
+        if *rigid_body == RigidBody::Dynamic {
+            forces.apply_force(force.force);
+            forces.apply_torque(force.torque);
+        }
+    }
+}
+
+fn shape_variant(shape: &dyn Shape, geometry: &mut SolidGeometry) -> Option<ShapeVariant> {
+    let shape_type = shape.shape_type();
+    let variant = match shape_type {
+        ShapeType::Ball => ShapeVariant::from_ball(shape.as_ball().unwrap()),
+        ShapeType::Cuboid => ShapeVariant::from_rectangle(shape.as_cuboid().unwrap()),
+        ShapeType::Triangle => ShapeVariant::from_triangle(shape.as_triangle().unwrap()),
+        ShapeType::Capsule => ShapeVariant::from_capsule(shape.as_capsule().unwrap()),
+        ShapeType::RoundCuboid => {
+            ShapeVariant::from_round_rectangle(shape.as_round_cuboid().unwrap())
+        }
+        ShapeType::ConvexPolygon => {
+            geometry.convex_polygon(shape.as_convex_polygon().unwrap().points())
+        }
+        ShapeType::Polyline => geometry.polyline(
+            shape
+                .as_polyline()
+                .unwrap()
+                .segments()
+                .map(|segment| [segment.a, segment.b]),
+        ),
+        ShapeType::Compound => {
+            let parts = shape
+                .as_compound()
+                .unwrap()
+                .shapes()
+                .iter()
+                .filter_map(|(pose, part)| {
+                    let variant = shape_variant(part.as_ref(), geometry)?;
+                    let isometry =
+                        Isometry2d::new(pose.translation, Rot2::radians(pose.rotation.angle()));
+                    Some((isometry, variant))
+                })
+                .collect::<Vec<_>>();
+            geometry.compound(parts)
+        }
+        // Rasterized as `FluidTerrain` instead.
+        ShapeType::TriMesh | ShapeType::HeightField => return None,
+        _ => {
+            warn!("Unsupported shape type for solid: {:?}", shape_type);
+            return None;
+        }
+    };
+
+    Some(variant)
+}
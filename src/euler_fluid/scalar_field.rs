@@ -0,0 +1,607 @@
+use bevy::{
+    asset::{embedded_asset, embedded_path},
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_asset::RenderAssets,
+        render_resource::{
+            binding_types::{storage_buffer_read_only_sized, texture_storage_2d, uniform_buffer},
+            BindGroup, BindGroupEntries, BindGroupLayoutDescriptor, BindGroupLayoutEntries,
+            CachedComputePipelineId, ComputePass, PipelineCache, ShaderStages, ShaderType,
+            StorageTextureAccess, TextureFormat, UniformBuffer,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        storage::{GpuShaderStorageBuffer, ShaderStorageBuffer},
+        texture::GpuImage,
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+use crate::{
+    fluid_source::{
+        fluid_source_uniform::{
+            fluid_source_uniform_bind_group_layout_desc, FluidSourceUniformBindGroup,
+            MAX_FLUID_SOURCE,
+        },
+        FluidSource, FluidSourceOneshot, FluidSourceScalars, FluidSourceTemperature,
+    },
+    fluid_uniform::{uniform_bind_group_layout_desc, SimulationUniformBindGroup},
+    pipeline::{is_pipeline_loaded, queue_compute_pipeline, DispatchFluidPass},
+    projection::gauss_seidel::GaussSeidelConfig,
+    settings::FluidTextures,
+    texture::NewTexture,
+};
+
+pub(crate) struct ScalarFieldPlugin;
+
+impl Plugin for ScalarFieldPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/scalar_field/advect_scalar.wgsl");
+        embedded_asset!(app, "shaders/scalar_field/diffuse_scalar.wgsl");
+        embedded_asset!(app, "shaders/scalar_field/inject_scalar.wgsl");
+
+        app.add_plugins((
+            ExtractComponentPlugin::<FluidScalarFields>::default(),
+            ExtractComponentPlugin::<FluidTemperature>::default(),
+            ExtractComponentPlugin::<ScalarFieldResources>::default(),
+        ))
+        .add_systems(FixedPostUpdate, update_source_values);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.add_systems(
+            Render,
+            prepare_bind_groups.in_set(RenderSystems::PrepareBindGroups),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<ScalarFieldPipelines>();
+    }
+}
+
+/// Passive scalar fields (e.g. dye, smoke density or concentration) carried by the flow.
+/// Insert this component together with [`crate::settings::FluidSettings`] to allocate one texture per channel.
+/// The textures can be found on [`crate::settings::FluidTextures::scalars`].
+///
+/// Each channel is advected with the same back-tracing as the velocity, then decayed and diffused.
+/// Values can be injected by spawning [`FluidSourceScalars`] with [`FluidSource`].
+///
+/// The number of channels is fixed when the fluid is spawned, while [`ScalarChannel`] parameters can be changed at runtime.
+/// # Fields
+/// * `channels`: Parameters of each channel.
+/// * `diffusion_solver`: Iterative solver used for the implicit diffusion of the channels.
+#[derive(Component, ExtractComponent, Clone, Debug, Default)]
+pub struct FluidScalarFields {
+    pub channels: Vec<ScalarChannel>,
+    pub diffusion_solver: GaussSeidelConfig,
+}
+
+/// Parameters of a passive scalar channel.
+/// # Fields
+/// * `diffusion`: Diffusion coefficient in unit of [m^2/s]. Set 0.0 to skip the diffusion solve.
+/// * `decay`: Decay rate in unit of [1/s]. The value is multiplied by `exp(-decay * dt)` every step.
+#[derive(Clone, Debug, Default)]
+pub struct ScalarChannel {
+    pub diffusion: f32,
+    pub decay: f32,
+}
+
+/// Temperature field transported by the flow, which produces Boussinesq buoyancy force
+/// `expansion_coefficient * (T - ambient) * -gravity`.
+/// Insert this component together with [`crate::settings::FluidSettings`] to enable it.
+/// The texture can be found on [`crate::settings::FluidTextures::temperature`].
+///
+/// The temperature is initialized to `ambient` and hot fluid can be emitted by spawning
+/// [`FluidSourceTemperature`] with [`FluidSource`].
+/// The buoyancy force is applied only inside the liquid.
+/// # Fields
+/// * `ambient`: Ambient temperature, where no buoyancy force is produced.
+/// * `expansion_coefficient`: Thermal expansion coefficient in unit of [1/K].
+/// * `diffusion`: Thermal diffusivity in unit of [m^2/s]. Set 0.0 to skip the diffusion solve.
+/// * `cooling`: Rate of relaxation toward the ambient temperature in unit of [1/s].
+#[derive(Component, ExtractComponent, Clone, Debug)]
+pub struct FluidTemperature {
+    pub ambient: f32,
+    pub expansion_coefficient: f32,
+    pub diffusion: f32,
+    pub cooling: f32,
+}
+
+impl Default for FluidTemperature {
+    fn default() -> Self {
+        Self {
+            ambient: 300.0,
+            expansion_coefficient: 1.0 / 300.0,
+            diffusion: 0.0,
+            cooling: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ShaderType)]
+struct ScalarChannelUniform {
+    diffusion: f32,
+    decay: f32,
+    rest_value: f32,
+    index: u32,
+    num_channels: u32,
+}
+
+#[derive(Component, ExtractComponent, Clone)]
+pub(crate) struct ScalarFieldResources {
+    scalar0: Vec<Handle<Image>>,
+    scalar1: Vec<Handle<Image>>,
+    u0: Handle<Image>,
+    v0: Handle<Image>,
+    levelset_solid: Handle<Image>,
+    /// The temperature is transported as the last channel if [`FluidTemperature`] is enabled.
+    temperature_channel: Option<usize>,
+    source_values: Handle<ShaderStorageBuffer>,
+    init_source_values: Handle<ShaderStorageBuffer>,
+}
+
+/// Allocates the textures of each channel into `fluid_textures.scalars`, which are exposed to users.
+/// `fluid_textures.temperature` is transported as an additional channel if it is given.
+pub(crate) fn setup(
+    commands: &mut Commands,
+    entity: Entity,
+    images: &mut ResMut<Assets<Image>>,
+    buffers: &mut ResMut<Assets<ShaderStorageBuffer>>,
+    grid_size: UVec2,
+    scalar_fields: Option<&FluidScalarFields>,
+    fluid_textures: &mut FluidTextures,
+) {
+    let num_scalars = scalar_fields.map_or(0, |s| s.channels.len());
+    let mut scalar0 = Vec::with_capacity(num_scalars + 1);
+    let mut scalar1 = Vec::with_capacity(num_scalars + 1);
+    for _ in 0..num_scalars {
+        scalar0.push(images.new_texture_storage(grid_size, TextureFormat::R32Float));
+        scalar1.push(images.new_texture_storage(grid_size, TextureFormat::R32Float));
+    }
+    fluid_textures.scalars = scalar0.clone();
+
+    let temperature_channel = fluid_textures.temperature.as_ref().map(|temperature| {
+        scalar0.push(temperature.clone());
+        scalar1.push(images.new_texture_storage(grid_size, TextureFormat::R32Float));
+        num_scalars
+    });
+
+    let num_channels = scalar0.len();
+    if num_channels == 0 {
+        return;
+    }
+
+    let source_values = buffers.add(ShaderStorageBuffer::from(vec![
+        Vec2::ZERO;
+        MAX_FLUID_SOURCE * num_channels
+    ]));
+    let init_source_values = buffers.add(ShaderStorageBuffer::from(vec![
+        Vec2::ZERO;
+        MAX_FLUID_SOURCE * num_channels
+    ]));
+
+    commands.entity(entity).insert(ScalarFieldResources {
+        scalar0,
+        scalar1,
+        u0: fluid_textures.u.clone(),
+        v0: fluid_textures.v.clone(),
+        levelset_solid: fluid_textures.levelset_solid.clone(),
+        temperature_channel,
+        source_values,
+        init_source_values,
+    });
+}
+
+type FluidSourceValuesQuery = (
+    &'static FluidSource,
+    Option<&'static FluidSourceScalars>,
+    Option<&'static FluidSourceTemperature>,
+    Has<FluidSourceOneshot>,
+);
+
+/// Writes the values of [`FluidSourceScalars`] and [`FluidSourceTemperature`] in the same order as the sources in
+/// [`crate::fluid_source::fluid_source_uniform::FluidSourceUniform`] and its init counterpart.
+fn update_source_values(
+    q_fluid: Query<(&ScalarFieldResources, Option<&Children>)>,
+    q_source: Query<FluidSourceValuesQuery>,
+    mut buffers: ResMut<Assets<ShaderStorageBuffer>>,
+) {
+    for (resources, children) in &q_fluid {
+        let Some(children) = children else {
+            continue;
+        };
+        let num_channels = resources.scalar0.len();
+        let mut values = vec![Vec2::ZERO; MAX_FLUID_SOURCE * num_channels];
+        let mut init_values = vec![Vec2::ZERO; MAX_FLUID_SOURCE * num_channels];
+        let mut count = 0;
+        let mut init_count = 0;
+        for &child in children {
+            let Ok((source, scalars, temperature, is_oneshot)) = q_source.get(child) else {
+                continue;
+            };
+            if !source.active {
+                continue;
+            }
+            let (values, count) = if is_oneshot {
+                (&mut init_values, &mut init_count)
+            } else {
+                (&mut values, &mut count)
+            };
+            if *count >= MAX_FLUID_SOURCE {
+                continue;
+            }
+            if let Some(scalars) = scalars {
+                let num_scalars = resources.temperature_channel.unwrap_or(num_channels);
+                for (channel, value) in scalars.0.iter().take(num_scalars).enumerate() {
+                    values[*count * num_channels + channel] = Vec2::new(*value, 1.0);
+                }
+            }
+            if let (Some(temperature), Some(channel)) = (temperature, resources.temperature_channel)
+            {
+                values[*count * num_channels + channel] = Vec2::new(temperature.0, 1.0);
+            }
+            *count += 1;
+        }
+
+        buffers
+            .get_mut(&resources.source_values)
+            .unwrap()
+            .set_data(values);
+        buffers
+            .get_mut(&resources.init_source_values)
+            .unwrap()
+            .set_data(init_values);
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct ScalarFieldPipelines {
+    advect_pipeline: CachedComputePipelineId,
+    copy_pipeline: CachedComputePipelineId,
+    diffuse_red_pipeline: CachedComputePipelineId,
+    diffuse_black_pipeline: CachedComputePipelineId,
+    initialize_pipeline: CachedComputePipelineId,
+    inject_pipeline: CachedComputePipelineId,
+    advect_bind_group_layout: BindGroupLayoutDescriptor,
+    diffuse_bind_group_layout: BindGroupLayoutDescriptor,
+    source_values_bind_group_layout: BindGroupLayoutDescriptor,
+}
+
+impl FromWorld for ScalarFieldPipelines {
+    fn from_world(world: &mut World) -> Self {
+        let uniform_bind_group_layout = uniform_bind_group_layout_desc();
+        let fluid_source_uniform_bind_group_layout = fluid_source_uniform_bind_group_layout_desc();
+
+        let advect_bind_group_layout = BindGroupLayoutDescriptor::new(
+            "AdvectScalarBindGroupLayout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    texture_storage_2d(TextureFormat::R32Float, StorageTextureAccess::ReadOnly),
+                    texture_storage_2d(TextureFormat::R32Float, StorageTextureAccess::WriteOnly),
+                    texture_storage_2d(TextureFormat::R32Float, StorageTextureAccess::ReadOnly),
+                    texture_storage_2d(TextureFormat::R32Float, StorageTextureAccess::ReadOnly),
+                    uniform_buffer::<ScalarChannelUniform>(false),
+                ),
+            ),
+        );
+
+        let diffuse_bind_group_layout = BindGroupLayoutDescriptor::new(
+            "DiffuseScalarBindGroupLayout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    texture_storage_2d(TextureFormat::R32Float, StorageTextureAccess::ReadWrite),
+                    texture_storage_2d(TextureFormat::R32Float, StorageTextureAccess::ReadOnly),
+                    texture_storage_2d(TextureFormat::R32Float, StorageTextureAccess::ReadOnly),
+                    uniform_buffer::<ScalarChannelUniform>(false),
+                ),
+            ),
+        );
+
+        let source_values_bind_group_layout = BindGroupLayoutDescriptor::new(
+            "ScalarSourceValuesBindGroupLayout",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::COMPUTE,
+                storage_buffer_read_only_sized(false, None),
+            ),
+        );
+
+        let advect_pipeline = queue_compute_pipeline(
+            world,
+            "AdvectScalarPipeline",
+            embedded_path!("shaders/scalar_field/advect_scalar.wgsl"),
+            "advect_scalar",
+            vec![
+                advect_bind_group_layout.clone(),
+                uniform_bind_group_layout.clone(),
+            ],
+        );
+
+        let diffuse_layouts = vec![
+            diffuse_bind_group_layout.clone(),
+            uniform_bind_group_layout,
+        ];
+        let copy_pipeline = queue_compute_pipeline(
+            world,
+            "CopyScalarPipeline",
+            embedded_path!("shaders/scalar_field/diffuse_scalar.wgsl"),
+            "copy_scalar",
+            diffuse_layouts.clone(),
+        );
+        let diffuse_red_pipeline = queue_compute_pipeline(
+            world,
+            "DiffuseScalarRedPipeline",
+            embedded_path!("shaders/scalar_field/diffuse_scalar.wgsl"),
+            "diffuse_scalar_red",
+            diffuse_layouts.clone(),
+        );
+        let diffuse_black_pipeline = queue_compute_pipeline(
+            world,
+            "DiffuseScalarBlackPipeline",
+            embedded_path!("shaders/scalar_field/diffuse_scalar.wgsl"),
+            "diffuse_scalar_black",
+            diffuse_layouts,
+        );
+
+        let inject_layouts = vec![
+            diffuse_bind_group_layout.clone(),
+            fluid_source_uniform_bind_group_layout,
+            source_values_bind_group_layout.clone(),
+        ];
+        let initialize_pipeline = queue_compute_pipeline(
+            world,
+            "InitializeScalarPipeline",
+            embedded_path!("shaders/scalar_field/inject_scalar.wgsl"),
+            "initialize_scalar",
+            inject_layouts.clone(),
+        );
+        let inject_pipeline = queue_compute_pipeline(
+            world,
+            "InjectScalarPipeline",
+            embedded_path!("shaders/scalar_field/inject_scalar.wgsl"),
+            "inject_scalar",
+            inject_layouts,
+        );
+
+        Self {
+            advect_pipeline,
+            copy_pipeline,
+            diffuse_red_pipeline,
+            diffuse_black_pipeline,
+            initialize_pipeline,
+            inject_pipeline,
+            advect_bind_group_layout,
+            diffuse_bind_group_layout,
+            source_values_bind_group_layout,
+        }
+    }
+}
+
+impl ScalarFieldPipelines {
+    pub fn is_ready(&self, pipeline_cache: &PipelineCache) -> bool {
+        is_pipeline_loaded(pipeline_cache, self.advect_pipeline)
+            && is_pipeline_loaded(pipeline_cache, self.copy_pipeline)
+            && is_pipeline_loaded(pipeline_cache, self.diffuse_red_pipeline)
+            && is_pipeline_loaded(pipeline_cache, self.diffuse_black_pipeline)
+            && is_pipeline_loaded(pipeline_cache, self.initialize_pipeline)
+            && is_pipeline_loaded(pipeline_cache, self.inject_pipeline)
+    }
+
+    pub fn dispatch_initialize(
+        &self,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        bind_groups: &ScalarFieldBindGroups,
+        fluid_source_bind_group: &FluidSourceUniformBindGroup,
+        size: UVec2,
+    ) {
+        let initialize_pipeline = pipeline_cache
+            .get_compute_pipeline(self.initialize_pipeline)
+            .unwrap();
+
+        pass.push_debug_group("Initialize scalar fields");
+        pass.set_pipeline(initialize_pipeline);
+        pass.set_bind_group(
+            1,
+            &fluid_source_bind_group.init_bind_group,
+            &[fluid_source_bind_group.init_index],
+        );
+        pass.set_bind_group(2, &bind_groups.init_source_values_bind_group, &[]);
+        for channel in &bind_groups.channels {
+            pass.set_bind_group(0, &channel.diffuse_bind_group, &[]);
+            pass.dispatch_center(size);
+        }
+        pass.pop_debug_group();
+    }
+
+    pub fn dispatch(
+        &self,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        bind_groups: &ScalarFieldBindGroups,
+        uniform_bind_group: &SimulationUniformBindGroup,
+        fluid_source_bind_group: &FluidSourceUniformBindGroup,
+        size: UVec2,
+    ) {
+        let advect_pipeline = pipeline_cache
+            .get_compute_pipeline(self.advect_pipeline)
+            .unwrap();
+        let copy_pipeline = pipeline_cache
+            .get_compute_pipeline(self.copy_pipeline)
+            .unwrap();
+        let diffuse_red_pipeline = pipeline_cache
+            .get_compute_pipeline(self.diffuse_red_pipeline)
+            .unwrap();
+        let diffuse_black_pipeline = pipeline_cache
+            .get_compute_pipeline(self.diffuse_black_pipeline)
+            .unwrap();
+        let inject_pipeline = pipeline_cache
+            .get_compute_pipeline(self.inject_pipeline)
+            .unwrap();
+
+        pass.push_debug_group("Scalar fields");
+        for channel in &bind_groups.channels {
+            pass.set_bind_group(
+                1,
+                &uniform_bind_group.bind_group,
+                &[uniform_bind_group.index],
+            );
+
+            // advect scalar0 to scalar1, then solve diffusion back to scalar0.
+            pass.set_pipeline(advect_pipeline);
+            pass.set_bind_group(0, &channel.advect_bind_group, &[]);
+            pass.dispatch_center(size);
+
+            pass.set_bind_group(0, &channel.diffuse_bind_group, &[]);
+            pass.set_pipeline(copy_pipeline);
+            pass.dispatch_center(size);
+            if channel.diffusion > 0.0 {
+                for _ in 0..bind_groups.diffusion_iterations {
+                    pass.set_pipeline(diffuse_red_pipeline);
+                    pass.dispatch_center(size);
+                    pass.set_pipeline(diffuse_black_pipeline);
+                    pass.dispatch_center(size);
+                }
+            }
+
+            pass.set_pipeline(inject_pipeline);
+            pass.set_bind_group(
+                1,
+                &fluid_source_bind_group.update_bind_group,
+                &[fluid_source_bind_group.update_index],
+            );
+            pass.set_bind_group(2, &bind_groups.source_values_bind_group, &[]);
+            pass.dispatch_center(size);
+        }
+        pass.pop_debug_group();
+    }
+}
+
+pub(crate) struct ScalarChannelBindGroups {
+    advect_bind_group: BindGroup,
+    diffuse_bind_group: BindGroup,
+    diffusion: f32,
+}
+
+#[derive(Component)]
+pub(crate) struct ScalarFieldBindGroups {
+    channels: Box<[ScalarChannelBindGroups]>,
+    source_values_bind_group: BindGroup,
+    init_source_values_bind_group: BindGroup,
+    diffusion_iterations: u32,
+}
+
+fn prepare_bind_groups<'a>(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    pipeline_cache: Res<PipelineCache>,
+    (gpu_images, buffers): (
+        Res<'a, RenderAssets<GpuImage>>,
+        Res<'a, RenderAssets<GpuShaderStorageBuffer>>,
+    ),
+    query: Query<(
+        Entity,
+        &ScalarFieldResources,
+        Option<&FluidScalarFields>,
+        Option<&FluidTemperature>,
+    )>,
+    pipelines: Res<ScalarFieldPipelines>,
+) {
+    for (entity, resources, scalar_fields, temperature) in &query {
+        let num_channels = resources.scalar0.len();
+        let u0 = gpu_images.get(&resources.u0).unwrap();
+        let v0 = gpu_images.get(&resources.v0).unwrap();
+        let levelset_solid = gpu_images.get(&resources.levelset_solid).unwrap();
+
+        let mut channels = Vec::with_capacity(num_channels);
+        for i in 0..num_channels {
+            let channel = if resources.temperature_channel == Some(i) {
+                let temperature = temperature.cloned().unwrap_or_default();
+                ScalarChannelUniform {
+                    diffusion: temperature.diffusion,
+                    decay: temperature.cooling,
+                    rest_value: temperature.ambient,
+                    index: i as u32,
+                    num_channels: num_channels as u32,
+                }
+            } else {
+                let channel = scalar_fields
+                    .and_then(|s| s.channels.get(i))
+                    .cloned()
+                    .unwrap_or_default();
+                ScalarChannelUniform {
+                    diffusion: channel.diffusion,
+                    decay: channel.decay,
+                    rest_value: 0.0,
+                    index: i as u32,
+                    num_channels: num_channels as u32,
+                }
+            };
+            let mut channel_buffer = UniformBuffer::from(channel);
+            channel_buffer.write_buffer(&render_device, &render_queue);
+
+            let scalar0 = gpu_images.get(&resources.scalar0[i]).unwrap();
+            let scalar1 = gpu_images.get(&resources.scalar1[i]).unwrap();
+
+            let advect_bind_group = render_device.create_bind_group(
+                format!("AdvectScalarBindGroup_Channel{i}").as_str(),
+                &pipeline_cache.get_bind_group_layout(&pipelines.advect_bind_group_layout),
+                &BindGroupEntries::sequential((
+                    &scalar0.texture_view,
+                    &scalar1.texture_view,
+                    &u0.texture_view,
+                    &v0.texture_view,
+                    channel_buffer.binding().unwrap(),
+                )),
+            );
+
+            let diffuse_bind_group = render_device.create_bind_group(
+                format!("DiffuseScalarBindGroup_Channel{i}").as_str(),
+                &pipeline_cache.get_bind_group_layout(&pipelines.diffuse_bind_group_layout),
+                &BindGroupEntries::sequential((
+                    &scalar0.texture_view,
+                    &scalar1.texture_view,
+                    &levelset_solid.texture_view,
+                    channel_buffer.binding().unwrap(),
+                )),
+            );
+
+            channels.push(ScalarChannelBindGroups {
+                advect_bind_group,
+                diffuse_bind_group,
+                diffusion: channel.diffusion,
+            });
+        }
+
+        let source_values = buffers.get(&resources.source_values).unwrap();
+        let source_values_bind_group = render_device.create_bind_group(
+            "ScalarSourceValuesBindGroup",
+            &pipeline_cache.get_bind_group_layout(&pipelines.source_values_bind_group_layout),
+            &BindGroupEntries::single(source_values.buffer.as_entire_buffer_binding()),
+        );
+
+        let init_source_values = buffers.get(&resources.init_source_values).unwrap();
+        let init_source_values_bind_group = render_device.create_bind_group(
+            "ScalarInitSourceValuesBindGroup",
+            &pipeline_cache.get_bind_group_layout(&pipelines.source_values_bind_group_layout),
+            &BindGroupEntries::single(init_source_values.buffer.as_entire_buffer_binding()),
+        );
+
+        commands.entity(entity).insert(ScalarFieldBindGroups {
+            channels: channels.into_boxed_slice(),
+            source_values_bind_group,
+            init_source_values_bind_group,
+            diffusion_iterations: scalar_fields
+                .map_or(GaussSeidelConfig::default(), |s| s.diffusion_solver.clone())
+                .num_iterations,
+        });
+    }
+}
@@ -1,11 +1,17 @@
 use bevy::{prelude::*, render::extract_component::ExtractComponent};
 
 use crate::{
+    advection::AdvectionMethod,
     apply_forces::ForcesToFluid,
     fluid_source::fluid_source_uniform::{FluidSourceInitUniform, FluidSourceUniform},
     fluid_status::FluidStatus,
+    fluid_to_solid::SolidCouplingMethod,
+    particle_levelset_two_layers::LevelSetCorrection,
     projection::ProjectionMethod,
     reinitialize_levelset::ReinitializeMethod,
+    substep::FluidSubsteps,
+    velocity_transfer::VelocityTransferMethod,
+    viscosity::ViscositySolver,
 };
 
 /// Setting for fluid simulation. Spawning a FluidSettings automatically inserts the components required to the simulation and the simulation will start.
@@ -14,6 +20,17 @@ use crate::{
 /// * `size`: The size of 2D simulation domain in pixels. The size is recommended to be multiple of 64 pixels.
 /// * `rho`: The density of fluid in unit of [kg/m^2]. Currently, only uniform density is supported.
 /// * `gravity`: Uniform force enforced uniformly to the simulation domain in unit of [m/s^2].
+/// * `viscosity`: Kinematic viscosity of fluid in unit of [m^2/s]. Viscous diffusion is solved implicitly, so large values (e.g. honey, mud) are stable.
+///   Set 0.0 to simulate inviscid fluid. The number of iterations for the solver can be configured by [`ViscositySolver`].
+/// * `surface_tension`: Surface tension coefficient at the liquid-air interface in unit of [N] (force per unit length times the depth of 2D domain).
+///   It is imposed as a pressure jump at the free surface. Large values require small time steps to be stable.
+/// * `mode`: Whether the simulation has a free surface or not. See [`FluidMode`].
+/// * `cfl`: Target CFL number, i.e. the maximum distance in cells the fluid travels in a substep.
+///   Defaults to 0.0, which advances the fluid in a single step per physics step.
+///   Set a positive value (e.g. 1.0) to opt in to adaptive substepping, where a physics step is split into up to
+///   [`crate::substep::MAX_SUBSTEPS`] substeps depending on the maximum velocity of the fluid.
+///   Each substep runs the whole simulation again, so this multiplies the GPU work for fast flows.
+///   The current number of substeps can be found on [`FluidSubsteps`].
 ///
 /// To let fluids flow in or out of the domain, spawn [`crate::fluid_source::FluidSource`] as a child component.
 ///
@@ -42,6 +59,7 @@ use crate::{
 ///             rho: 99.7, // water density in 2D
 ///             gravity: Vec2::ZERO,
 ///             size,
+///             ..default()
 ///         },
 ///         Mesh2d(mesh),
 ///     ))
@@ -104,6 +122,12 @@ use crate::{
     ForcesToFluid,
     ProjectionMethod,
     ReinitializeMethod,
+    AdvectionMethod,
+    VelocityTransferMethod,
+    LevelSetCorrection,
+    FluidSubsteps,
+    ViscositySolver,
+    SolidCouplingMethod,
     FluidSourceUniform,
     FluidSourceInitUniform
 )]
@@ -111,6 +135,36 @@ pub struct FluidSettings {
     pub rho: f32,
     pub gravity: Vec2,
     pub size: UVec2,
+    pub viscosity: f32,
+    pub surface_tension: f32,
+    pub mode: FluidMode,
+    pub cfl: f32,
+}
+
+impl Default for FluidSettings {
+    fn default() -> Self {
+        Self {
+            rho: 99.7,
+            gravity: Vec2::Y * 9.8,
+            size: UVec2::splat(256),
+            viscosity: 0.0,
+            surface_tension: 0.0,
+            mode: FluidMode::default(),
+            cfl: 0.0,
+        }
+    }
+}
+
+/// Simulation mode of [`FluidSettings`]. The mode is expected to be fixed after the fluid is spawned.
+/// * `FreeSurface`: Liquid with a free surface tracked by `levelset_air`. The liquid region is defined by [`crate::fluid_source::FluidSource`].
+/// * `SinglePhase`: The whole domain is filled with a single fluid (e.g. gas), which is suitable for smoke and wind.
+///   Level set advection, reinitialization and velocity extrapolation are skipped.
+///   [`crate::fluid_source::FluidSource`]s only drive the velocity, and sinks have no effect.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FluidMode {
+    #[default]
+    FreeSurface,
+    SinglePhase,
 }
 
 #[derive(Resource, Clone, Copy)]
@@ -130,6 +184,8 @@ impl Default for FluidGridLength {
 /// * **`v_solid`**: y-component velocity of solid boundary with size [`FluidSettings::size`] + (0, 1). Format: R32Float.
 /// * **`levelset_air`**: levelset between empty air (>=0) vs fluid or solid (<0) with size [`FluidSettings::size`]. Format: R32Float.
 /// * **`levelset_solid`**: levelset between solid (<0) vs fluid or empty air (>=0) with size [`FluidSettings::size`]. Format: R32Float.
+/// * **`scalars`**: passive scalar channels configured by [`crate::scalar_field::FluidScalarFields`] with size [`FluidSettings::size`]. Empty if the component is not inserted. Format: R32Float.
+/// * **`temperature`**: temperature with size [`FluidSettings::size`] if [`crate::scalar_field::FluidTemperature`] is inserted. Format: R32Float.
 ///
 /// # Notes
 /// A staggered (MAC) grid is used for the simulation.
@@ -144,4 +200,6 @@ pub struct FluidTextures {
     pub v_solid: Handle<Image>,
     pub levelset_air: Handle<Image>,
     pub levelset_solid: Handle<Image>,
+    pub scalars: Vec<Handle<Image>>,
+    pub temperature: Option<Handle<Image>>,
 }
@@ -9,48 +9,94 @@ use bevy::{
 
 use crate::{
     advect_levelset::AdvectLevelSetResource,
-    advection::AdvectionResource,
+    advection::{
+        high_order::HighOrderAdvectionResource, AdvectionMethod, AdvectionResource,
+        InterpolationMethod,
+    },
     apply_forces::{ApplyForcesResource, ForceToFluid},
+    boundary_condition::FluidBoundaryConditions,
     divergence::DivergenceResource,
     extrapolate_velocity::{
         ExtrapolateUResource, ExtrapolateVResource, InitializeUValid, InitializeVValid,
     },
     fluid_source::update_fluid_source::UpdateFluidSourceResource,
     fluid_to_solid::{
-        forces_to_solid_readback, AccumulateForcesResource, FluidToSolidForce,
-        SampleForcesResource, MAX_SOLIDS,
+        AccumulateForcesResource, FluidToSolidForce, SampleForcesResource, SolidCouplingMethod,
     },
     fluid_uniform::SimulationUniform,
     initialize::{InitializeGridCenterResource, InitializeGridEdgeResource},
     levelset_gradient::LevelSetGradientResource,
-    obstacle::SolidEntities,
-    particle_levelset_two_layers,
-    projection::{gauss_seidel::GaussSeidelResource, multi_grid},
+    obstacle::{SolidEntities, SolidGeometryBuffers},
+    particle_levelset_two_layers::{
+        self,
+        plugin::{PLSFluidTextures, PLSResources},
+        LevelSetCorrection,
+    },
+    projection::{
+        conjugate_gradient::{self, ConjugateGradientPreconditioner, ConjugateGradientResource},
+        diagnostics::{self, ProjectionDiagnostics},
+        gauss_seidel::GaussSeidelResource,
+        multi_grid,
+        solid_coupling::{BodyVelocityResource, SolidCouplingResource},
+        PressurePoissonTextures, ProjectionMethod,
+    },
     reinitialize_levelset::{self, ReinitializeMethod},
-    settings::{FluidGridLength, FluidSettings, FluidTextures},
+    scalar_field::{self, FluidScalarFields, FluidTemperature},
+    settings::{FluidGridLength, FluidMode, FluidSettings, FluidTextures},
     solve_pressure::{JacobiIterationResource, JacobiIterationReverseResource},
     solve_velocity::{SolveUResource, SolveVResource},
+    surface_tension::CurvatureResource,
+    terrain,
     texture::NewTexture,
     update_area_fraction::UpdateAreaFractionResource,
     update_solid::UpdateSolidResource,
+    velocity_transfer::{VelocityTransferMethod, VelocityTransferResource},
+    viscosity::ViscosityResource,
+    vorticity_confinement::VorticityConfinementResource,
 };
 
+type FluidComponentsQuery = (
+    Entity,
+    &'static FluidSettings,
+    &'static ReinitializeMethod,
+    &'static ProjectionMethod,
+    &'static AdvectionMethod,
+    &'static VelocityTransferMethod,
+    &'static LevelSetCorrection,
+    &'static SolidCouplingMethod,
+    Option<&'static Transform>,
+    Option<&'static FluidScalarFields>,
+    Option<&'static FluidTemperature>,
+    Option<&'static FluidBoundaryConditions>,
+    Option<&'static ProjectionDiagnostics>,
+    Option<&'static InterpolationMethod>,
+);
+
 pub(crate) fn watch_fluid_component(
     mut commands: Commands,
-    query: Query<
-        (
-            Entity,
-            &FluidSettings,
-            &ReinitializeMethod,
-            Option<&Transform>,
-        ),
-        Added<FluidSettings>,
-    >,
+    query: Query<FluidComponentsQuery, Added<FluidSettings>>,
     mut images: ResMut<Assets<Image>>,
     mut buffers: ResMut<Assets<ShaderStorageBuffer>>,
     grid_length: Res<FluidGridLength>,
+    solid_geometry_buffers: Res<SolidGeometryBuffers>,
 ) {
-    for (entity, settings, reinit_method, transform) in &query {
+    for (
+        entity,
+        settings,
+        reinit_method,
+        projection_method,
+        advection_method,
+        velocity_transfer_method,
+        level_set_correction,
+        solid_coupling_method,
+        transform,
+        scalar_fields,
+        fluid_temperature,
+        boundary_conditions,
+        projection_diagnostics,
+        interpolation,
+    ) in &query
+    {
         let size = settings.size;
 
         if size.x % 64 != 0 || size.y % 64 != 0 {
@@ -68,6 +114,7 @@ pub(crate) fn watch_fluid_component(
         let u_solid = images.new_texture_storage(size_u, TextureFormat::R32Float);
         let v_solid = images.new_texture_storage(size_v, TextureFormat::R32Float);
         let solid_id = images.new_texture_storage(size, TextureFormat::R32Sint);
+        let solid_id_face = images.new_texture_storage(size, TextureFormat::Rg32Sint);
 
         let in_is_u_valid = images.new_texture_storage(size_u, TextureFormat::R32Sint);
         let out_is_u_valid = images.new_texture_storage(size_u, TextureFormat::R32Sint);
@@ -83,18 +130,25 @@ pub(crate) fn watch_fluid_component(
         let levelset_air1 = images.new_texture_storage(size, TextureFormat::R32Float);
         let grad_levelset_air = images.new_texture_storage(size, TextureFormat::Rg32Float);
         let levelset_solid = images.new_texture_storage(size, TextureFormat::R32Float);
+        let levelset_terrain = terrain::new_levelset_terrain(&mut images, size);
 
         let area_fraction_solid = images.new_texture_storage(size, TextureFormat::Rgba32Float);
 
+        let vorticity = images.new_texture_storage(size, TextureFormat::R32Float);
+        let curvature = images.new_texture_storage(size, TextureFormat::R32Float);
+        // Always allocated since apply_forces binds it. Buoyancy is zero without FluidTemperature.
+        let temperature = images.new_texture_storage(size, TextureFormat::R32Float);
+
         let forces_to_fluid =
             buffers.add(ShaderStorageBuffer::from(vec![ForceToFluid::default(); 0]));
 
-        let bins_force_x = buffers.add(ShaderStorageBuffer::from(vec![0u32; MAX_SOLIDS]));
-        let bins_force_y = buffers.add(ShaderStorageBuffer::from(vec![0u32; MAX_SOLIDS]));
-        let bins_torque = buffers.add(ShaderStorageBuffer::from(vec![0u32; MAX_SOLIDS]));
+        // Resized to the number of obstacles in the fluid every frame.
+        let bins_force_x = buffers.add(ShaderStorageBuffer::from(vec![0u32; 1]));
+        let bins_force_y = buffers.add(ShaderStorageBuffer::from(vec![0u32; 1]));
+        let bins_torque = buffers.add(ShaderStorageBuffer::from(vec![0u32; 1]));
 
         let mut forces_to_solid_buffer =
-            ShaderStorageBuffer::from(vec![FluidToSolidForce::default(); MAX_SOLIDS]);
+            ShaderStorageBuffer::from(vec![FluidToSolidForce::default(); 1]);
         forces_to_solid_buffer.buffer_description.usage |= BufferUsages::COPY_SRC;
         let forces_to_solid_buffer = buffers.add(forces_to_solid_buffer);
 
@@ -103,23 +157,48 @@ pub(crate) fn watch_fluid_component(
             None => Mat4::IDENTITY,
         };
 
+        let boundary_conditions = boundary_conditions.copied().unwrap_or_default();
+        let (inflow_velocity_x, inflow_velocity_y) = boundary_conditions.inflow_velocities();
         let uniform = SimulationUniform {
             dx: grid_length.0,
             dt: 0.0,
             rho: settings.rho,
+            viscosity: settings.viscosity,
+            surface_tension: settings.surface_tension,
+            vorticity_confinement: 0.0,
+            ambient_temperature: fluid_temperature.map_or(0.0, |t| t.ambient),
+            thermal_expansion: fluid_temperature.map_or(0.0, |t| t.expansion_coefficient),
             gravity: settings.gravity,
             fluid_transform,
             size: size.as_vec2(),
+            boundary_types: boundary_conditions.boundary_types(),
+            wall_slip: boundary_conditions.wall_slip(),
+            inflow_velocity_x,
+            inflow_velocity_y,
+            interpolation: interpolation.copied().unwrap_or_default().as_u32(),
+            velocity_transfer: velocity_transfer_method.as_u32(),
+            flip_ratio: velocity_transfer_method.flip_ratio(),
         };
 
-        let fluid_textures = FluidTextures {
+        let mut fluid_textures = FluidTextures {
             u: u0.clone(),
             v: v0.clone(),
             u_solid: u_solid.clone(),
             v_solid: v_solid.clone(),
             levelset_air: levelset_air0.clone(),
             levelset_solid: levelset_solid.clone(),
+            scalars: Vec::new(),
+            temperature: fluid_temperature.map(|_| temperature.clone()),
         };
+        scalar_field::setup(
+            &mut commands,
+            entity,
+            &mut images,
+            &mut buffers,
+            size,
+            scalar_fields,
+            &mut fluid_textures,
+        );
 
         let initialize_resource = InitializeGridEdgeResource {
             u0: u0.clone(),
@@ -132,6 +211,7 @@ pub(crate) fn watch_fluid_component(
             levelset_air0: levelset_air0.clone(),
             levelset_air1: levelset_air1.clone(),
             grad_levelset_air: grad_levelset_air.clone(),
+            single_phase: (settings.mode == FluidMode::SinglePhase) as u32,
         };
 
         let update_solid_resource = UpdateSolidResource {
@@ -139,6 +219,8 @@ pub(crate) fn watch_fluid_component(
             v_solid: v_solid.clone(),
             levelset_solid: levelset_solid.clone(),
             solid_id: solid_id.clone(),
+            levelset_terrain,
+            solid_id_face: solid_id_face.clone(),
         };
 
         let update_area_fraction_resource =
@@ -157,6 +239,26 @@ pub(crate) fn watch_fluid_component(
             levelset_air0: levelset_air0.clone(),
             forces_to_fluid: forces_to_fluid.clone(),
             area_fraction_solid: area_fraction_solid.clone(),
+            temperature,
+        };
+
+        let vorticity_confinement_resource = VorticityConfinementResource {
+            u1: u1.clone(),
+            v1: v1.clone(),
+            levelset_air0: levelset_air0.clone(),
+            area_fraction_solid: area_fraction_solid.clone(),
+            vorticity,
+        };
+
+        let viscosity_resource = ViscosityResource {
+            u0: u0.clone(),
+            v0: v0.clone(),
+            u1: u1.clone(),
+            v1: v1.clone(),
+            u_solid: u_solid.clone(),
+            v_solid: v_solid.clone(),
+            levelset_air0: levelset_air0.clone(),
+            area_fraction_solid: area_fraction_solid.clone(),
         };
 
         let divergence_resource = DivergenceResource {
@@ -164,7 +266,7 @@ pub(crate) fn watch_fluid_component(
             v1: v1.clone(),
             u_solid: u_solid.clone(),
             v_solid: v_solid.clone(),
-            levelset_solid: levelset_solid.clone(),
+            area_fraction_solid: area_fraction_solid.clone(),
             div: div.clone(),
         };
 
@@ -174,6 +276,7 @@ pub(crate) fn watch_fluid_component(
             div: div.clone(),
             levelset_air0: levelset_air0.clone(),
             levelset_solid: levelset_solid.clone(),
+            curvature: curvature.clone(),
         };
 
         let jacobi_iter_rev_resource = JacobiIterationReverseResource {
@@ -182,10 +285,13 @@ pub(crate) fn watch_fluid_component(
             div: div.clone(),
             levelset_air0: levelset_air0.clone(),
             levelset_solid: levelset_solid.clone(),
+            curvature: curvature.clone(),
         };
 
         let gauss_seidel_resource =
-            GaussSeidelResource::new(&p0, &div, &levelset_air0, &area_fraction_solid);
+            GaussSeidelResource::new(&p0, &div, &levelset_air0, &area_fraction_solid, &curvature);
+
+        let curvature_resource = CurvatureResource::new(&levelset_air0, &curvature);
 
         let solve_u_resource = SolveUResource {
             u0: u0.clone(),
@@ -194,6 +300,7 @@ pub(crate) fn watch_fluid_component(
             p0: p0.clone(),
             levelset_air0: levelset_air0.clone(),
             area_fraction_solid: area_fraction_solid.clone(),
+            curvature: curvature.clone(),
         };
 
         let solve_v_resource = SolveVResource {
@@ -203,6 +310,7 @@ pub(crate) fn watch_fluid_component(
             p0: p0.clone(),
             levelset_air0: levelset_air0.clone(),
             area_fraction_solid: area_fraction_solid.clone(),
+            curvature: curvature.clone(),
         };
 
         let init_u_valid = InitializeUValid {
@@ -243,6 +351,9 @@ pub(crate) fn watch_fluid_component(
             levelset_solid: levelset_solid.clone(),
             solid_id: solid_id.clone(),
             p0: p0.clone(),
+            u0: u0.clone(),
+            v0: v0.clone(),
+            levelset_air0: levelset_air0.clone(),
         };
 
         let accumulate_forces_resource = AccumulateForcesResource {
@@ -250,13 +361,16 @@ pub(crate) fn watch_fluid_component(
             bins_force_y: bins_force_y.clone(),
             bins_torque: bins_torque.clone(),
             forces: forces_to_solid_buffer.clone(),
+            num_solids: 1,
         };
 
         let solid_entites = SolidEntities {
             entities: Vec::new(),
         };
+        let solid_obstacles_buffer = solid_geometry_buffers.new_obstacles_buffer(&mut buffers);
 
-        let update_fluid_source = UpdateFluidSourceResource::new(&levelset_air1, &u0, &v0);
+        let update_fluid_source =
+            UpdateFluidSourceResource::new(&levelset_air1, &u0, &v0, settings.mode);
 
         commands
             .entity(entity)
@@ -287,11 +401,13 @@ pub(crate) fn watch_fluid_component(
                 init_v_valid,
                 extrapolate_u_resource,
                 extrapolate_v_resource,
+                viscosity_resource,
+                vorticity_confinement_resource,
+                curvature_resource,
             ))
             .insert(uniform)
-            .insert(solid_entites)
-            .insert(Readback::buffer(forces_to_solid_buffer.clone()))
-            .observe(forces_to_solid_readback);
+            .insert((solid_entites, solid_obstacles_buffer))
+            .insert(Readback::buffer(forces_to_solid_buffer.clone()));
 
         reinitialize_levelset::setup(
             &mut commands,
@@ -303,28 +419,132 @@ pub(crate) fn watch_fluid_component(
             reinit_method,
         );
 
-        particle_levelset_two_layers::plugin::setup(
-            &mut commands,
-            entity,
-            &mut images,
-            &mut buffers,
-            settings.size,
-            &u0,
-            &v0,
-            &levelset_air0,
-            &levelset_air1,
-            &grad_levelset_air,
-        );
+        if let LevelSetCorrection::ParticleLevelSet { particles_per_cell } = level_set_correction {
+            if settings.mode == FluidMode::FreeSurface {
+                let pls_resources = PLSResources::new(
+                    &mut images,
+                    &mut buffers,
+                    settings.size,
+                    *particles_per_cell,
+                );
+                particle_levelset_two_layers::plugin::setup(
+                    &mut commands,
+                    entity,
+                    pls_resources,
+                    settings.size,
+                    PLSFluidTextures {
+                        u0: &u0,
+                        v0: &v0,
+                        levelset_air0: &levelset_air0,
+                        levelset_air1: &levelset_air1,
+                        grad_levelset_air: &grad_levelset_air,
+                    },
+                );
+            }
+        }
+
+        let poisson_textures = PressurePoissonTextures {
+            p: p0.clone(),
+            div: div.clone(),
+            levelset_air: levelset_air0.clone(),
+            area_fraction_solid: area_fraction_solid.clone(),
+            curvature: curvature.clone(),
+        };
 
         multi_grid::setup_multigrid_resources(
             &mut commands,
             entity,
             settings.size,
-            &div,
-            &p0,
-            &levelset_air0,
-            &area_fraction_solid,
+            &poisson_textures,
             &mut images,
         );
+
+        if !matches!(advection_method, AdvectionMethod::SemiLagrangian) {
+            let mut new_textures =
+                |size| [0, 1].map(|_| images.new_texture_storage(size, TextureFormat::R32Float));
+            commands
+                .entity(entity)
+                .insert(HighOrderAdvectionResource::new(
+                    [&u0, &u1],
+                    [&v0, &v1],
+                    [&levelset_air0, &levelset_air1],
+                    new_textures(size_u),
+                    new_textures(size_v),
+                    new_textures(size),
+                ));
+        }
+
+        if *velocity_transfer_method != VelocityTransferMethod::Grid {
+            commands
+                .entity(entity)
+                .insert(VelocityTransferResource::new(
+                    &mut images,
+                    &mut buffers,
+                    size,
+                    [&u0, &u1],
+                    [&v0, &v1],
+                    &levelset_air0,
+                    &levelset_solid,
+                ));
+        }
+
+        if let Some(projection_diagnostics) = projection_diagnostics {
+            diagnostics::setup(
+                &mut commands,
+                entity,
+                &mut buffers,
+                size,
+                projection_diagnostics,
+                &poisson_textures,
+            );
+        }
+
+        if let ProjectionMethod::ConjugateGradient(config) = projection_method {
+            let r = images.new_texture_storage(size, TextureFormat::R32Float);
+            let z = images.new_texture_storage(size, TextureFormat::R32Float);
+            let [d, q, partial_sums, state, indirect_args] =
+                conjugate_gradient::new_buffers(&mut buffers, size);
+
+            if let ConjugateGradientPreconditioner::MultiGrid(_) = config.preconditioner {
+                multi_grid::setup_multigrid_preconditioner_resources(
+                    &mut commands,
+                    entity,
+                    size,
+                    &PressurePoissonTextures {
+                        p: z.clone(),
+                        div: r.clone(),
+                        ..poisson_textures.clone()
+                    },
+                    &mut images,
+                );
+            }
+
+            commands.entity(entity).insert(ConjugateGradientResource {
+                p: p0.clone(),
+                div: div.clone(),
+                levelset_air: levelset_air0.clone(),
+                area_fraction_solid: area_fraction_solid.clone(),
+                curvature: curvature.clone(),
+                r,
+                z,
+                d,
+                q,
+                partial_sums,
+                state,
+                tolerance: config.tolerance,
+                indirect_args,
+            });
+
+            if *solid_coupling_method == SolidCouplingMethod::Monolithic {
+                let solid_coupling_resource =
+                    SolidCouplingResource::new(&mut buffers, &solid_id_face);
+                commands.entity(entity).insert((
+                    BodyVelocityResource::new(&solid_coupling_resource, &u_solid, &v_solid),
+                    solid_coupling_resource,
+                ));
+            }
+        } else if *solid_coupling_method == SolidCouplingMethod::Monolithic {
+            warn!("SolidCouplingMethod::Monolithic requires ProjectionMethod::ConjugateGradient. Falling back to AreaFraction.");
+        }
     }
 }
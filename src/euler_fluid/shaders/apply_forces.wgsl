@@ -11,6 +11,7 @@ struct Force {
 @group(0) @binding(2) var levelset_air0: texture_storage_2d<r32float, read>;
 @group(0) @binding(3) var<storage, read> forces: array<Force>;
 @group(0) @binding(4) var area_fraction_solid: texture_storage_2d<rgba32float, read>;
+@group(0) @binding(5) var temperature: texture_storage_2d<r32float, read>;
 
 @group(1) @binding(0) var<uniform> constants: SimulationUniform;
 
@@ -27,7 +28,7 @@ fn apply_forces_u(
         return;
     }
 
-    var net_force = constants.gravity.x;
+    var net_force = constants.gravity.x + buoyancy(idx - vec2<i32>(1, 0), idx).x;
     var n = arrayLength(&forces);
     loop {
         if (n == 0) {
@@ -55,7 +56,7 @@ fn apply_forces_v(
         return;
     }
 
-    var net_force = constants.gravity.y;
+    var net_force = constants.gravity.y + buoyancy(idx - vec2<i32>(0, 1), idx).y;
     var n = arrayLength(&forces);
     loop {
         if (n == 0) {
@@ -74,3 +75,16 @@ fn gaussian_2d(x: vec2<f32>, x0: vec2<f32>, sigma: f32) -> f32 {
     let b = -1.0 / (2.0 * sigma * sigma);
     return exp(b * dot(x - x0, x - x0));
 }
+
+// Boussinesq buoyancy force at the face between idx0 and idx1.
+fn buoyancy(idx0: vec2<i32>, idx1: vec2<i32>) -> vec2<f32> {
+    if constants.thermal_expansion == 0.0 {
+        return vec2<f32>(0.0);
+    }
+    let dim = vec2<i32>(textureDimensions(temperature));
+    let t0 = textureLoad(temperature, clamp(idx0, vec2<i32>(0), dim - 1)).r;
+    let t1 = textureLoad(temperature, clamp(idx1, vec2<i32>(0), dim - 1)).r;
+    let t = 0.5 * (t0 + t1);
+
+    return -constants.thermal_expansion * (t - constants.ambient_temperature) * constants.gravity;
+}
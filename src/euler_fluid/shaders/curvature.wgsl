@@ -0,0 +1,49 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::boundary_condition::wrap_periodic;
+
+@group(0) @binding(0) var levelset_air: texture_storage_2d<r32float, read>;
+@group(0) @binding(1) var curvature: texture_storage_2d<r32float, write>;
+
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
+@compute @workgroup_size(8, 8, 1)
+fn calculate_curvature(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    let dim = vec2<i32>(textureDimensions(levelset_air));
+    if any(idx >= dim) {
+        return;
+    }
+
+    let level = textureLoad(levelset_air, idx).r;
+    let level_iminusj = level_at(idx + vec2<i32>(-1, 0));
+    let level_iplusj = level_at(idx + vec2<i32>(1, 0));
+    let level_ijminus = level_at(idx + vec2<i32>(0, -1));
+    let level_ijplus = level_at(idx + vec2<i32>(0, 1));
+
+    let phi_x = 0.5 * (level_iplusj - level_iminusj);
+    let phi_y = 0.5 * (level_ijplus - level_ijminus);
+    let phi_xx = level_iplusj - 2.0 * level + level_iminusj;
+    let phi_yy = level_ijplus - 2.0 * level + level_ijminus;
+    let phi_xy = 0.25 * (level_at(idx + vec2<i32>(1, 1))
+        - level_at(idx + vec2<i32>(1, -1))
+        - level_at(idx + vec2<i32>(-1, 1))
+        + level_at(idx + vec2<i32>(-1, -1)));
+
+    let grad_norm = sqrt(phi_x * phi_x + phi_y * phi_y);
+    var kappa = 0.0;
+    if (grad_norm > 1.0e-6) {
+        kappa = (phi_xx * phi_y * phi_y - 2.0 * phi_x * phi_y * phi_xy + phi_yy * phi_x * phi_x)
+            / (grad_norm * grad_norm * grad_norm);
+    }
+    // Curvature cannot be resolved finer than the grid.
+    kappa = clamp(kappa, -1.0, 1.0);
+
+    textureStore(curvature, idx, vec4<f32>(kappa, 0.0, 0.0, 0.0));
+}
+
+fn level_at(idx: vec2<i32>) -> f32 {
+    let dim = vec2<i32>(textureDimensions(levelset_air));
+    return textureLoad(levelset_air, clamp(wrap_periodic(constants, idx, dim), vec2<i32>(0), dim - 1)).r;
+}
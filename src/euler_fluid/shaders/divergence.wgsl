@@ -1,5 +1,5 @@
 #import bevy_fluid::coordinate::{left, right, bottom, top};
-#import bevy_fluid::area_fraction::area_fractions;
+#import bevy_fluid::area_fraction::AreaFractions;
 #import bevy_fluid::fluid_uniform::SimulationUniform;
 
 // The number of texture_storage binding for WebGPU is limited to 8.
@@ -8,7 +8,7 @@
 @group(0) @binding(1) var v1: texture_storage_2d<r32float, read>;
 @group(0) @binding(2) var u_solid: texture_storage_2d<r32float, read>;
 @group(0) @binding(3) var v_solid: texture_storage_2d<r32float, read>;
-@group(0) @binding(4) var levelset_solid: texture_storage_2d<r32float, read>;
+@group(0) @binding(4) var area_fraction_solid: texture_storage_2d<rgba32float, read>;
 @group(0) @binding(5) var div: texture_storage_2d<r32float, write>;
 
 @group(1) @binding(0) var<uniform> constants: SimulationUniform;
@@ -16,10 +16,13 @@
 @compute @workgroup_size(8, 8, 1)
 fn divergence(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
     let idx = vec2<i32>(invocation_id.xy);
-    let f = area_fractions(levelset_solid, idx);
+    // Solid area fractions of (iminusj, iplusj, ijminus, ijplus) faces, including the domain boundary.
+    let area_fraction = textureLoad(area_fraction_solid, idx);
+    let f = AreaFractions(area_fraction.x, area_fraction.y, area_fraction.z, area_fraction.w);
 
-    let ijplus = top(idx);
-    let iplusj = right(idx);
+    // Faces on the +x and +y sides always exist in the staggered grid, so no wrapping is needed.
+    let ijplus = top(idx, vec2<i32>(0));
+    let iplusj = right(idx, vec2<i32>(0));
 
     let u_ij = textureLoad(u1, idx).r;
     let u_iplusj = textureLoad(u1, iplusj).r;
@@ -1,13 +1,19 @@
+#import bevy_fluid::boundary_condition::wrap_periodic;
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+
 @group(0) @binding(0) var u0: texture_storage_2d<r32float, read_write>;
 @group(0) @binding(1) var in_is_u_valid: texture_storage_2d<r32sint, read>;
 @group(0) @binding(2) var out_is_u_valid: texture_storage_2d<r32sint, write>;
 
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
 @compute @workgroup_size(1, 64, 1)
 fn extrapolate_u(
     @builtin(global_invocation_id) invocation_id: vec3<u32>,
 ) {
     let idx = vec2<i32>(invocation_id.xy);
     let dim = vec2<i32>(textureDimensions(in_is_u_valid));
+    let grid_size = vec2<i32>(constants.size);
     let is_valid = textureLoad(in_is_u_valid, idx).r;
     if (is_valid == 1) {
         textureStore(out_is_u_valid, idx, vec4<i32>(1, 0, 0, 0));
@@ -15,7 +21,7 @@ fn extrapolate_u(
         var count = 0;
         var new_u = 0.0;
 
-        let right = idx + vec2<i32>(1, 0);
+        let right = wrap_periodic(constants, idx + vec2<i32>(1, 0), grid_size);
         if all(right < dim) {
             let is_valid_right = textureLoad(in_is_u_valid, right).r;
             if is_valid_right == 1 {
@@ -25,7 +31,7 @@ fn extrapolate_u(
 
         }
         
-        let left = idx + vec2<i32>(-1, 0);
+        let left = wrap_periodic(constants, idx + vec2<i32>(-1, 0), grid_size);
         if all(left > vec2<i32>(0)) {
             let is_valid_left = textureLoad(in_is_u_valid, left).r;
             if is_valid_left == 1 {
@@ -34,7 +40,7 @@ fn extrapolate_u(
             }
         }
         
-        let top = idx + vec2<i32>(0, 1);
+        let top = wrap_periodic(constants, idx + vec2<i32>(0, 1), grid_size);
         if all(top < dim) {
             let is_valid_top = textureLoad(in_is_u_valid, top).r;
             if is_valid_top == 1 {
@@ -43,7 +49,7 @@ fn extrapolate_u(
             }
         }
         
-        let bottom = idx + vec2<i32>(0, -1);
+        let bottom = wrap_periodic(constants, idx + vec2<i32>(0, -1), grid_size);
         if all(bottom > vec2<i32>(0)) {
             let is_valid_bottom = textureLoad(in_is_u_valid, bottom).r;
             if is_valid_bottom == 1 {
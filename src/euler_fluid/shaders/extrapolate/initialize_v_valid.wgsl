@@ -1,8 +1,12 @@
 #import bevy_fluid::area_fraction::area_fraction;
+#import bevy_fluid::boundary_condition::{BOUNDARY_INFLOW, EDGE_NONE, EDGE_Y_MINUS, EDGE_Y_PLUS, boundary_type};
+#import bevy_fluid::fluid_uniform::SimulationUniform;
 
 @group(0) @binding(0) var is_v_valid: texture_storage_2d<r32sint, write>;
 @group(0) @binding(1) var levelset_air: texture_storage_2d<r32float, read>;
 
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
 @compute @workgroup_size(64, 1, 1)
 fn initialize_v_valid(
     @builtin(global_invocation_id) invocation_id: vec3<u32>,
@@ -22,9 +26,20 @@ fn initialize_v_valid(
     let level_air_vertex_plus = 0.25 * (level_centers[2] + level_centers[3] + level_centers[4] + level_centers[5]);
     let area_fraction = area_fraction(level_air_vertex_minus, level_air_vertex_plus);
 
-    if area_fraction > 0.0 {
+    // Velocities on the inflow boundary are prescribed and never overwritten.
+    if area_fraction > 0.0 && !is_inflow_face(idx) {
         textureStore(is_v_valid, idx, vec4<i32>(0, 0, 0, 0));
     } else {
         textureStore(is_v_valid, idx, vec4<i32>(1, 0, 0, 0));
     }
-}
\ No newline at end of file
+}
+
+fn is_inflow_face(idx: vec2<i32>) -> bool {
+    var edge = EDGE_NONE;
+    if idx.y == 0 {
+        edge = EDGE_Y_MINUS;
+    } else if idx.y == i32(constants.size.y) {
+        edge = EDGE_Y_PLUS;
+    }
+    return edge != EDGE_NONE && boundary_type(constants, edge) == BOUNDARY_INFLOW;
+}
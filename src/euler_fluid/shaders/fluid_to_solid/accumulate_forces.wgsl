@@ -1,6 +1,5 @@
 #import bevy_fluid::fluid_to_solid::fixed_point_conversion::{i32_to_f32};
-
-const MAX_SOLIDS: u32 = 256;
+#import bevy_fluid::fluid_uniform::SimulationUniform;
 
 struct Force {
     force: vec2<f32>,
@@ -13,6 +12,11 @@ struct Force {
 @group(0) @binding(2) var<storage, read_write> bins_torque: array<atomic<i32>>;
 @group(0) @binding(3) var<storage, read_write> forces: array<Force>;
 
+// Bound only to accumulate_coupled_forces. J^T p of the monolithic coupling as bits of f32.
+@group(1) @binding(4) var<storage, read> body_forces: array<u32>;
+
+@group(2) @binding(0) var<uniform> simulation_uniform: SimulationUniform;
+
 @compute @workgroup_size(1, 1, 1)
 fn accumulate_forces(
     @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
@@ -30,3 +34,28 @@ fn accumulate_forces(
     let torque = i32_to_f32(atomicLoad(&bins_torque[idx]));
     forces[idx] = Force(force, torque);
 }
+
+// Same as accumulate_forces, with the pressure forces by which the bodies were updated in the monolithic coupling.
+@compute @workgroup_size(1, 1, 1)
+fn accumulate_coupled_forces(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = global_invocation_id.x;
+    if (arrayLength(&forces) <= idx) {
+        return;
+    }
+
+    var force = vec2<f32>(
+        i32_to_f32(atomicLoad(&bins_force_x[idx])),
+        i32_to_f32(atomicLoad(&bins_force_y[idx]))
+    );
+    var torque = i32_to_f32(atomicLoad(&bins_torque[idx]));
+    let i = 3u * idx;
+    if (i + 2u < arrayLength(&body_forces)) {
+        // J omits the length of the faces dx, and its torque has the lever arm in meters instead of world units.
+        let scale = length(simulation_uniform.fluid_transform[0].xy);
+        force += vec2<f32>(bitcast<f32>(body_forces[i]), bitcast<f32>(body_forces[i + 1u])) * simulation_uniform.dx;
+        torque += bitcast<f32>(body_forces[i + 2u]) * scale;
+    }
+    forces[idx] = Force(force, torque);
+}
@@ -1,9 +1,7 @@
 #import bevy_fluid::area_fraction::area_fractions;
 #import bevy_fluid::fluid_to_solid::fixed_point_conversion::{f32_to_i32};
 #import bevy_fluid::fluid_uniform::SimulationUniform;
-#import bevy_fluid::solid_obstacle::{SolidObstacle, center_of_mass};
-
-const MAX_SOLIDS: u32 = 256;
+#import bevy_fluid::solid_obstacle::{SolidObstacle, center_of_mass, velocity_at};
 
 @group(0) @binding(0) var<storage, read_write> bins_force_x: array<atomic<i32>>;
 @group(0) @binding(1) var<storage, read_write> bins_force_y: array<atomic<i32>>;
@@ -11,17 +9,40 @@ const MAX_SOLIDS: u32 = 256;
 @group(0) @binding(3) var levelset_solid: texture_storage_2d<r32float, read>;
 @group(0) @binding(4) var solid_id: texture_storage_2d<r32sint, read>;
 @group(0) @binding(5) var p0: texture_storage_2d<r32float, read>;
+@group(0) @binding(6) var u0: texture_storage_2d<r32float, read>;
+@group(0) @binding(7) var v0: texture_storage_2d<r32float, read>;
+
+@group(0) @binding(8) var levelset_air0: texture_storage_2d<r32float, read>;
 
 @group(1) @binding(0) var<storage, read> obstacles: array<SolidObstacle>;
+@group(1) @binding(3) var<storage, read> boundary_points: array<BoundaryQuadraturePoint>;
 
 @group(2) @binding(0) var<uniform> simulation_uniform: SimulationUniform;
 
-// @compute @workgroup_size(MAX_SOLIDS, 8, 1)
+// Point on the boundary of an obstacle, in the local space of the fluid.
+struct BoundaryQuadraturePoint {
+    position: vec2<f32>,
+    normal: vec2<f32>,
+    weight: f32,
+    solid_id: u32,
+}
+
 @compute @workgroup_size(8, 8, 1)
 fn sample_forces_to_solid(
     @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
 ) {
-    let idx = vec2<i32>(global_invocation_id.xy);
+    sample_cell_forces(vec2<i32>(global_invocation_id.xy), true);
+}
+
+// Pressure is integrated along the boundary by sample_boundary_forces_to_solid instead.
+@compute @workgroup_size(8, 8, 1)
+fn sample_shear_to_solid(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    sample_cell_forces(vec2<i32>(global_invocation_id.xy), false);
+}
+
+fn sample_cell_forces(idx: vec2<i32>, include_pressure: bool) {
 
     let f = area_fractions(levelset_solid, idx);
     if (f.iminusj == 0.0 && f.iplusj == 0.0 && f.ijminus == 0.0 && f.ijplus == 0.0) {
@@ -30,12 +51,17 @@ fn sample_forces_to_solid(
     }
 
     let solid_id = textureLoad(solid_id, idx).r;
-    if (solid_id == -1) {
+    // The bins are resized with the obstacles, and may not be uploaded yet.
+    if (solid_id == -1 || u32(solid_id) >= arrayLength(&bins_force_x)) {
         return;
     }
 
-    let p = textureLoad(p0, idx).r;
-    let force = -vec2<f32>(f.iplusj - f.iminusj, f.ijplus - f.ijminus) * p * simulation_uniform.dx;
+    let grad_f = vec2<f32>(f.iplusj - f.iminusj, f.ijplus - f.ijminus);
+    var force = vec2<f32>(0.0);
+    if (include_pressure) {
+        let p = textureLoad(p0, idx).r;
+        force = -grad_f * p * simulation_uniform.dx;
+    }
 
     let obstacle = obstacles[solid_id];
     let center = center_of_mass(obstacle);
@@ -44,6 +70,8 @@ fn sample_forces_to_solid(
     let xy = vec2<f32>(uv.x - 0.5, -uv.y + 0.5) * simulation_uniform.size;
     let x = (simulation_uniform.fluid_transform * vec4<f32>(xy, 0.0, 1.0)).xy;
 
+    force += shear_force(obstacle, idx, x, grad_f);
+
     let r = x - center;
     let torque = r.x * force.y - r.y * force.x;
 
@@ -51,3 +79,123 @@ fn sample_forces_to_solid(
     atomicAdd(&bins_force_y[solid_id], f32_to_i32(force.y));
     atomicAdd(&bins_torque[solid_id], f32_to_i32(torque));
 }
+
+// Viscous shear on the boundary of the obstacle in the cell. The velocity gradient is estimated from
+// the velocity relative to the obstacle at the neighboring fluid cell, assuming it changes linearly from the boundary.
+// `grad_f` points from the solid to the fluid, and its length is the boundary length in units of cells.
+fn shear_force(obstacle: SolidObstacle, idx: vec2<i32>, x: vec2<f32>, grad_f: vec2<f32>) -> vec2<f32> {
+    let boundary_length = length(grad_f) * simulation_uniform.dx;
+    if (boundary_length == 0.0) {
+        return vec2<f32>(0.0);
+    }
+    let n = grad_f / length(grad_f);
+
+    let dim = vec2<i32>(textureDimensions(levelset_solid));
+    let idx_fluid = clamp(idx + vec2<i32>(round(n)), vec2<i32>(0), dim - vec2<i32>(1));
+    let u = 0.5 * (textureLoad(u0, idx_fluid).r + textureLoad(u0, idx_fluid + vec2<i32>(1, 0)).r);
+    let v = 0.5 * (textureLoad(v0, idx_fluid).r + textureLoad(v0, idx_fluid + vec2<i32>(0, 1)).r);
+    let velocity_obstacle = velocity_at(obstacle, x);
+    // The grid has y-axis pointing down.
+    let relative_velocity = vec2<f32>(u, v) - vec2<f32>(velocity_obstacle.x, -velocity_obstacle.y);
+    let tangential_velocity = relative_velocity - dot(relative_velocity, n) * n;
+
+    // The level set is in units of cells. At least half a cell, to avoid blowing up next to the boundary.
+    let distance = max(textureLoad(levelset_solid, idx_fluid).r, 0.5) * simulation_uniform.dx;
+    let mu = simulation_uniform.rho * simulation_uniform.viscosity;
+    return obstacle.drag_coefficient * mu * tangential_velocity / distance * boundary_length;
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn sample_boundary_forces_to_solid(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let i = global_invocation_id.x;
+    if (i >= arrayLength(&boundary_points)) {
+        return;
+    }
+
+    let point = boundary_points[i];
+    if (point.weight == 0.0 || point.solid_id >= arrayLength(&bins_force_x)) {
+        return;
+    }
+    let half_size = 0.5 * simulation_uniform.size;
+    if (any(abs(point.position) > half_size)) {
+        // Outside of the domain
+        return;
+    }
+
+    let p = boundary_pressure(point);
+    // The grid has y-axis pointing down.
+    let n = vec2<f32>(point.normal.x, -point.normal.y);
+    let force = -p * n * point.weight * simulation_uniform.dx;
+
+    let obstacle = obstacles[point.solid_id];
+    let center = center_of_mass(obstacle);
+    let x = (simulation_uniform.fluid_transform * vec4<f32>(point.position, 0.0, 1.0)).xy;
+    let r = x - center;
+    let torque = r.x * force.y - r.y * force.x;
+
+    atomicAdd(&bins_force_x[point.solid_id], f32_to_i32(force.x));
+    atomicAdd(&bins_force_y[point.solid_id], f32_to_i32(force.y));
+    atomicAdd(&bins_torque[point.solid_id], f32_to_i32(torque));
+}
+
+// Pressure on the boundary, linearly extrapolated from the fluid along the normal.
+// The samples are taken far enough from the boundary for their stencils not to reach into the obstacle.
+fn boundary_pressure(point: BoundaryQuadraturePoint) -> f32 {
+    if (sample_pressure(point.position).y > 0.0) {
+        // In the air
+        return 0.0;
+    }
+    let p1 = sample_pressure(point.position + 1.5 * point.normal).x;
+    let p2 = sample_pressure(point.position + 2.5 * point.normal).x;
+    return 2.5 * p1 - 1.5 * p2;
+}
+
+// Bilinear interpolation of the pressure and the level set of air at a position in the local space of the fluid.
+// The pressure of air cells is replaced by the ghost pressure, which vanishes at the free surface.
+fn sample_pressure(position: vec2<f32>) -> vec2<f32> {
+    let size = vec2<i32>(textureDimensions(p0));
+    let half_size = 0.5 * vec2<f32>(size);
+    let x = clamp(
+        vec2<f32>(position.x + half_size.x, half_size.y - position.y),
+        vec2<f32>(0.0),
+        vec2<f32>(size - vec2<i32>(1)),
+    );
+    let i = min(vec2<i32>(floor(x)), size - vec2<i32>(2));
+    let t = x - vec2<f32>(i);
+
+    var offsets = array<vec2<i32>, 4>(
+        vec2<i32>(0, 0),
+        vec2<i32>(1, 0),
+        vec2<i32>(0, 1),
+        vec2<i32>(1, 1),
+    );
+    var p: array<f32, 4>;
+    var levelset_air: array<f32, 4>;
+    for (var k = 0u; k < 4u; k++) {
+        p[k] = textureLoad(p0, i + offsets[k]).r;
+        levelset_air[k] = textureLoad(levelset_air0, i + offsets[k]).r;
+    }
+
+    var p_ghost = p;
+    for (var k = 0u; k < 4u; k++) {
+        if (levelset_air[k] > 0.0) {
+            p_ghost[k] = 0.0;
+            // Vertical neighbor first, then horizontal.
+            for (var j = 0u; j < 2u; j++) {
+                let l = k ^ (2u >> j);
+                if (levelset_air[l] < 0.0) {
+                    p_ghost[k] = p[l] * levelset_air[k] / levelset_air[l];
+                    break;
+                }
+            }
+        }
+    }
+
+    return vec2<f32>(bilinear(p_ghost, t), bilinear(levelset_air, t));
+}
+
+fn bilinear(values: array<f32, 4>, t: vec2<f32>) -> f32 {
+    return mix(mix(values[0], values[1], t.x), mix(values[2], values[3], t.x), t.y);
+}
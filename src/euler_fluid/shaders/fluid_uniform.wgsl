@@ -4,7 +4,19 @@ struct SimulationUniform {
     dx: f32,
     dt: f32,
     rho: f32,
+    viscosity: f32,
+    surface_tension: f32,
+    vorticity_confinement: f32,
+    ambient_temperature: f32,
+    thermal_expansion: f32,
     gravity: vec2<f32>,
     fluid_transform: mat4x4<f32>,
     size: vec2<f32>,
+    boundary_types: vec4<u32>,
+    wall_slip: vec4<f32>,
+    inflow_velocity_x: vec4<f32>,
+    inflow_velocity_y: vec4<f32>,
+    interpolation: u32,
+    velocity_transfer: u32,
+    flip_ratio: f32,
 }
\ No newline at end of file
@@ -0,0 +1,18 @@
+@group(0) @binding(0) var mask: texture_2d<f32>;
+@group(0) @binding(1) var levelset_binary: texture_storage_2d<r32float, write>;
+
+@compute @workgroup_size(8, 8, 1)
+fn initialize_solid_mask(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let x = vec2<i32>(global_id.xy);
+    let dim = vec2<i32>(textureDimensions(mask));
+    if (any(x >= dim)) {
+        return;
+    }
+
+    // Negative inside the solid, as the input of jump flooding.
+    var level = 1.0;
+    if (textureLoad(mask, x, 0).r > 0.5) {
+        level = -1.0;
+    }
+    textureStore(levelset_binary, x, vec4<f32>(level, 0.0, 0.0, 0.0));
+}
@@ -1,6 +1,8 @@
 #import bevy_fluid::fluid_uniform::SimulationUniform;
 #import bevy_fluid::coordinate::{left, right, bottom, top};
-#import bevy_fluid::area_fraction::area_fractions;
+#import bevy_fluid::area_fraction::{AreaFractions, area_fractions};
+#import bevy_fluid::levelset_utils::{interface_curvature, interface_pressure_jump};
+#import bevy_fluid::boundary_condition::{boundary_area_fractions, grid_period, is_open_outside};
 
 #ifdef REVERSE
 @group(0) @binding(0) var p0: texture_storage_2d<r32float, write>;
@@ -12,6 +14,7 @@
 @group(0) @binding(2) var div: texture_storage_2d<r32float, read>;
 @group(0) @binding(3) var levelset_air0: texture_storage_2d<r32float, read>;
 @group(0) @binding(4) var levelset_solid: texture_storage_2d<r32float, read>;
+@group(0) @binding(5) var curvature: texture_storage_2d<r32float, read>;
 
 @group(1) @binding(0) var<uniform> constants: SimulationUniform;
 
@@ -44,7 +47,15 @@ fn update_pressure(
     if (level_air_ij >= 0.0) {
         return 0.0;
     }
-    let f = area_fractions(levelset_solid, x); // 0: solid, 1: non-solid
+    let dim = vec2<i32>(textureDimensions(div));
+    let f_inner = area_fractions(levelset_solid, x); // 0: solid, 1: non-solid
+    let f_vec = boundary_area_fractions(
+        constants,
+        x,
+        dim,
+        vec4<f32>(f_inner.iminusj, f_inner.iplusj, f_inner.ijminus, f_inner.ijplus),
+    );
+    let f = AreaFractions(f_vec.x, f_vec.y, f_vec.z, f_vec.w);
     let fully_solid = f.iminusj == 0.0 && f.iplusj == 0.0 && f.ijminus == 0.0 && f.ijplus == 0.0;
 
     if (fully_solid) {
@@ -52,20 +63,21 @@ fn update_pressure(
         return p_ij;
     }
 
-    let x_top = top(x);
-    let x_right = right(x);
-    let x_bottom = bottom(x);
-    let x_left = left(x);
+    let period = grid_period(constants, dim);
+    let x_top = top(x, period);
+    let x_right = right(x, period);
+    let x_bottom = bottom(x, period);
+    let x_left = left(x, period);
 
-    let p_iminusj = textureLoad(p, x_left).r;
-    let p_iplusj = textureLoad(p, x_right).r;
-    let p_ijminus = textureLoad(p, x_bottom).r;
-    let p_ijplus = textureLoad(p, x_top).r;
+    let p_iminusj = neighbor_pressure(p, x_left, dim);
+    let p_iplusj = neighbor_pressure(p, x_right, dim);
+    let p_ijminus = neighbor_pressure(p, x_bottom, dim);
+    let p_ijplus = neighbor_pressure(p, x_top, dim);
 
-    let level_air_iminusj = textureLoad(levelset_air0, x_left).r;
-    let level_air_iplusj = textureLoad(levelset_air0, x_right).r;
-    let level_air_ijminus = textureLoad(levelset_air0, x_bottom).r;
-    let level_air_ijplus = textureLoad(levelset_air0, x_top).r;
+    let level_air_iminusj = neighbor_level(x_left, level_air_ij, dim);
+    let level_air_iplusj = neighbor_level(x_right, level_air_ij, dim);
+    let level_air_ijminus = neighbor_level(x_bottom, level_air_ij, dim);
+    let level_air_ijplus = neighbor_level(x_top, level_air_ij, dim);
     let f_fluid_iminusj = min(0.0, level_air_iminusj / level_air_ij);
     let f_fluid_iplusj = min(0.0, level_air_iplusj / level_air_ij);
     let f_fluid_ijminus = min(0.0, level_air_ijminus / level_air_ij);
@@ -87,5 +99,37 @@ fn update_pressure(
     let dp01 = f.ijminus * step(0.0, f_fluid_ijminus) * p_ijminus;
     let dp11 = f.ijplus * step(0.0, f_fluid_ijplus) * p_ijplus;
 
-    return (dp00 + dp10 + dp01 + dp11 - factor * div_ij) / coef;
+    // Pressure jump at the free surface due to surface tension
+    let kappa_ij = textureLoad(curvature, x).r;
+    let dp_surface = f.iminusj * (1.0 - f_fluid_iminusj) * (1.0 - step(0.0, f_fluid_iminusj))
+            * interface_pressure_jump(constants, interface_curvature(level_air_ij, level_air_iminusj, kappa_ij, textureLoad(curvature, x_left).r))
+        + f.iplusj * (1.0 - f_fluid_iplusj) * (1.0 - step(0.0, f_fluid_iplusj))
+            * interface_pressure_jump(constants, interface_curvature(level_air_ij, level_air_iplusj, kappa_ij, textureLoad(curvature, x_right).r))
+        + f.ijminus * (1.0 - f_fluid_ijminus) * (1.0 - step(0.0, f_fluid_ijminus))
+            * interface_pressure_jump(constants, interface_curvature(level_air_ij, level_air_ijminus, kappa_ij, textureLoad(curvature, x_bottom).r))
+        + f.ijplus * (1.0 - f_fluid_ijplus) * (1.0 - step(0.0, f_fluid_ijplus))
+            * interface_pressure_jump(constants, interface_curvature(level_air_ij, level_air_ijplus, kappa_ij, textureLoad(curvature, x_top).r));
+
+    return (dp00 + dp10 + dp01 + dp11 + dp_surface - factor * div_ij) / coef;
+}
+
+
+// Pressure is fixed to zero outside of the open boundary.
+fn neighbor_pressure(
+    p: texture_storage_2d<r32float, read>,
+    x: vec2<i32>,
+    dim: vec2<i32>,
+) -> f32 {
+    if is_open_outside(constants, x, dim) {
+        return 0.0;
+    }
+    return textureLoad(p, x).r;
+}
+
+// The region outside of the open boundary is regarded as the same phase as the cell next to it.
+fn neighbor_level(x: vec2<i32>, level_inside: f32, dim: vec2<i32>) -> f32 {
+    if is_open_outside(constants, x, dim) {
+        return level_inside;
+    }
+    return textureLoad(levelset_air0, x).r;
 }
@@ -0,0 +1,59 @@
+const ENCODING_BINARY: u32 = 0;
+const ENCODING_SIGNED_DISTANCE: u32 = 1;
+const LARGE_FLOAT: f32 = 1.0e6;
+
+struct SolidMaskUniform {
+    local_to_texel: mat4x4<f32>,
+    texel_length: f32,
+    encoding: u32,
+    linear_velocity: vec2<f32>,
+}
+
+@group(0) @binding(0) var u_solid: texture_storage_2d<r32float, write>;
+@group(0) @binding(1) var v_solid: texture_storage_2d<r32float, write>;
+@group(0) @binding(2) var levelset_solid: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(3) var levelset_mask: texture_2d<f32>;
+@group(0) @binding(4) var<uniform> mask_uniform: SolidMaskUniform;
+
+@compute @workgroup_size(8, 8, 1)
+fn merge_solid_mask(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let x = vec2<i32>(i32(global_id.x), i32(global_id.y));
+    let dim = vec2<i32>(textureDimensions(levelset_solid));
+    // The cells along the walls of the domain are left to update_solid.
+    if (any(x <= vec2<i32>(0)) || any(x >= dim - vec2<i32>(1))) {
+        return;
+    }
+
+    let level = level_mask(vec2<f32>(x));
+    if (level < textureLoad(levelset_solid, x).r) {
+        textureStore(levelset_solid, x, vec4<f32>(level, 0.0, 0.0, 0.0));
+    }
+
+    if (level_mask(vec2<f32>(x) - vec2<f32>(0.5, 0.0)) < 0.5) {
+        textureStore(u_solid, x, vec4<f32>(mask_uniform.linear_velocity.x, 0.0, 0.0, 0.0));
+    }
+    if (level_mask(vec2<f32>(x) - vec2<f32>(0.0, 0.5)) < 0.5) {
+        textureStore(v_solid, x, vec4<f32>(-mask_uniform.linear_velocity.y, 0.0, 0.0, 0.0));
+    }
+}
+
+// Level of the mask at the cell coordinate `x`. Outside of the image is not solid.
+fn level_mask(x: vec2<f32>) -> f32 {
+    let dim = vec2<f32>(textureDimensions(levelset_solid));
+    let xy = vec2<f32>(x.x - 0.5 * dim.x, 0.5 * dim.y - x.y);
+    let texel = vec2<i32>(floor((mask_uniform.local_to_texel * vec4<f32>(xy, 0.0, 1.0)).xy));
+    let dim_mask = vec2<i32>(textureDimensions(levelset_mask));
+    if (any(texel < vec2<i32>(0)) || any(texel >= dim_mask)) {
+        return LARGE_FLOAT;
+    }
+
+    let value = textureLoad(levelset_mask, texel, 0).r;
+    if (mask_uniform.encoding == ENCODING_BINARY) {
+        var level = 0.5;
+        if (value > 0.5) {
+            level = -0.5;
+        }
+        return level * mask_uniform.texel_length;
+    }
+    return value * mask_uniform.texel_length;
+}
@@ -0,0 +1,32 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::coordinate::{interp2d_center_with, runge_kutta};
+#import bevy_fluid::boundary_condition::{confine_position, grid_period};
+#import bevy_fluid::scalar_channel::ScalarChannelUniform;
+
+@group(0) @binding(0) var scalar0: texture_storage_2d<r32float, read>;
+@group(0) @binding(1) var scalar1: texture_storage_2d<r32float, write>;
+@group(0) @binding(2) var u0: texture_storage_2d<r32float, read>;
+@group(0) @binding(3) var v0: texture_storage_2d<r32float, read>;
+@group(0) @binding(4) var<uniform> channel: ScalarChannelUniform;
+
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
+@compute @workgroup_size(8, 8, 1)
+fn advect_scalar(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    let dim = vec2<i32>(textureDimensions(scalar0));
+    if any(idx >= dim) {
+        return;
+    }
+
+    let dt = constants.dt;
+    let period = grid_period(constants, dim);
+    var x_new = runge_kutta(u0, v0, vec2<f32>(idx), dt, period);
+    x_new = confine_position(constants, x_new, vec2<f32>(dim));
+    let value = interp2d_center_with(scalar0, x_new, period, constants.interpolation);
+    let decayed = channel.rest_value + (value - channel.rest_value) * exp(-channel.decay * dt);
+
+    textureStore(scalar1, idx, vec4<f32>(decayed, 0.0, 0.0, 0.0));
+}
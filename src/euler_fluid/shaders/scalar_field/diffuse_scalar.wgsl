@@ -0,0 +1,80 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::scalar_channel::ScalarChannelUniform;
+#import bevy_fluid::boundary_condition::wrap_periodic;
+
+@group(0) @binding(0) var scalar0: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(1) var scalar1: texture_storage_2d<r32float, read>;
+@group(0) @binding(2) var levelset_solid: texture_storage_2d<r32float, read>;
+@group(0) @binding(3) var<uniform> channel: ScalarChannelUniform;
+
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
+// Copies the advected scalar, which is also the initial guess of the diffusion solve.
+@compute @workgroup_size(8, 8, 1)
+fn copy_scalar(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = global_invocation_id.xy;
+    if any(idx >= textureDimensions(scalar0)) {
+        return;
+    }
+    textureStore(scalar0, idx, textureLoad(scalar1, idx));
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn diffuse_scalar_red(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if ((idx.x + idx.y) % 2 == 1) {
+        diffuse_scalar(idx);
+    }
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn diffuse_scalar_black(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if ((idx.x + idx.y) % 2 == 0) {
+        diffuse_scalar(idx);
+    }
+}
+
+const OFFSETS = array<vec2<i32>, 4>(
+    vec2<i32>(-1, 0),
+    vec2<i32>(1, 0),
+    vec2<i32>(0, -1),
+    vec2<i32>(0, 1),
+);
+
+// Gauss-Seidel iteration of (1 - dt * k * laplacian) s = s*, where s* is the advected scalar in scalar1.
+// Domain edges other than periodic ones and solid cells are treated as zero-flux (Neumann) boundaries.
+fn diffuse_scalar(idx: vec2<i32>) {
+    let dim = vec2<i32>(textureDimensions(scalar0));
+    if any(idx >= dim) {
+        return;
+    }
+    if textureLoad(levelset_solid, idx).r < 0.0 {
+        return;
+    }
+
+    let a = channel.diffusion * constants.dt / (constants.dx * constants.dx);
+    var sum = 0.0;
+    var num_neighbors = 0.0;
+    for (var i = 0; i < 4; i++) {
+        let neighbor = wrap_periodic(constants, idx + OFFSETS[i], dim);
+        if any(neighbor < vec2<i32>(0)) || any(neighbor >= dim) {
+            continue;
+        }
+        if textureLoad(levelset_solid, neighbor).r < 0.0 {
+            continue;
+        }
+        sum += textureLoad(scalar0, neighbor).r;
+        num_neighbors += 1.0;
+    }
+
+    let rhs = textureLoad(scalar1, idx).r;
+    let value = (rhs + a * sum) / (1.0 + a * num_neighbors);
+    textureStore(scalar0, idx, vec4<f32>(value, 0.0, 0.0, 0.0));
+}
@@ -0,0 +1,54 @@
+#import bevy_render::view::uv_to_ndc;
+#import bevy_fluid::fluid_source::{FluidSourceUniform, level_source};
+#import bevy_fluid::scalar_channel::ScalarChannelUniform;
+
+@group(0) @binding(0) var scalar0: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(3) var<uniform> channel: ScalarChannelUniform;
+
+@group(1) @binding(0) var<uniform> fluid_source_uniform: FluidSourceUniform;
+
+// Scalar values of each source, laid out as [source][channel].
+// x: the value, y: 1.0 if the source injects the channel, otherwise 0.0.
+@group(2) @binding(0) var<storage, read> source_values: array<vec2<f32>>;
+
+// Resets the scalar to the rest value and injects the values of oneshot sources on simulation startup.
+@compute @workgroup_size(8, 8, 1)
+fn initialize_scalar(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = global_invocation_id.xy;
+    if any(idx >= textureDimensions(scalar0)) {
+        return;
+    }
+    inject(idx, channel.rest_value);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn inject_scalar(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = global_invocation_id.xy;
+    if any(idx >= textureDimensions(scalar0)) {
+        return;
+    }
+    inject(idx, textureLoad(scalar0, idx).r);
+}
+
+fn inject(idx: vec2<u32>, initial_value: f32) {
+    let dimf = vec2f(textureDimensions(scalar0));
+    let uv = vec2f(idx) / dimf;
+    let position = 0.5 * dimf * uv_to_ndc(uv);
+
+    var value = initial_value;
+    for (var i: u32 = 0; i < fluid_source_uniform.count; i++) {
+        let source_value = source_values[i * channel.num_channels + channel.index];
+        if source_value.y == 0.0 {
+            continue;
+        }
+        if level_source(fluid_source_uniform.data[i], position) < 0.0 {
+            value = source_value.x;
+        }
+    }
+
+    textureStore(scalar0, idx, vec4f(value, vec3f(0.0)));
+}
@@ -0,0 +1,10 @@
+#define_import_path bevy_fluid::scalar_channel
+
+struct ScalarChannelUniform {
+    diffusion: f32,
+    decay: f32,
+    // The value decays toward rest_value.
+    rest_value: f32,
+    index: u32,
+    num_channels: u32,
+}
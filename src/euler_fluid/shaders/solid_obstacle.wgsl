@@ -5,6 +5,10 @@ const SHAPE_CIRCLE: u32 = 0;
 const SHAPE_RECTANGLE: u32 = 1;
 const SHAPE_CAPSULE: u32 = 2;
 const SHAPE_TRIANGLE: u32 = 4;
+const SHAPE_POLYLINE: u32 = 7;
+const SHAPE_COMPOUND: u32 = 10;
+const SHAPE_CONVEX_POLYGON: u32 = 11;
+const SHAPE_ROUND_RECTANGLE: u32 = 12;
 
 struct Circle {
     radius: f32,
@@ -26,6 +30,17 @@ struct Triangle {
     c: vec2<f32>,
 }
 
+struct RoundRectangle {
+    half_size: vec2<f32>,
+    border_radius: f32,
+}
+
+// Range of the vertices or compound parts of a shape in the storage buffers.
+struct ShapeRange {
+    offset: u32,
+    count: u32,
+}
+
 struct ShapeVariant {
     shape: u32,
     values: array<f32, 6>,
@@ -38,6 +53,16 @@ struct SolidObstacle {
     inverse_transform: mat4x4<f32>,
     linear_velocity: vec2<f32>,
     angular_velocity: f32,
+    center_of_mass: vec2<f32>,
+    drag_coefficient: f32,
+    inverse_mass: f32,
+    inverse_inertia: f32,
+}
+
+struct SolidObstaclePart {
+    shape: ShapeVariant,
+    transform: mat4x4<f32>,
+    inverse_transform: mat4x4<f32>,
 }
 
 fn get_circle(variant: ShapeVariant) -> Circle {
@@ -64,26 +89,28 @@ fn get_triangle(variant: ShapeVariant) -> Triangle {
     );
 }
 
+fn get_round_rectangle(variant: ShapeVariant) -> RoundRectangle {
+    return RoundRectangle(
+        vec2<f32>(variant.values[0], variant.values[1]),
+        variant.values[2],
+    );
+}
+
+fn get_range(variant: ShapeVariant) -> ShapeRange {
+    return ShapeRange(u32(variant.values[0]), u32(variant.values[1]));
+}
+
 fn center_of_mass(solid_obstacle: SolidObstacle) -> vec2<f32> {
-    switch (solid_obstacle.shape.shape) {
-        case SHAPE_CIRCLE, SHAPE_RECTANGLE, SHAPE_CAPSULE:
-        {
-            return solid_obstacle.transform[3].xy;
-        }
-        case SHAPE_TRIANGLE:
-        {
-            let triangle = get_triangle(solid_obstacle.shape);
-            let triangle_center_local = vec4<f32>(
-                (triangle.a.x + triangle.b.x + triangle.c.x) / 3.0,
-                (triangle.a.y + triangle.b.y + triangle.c.y) / 3.0,
-                0.0,
-                1.0,
-            );
-            return (solid_obstacle.transform * triangle_center_local).xy;
-        }
-        default:
-        {
-            return vec2<f32>(0.0);
-        }
-    }
+    return (solid_obstacle.transform * vec4<f32>(solid_obstacle.center_of_mass, 0.0, 1.0)).xy;
+}
+
+// Velocity of the obstacle at the world position `x`, with y-axis pointing up.
+fn velocity_at(
+    obstacle: SolidObstacle,
+    x: vec2<f32>
+) -> vec2<f32> {
+    let r = x - center_of_mass(obstacle);
+    let omega = obstacle.angular_velocity;
+    let v = obstacle.linear_velocity + vec2<f32>(-omega * r.y, omega * r.x);
+    return v;
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
 #import bevy_fluid::fluid_uniform::SimulationUniform;
-#import bevy_fluid::area_fraction::area_fraction;
+#import bevy_fluid::area_fraction::solid_fraction_v;
+#import bevy_fluid::boundary_condition::{is_open_outside, wrap_periodic};
+#import bevy_fluid::levelset_utils::{ghost_pressure, interface_curvature, interface_pressure_jump};
 
 // The number of texture_storage binding for WebGPU is limited to 8.
 // So solve_velocity_u and solve_velocity_v have different bindings for u0, u1 and v0, v1.
@@ -9,6 +11,7 @@
 @group(0) @binding(3) var p0: texture_storage_2d<r32float, read>;
 @group(0) @binding(4) var levelset_air0: texture_storage_2d<r32float, read>;
 @group(0) @binding(5) var area_fraction_solid: texture_storage_2d<rgba32float, read>;
+@group(0) @binding(6) var curvature: texture_storage_2d<r32float, read>;
 
 @group(1) @binding(0) var<uniform> constants: SimulationUniform;
 
@@ -22,21 +25,39 @@ fn solve_velocity_v(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
 //      return;
 //  }
 
-    let fraction = textureLoad(area_fraction_solid, x).z;
+    let fraction = solid_fraction_v(area_fraction_solid, x);
     if (fraction == 0.0) {
         textureStore(v0, x, textureLoad(v_solid, x));
         return;
     }
 
-    var p_ij = textureLoad(p0, x).r;
-    var p_ijminus = textureLoad(p0, x - vec2<i32>(0, 1)).r;
+    let dim = vec2<i32>(textureDimensions(p0));
+    let x_minus = wrap_periodic(constants, x - vec2<i32>(0, 1), dim);
+    let x_plus = wrap_periodic(constants, x, dim);
+    var p_ij = textureLoad(p0, x_plus).r;
+    var p_ijminus = textureLoad(p0, x_minus).r;
 
-    let level_plus = textureLoad(levelset_air0, x).r;
-    let level_minus = textureLoad(levelset_air0, x - vec2<i32>(0, 1)).r;
+    var level_plus = textureLoad(levelset_air0, x_plus).r;
+    var level_minus = textureLoad(levelset_air0, x_minus).r;
+    // Zero pressure outside of the open boundary, where the phase is the same as inside.
+    if is_open_outside(constants, x_plus, dim) {
+        p_ij = 0.0;
+        level_plus = level_minus;
+    } else if is_open_outside(constants, x_minus, dim) {
+        p_ijminus = 0.0;
+        level_minus = level_plus;
+    }
+    let kappa = interface_curvature(
+        level_minus,
+        level_plus,
+        textureLoad(curvature, x_minus).r,
+        textureLoad(curvature, x_plus).r,
+    );
+    let p_interface = interface_pressure_jump(constants, kappa);
     if (level_minus >= 0.0 && level_plus < 0.0) {
-        p_ijminus = level_minus / level_plus * p_ij;
+        p_ijminus = ghost_pressure(p_ij, level_plus, level_minus, p_interface);
     } else if (level_minus < 0.0 && level_plus >= 0.0) {
-        p_ij = level_plus / level_minus * p_ijminus;
+        p_ij = ghost_pressure(p_ijminus, level_minus, level_plus, p_interface);
     }
 
     let v = textureLoad(v1, x);
@@ -1,14 +1,25 @@
 #import bevy_fluid::area_fraction::area_fractions
+#import bevy_fluid::boundary_condition::boundary_area_fractions
+#import bevy_fluid::fluid_uniform::SimulationUniform;
 
 @group(0) @binding(0) var levelset_solid: texture_storage_2d<r32float, read>;
 @group(0) @binding(1) var area_fraction_solid: texture_storage_2d<rgba32float, write>;
 
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
 @compute @workgroup_size(8, 8, 1)
 fn update_area_fraction(
     @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
 ) {
     let idx = vec2i(global_invocation_id.xy);
+    let dim = vec2i(textureDimensions(levelset_solid));
     let f = area_fractions(levelset_solid, idx);
+    let f_boundary = boundary_area_fractions(
+        constants,
+        idx,
+        dim,
+        vec4<f32>(f.iminusj, f.iplusj, f.ijminus, f.ijplus),
+    );
 
-    textureStore(area_fraction_solid, idx, vec4<f32>(f.iminusj, f.iplusj, f.ijminus, f.ijplus));
-}
\ No newline at end of file
+    textureStore(area_fraction_solid, idx, f_boundary);
+}
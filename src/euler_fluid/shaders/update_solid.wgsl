@@ -1,5 +1,14 @@
 #import bevy_fluid::fluid_uniform::SimulationUniform;
-#import bevy_fluid::solid_obstacle::{SolidObstacle, get_circle, get_rectangle, get_capsule, get_triangle, Rectangle, SHAPE_CIRCLE, SHAPE_RECTANGLE, SHAPE_CAPSULE, SHAPE_TRIANGLE};
+#import bevy_fluid::boundary_condition::{
+    EDGE_X_MINUS, EDGE_X_PLUS, EDGE_Y_MINUS, EDGE_Y_PLUS, BOUNDARY_INFLOW,
+    boundary_type, inflow_velocity, is_solid_boundary,
+};
+#import bevy_fluid::solid_obstacle::{
+    SolidObstacle, SolidObstaclePart, ShapeVariant, Rectangle, ShapeRange,
+    get_circle, get_rectangle, get_capsule, get_triangle, get_round_rectangle, get_range, velocity_at,
+    SHAPE_CIRCLE, SHAPE_RECTANGLE, SHAPE_CAPSULE, SHAPE_TRIANGLE,
+    SHAPE_POLYLINE, SHAPE_COMPOUND, SHAPE_CONVEX_POLYGON, SHAPE_ROUND_RECTANGLE,
+};
 
 const LARGE_FLOAT: f32 = 1.0e6;
 
@@ -7,8 +16,13 @@ const LARGE_FLOAT: f32 = 1.0e6;
 @group(0) @binding(1) var v_solid: texture_storage_2d<r32float, write>;
 @group(0) @binding(2) var levelset_solid: texture_storage_2d<r32float, write>;
 @group(0) @binding(3) var solid_id: texture_storage_2d<r32sint, write>;
+@group(0) @binding(4) var levelset_terrain: texture_storage_2d<r32float, read>;
+// Obstacles covering the -x and -y faces of each cell, or -1.
+@group(0) @binding(5) var solid_id_face: texture_storage_2d<rg32sint, write>;
 
 @group(1) @binding(0) var<storage, read> obstacles: array<SolidObstacle>;
+@group(1) @binding(1) var<storage, read> vertices: array<vec2<f32>>;
+@group(1) @binding(2) var<storage, read> parts: array<SolidObstaclePart>;
 
 @group(2) @binding(0) var<uniform> simulation_uniform: SimulationUniform;
 
@@ -20,24 +34,52 @@ fn update_solid(@builtin(global_invocation_id) global_id: vec3<u32>) {
     let xy_edge_x = to_world(vec2<f32>(x) - vec2<f32>(0.5, 0.0), dim_grid);
     let xy_edge_y = to_world(vec2<f32>(x) - vec2<f32>(0.0, 0.5), dim_grid);
 
-    // ToDo: User defined boundary conditions
-    if (any(x == vec2<i32>(0)) || any(x == vec2<i32>(dim_grid) - 1)) {
-        textureStore(levelset_solid, x, vec4<f32>(0));
-        textureStore(u_solid, x, vec4<f32>(0, 0, 0, 0));
-        textureStore(v_solid, x, vec4<f32>(0, 0, 0, 0));
-        textureStore(solid_id, x, vec4<i32>(-1, 0, 0, 0));
-        return;
+    // Cells along the walls and inflows of the domain are regarded as solid.
+    let dim = vec2<i32>(dim_grid);
+    let edges = array<u32, 4>(EDGE_X_MINUS, EDGE_X_PLUS, EDGE_Y_MINUS, EDGE_Y_PLUS);
+    let on_edge = array<bool, 4>(x.x == 0, x.x == dim.x - 1, x.y == 0, x.y == dim.y - 1);
+    let distance_to_edge = array<i32, 4>(x.x, dim.x - 1 - x.x, x.y, dim.y - 1 - x.y);
+    // Initialize solid level set to the solid edges of the domain in uv space
+    var level = LARGE_FLOAT;
+    for (var e = 0; e < 4; e++) {
+        let kind = boundary_type(simulation_uniform, edges[e]);
+        if !is_solid_boundary(kind) {
+            continue;
+        }
+        if on_edge[e] {
+            var boundary_velocity = vec2<f32>(0.0);
+            if kind == BOUNDARY_INFLOW {
+                boundary_velocity = inflow_velocity(simulation_uniform, edges[e]);
+            }
+            textureStore(levelset_solid, x, vec4<f32>(0));
+            textureStore(u_solid, x, vec4<f32>(boundary_velocity.x, 0, 0, 0));
+            textureStore(v_solid, x, vec4<f32>(boundary_velocity.y, 0, 0, 0));
+            // The faces on the outer edges of the last row and column.
+            if x.x == dim.x - 1 {
+                textureStore(u_solid, x + vec2<i32>(1, 0), vec4<f32>(boundary_velocity.x, 0, 0, 0));
+            }
+            if x.y == dim.y - 1 {
+                textureStore(v_solid, x + vec2<i32>(0, 1), vec4<f32>(boundary_velocity.y, 0, 0, 0));
+            }
+            textureStore(solid_id, x, vec4<i32>(-1, 0, 0, 0));
+            textureStore(solid_id_face, x, vec4<i32>(-1, -1, 0, 0));
+            return;
+        }
+        level = min(level, f32(distance_to_edge[e]));
     }
 
-    // Initialize solid level set to the domain boundary in uv space
-    let tmp = min(x, abs(vec2<i32>(dim_grid) - 1 - x));
-    var level = f32(min(tmp.x, tmp.y));
+    // Static terrain is rasterized in advance.
+    level = min(level, textureLoad(levelset_terrain, x).r);
 
     let num_obstacles = arrayLength(&obstacles);
     var i = 0u;
     var u = 0.0;
     var v = 0.0;
     var solid_id_sample = -1;
+    // A face within half a cell of an obstacle may be partly covered by it.
+    // The closest obstacle owns the face, and the solid velocity of the face is taken from it.
+    var face_owner = vec2<i32>(-1);
+    var level_face = vec2<f32>(0.5);
     loop {
         if (i >= num_obstacles) {
             break;
@@ -53,12 +95,16 @@ fn update_solid(@builtin(global_invocation_id) global_id: vec3<u32>) {
         }
 
         let level_edge_x = level_obstacle(obstacle, xy_edge_x);
-        if (level_edge_x < 0.5) {
+        if (level_edge_x < level_face.x) {
+            level_face.x = level_edge_x;
+            face_owner.x = i32(obstacle.entity_id);
             u = velocity_at(obstacle, xy_edge_x).x;
         }
 
         let level_edge_y = level_obstacle(obstacle, xy_edge_y);
-        if (level_edge_y < 0.5) {
+        if (level_edge_y < level_face.y) {
+            level_face.y = level_edge_y;
+            face_owner.y = i32(obstacle.entity_id);
             v = -velocity_at(obstacle, xy_edge_y).y;
         }
 
@@ -75,6 +121,7 @@ fn update_solid(@builtin(global_invocation_id) global_id: vec3<u32>) {
     }
     textureStore(levelset_solid, x, vec4<f32>(level, 0.0, 0.0, 0.0));
     textureStore(solid_id, x, vec4<i32>(solid_id_sample, 0, 0, 0));
+    textureStore(solid_id_face, x, vec4<i32>(face_owner, 0, 0));
 }
 
 fn to_world(x: vec2<f32>, dim: vec2<u32>) -> vec2<f32> {
@@ -108,39 +155,49 @@ fn level_rectangle(rectangle: Rectangle, transform: mat4x4<f32>, inverse_transfo
     return level;
 }
 
-fn velocity_at(
-    obstacle: SolidObstacle,
-    x: vec2<f32>
-) -> vec2<f32> {
-    let r = x - obstacle.transform[3].xy;
-    let omega = obstacle.angular_velocity;
-    let v = obstacle.linear_velocity + vec2<f32>(-omega * r.y, omega * r.x);
-    return v;
+fn level_obstacle(obstacle: SolidObstacle, x: vec2<f32>) -> f32 {
+    if (obstacle.shape.shape == SHAPE_COMPOUND) {
+        let range = get_range(obstacle.shape);
+        var level = LARGE_FLOAT;
+        for (var i = 0u; i < range.count; i++) {
+            let part = parts[range.offset + i];
+            let transform = obstacle.transform * part.transform;
+            let inverse_transform = part.inverse_transform * obstacle.inverse_transform;
+            level = min(level, level_shape(part.shape, transform, inverse_transform, x));
+        }
+        return level;
+    }
+    return level_shape(obstacle.shape, obstacle.transform, obstacle.inverse_transform, x);
 }
 
-fn level_obstacle(obstacle: SolidObstacle, x: vec2<f32>) -> f32 {
-    switch (obstacle.shape.shape) {
+fn level_shape(
+    shape: ShapeVariant,
+    transform: mat4x4<f32>,
+    inverse_transform: mat4x4<f32>,
+    x: vec2<f32>,
+) -> f32 {
+    switch (shape.shape) {
         case SHAPE_CIRCLE: {
-            let circle = get_circle(obstacle.shape);
-            let translation = obstacle.transform[3].xy;
+            let circle = get_circle(shape);
+            let translation = transform[3].xy;
             return distance(x, translation) - circle.radius;
         }
         case SHAPE_RECTANGLE: {
-            let rectangle = get_rectangle(obstacle.shape);
-            return level_rectangle(rectangle, obstacle.transform, obstacle.inverse_transform, x);
+            let rectangle = get_rectangle(shape);
+            return level_rectangle(rectangle, transform, inverse_transform, x);
         }
         case SHAPE_CAPSULE: {
-            let capsule = get_capsule(obstacle.shape);
-            let a = obstacle.transform * vec4<f32>(capsule.a, 0.0, 1.0);
-            let b = obstacle.transform * vec4<f32>(capsule.b, 0.0, 1.0);
+            let capsule = get_capsule(shape);
+            let a = transform * vec4<f32>(capsule.a, 0.0, 1.0);
+            let b = transform * vec4<f32>(capsule.b, 0.0, 1.0);
             let level = distance_to_line_segment(x, a.xy, b.xy) - capsule.radius;
             return level;
         }
         case SHAPE_TRIANGLE: {
-            let triangle = get_triangle(obstacle.shape);
-            let p0 = obstacle.transform * vec4<f32>(triangle.a, 0.0, 1.0);
-            let p1 = obstacle.transform * vec4<f32>(triangle.b, 0.0, 1.0);
-            let p2 = obstacle.transform * vec4<f32>(triangle.c, 0.0, 1.0);
+            let triangle = get_triangle(shape);
+            let p0 = transform * vec4<f32>(triangle.a, 0.0, 1.0);
+            let p1 = transform * vec4<f32>(triangle.b, 0.0, 1.0);
+            let p2 = transform * vec4<f32>(triangle.c, 0.0, 1.0);
 
             let dist0 = distance_to_line_segment(x, p0.xy, p1.xy);
             let dist1 = distance_to_line_segment(x, p1.xy, p2.xy);
@@ -155,12 +212,58 @@ fn level_obstacle(obstacle: SolidObstacle, x: vec2<f32>) -> f32 {
                 return min(min(dist0, dist1), dist2);
             }
         }
+        case SHAPE_ROUND_RECTANGLE: {
+            let round_rectangle = get_round_rectangle(shape);
+            let inner = Rectangle(round_rectangle.half_size);
+            return level_rectangle(inner, transform, inverse_transform, x) - round_rectangle.border_radius;
+        }
+        case SHAPE_CONVEX_POLYGON: {
+            return level_polygon(get_range(shape), transform, x);
+        }
+        case SHAPE_POLYLINE: {
+            // Segments have no interior, so they are thickened to walls of one cell.
+            let cell_size = simulation_uniform.size.x / f32(textureDimensions(levelset_solid).x);
+            return level_polyline(get_range(shape), transform, x) - 0.5 * cell_size;
+        }
         default: {
             return LARGE_FLOAT;
         }
     }
 }
 
+fn level_polygon(range: ShapeRange, transform: mat4x4<f32>, x: vec2<f32>) -> f32 {
+    if (range.count == 0u) {
+        return LARGE_FLOAT;
+    }
+    var distance_squared = LARGE_FLOAT * LARGE_FLOAT;
+    var inside_sign = 1.0;
+    var prev = (transform * vec4<f32>(vertices[range.offset + range.count - 1u], 0.0, 1.0)).xy;
+    for (var i = 0u; i < range.count; i++) {
+        let current = (transform * vec4<f32>(vertices[range.offset + i], 0.0, 1.0)).xy;
+        let edge = prev - current;
+        let w = x - current;
+        let b = w - edge * clamp(dot(w, edge) / dot(edge, edge), 0.0, 1.0);
+        distance_squared = min(distance_squared, dot(b, b));
+        // Flip the sign every time a ray from x crosses the edge.
+        let c = vec3<bool>(x.y >= current.y, x.y < prev.y, edge.x * w.y > edge.y * w.x);
+        if (all(c) || !any(c)) {
+            inside_sign = -inside_sign;
+        }
+        prev = current;
+    }
+    return inside_sign * sqrt(distance_squared);
+}
+
+fn level_polyline(range: ShapeRange, transform: mat4x4<f32>, x: vec2<f32>) -> f32 {
+    var level = LARGE_FLOAT;
+    for (var i = 0u; i + 1u < range.count; i += 2u) {
+        let a = (transform * vec4<f32>(vertices[range.offset + i], 0.0, 1.0)).xy;
+        let b = (transform * vec4<f32>(vertices[range.offset + i + 1u], 0.0, 1.0)).xy;
+        level = min(level, distance_to_line_segment(x, a, b));
+    }
+    return level;
+}
+
 fn distance_to_line_segment(x: vec2<f32>, a: vec2<f32>, b: vec2<f32>) -> f32 {
     let ab = b - a;
     let t = clamp(dot(x - a, ab) / dot(ab, ab), 0.0, 1.0);
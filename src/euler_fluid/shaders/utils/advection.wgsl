@@ -0,0 +1,203 @@
+#define_import_path bevy_fluid::advection
+
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::coordinate::{
+    INTERPOLATION_BILINEAR, INTERPOLATION_CUBIC, interp2d_center_with, interp2d_edge_x_with,
+    interp2d_edge_y_with, minmax2d_center, minmax2d_edge_x, minmax2d_edge_y, runge_kutta,
+};
+#import bevy_fluid::boundary_condition::{
+    BOUNDARY_INFLOW, BOUNDARY_OPEN, BOUNDARY_PERIODIC, EDGE_NONE, EDGE_X_MINUS, EDGE_X_PLUS, EDGE_Y_MINUS, EDGE_Y_PLUS,
+    boundary_type, confine_position, grid_period, inflow_velocity, wrap_periodic_position,
+};
+
+// Backtraces the point `x` along the velocity (u, v) over `dt`. A negative `dt` traces forward in time.
+fn backtrace(
+    constants: SimulationUniform,
+    u: texture_storage_2d<r32float, read>,
+    v: texture_storage_2d<r32float, read>,
+    x: vec2<f32>,
+    dt: f32,
+) -> vec2<f32> {
+    let period = grid_period(constants, vec2<i32>(constants.size));
+    return wrap_periodic_position(constants, runge_kutta(u, v, x, dt, period));
+}
+
+// The edge of the domain the backtraced point `x` went across, or EDGE_NONE if it is inside the domain.
+fn crossed_edge(
+    x: vec2<f32>,
+    dim: vec2<f32>,
+) -> u32 {
+    if (x.x < 0.0) {
+        return EDGE_X_MINUS;
+    }
+    if (x.x > dim.x - 1.0) {
+        return EDGE_X_PLUS;
+    }
+    if (x.y < 0.0) {
+        return EDGE_Y_MINUS;
+    }
+    if (x.y > dim.y - 1.0) {
+        return EDGE_Y_PLUS;
+    }
+    return EDGE_NONE;
+}
+
+// Whether the backtraced point `x` samples the field from inside of the domain (or across periodic edges).
+fn is_sampled_inside(constants: SimulationUniform, x: vec2<f32>, dim: vec2<f32>) -> bool {
+    let edge = crossed_edge(x, dim);
+    return edge == EDGE_NONE || boundary_type(constants, edge) == BOUNDARY_PERIODIC;
+}
+
+// Semi-Lagrangian advection of the x-component `q` at the face `idx` along the velocity (u, v).
+fn semi_lagrangian_u(
+    constants: SimulationUniform,
+    u: texture_storage_2d<r32float, read>,
+    v: texture_storage_2d<r32float, read>,
+    q: texture_storage_2d<r32float, read>,
+    idx: vec2<i32>,
+    dt: f32,
+) -> f32 {
+    // backtrace the velocity at the point (i - 0.5, j).
+    let x = vec2<f32>(idx) + vec2<f32>(-0.5, 0.0);
+    let period = grid_period(constants, vec2<i32>(constants.size));
+    let backtraced_x = backtrace(constants, u, v, x, dt);
+    let dim = vec2<f32>(textureDimensions(q));
+    let edge = crossed_edge(backtraced_x, dim);
+    switch boundary_type(constants, edge) {
+        case BOUNDARY_INFLOW: {
+            return inflow_velocity(constants, edge).x;
+        }
+        case BOUNDARY_OPEN: {
+            // Zero gradient across the open boundary.
+            return interp2d_edge_x_with(q, clamp(backtraced_x, vec2<f32>(0.0), dim - vec2<f32>(1.0)), period, constants.interpolation);
+        }
+        case BOUNDARY_PERIODIC: {
+            return interp2d_edge_x_with(q, backtraced_x, period, constants.interpolation);
+        }
+        default: {
+            if (edge == EDGE_NONE) {
+                return interp2d_edge_x_with(q, backtraced_x, period, constants.interpolation);
+            }
+            // Walls keep the current velocity.
+            return textureLoad(q, idx).x;
+        }
+    }
+}
+
+// Semi-Lagrangian advection of the y-component `q` at the face `idx` along the velocity (u, v).
+fn semi_lagrangian_v(
+    constants: SimulationUniform,
+    u: texture_storage_2d<r32float, read>,
+    v: texture_storage_2d<r32float, read>,
+    q: texture_storage_2d<r32float, read>,
+    idx: vec2<i32>,
+    dt: f32,
+) -> f32 {
+    // backtrace the velocity at the point (i, j - 0.5).
+    let x = vec2<f32>(idx) + vec2<f32>(0.0, -0.5);
+    let period = grid_period(constants, vec2<i32>(constants.size));
+    let backtraced_x = backtrace(constants, u, v, x, dt);
+    let dim = vec2<f32>(textureDimensions(q));
+    let edge = crossed_edge(backtraced_x, dim);
+    switch boundary_type(constants, edge) {
+        case BOUNDARY_INFLOW: {
+            return inflow_velocity(constants, edge).y;
+        }
+        case BOUNDARY_OPEN: {
+            // Zero gradient across the open boundary.
+            return interp2d_edge_y_with(q, clamp(backtraced_x, vec2<f32>(0.0), dim - vec2<f32>(1.0)), period, constants.interpolation);
+        }
+        case BOUNDARY_PERIODIC: {
+            return interp2d_edge_y_with(q, backtraced_x, period, constants.interpolation);
+        }
+        default: {
+            if (edge == EDGE_NONE) {
+                return interp2d_edge_y_with(q, backtraced_x, period, constants.interpolation);
+            }
+            // Walls keep the current velocity.
+            return textureLoad(q, idx).x;
+        }
+    }
+}
+
+// Interpolation of the level set. It stays cubic by default, since bilinear interpolation loses the volume quickly.
+fn levelset_interpolation(constants: SimulationUniform) -> u32 {
+    if constants.interpolation == INTERPOLATION_BILINEAR {
+        return INTERPOLATION_CUBIC;
+    }
+    return constants.interpolation;
+}
+
+// Semi-Lagrangian advection of the cell-centered `q` at the cell `idx` along the velocity (u, v), interpolated with `method`.
+fn semi_lagrangian_center(
+    constants: SimulationUniform,
+    u: texture_storage_2d<r32float, read>,
+    v: texture_storage_2d<r32float, read>,
+    q: texture_storage_2d<r32float, read>,
+    idx: vec2<i32>,
+    dt: f32,
+    method: u32,
+) -> f32 {
+    let dim = vec2<f32>(textureDimensions(q));
+    let period = grid_period(constants, vec2<i32>(dim));
+    let backtraced_x = confine_position(constants, backtrace(constants, u, v, vec2<f32>(idx), dt), dim);
+    return interp2d_center_with(q, backtraced_x, period, method);
+}
+
+// Clamps `value` of the x-component at the face `idx` into the range of `q` around the backtraced point,
+// so that the error correction of MacCormack and BFECC does not create new extrema.
+// Returns `fallback` if the backtraced point is outside of the domain, where `q` is given by the boundary condition.
+fn limit_u(
+    constants: SimulationUniform,
+    u: texture_storage_2d<r32float, read>,
+    v: texture_storage_2d<r32float, read>,
+    q: texture_storage_2d<r32float, read>,
+    idx: vec2<i32>,
+    dt: f32,
+    value: f32,
+    fallback: f32,
+) -> f32 {
+    let x = vec2<f32>(idx) + vec2<f32>(-0.5, 0.0);
+    let backtraced_x = backtrace(constants, u, v, x, dt);
+    if !is_sampled_inside(constants, backtraced_x, vec2<f32>(textureDimensions(q))) {
+        return fallback;
+    }
+    let bounds = minmax2d_edge_x(q, backtraced_x, grid_period(constants, vec2<i32>(constants.size)));
+    return clamp(value, bounds.x, bounds.y);
+}
+
+// Same as `limit_u` for the y-component.
+fn limit_v(
+    constants: SimulationUniform,
+    u: texture_storage_2d<r32float, read>,
+    v: texture_storage_2d<r32float, read>,
+    q: texture_storage_2d<r32float, read>,
+    idx: vec2<i32>,
+    dt: f32,
+    value: f32,
+    fallback: f32,
+) -> f32 {
+    let x = vec2<f32>(idx) + vec2<f32>(0.0, -0.5);
+    let backtraced_x = backtrace(constants, u, v, x, dt);
+    if !is_sampled_inside(constants, backtraced_x, vec2<f32>(textureDimensions(q))) {
+        return fallback;
+    }
+    let bounds = minmax2d_edge_y(q, backtraced_x, grid_period(constants, vec2<i32>(constants.size)));
+    return clamp(value, bounds.x, bounds.y);
+}
+
+// Same as `limit_u` for the cell-centered field. Points outside of the domain are confined as in `semi_lagrangian_center`.
+fn limit_center(
+    constants: SimulationUniform,
+    u: texture_storage_2d<r32float, read>,
+    v: texture_storage_2d<r32float, read>,
+    q: texture_storage_2d<r32float, read>,
+    idx: vec2<i32>,
+    dt: f32,
+    value: f32,
+) -> f32 {
+    let dim = vec2<f32>(textureDimensions(q));
+    let backtraced_x = confine_position(constants, backtrace(constants, u, v, vec2<f32>(idx), dt), dim);
+    let bounds = minmax2d_center(q, backtraced_x, grid_period(constants, vec2<i32>(dim)));
+    return clamp(value, bounds.x, bounds.y);
+}
@@ -54,3 +54,28 @@ fn area_fractions(
         area_fraction(level_vertex_iminusjplus, level_vertex_iplusjplus),
     );
 }
+
+
+// Solid area fraction of the x-face (i - 1/2, j) of the MAC grid.
+fn solid_fraction_u(
+    area_fraction_solid: texture_storage_2d<rgba32float, read>,
+    idx: vec2<i32>,
+) -> f32 {
+    let dim = vec2<i32>(textureDimensions(area_fraction_solid));
+    if idx.x < dim.x {
+        return textureLoad(area_fraction_solid, idx).x;
+    }
+    return textureLoad(area_fraction_solid, idx - vec2<i32>(1, 0)).y;
+}
+
+// Solid area fraction of the y-face (i, j - 1/2) of the MAC grid.
+fn solid_fraction_v(
+    area_fraction_solid: texture_storage_2d<rgba32float, read>,
+    idx: vec2<i32>,
+) -> f32 {
+    let dim = vec2<i32>(textureDimensions(area_fraction_solid));
+    if idx.y < dim.y {
+        return textureLoad(area_fraction_solid, idx).z;
+    }
+    return textureLoad(area_fraction_solid, idx - vec2<i32>(0, 1)).w;
+}
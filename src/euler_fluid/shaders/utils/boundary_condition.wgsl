@@ -0,0 +1,160 @@
+#define_import_path bevy_fluid::boundary_condition
+
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::coordinate::wrap;
+
+const BOUNDARY_NO_SLIP: u32 = 0u;
+const BOUNDARY_FREE_SLIP: u32 = 1u;
+const BOUNDARY_OPEN: u32 = 2u;
+const BOUNDARY_INFLOW: u32 = 3u;
+const BOUNDARY_PERIODIC: u32 = 4u;
+
+// Edges of the grid. The y axis of the grid points downward in world space,
+// so EDGE_Y_MINUS is the top edge of the fluid and EDGE_Y_PLUS is the bottom edge.
+const EDGE_X_MINUS: u32 = 0u;
+const EDGE_X_PLUS: u32 = 1u;
+const EDGE_Y_MINUS: u32 = 2u;
+const EDGE_Y_PLUS: u32 = 3u;
+const EDGE_NONE: u32 = 4u;
+
+fn boundary_type(constants: SimulationUniform, edge: u32) -> u32 {
+    if edge == EDGE_NONE {
+        return BOUNDARY_NO_SLIP;
+    }
+    return constants.boundary_types[edge];
+}
+
+// Walls (no-slip, free-slip) and inflows are regarded as solid with prescribed velocity.
+fn is_solid_boundary(kind: u32) -> bool {
+    return kind == BOUNDARY_NO_SLIP || kind == BOUNDARY_FREE_SLIP || kind == BOUNDARY_INFLOW;
+}
+
+// Inflow velocity of the edge in grid coordinates.
+fn inflow_velocity(constants: SimulationUniform, edge: u32) -> vec2<f32> {
+    switch edge {
+        case EDGE_X_MINUS: {
+            return constants.inflow_velocity_x.xy;
+        }
+        case EDGE_X_PLUS: {
+            return constants.inflow_velocity_x.zw;
+        }
+        case EDGE_Y_MINUS: {
+            return constants.inflow_velocity_y.xy;
+        }
+        case EDGE_Y_PLUS: {
+            return constants.inflow_velocity_y.zw;
+        }
+        default: {
+            return vec2<f32>(0.0);
+        }
+    }
+}
+
+// Weight of the fluid velocity in the tangential velocity of the wall cell `x` at the -y and +y edges,
+// which are tangential to the x-component of the velocity. 0 if `x` is not on those edges.
+fn wall_slip_u(constants: SimulationUniform, x: vec2<i32>) -> f32 {
+    if x.y == 0 {
+        return constants.wall_slip[EDGE_Y_MINUS];
+    }
+    if x.y == i32(constants.size.y) - 1 {
+        return constants.wall_slip[EDGE_Y_PLUS];
+    }
+    return 0.0;
+}
+
+// Same as `wall_slip_u` for the y-component at the -x and +x edges.
+fn wall_slip_v(constants: SimulationUniform, x: vec2<i32>) -> f32 {
+    if x.x == 0 {
+        return constants.wall_slip[EDGE_X_MINUS];
+    }
+    if x.x == i32(constants.size.x) - 1 {
+        return constants.wall_slip[EDGE_X_PLUS];
+    }
+    return 0.0;
+}
+
+// The edge beyond which the cell `x` lies, or EDGE_NONE if `x` is inside the grid of size `dim`.
+fn outside_edge(x: vec2<i32>, dim: vec2<i32>) -> u32 {
+    if x.x < 0 {
+        return EDGE_X_MINUS;
+    }
+    if x.x >= dim.x {
+        return EDGE_X_PLUS;
+    }
+    if x.y < 0 {
+        return EDGE_Y_MINUS;
+    }
+    if x.y >= dim.y {
+        return EDGE_Y_PLUS;
+    }
+    return EDGE_NONE;
+}
+
+// Whether each axis of the grid is periodic.
+fn periodic_axes(constants: SimulationUniform) -> vec2<bool> {
+    return vec2<bool>(
+        constants.boundary_types.x == BOUNDARY_PERIODIC,
+        constants.boundary_types.z == BOUNDARY_PERIODIC,
+    );
+}
+
+// Period of the grid with `dim` cells along the periodic axes, and zero along the others.
+fn grid_period(constants: SimulationUniform, dim: vec2<i32>) -> vec2<i32> {
+    return select(vec2<i32>(0), dim, periodic_axes(constants));
+}
+
+// Wraps the index `x` along the periodic axes. `dim` is the number of cells of the grid.
+fn wrap_periodic(constants: SimulationUniform, x: vec2<i32>, dim: vec2<i32>) -> vec2<i32> {
+    return wrap(x, grid_period(constants, dim));
+}
+
+// Wraps the position `x` in grid cell units along the periodic axes, into [-0.5, size - 0.5).
+fn wrap_periodic_position(constants: SimulationUniform, x: vec2<f32>) -> vec2<f32> {
+    let size = constants.size;
+    let wrapped = x - size * floor((x + 0.5) / size);
+    return select(x, wrapped, periodic_axes(constants));
+}
+
+// Keeps the position `x` in grid cell units inside the domain of `dim` cells,
+// wrapping along the periodic axes and clamping along the others.
+fn confine_position(constants: SimulationUniform, x: vec2<f32>, dim: vec2<f32>) -> vec2<f32> {
+    return select(
+        clamp(x, vec2<f32>(0.0), dim - vec2<f32>(1.0)),
+        wrap_periodic_position(constants, x),
+        periodic_axes(constants),
+    );
+}
+
+// Whether the cell `x` lies beyond an open boundary, where the pressure is fixed to zero.
+fn is_open_outside(constants: SimulationUniform, x: vec2<i32>, dim: vec2<i32>) -> bool {
+    let edge = outside_edge(x, dim);
+    return edge != EDGE_NONE && boundary_type(constants, edge) == BOUNDARY_OPEN;
+}
+
+// Overrides the solid area fractions (iminusj, iplusj, ijminus, ijplus) of the cell `idx`
+// on the faces lying on the domain boundary.
+fn boundary_area_fractions(
+    constants: SimulationUniform,
+    idx: vec2<i32>,
+    dim: vec2<i32>,
+    f: vec4<f32>,
+) -> vec4<f32> {
+    var result = f;
+    if idx.x == 0 {
+        result.x = boundary_face_fraction(constants, EDGE_X_MINUS);
+    }
+    if idx.x == dim.x - 1 {
+        result.y = boundary_face_fraction(constants, EDGE_X_PLUS);
+    }
+    if idx.y == 0 {
+        result.z = boundary_face_fraction(constants, EDGE_Y_MINUS);
+    }
+    if idx.y == dim.y - 1 {
+        result.w = boundary_face_fraction(constants, EDGE_Y_PLUS);
+    }
+    return result;
+}
+
+fn boundary_face_fraction(constants: SimulationUniform, edge: u32) -> f32 {
+    return select(1.0, 0.0, is_solid_boundary(boundary_type(constants, edge)));
+}
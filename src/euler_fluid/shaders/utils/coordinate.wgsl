@@ -1,33 +1,49 @@
 #define_import_path bevy_fluid::coordinate
 
-fn left(x: vec2<i32>) -> vec2<i32> {
-    return x - vec2<i32>(1, 0);
+// Wraps the index `x` along the periodic axes, where `period` is the number of cells of the grid.
+// Zero `period` means the axis is not periodic.
+fn wrap(x: vec2<i32>, period: vec2<i32>) -> vec2<i32> {
+    let p = max(period, vec2<i32>(1));
+    let wrapped = ((x % p) + p) % p;
+    return select(x, wrapped, period > vec2<i32>(0));
 }
 
-fn right(x: vec2<i32>) -> vec2<i32> {
-    return x + vec2<i32>(1, 0);
+// Shortest displacement between two points in a periodic domain.
+fn minimum_image(d: vec2<f32>, period: vec2<i32>) -> vec2<f32> {
+    let p = vec2<f32>(period);
+    let wrapped = d - p * round(d / max(p, vec2<f32>(1.0)));
+    return select(d, wrapped, period > vec2<i32>(0));
 }
 
-fn bottom(x: vec2<i32>) -> vec2<i32> {
-    return x - vec2<i32>(0, 1);
+fn left(x: vec2<i32>, period: vec2<i32>) -> vec2<i32> {
+    return wrap(x - vec2<i32>(1, 0), period);
 }
 
-fn top(x: vec2<i32>) -> vec2<i32> {
-    return x + vec2<i32>(0, 1);
+fn right(x: vec2<i32>, period: vec2<i32>) -> vec2<i32> {
+    return wrap(x + vec2<i32>(1, 0), period);
+}
+
+fn bottom(x: vec2<i32>, period: vec2<i32>) -> vec2<i32> {
+    return wrap(x - vec2<i32>(0, 1), period);
+}
+
+fn top(x: vec2<i32>, period: vec2<i32>) -> vec2<i32> {
+    return wrap(x + vec2<i32>(0, 1), period);
 }
 
 fn interp2d_center(
     levelset: texture_storage_2d<r32float, read>,
     x: vec2<f32>,
+    period: vec2<i32>,
 ) -> f32 {
     let i = i32(floor(x.x));
     let j = i32(floor(x.y));
     let fract_i = x.x - f32(i);
     let fract_j = x.y - f32(j);
-    let levelset00 = textureLoad(levelset, vec2<i32>(i, j)).r;
-    let levelset10 = textureLoad(levelset, vec2<i32>(i + 1, j)).r;
-    let levelset01 = textureLoad(levelset, vec2<i32>(i, j + 1)).r;
-    let levelset11 = textureLoad(levelset, vec2<i32>(i + 1, j + 1)).r;
+    let levelset00 = textureLoad(levelset, wrap(vec2<i32>(i, j), period)).r;
+    let levelset10 = textureLoad(levelset, wrap(vec2<i32>(i + 1, j), period)).r;
+    let levelset01 = textureLoad(levelset, wrap(vec2<i32>(i, j + 1), period)).r;
+    let levelset11 = textureLoad(levelset, wrap(vec2<i32>(i + 1, j + 1), period)).r;
 
     return mix(mix(levelset00, levelset10, fract_i), mix(levelset01, levelset11, fract_i), fract_j);
 }
@@ -35,15 +51,16 @@ fn interp2d_center(
 fn interp2d_center_rg32float(
     levelset: texture_storage_2d<rg32float, read>,
     x: vec2<f32>,
+    period: vec2<i32>,
 ) -> vec2<f32> {
     let i = i32(floor(x.x));
     let j = i32(floor(x.y));
     let fract_i = x.x - f32(i);
     let fract_j = x.y - f32(j);
-    let levelset00 = textureLoad(levelset, vec2<i32>(i, j)).rg;
-    let levelset10 = textureLoad(levelset, vec2<i32>(i + 1, j)).rg;
-    let levelset01 = textureLoad(levelset, vec2<i32>(i, j + 1)).rg;
-    let levelset11 = textureLoad(levelset, vec2<i32>(i + 1, j + 1)).rg;
+    let levelset00 = textureLoad(levelset, wrap(vec2<i32>(i, j), period)).rg;
+    let levelset10 = textureLoad(levelset, wrap(vec2<i32>(i + 1, j), period)).rg;
+    let levelset01 = textureLoad(levelset, wrap(vec2<i32>(i, j + 1), period)).rg;
+    let levelset11 = textureLoad(levelset, wrap(vec2<i32>(i + 1, j + 1), period)).rg;
 
     return mix(mix(levelset00, levelset10, fract_i), mix(levelset01, levelset11, fract_i), fract_j);
 }
@@ -51,15 +68,16 @@ fn interp2d_center_rg32float(
 fn interp2d_edge_x(
     u: texture_storage_2d<r32float, read>,
     x: vec2<f32>,
+    period: vec2<i32>,
 ) -> f32 {
     let i = i32(round(x.x));
     let j = i32(floor(x.y));
     let fract_i = x.x + 0.5 - f32(i);
     let fract_j = x.y - f32(j);
-    let u00 = textureLoad(u, vec2<i32>(i, j)).r;
-    let u10 = textureLoad(u, vec2<i32>(i + 1, j)).r;
-    let u01 = textureLoad(u, vec2<i32>(i, j + 1)).r;
-    let u11 = textureLoad(u, vec2<i32>(i + 1, j + 1)).r;
+    let u00 = textureLoad(u, wrap(vec2<i32>(i, j), period)).r;
+    let u10 = textureLoad(u, wrap(vec2<i32>(i + 1, j), period)).r;
+    let u01 = textureLoad(u, wrap(vec2<i32>(i, j + 1), period)).r;
+    let u11 = textureLoad(u, wrap(vec2<i32>(i + 1, j + 1), period)).r;
 
     return mix(mix(u00, u10, fract_i), mix(u01, u11, fract_i), fract_j);
 }
@@ -67,28 +85,199 @@ fn interp2d_edge_x(
 fn interp2d_edge_y(
     v: texture_storage_2d<r32float, read>,
     x: vec2<f32>,
+    period: vec2<i32>,
 ) -> f32 {
     let i = i32(floor(x.x));
     let j = i32(round(x.y));
     let fract_i = x.x - f32(i);
     let fract_j = x.y + 0.5 - f32(j);
-    let v00 = textureLoad(v, vec2<i32>(i, j)).r;
-    let v10 = textureLoad(v, vec2<i32>(i + 1, j)).r;
-    let v01 = textureLoad(v, vec2<i32>(i, j + 1)).r;
-    let v11 = textureLoad(v, vec2<i32>(i + 1, j + 1)).r;
+    let v00 = textureLoad(v, wrap(vec2<i32>(i, j), period)).r;
+    let v10 = textureLoad(v, wrap(vec2<i32>(i + 1, j), period)).r;
+    let v01 = textureLoad(v, wrap(vec2<i32>(i, j + 1), period)).r;
+    let v11 = textureLoad(v, wrap(vec2<i32>(i + 1, j + 1), period)).r;
 
     return mix(mix(v00, v10, fract_i), mix(v01, v11, fract_i), fract_j);
 }
 
+// Minimum and maximum of the values used by `interp2d_center`, to limit the overshoots of higher-order schemes.
+fn minmax2d_center(
+    q: texture_storage_2d<r32float, read>,
+    x: vec2<f32>,
+    period: vec2<i32>,
+) -> vec2<f32> {
+    let i = i32(floor(x.x));
+    let j = i32(floor(x.y));
+    return minmax4(
+        textureLoad(q, wrap(vec2<i32>(i, j), period)).r,
+        textureLoad(q, wrap(vec2<i32>(i + 1, j), period)).r,
+        textureLoad(q, wrap(vec2<i32>(i, j + 1), period)).r,
+        textureLoad(q, wrap(vec2<i32>(i + 1, j + 1), period)).r,
+    );
+}
+
+// Minimum and maximum of the values used by `interp2d_edge_x`.
+fn minmax2d_edge_x(
+    u: texture_storage_2d<r32float, read>,
+    x: vec2<f32>,
+    period: vec2<i32>,
+) -> vec2<f32> {
+    let i = i32(round(x.x));
+    let j = i32(floor(x.y));
+    return minmax4(
+        textureLoad(u, wrap(vec2<i32>(i, j), period)).r,
+        textureLoad(u, wrap(vec2<i32>(i + 1, j), period)).r,
+        textureLoad(u, wrap(vec2<i32>(i, j + 1), period)).r,
+        textureLoad(u, wrap(vec2<i32>(i + 1, j + 1), period)).r,
+    );
+}
+
+// Minimum and maximum of the values used by `interp2d_edge_y`.
+fn minmax2d_edge_y(
+    v: texture_storage_2d<r32float, read>,
+    x: vec2<f32>,
+    period: vec2<i32>,
+) -> vec2<f32> {
+    let i = i32(floor(x.x));
+    let j = i32(round(x.y));
+    return minmax4(
+        textureLoad(v, wrap(vec2<i32>(i, j), period)).r,
+        textureLoad(v, wrap(vec2<i32>(i + 1, j), period)).r,
+        textureLoad(v, wrap(vec2<i32>(i, j + 1), period)).r,
+        textureLoad(v, wrap(vec2<i32>(i + 1, j + 1), period)).r,
+    );
+}
+
+fn minmax4(a: f32, b: f32, c: f32, d: f32) -> vec2<f32> {
+    return vec2<f32>(min(min(a, b), min(c, d)), max(max(a, b), max(c, d)));
+}
+
+// Interpolation kernels, see `InterpolationMethod`.
+const INTERPOLATION_BILINEAR: u32 = 0u;
+const INTERPOLATION_MONOTONE_CUBIC: u32 = 1u;
+const INTERPOLATION_CATMULL_ROM: u32 = 2u;
+// Catmull-Rom without clamping, used for the level set.
+const INTERPOLATION_CUBIC: u32 = 3u;
+
+fn interp2d_center_with(
+    q: texture_storage_2d<r32float, read>,
+    x: vec2<f32>,
+    period: vec2<i32>,
+    method: u32,
+) -> f32 {
+    if method == INTERPOLATION_BILINEAR {
+        return interp2d_center(q, x, period);
+    }
+    return cubic2d(q, x, period, method);
+}
+
+fn interp2d_edge_x_with(
+    u: texture_storage_2d<r32float, read>,
+    x: vec2<f32>,
+    period: vec2<i32>,
+    method: u32,
+) -> f32 {
+    if method == INTERPOLATION_BILINEAR {
+        return interp2d_edge_x(u, x, period);
+    }
+    // u[i, j] is located at (i - 0.5, j).
+    return cubic2d(u, x + vec2<f32>(0.5, 0.0), period, method);
+}
+
+fn interp2d_edge_y_with(
+    v: texture_storage_2d<r32float, read>,
+    x: vec2<f32>,
+    period: vec2<i32>,
+    method: u32,
+) -> f32 {
+    if method == INTERPOLATION_BILINEAR {
+        return interp2d_edge_y(v, x, period);
+    }
+    // v[i, j] is located at (i, j - 0.5).
+    return cubic2d(v, x + vec2<f32>(0.0, 0.5), period, method);
+}
+
+// Bicubic interpolation over the 4x4 texels around `x` in texel units.
+// The stencil is wrapped along the periodic axes and clamped to the texture along the others.
+fn cubic2d(
+    q: texture_storage_2d<r32float, read>,
+    x: vec2<f32>,
+    period: vec2<i32>,
+    method: u32,
+) -> f32 {
+    let base_idx = vec2<i32>(floor(x));
+    let t = x - floor(x);
+    let y0 = cubic1d_x(base_idx + vec2<i32>(0, -1), q, t.x, period, method);
+    let y1 = cubic1d_x(base_idx, q, t.x, period, method);
+    let y2 = cubic1d_x(base_idx + vec2<i32>(0, 1), q, t.x, period, method);
+    let y3 = cubic1d_x(base_idx + vec2<i32>(0, 2), q, t.x, period, method);
+
+    return cubic1d(vec4<f32>(y0, y1, y2, y3), t.y, method);
+}
+
+fn cubic1d_x(
+    base_idx: vec2<i32>,
+    q: texture_storage_2d<r32float, read>,
+    t: f32,
+    period: vec2<i32>,
+    method: u32,
+) -> f32 {
+    let y0 = load_clamped(q, base_idx - vec2<i32>(1, 0), period);
+    let y1 = load_clamped(q, base_idx, period);
+    let y2 = load_clamped(q, base_idx + vec2<i32>(1, 0), period);
+    let y3 = load_clamped(q, base_idx + vec2<i32>(2, 0), period);
+
+    return cubic1d(vec4<f32>(y0, y1, y2, y3), t, method);
+}
+
+fn load_clamped(
+    q: texture_storage_2d<r32float, read>,
+    x: vec2<i32>,
+    period: vec2<i32>,
+) -> f32 {
+    let dim = vec2<i32>(textureDimensions(q));
+    return textureLoad(q, clamp(wrap(x, period), vec2<i32>(0), dim - vec2<i32>(1))).x;
+}
+
+// y: values at points [-1, 0, 1, 2]
+// t: interpolant in range [0, 1]
+fn cubic1d(y: vec4<f32>, t: f32, method: u32) -> f32 {
+    var dydx1 = 0.5 * (y.z - y.x);
+    var dydx2 = 0.5 * (y.w - y.y);
+    let delta = y.z - y.y;
+
+    if method == INTERPOLATION_MONOTONE_CUBIC {
+        // Fedkiw et al. 2001: the slopes disagreeing with the sign of the interval are set to zero.
+        if delta == 0.0 {
+            dydx1 = 0.0;
+            dydx2 = 0.0;
+        } else {
+            dydx1 = select(0.0, dydx1, sign(dydx1) == sign(delta));
+            dydx2 = select(0.0, dydx2, sign(dydx2) == sign(delta));
+        }
+    }
+
+    let a0 = y.y;
+    let a1 = dydx1;
+    let a2 = -2.0 * dydx1 - dydx2 + 3.0 * delta;
+    let a3 = dydx1 + dydx2 - 2.0 * delta;
+    let value = a3 * t * t * t + a2 * t * t + a1 * t + a0;
+
+    if method == INTERPOLATION_CATMULL_ROM {
+        return clamp(value, min(y.y, y.z), max(y.y, y.z));
+    }
+    return value;
+}
+
 fn runge_kutta(
     u: texture_storage_2d<r32float, read>,
     v: texture_storage_2d<r32float, read>,
     x: vec2<f32>,
     dt: f32,
+    period: vec2<i32>,
 ) -> vec2<f32> {
-    let velocity = vec2<f32>(interp2d_edge_x(u, x), interp2d_edge_y(v, x));
+    let velocity = vec2<f32>(interp2d_edge_x(u, x, period), interp2d_edge_y(v, x, period));
     let x_mid = x - vec2<f32>(0.5 * dt) * velocity;
-    let velocity_mid = vec2<f32>(interp2d_edge_x(u, x_mid), interp2d_edge_y(v, x_mid));
+    let velocity_mid = vec2<f32>(interp2d_edge_x(u, x_mid, period), interp2d_edge_y(v, x_mid, period));
 
     return x - dt * velocity_mid;
 }
@@ -98,12 +287,13 @@ fn tvd_rk3(
     v: texture_storage_2d<r32float, read>,
     x: vec2<f32>,
     dt: f32,
+    period: vec2<i32>,
 ) -> vec2<f32> {
-    let u0 = vec2<f32>(interp2d_edge_x(u, x), interp2d_edge_y(v, x));
+    let u0 = vec2<f32>(interp2d_edge_x(u, x, period), interp2d_edge_y(v, x, period));
     let x1 = x + dt * u0;
-    let u1 = vec2<f32>(interp2d_edge_x(u, x1), interp2d_edge_y(v, x1));
+    let u1 = vec2<f32>(interp2d_edge_x(u, x1, period), interp2d_edge_y(v, x1, period));
     let x2 = 0.75 * x + 0.25 * (x1 + dt * u1);
-    let u2 = vec2<f32>(interp2d_edge_x(u, x2), interp2d_edge_y(v, x2));
+    let u2 = vec2<f32>(interp2d_edge_x(u, x2, period), interp2d_edge_y(v, x2, period));
 
     return (1.0 / 3.0) * x + (2.0 / 3.0) * (x2 + dt * u2);
 }
\ No newline at end of file
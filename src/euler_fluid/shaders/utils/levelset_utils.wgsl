@@ -1,5 +1,7 @@
 #define_import_path bevy_fluid::levelset_utils
 
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+
 fn project_onto_surface(
     levelset_solid: texture_storage_2d<r32float, read>,
     x: vec2<f32>,
@@ -47,3 +49,33 @@ fn snap_to_free_surface(
     let x_surface = round(vec2<f32>(x) - level * level_gradient);
     return x_surface;
 }
+
+// Curvature at the free surface between two adjacent cells, linearly interpolated by the levelset values.
+fn interface_curvature(
+    level0: f32,
+    level1: f32,
+    curvature0: f32,
+    curvature1: f32,
+) -> f32 {
+    let denom = abs(level0) + abs(level1);
+    if (denom < 1.0e-6) {
+        return 0.5 * (curvature0 + curvature1);
+    }
+    return (abs(level1) * curvature0 + abs(level0) * curvature1) / denom;
+}
+
+// Pressure jump sigma * kappa at the free surface, where the curvature `kappa` is in units of cells.
+fn interface_pressure_jump(constants: SimulationUniform, kappa: f32) -> f32 {
+    return constants.surface_tension * kappa / constants.dx;
+}
+
+// Ghost pressure in the air cell such that the pressure at the free surface equals to `p_interface`.
+fn ghost_pressure(
+    p_liquid: f32,
+    level_liquid: f32,
+    level_air: f32,
+    p_interface: f32,
+) -> f32 {
+    let ratio = level_air / level_liquid;
+    return ratio * p_liquid + (1.0 - ratio) * p_interface;
+}
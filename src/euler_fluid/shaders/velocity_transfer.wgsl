@@ -0,0 +1,295 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::coordinate::{interp2d_center, interp2d_edge_x, interp2d_edge_y, tvd_rk3, wrap};
+#import bevy_fluid::boundary_condition::{grid_period, wrap_periodic_position};
+#import bevy_fluid::hash::hash22;
+
+struct TransferParticle {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+    // Gradients of u and v around the particle, used by APIC.
+    c_u: vec2<f32>,
+    c_v: vec2<f32>,
+}
+
+@group(0) @binding(0) var<storage, read_write> particles_count: atomic<u32>;
+@group(0) @binding(1) var<storage, read_write> particles: array<TransferParticle>;
+@group(0) @binding(2) var<storage, read_write> next_particles_count: atomic<u32>;
+@group(0) @binding(3) var<storage, read_write> next_particles: array<TransferParticle>;
+@group(0) @binding(4) var<storage, read_write> particles_in_cell: array<atomic<u32>>;
+// Pairs of (weighted velocity, weight) in fixed point. u faces followed by v faces.
+@group(0) @binding(5) var<storage, read_write> face_weights: array<atomic<i32>>;
+@group(0) @binding(6) var u0: texture_storage_2d<r32float, read>;
+@group(0) @binding(7) var v0: texture_storage_2d<r32float, read>;
+@group(0) @binding(8) var u1: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(9) var v1: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(10) var u_saved: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(11) var v_saved: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(12) var levelset_air: texture_storage_2d<r32float, read>;
+@group(0) @binding(13) var levelset_solid: texture_storage_2d<r32float, read>;
+
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
+const VELOCITY_TRANSFER_FLIP: u32 = 2u;
+const VELOCITY_TRANSFER_APIC: u32 = 3u;
+
+const PARTICLE_WORKGROUP_SIZE: u32 = 256u;
+const PARTICLES_PER_CELL: u32 = 4u;
+const MAX_PARTICLES_PER_CELL: u32 = 8u;
+const FIXED_POINT_SCALE: f32 = 10000.0;
+
+// Bilinear stencil of the point `x` given in the coordinates where the samples lie on integers.
+struct Stencil {
+    base: vec2<i32>,
+    fraction: vec2<f32>,
+}
+
+fn stencil(x: vec2<f32>) -> Stencil {
+    let base = floor(x);
+    return Stencil(vec2<i32>(base), x - base);
+}
+
+fn corner(k: i32) -> vec2<i32> {
+    return vec2<i32>(k & 1, k >> 1);
+}
+
+fn corner_weight(fraction: vec2<f32>, c: vec2<i32>) -> f32 {
+    let w = select(1.0 - fraction, fraction, c == vec2<i32>(1));
+    return w.x * w.y;
+}
+
+fn corner_weight_gradient(fraction: vec2<f32>, c: vec2<i32>) -> vec2<f32> {
+    let w = select(1.0 - fraction, fraction, c == vec2<i32>(1));
+    let dw = select(vec2<f32>(-1.0), vec2<f32>(1.0), c == vec2<i32>(1));
+    return vec2<f32>(dw.x * w.y, w.x * dw.y);
+}
+
+fn is_inside(x: vec2<i32>, dim: vec2<i32>) -> bool {
+    return all(x >= vec2<i32>(0)) && all(x < dim);
+}
+
+fn flatten(x: vec2<i32>, dim: vec2<i32>) -> u32 {
+    return u32(x.y * dim.x + x.x);
+}
+
+fn num_u_faces() -> u32 {
+    let dim = textureDimensions(u1);
+    return dim.x * dim.y;
+}
+
+fn accumulate(face: u32, weight: f32, value: f32) {
+    atomicAdd(&face_weights[2u * face], i32(round(weight * value * FIXED_POINT_SCALE)));
+    atomicAdd(&face_weights[2u * face + 1u], i32(round(weight * FIXED_POINT_SCALE)));
+}
+
+// Splats `value` to the faces of `dim` around `x`, where the faces lie on integer coordinates.
+// `offset` is the index of the first face in `face_weights` and `axis` the direction normal to the faces.
+fn splat(x: vec2<f32>, value: f32, affine: vec2<f32>, dim: vec2<i32>, offset: u32, axis: vec2<i32>) {
+    let period = grid_period(constants, vec2<i32>(constants.size));
+    let s = stencil(x);
+    for (var k = 0; k < 4; k++) {
+        let c = corner(k);
+        let face = wrap(s.base + c, period);
+        if !is_inside(face, dim) {
+            continue;
+        }
+        let weight = corner_weight(s.fraction, c);
+        let face_value = value + dot(affine, vec2<f32>(c) - s.fraction);
+        accumulate(offset + flatten(face, dim), weight, face_value);
+        // The faces on the periodic edges are stored twice.
+        if dot(period, axis) > 0 && dot(face, axis) == 0 {
+            accumulate(offset + flatten(face + axis * period, dim), weight, face_value);
+        }
+    }
+}
+
+struct GridSample {
+    value: f32,
+    saved: f32,
+    gradient: vec2<f32>,
+}
+
+// Samples the new and the saved velocity component around `x`, where the faces lie on integer coordinates.
+fn gather(
+    q: texture_storage_2d<r32float, read>,
+    q_saved: texture_storage_2d<r32float, read_write>,
+    x: vec2<f32>,
+) -> GridSample {
+    let period = grid_period(constants, vec2<i32>(constants.size));
+    let dim = vec2<i32>(textureDimensions(q));
+    let s = stencil(x);
+    var sample = GridSample(0.0, 0.0, vec2<f32>(0.0));
+    for (var k = 0; k < 4; k++) {
+        let c = corner(k);
+        let face = clamp(wrap(s.base + c, period), vec2<i32>(0), dim - vec2<i32>(1));
+        let weight = corner_weight(s.fraction, c);
+        let value = textureLoad(q, face).r;
+        sample.value += weight * value;
+        sample.saved += weight * textureLoad(q_saved, face).r;
+        sample.gradient += corner_weight_gradient(s.fraction, c) * value;
+    }
+    return sample;
+}
+
+fn cell_of(x: vec2<f32>) -> vec2<i32> {
+    let size = vec2<i32>(constants.size);
+    return clamp(wrap(vec2<i32>(floor(x + vec2<f32>(0.5))), grid_period(constants, size)), vec2<i32>(0), size - vec2<i32>(1));
+}
+
+fn is_liquid(x: vec2<f32>) -> bool {
+    let period = grid_period(constants, vec2<i32>(constants.size));
+    let inside = clamp(x, vec2<f32>(0.0), constants.size - vec2<f32>(1.0));
+    return interp2d_center(levelset_air, inside, period) < 0.0
+        && textureLoad(levelset_solid, cell_of(x)).r >= 0.0;
+}
+
+// Appends the particle to `next_particles` unless the cell already has enough particles.
+fn append(particle: TransferParticle) {
+    let cell = flatten(cell_of(particle.position), vec2<i32>(constants.size));
+    if atomicAdd(&particles_in_cell[cell], 1u) >= MAX_PARTICLES_PER_CELL {
+        return;
+    }
+    let idx = atomicAdd(&next_particles_count, 1u);
+    next_particles[idx] = particle;
+}
+
+@compute @workgroup_size(1, 1, 1)
+fn clear_particles() {
+    atomicStore(&particles_count, 0u);
+}
+
+@compute @workgroup_size(PARTICLE_WORKGROUP_SIZE, 1, 1)
+fn particle_to_grid(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
+    let idx = global_invocation_id.x;
+    if idx == 0u {
+        atomicStore(&next_particles_count, 0u);
+    }
+    if idx >= atomicLoad(&particles_count) {
+        return;
+    }
+
+    let particle = particles[idx];
+    var c_u = vec2<f32>(0.0);
+    var c_v = vec2<f32>(0.0);
+    if constants.velocity_transfer == VELOCITY_TRANSFER_APIC {
+        c_u = particle.c_u;
+        c_v = particle.c_v;
+    }
+    // u(i, j) lies on (i - 0.5, j) and v(i, j) on (i, j - 0.5).
+    splat(
+        particle.position + vec2<f32>(0.5, 0.0),
+        particle.velocity.x,
+        c_u,
+        vec2<i32>(textureDimensions(u1)),
+        0u,
+        vec2<i32>(1, 0),
+    );
+    splat(
+        particle.position + vec2<f32>(0.0, 0.5),
+        particle.velocity.y,
+        c_v,
+        vec2<i32>(textureDimensions(v1)),
+        num_u_faces(),
+        vec2<i32>(0, 1),
+    );
+}
+
+// Normalizes the splatted velocity. Faces without particles keep the velocity advected on the grid.
+// The accumulated weights are cleared for the next step.
+@compute @workgroup_size(1, 64, 1)
+fn grid_velocity_u(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    let dim = vec2<i32>(textureDimensions(u1));
+    let face = 2u * flatten(idx, dim);
+    let weighted_velocity = atomicExchange(&face_weights[face], 0);
+    let weight = atomicExchange(&face_weights[face + 1u], 0);
+    var u = textureLoad(u1, idx).r;
+    if weight > 0 {
+        u = f32(weighted_velocity) / f32(weight);
+    }
+    textureStore(u1, idx, vec4<f32>(u, 0.0, 0.0, 0.0));
+    textureStore(u_saved, idx, vec4<f32>(u, 0.0, 0.0, 0.0));
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn grid_velocity_v(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    let dim = vec2<i32>(textureDimensions(v1));
+    let face = 2u * (num_u_faces() + flatten(idx, dim));
+    let weighted_velocity = atomicExchange(&face_weights[face], 0);
+    let weight = atomicExchange(&face_weights[face + 1u], 0);
+    var v = textureLoad(v1, idx).r;
+    if weight > 0 {
+        v = f32(weighted_velocity) / f32(weight);
+    }
+    textureStore(v1, idx, vec4<f32>(v, 0.0, 0.0, 0.0));
+    textureStore(v_saved, idx, vec4<f32>(v, 0.0, 0.0, 0.0));
+}
+
+// Updates the particle velocity from the grid, then advects the particle.
+// Particles which left the liquid are deleted.
+@compute @workgroup_size(PARTICLE_WORKGROUP_SIZE, 1, 1)
+fn update_particles(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
+    let idx = global_invocation_id.x;
+    if idx >= atomicLoad(&particles_count) {
+        return;
+    }
+
+    var particle = particles[idx];
+    let u = gather(u0, u_saved, particle.position + vec2<f32>(0.5, 0.0));
+    let v = gather(v0, v_saved, particle.position + vec2<f32>(0.0, 0.5));
+    let velocity = vec2<f32>(u.value, v.value);
+    switch constants.velocity_transfer {
+        case VELOCITY_TRANSFER_FLIP: {
+            let flip_velocity = particle.velocity + velocity - vec2<f32>(u.saved, v.saved);
+            particle.velocity = mix(velocity, flip_velocity, constants.flip_ratio);
+        }
+        case VELOCITY_TRANSFER_APIC: {
+            particle.velocity = velocity;
+            particle.c_u = u.gradient;
+            particle.c_v = v.gradient;
+        }
+        default: {
+            particle.velocity = velocity;
+        }
+    }
+
+    let period = grid_period(constants, vec2<i32>(constants.size));
+    let position = wrap_periodic_position(constants, tvd_rk3(u0, v0, particle.position, constants.dt, period));
+    particle.position = clamp(position, vec2<f32>(-0.5), constants.size - vec2<f32>(0.5));
+    if is_liquid(particle.position) {
+        append(particle);
+    }
+}
+
+// Adds particles to the liquid cells which have too few of them, with the velocity of the grid.
+@compute @workgroup_size(8, 8, 1)
+fn reseed_particles(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
+    let cell = vec2<i32>(global_invocation_id.xy);
+    let count = atomicExchange(&particles_in_cell[flatten(cell, vec2<i32>(constants.size))], 0u);
+    if count >= PARTICLES_PER_CELL / 2u {
+        return;
+    }
+
+    let period = grid_period(constants, vec2<i32>(constants.size));
+    for (var k = count; k < PARTICLES_PER_CELL; k++) {
+        let position = vec2<f32>(cell) + hash22(vec2<f32>(cell) + vec2<f32>(f32(k) * 0.25)) - vec2<f32>(0.5);
+        if !is_liquid(position) {
+            continue;
+        }
+        let velocity = vec2<f32>(interp2d_edge_x(u0, position, period), interp2d_edge_y(v0, position, period));
+        let idx = atomicAdd(&next_particles_count, 1u);
+        next_particles[idx] = TransferParticle(position, velocity, vec2<f32>(0.0), vec2<f32>(0.0));
+    }
+}
+
+@compute @workgroup_size(PARTICLE_WORKGROUP_SIZE, 1, 1)
+fn copy_particles(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
+    let idx = global_invocation_id.x;
+    let count = atomicLoad(&next_particles_count);
+    if idx == 0u {
+        atomicStore(&particles_count, count);
+    }
+    if idx < count {
+        particles[idx] = next_particles[idx];
+    }
+}
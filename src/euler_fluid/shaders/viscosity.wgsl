@@ -0,0 +1,177 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::area_fraction::{solid_fraction_u, solid_fraction_v};
+#import bevy_fluid::boundary_condition::{
+    BOUNDARY_INFLOW, BOUNDARY_NO_SLIP, boundary_type, inflow_velocity, outside_edge, wall_slip_u, wall_slip_v, wrap_periodic,
+};
+
+@group(0) @binding(0) var u0: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(1) var v0: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(2) var u1: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(3) var v1: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(4) var u_solid: texture_storage_2d<r32float, read>;
+@group(0) @binding(5) var v_solid: texture_storage_2d<r32float, read>;
+@group(0) @binding(6) var levelset_air0: texture_storage_2d<r32float, read>;
+@group(0) @binding(7) var area_fraction_solid: texture_storage_2d<rgba32float, read>;
+
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
+// Copy the velocity after applying forces to u0 and v0 so that they can be used as the right hand side of
+// (1 - dt * nu * laplacian) u = u*.
+@compute @workgroup_size(1, 64, 1)
+fn copy_u(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = global_invocation_id.xy;
+    if any(idx >= textureDimensions(u1)) {
+        return;
+    }
+    textureStore(u0, idx, textureLoad(u1, idx));
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn copy_v(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = global_invocation_id.xy;
+    if any(idx >= textureDimensions(v1)) {
+        return;
+    }
+    textureStore(v0, idx, textureLoad(v1, idx));
+}
+
+@compute @workgroup_size(1, 64, 1)
+fn diffuse_u_red(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if ((idx.x + idx.y) % 2 == 1) {
+        diffuse_u(idx);
+    }
+}
+
+@compute @workgroup_size(1, 64, 1)
+fn diffuse_u_black(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if ((idx.x + idx.y) % 2 == 0) {
+        diffuse_u(idx);
+    }
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn diffuse_v_red(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if ((idx.x + idx.y) % 2 == 1) {
+        diffuse_v(idx);
+    }
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn diffuse_v_black(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    if ((idx.x + idx.y) % 2 == 0) {
+        diffuse_v(idx);
+    }
+}
+
+const OFFSETS = array<vec2<i32>, 4>(
+    vec2<i32>(-1, 0),
+    vec2<i32>(1, 0),
+    vec2<i32>(0, -1),
+    vec2<i32>(0, 1),
+);
+
+fn diffuse_u(idx: vec2<i32>) {
+    let dim = vec2<i32>(textureDimensions(u1));
+    if any(idx >= dim) {
+        return;
+    }
+    if !is_liquid_face(idx, vec2<i32>(-1, 0)) || solid_fraction_u(area_fraction_solid, idx) == 0.0 {
+        return;
+    }
+
+    let a = constants.viscosity * constants.dt / (constants.dx * constants.dx);
+    var nume = textureLoad(u0, idx).r;
+    var denom = 1.0;
+    for (var i = 0; i < 4; i++) {
+        let j = wrap_periodic(constants, idx + OFFSETS[i], vec2<i32>(constants.size));
+        if any(j < vec2<i32>(0)) || any(j >= dim) {
+            let boundary = boundary_velocity(j, dim);
+            nume += a * boundary.x;
+            denom += a * boundary.z;
+            continue;
+        }
+        if solid_fraction_u(area_fraction_solid, j) == 0.0 {
+            // No-slip at solid boundary. Free-slip walls of the domain are stress free along them.
+            let w = 1.0 - wall_slip_u(constants, j);
+            nume += a * w * textureLoad(u_solid, j).r;
+            denom += a * w;
+        } else if is_liquid_face(j, vec2<i32>(-1, 0)) {
+            nume += a * textureLoad(u1, j).r;
+            denom += a;
+        }
+        // Zero stress at free surface: the neighbor is regarded as having the same velocity.
+    }
+
+    textureStore(u1, idx, vec4<f32>(nume / denom, 0.0, 0.0, 0.0));
+}
+
+fn diffuse_v(idx: vec2<i32>) {
+    let dim = vec2<i32>(textureDimensions(v1));
+    if any(idx >= dim) {
+        return;
+    }
+    if !is_liquid_face(idx, vec2<i32>(0, -1)) || solid_fraction_v(area_fraction_solid, idx) == 0.0 {
+        return;
+    }
+
+    let a = constants.viscosity * constants.dt / (constants.dx * constants.dx);
+    var nume = textureLoad(v0, idx).r;
+    var denom = 1.0;
+    for (var i = 0; i < 4; i++) {
+        let j = wrap_periodic(constants, idx + OFFSETS[i], vec2<i32>(constants.size));
+        if any(j < vec2<i32>(0)) || any(j >= dim) {
+            let boundary = boundary_velocity(j, dim);
+            nume += a * boundary.y;
+            denom += a * boundary.z;
+            continue;
+        }
+        if solid_fraction_v(area_fraction_solid, j) == 0.0 {
+            let w = 1.0 - wall_slip_v(constants, j);
+            nume += a * w * textureLoad(v_solid, j).r;
+            denom += a * w;
+        } else if is_liquid_face(j, vec2<i32>(0, -1)) {
+            nume += a * textureLoad(v1, j).r;
+            denom += a;
+        }
+    }
+
+    textureStore(v1, idx, vec4<f32>(nume / denom, 0.0, 0.0, 0.0));
+}
+
+// Velocity (xy) and weight (z) of the ghost face beyond the domain boundary.
+// No-slip walls and inflows impose their velocity, while the others are stress free.
+fn boundary_velocity(j: vec2<i32>, dim: vec2<i32>) -> vec3<f32> {
+    let edge = outside_edge(j, dim);
+    let kind = boundary_type(constants, edge);
+    if kind == BOUNDARY_NO_SLIP {
+        return vec3<f32>(0.0, 0.0, 1.0);
+    }
+    if kind == BOUNDARY_INFLOW {
+        return vec3<f32>(inflow_velocity(constants, edge), 1.0);
+    }
+    return vec3<f32>(0.0);
+}
+
+// A face is regarded as liquid if either of the adjacent cells is liquid.
+fn is_liquid_face(idx: vec2<i32>, offset: vec2<i32>) -> bool {
+    let dim = vec2<i32>(textureDimensions(levelset_air0));
+    let level0 = textureLoad(levelset_air0, clamp(idx + offset, vec2<i32>(0), dim - 1)).r;
+    let level1 = textureLoad(levelset_air0, clamp(idx, vec2<i32>(0), dim - 1)).r;
+    return min(level0, level1) < 0.0;
+}
@@ -0,0 +1,107 @@
+#import bevy_fluid::fluid_uniform::SimulationUniform;
+#import bevy_fluid::area_fraction::{solid_fraction_u, solid_fraction_v};
+
+@group(0) @binding(0) var u1: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(1) var v1: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(2) var levelset_air0: texture_storage_2d<r32float, read>;
+@group(0) @binding(3) var area_fraction_solid: texture_storage_2d<rgba32float, read>;
+@group(0) @binding(4) var vorticity: texture_storage_2d<r32float, read_write>;
+
+@group(1) @binding(0) var<uniform> constants: SimulationUniform;
+
+@compute @workgroup_size(8, 8, 1)
+fn calculate_vorticity(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    let dim = vec2<i32>(textureDimensions(vorticity));
+    if any(idx >= dim) {
+        return;
+    }
+
+    if textureLoad(levelset_air0, idx).r >= 0.0 {
+        textureStore(vorticity, idx, vec4<f32>(0.0));
+        return;
+    }
+
+    // curl of the velocity at the cell center using cell-centered velocities of neighbors.
+    let dvdx = 0.5 * (velocity_center(idx + vec2<i32>(1, 0)).y - velocity_center(idx - vec2<i32>(1, 0)).y);
+    let dudy = 0.5 * (velocity_center(idx + vec2<i32>(0, 1)).x - velocity_center(idx - vec2<i32>(0, 1)).x);
+    textureStore(vorticity, idx, vec4<f32>(dvdx - dudy, 0.0, 0.0, 0.0));
+}
+
+@compute @workgroup_size(1, 64, 1)
+fn vorticity_confinement_u(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    let dim = vec2<i32>(textureDimensions(u1));
+    if any(idx >= dim) {
+        return;
+    }
+    if !is_inside_liquid(idx, vec2<i32>(-1, 0)) || solid_fraction_u(area_fraction_solid, idx) == 0.0 {
+        return;
+    }
+
+    let force = 0.5 * (confinement_force(idx - vec2<i32>(1, 0)).x + confinement_force(idx).x);
+    let u = textureLoad(u1, idx).r;
+    textureStore(u1, idx, vec4<f32>(u + force * constants.dt, 0.0, 0.0, 0.0));
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn vorticity_confinement_v(
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>,
+) {
+    let idx = vec2<i32>(global_invocation_id.xy);
+    let dim = vec2<i32>(textureDimensions(v1));
+    if any(idx >= dim) {
+        return;
+    }
+    if !is_inside_liquid(idx, vec2<i32>(0, -1)) || solid_fraction_v(area_fraction_solid, idx) == 0.0 {
+        return;
+    }
+
+    let force = 0.5 * (confinement_force(idx - vec2<i32>(0, 1)).y + confinement_force(idx).y);
+    let v = textureLoad(v1, idx).r;
+    textureStore(v1, idx, vec4<f32>(v + force * constants.dt, 0.0, 0.0, 0.0));
+}
+
+fn velocity_center(idx: vec2<i32>) -> vec2<f32> {
+    let dim = vec2<i32>(textureDimensions(levelset_air0));
+    let c = clamp(idx, vec2<i32>(0), dim - 1);
+    let u = 0.5 * (textureLoad(u1, c).r + textureLoad(u1, c + vec2<i32>(1, 0)).r);
+    let v = 0.5 * (textureLoad(v1, c).r + textureLoad(v1, c + vec2<i32>(0, 1)).r);
+    return vec2<f32>(u, v);
+}
+
+fn abs_vorticity(idx: vec2<i32>) -> f32 {
+    let dim = vec2<i32>(textureDimensions(vorticity));
+    return abs(textureLoad(vorticity, clamp(idx, vec2<i32>(0), dim - 1)).r);
+}
+
+// Confinement force f = epsilon * dx * (N x omega), N = grad|omega| / |grad|omega||.
+// Since the velocity is in unit of grid cells per second, dx is cancelled out.
+fn confinement_force(idx: vec2<i32>) -> vec2<f32> {
+    let dim = vec2<i32>(textureDimensions(vorticity));
+    let c = clamp(idx, vec2<i32>(0), dim - 1);
+    let omega = textureLoad(vorticity, c).r;
+    let grad = 0.5 * vec2<f32>(
+        abs_vorticity(c + vec2<i32>(1, 0)) - abs_vorticity(c - vec2<i32>(1, 0)),
+        abs_vorticity(c + vec2<i32>(0, 1)) - abs_vorticity(c - vec2<i32>(0, 1)),
+    );
+    let len = length(grad);
+    if len < 1.0e-6 {
+        return vec2<f32>(0.0);
+    }
+    let n = grad / len;
+
+    return constants.vorticity_confinement * vec2<f32>(n.y * omega, -n.x * omega);
+}
+
+// Confinement force is applied only on the faces whose both adjacent cells are liquid.
+fn is_inside_liquid(idx: vec2<i32>, offset: vec2<i32>) -> bool {
+    let dim = vec2<i32>(textureDimensions(levelset_air0));
+    let level0 = textureLoad(levelset_air0, clamp(idx + offset, vec2<i32>(0), dim - 1)).r;
+    let level1 = textureLoad(levelset_air0, clamp(idx, vec2<i32>(0), dim - 1)).r;
+    return max(level0, level1) < 0.0;
+}
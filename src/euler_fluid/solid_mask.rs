@@ -0,0 +1,373 @@
+use bevy::{
+    asset::{embedded_asset, embedded_path},
+    ecs::query::QueryData,
+    prelude::*,
+    render::{
+        extract_component::ExtractComponent,
+        render_resource::{
+            AsBindGroup, BindGroup, ComputePass, PipelineCache, ShaderType, TextureFormat,
+        },
+    },
+};
+
+use crate::{
+    fluid_uniform::{SimulationUniform, SimulationUniformBindGroup},
+    pipeline::{HasBindGroupLayout, SingleComputePipeline, WORKGROUP_SIZE},
+    plugin::{FluidComputePass, FluidComputePassPlugin},
+    reinitialize_levelset::jump_flooding::{
+        self, JumpFloodingBindGroups, JumpFloodingCalculateSdfResource,
+        JumpFloodingInitializeSeedsResource, JumpFloodingSeedsTextures,
+    },
+    texture::NewTexture,
+    update_solid::UpdateSolidResource,
+};
+
+// Same as the constants in merge_solid_mask.wgsl.
+const ENCODING_BINARY: u32 = 0;
+const ENCODING_SIGNED_DISTANCE: u32 = 1;
+
+pub(crate) struct SolidMaskPlugin;
+
+impl Plugin for SolidMaskPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            FluidComputePassPlugin::<SolidMaskInitializePass>::default(),
+            FluidComputePassPlugin::<SolidMaskMergePass>::default(),
+        ))
+        .add_systems(
+            Update,
+            (setup_solid_masks, update_solid_mask_uniforms).chain(),
+        );
+    }
+}
+
+/// Solid region given by an image, e.g. painted level geometry.
+/// Spawn it as a child of [`crate::euler_fluid::FluidSettings`]. The image is stretched over `size`
+/// and placed by the [`Transform`] relative to the center of the fluid, in the same units as the grid.
+/// The mask is merged into the solid level set every physics step, moving at `linear_velocity`.
+/// Unlike obstacles, masks do not receive forces from the fluid.
+///
+/// Replacing `image` or `encoding` takes effect once the new image is loaded.
+#[derive(Component, Clone, Debug)]
+#[require(Transform)]
+pub struct FluidSolidMask {
+    pub image: Handle<Image>,
+    pub encoding: SolidMaskEncoding,
+    /// Size of the mask in the local space of the fluid.
+    pub size: Vec2,
+    pub linear_velocity: Vec2,
+}
+
+impl FluidSolidMask {
+    pub fn new(image: Handle<Image>, size: Vec2) -> Self {
+        Self {
+            image,
+            encoding: SolidMaskEncoding::default(),
+            size,
+            linear_velocity: Vec2::ZERO,
+        }
+    }
+
+    pub fn with_encoding(mut self, encoding: SolidMaskEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+}
+
+/// How the red channel of a [`FluidSolidMask`] image is interpreted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SolidMaskEncoding {
+    /// Solid where the value is greater than 0.5.
+    /// The boundary of the solid is aligned with the texels.
+    #[default]
+    Binary,
+    /// Same as [`SolidMaskEncoding::Binary`], but converted to a signed distance field once
+    /// by jump flooding when the image is loaded, for smoother boundaries.
+    BinaryToSdf,
+    /// Signed distance to the boundary in texels, negative inside the solid.
+    SignedDistance,
+}
+
+/// Converts a binary mask into a level set of ±1, as the input of jump flooding.
+pub(crate) struct SolidMaskInitializePass;
+
+impl FluidComputePass for SolidMaskInitializePass {
+    type Pipeline = SolidMaskInitializePipeline;
+    type Resource = SolidMaskInitializeResource;
+    type BG = SolidMaskInitializeBindGroup;
+
+    fn register_assets(app: &mut App) {
+        embedded_asset!(app, "shaders/initialize_solid_mask.wgsl");
+    }
+}
+
+#[derive(Component, ExtractComponent, Clone, AsBindGroup)]
+pub(crate) struct SolidMaskInitializeResource {
+    #[texture(0, sample_type = "float", filterable = false, visibility(compute))]
+    pub mask: Handle<Image>,
+    #[storage_texture(1, image_format = R32Float, access = WriteOnly)]
+    pub levelset_binary: Handle<Image>,
+    pub size: UVec2,
+}
+
+#[derive(Resource)]
+pub(crate) struct SolidMaskInitializePipeline {
+    pub pipeline: SingleComputePipeline,
+}
+
+impl FromWorld for SolidMaskInitializePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let pipeline = SingleComputePipeline::new::<SolidMaskInitializeResource>(
+            world,
+            "SolidMaskInitializePipeline",
+            embedded_path!("shaders/initialize_solid_mask.wgsl"),
+            "initialize_solid_mask",
+        );
+
+        Self { pipeline }
+    }
+}
+
+impl HasBindGroupLayout for SolidMaskInitializePipeline {
+    fn bind_group_layout(&self) -> &bevy::render::render_resource::BindGroupLayoutDescriptor {
+        &self.pipeline.bind_group_layout
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct SolidMaskInitializeBindGroup {
+    pub bind_group: BindGroup,
+}
+
+impl From<BindGroup> for SolidMaskInitializeBindGroup {
+    fn from(bind_group: BindGroup) -> Self {
+        Self { bind_group }
+    }
+}
+
+/// Bind groups of a binary mask to be converted into a signed distance field.
+#[derive(QueryData)]
+pub(crate) struct SolidMaskSdfQuery {
+    pub entity: Entity,
+    pub resource: &'static SolidMaskInitializeResource,
+    pub bind_group: &'static SolidMaskInitializeBindGroup,
+    pub jump_flooding_bind_groups: &'static JumpFloodingBindGroups,
+    pub uniform_bind_group: &'static SimulationUniformBindGroup,
+}
+
+pub(crate) fn dispatch_sdf_conversion(
+    world: &World,
+    pipeline_cache: &PipelineCache,
+    pass: &mut ComputePass,
+    mask: SolidMaskSdfQueryItem,
+) {
+    // Images of any size are covered by whole workgroups.
+    let num_workgroups = (mask.resource.size + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
+    let initialize_pipeline = world.resource::<SolidMaskInitializePipeline>();
+    initialize_pipeline.pipeline.dispatch(
+        pipeline_cache,
+        pass,
+        &mask.bind_group.bind_group,
+        num_workgroups.extend(1),
+    );
+
+    jump_flooding::dispatch(
+        world,
+        pipeline_cache,
+        pass,
+        mask.jump_flooding_bind_groups,
+        mask.uniform_bind_group,
+        num_workgroups * WORKGROUP_SIZE,
+    );
+}
+
+/// Merges a mask into the solid level set and velocities of the fluid, after obstacles are rasterized.
+pub(crate) struct SolidMaskMergePass;
+
+impl FluidComputePass for SolidMaskMergePass {
+    type Pipeline = SolidMaskMergePipeline;
+    type Resource = SolidMaskMergeResource;
+    type BG = SolidMaskMergeBindGroup;
+
+    fn register_assets(app: &mut App) {
+        embedded_asset!(app, "shaders/merge_solid_mask.wgsl");
+    }
+}
+
+#[derive(ShaderType, Clone, Copy, Default)]
+pub(crate) struct SolidMaskUniform {
+    /// From the local space of the fluid to texel coordinates of the mask.
+    pub local_to_texel: Mat4,
+    /// Length of a texel in the local space of the fluid.
+    pub texel_length: f32,
+    pub encoding: u32,
+    pub linear_velocity: Vec2,
+}
+
+#[derive(Component, ExtractComponent, Clone, AsBindGroup)]
+pub(crate) struct SolidMaskMergeResource {
+    #[storage_texture(0, image_format = R32Float, access = WriteOnly)]
+    pub u_solid: Handle<Image>,
+    #[storage_texture(1, image_format = R32Float, access = WriteOnly)]
+    pub v_solid: Handle<Image>,
+    #[storage_texture(2, image_format = R32Float, access = ReadWrite)]
+    pub levelset_solid: Handle<Image>,
+    #[texture(3, sample_type = "float", filterable = false, visibility(compute))]
+    pub levelset_mask: Handle<Image>,
+    #[uniform(4)]
+    pub uniform: SolidMaskUniform,
+    pub mask_size: UVec2,
+    /// Image and encoding which the textures are created for.
+    pub source: (AssetId<Image>, SolidMaskEncoding),
+}
+
+#[derive(Resource)]
+pub(crate) struct SolidMaskMergePipeline {
+    pub pipeline: SingleComputePipeline,
+}
+
+impl FromWorld for SolidMaskMergePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let pipeline = SingleComputePipeline::new::<SolidMaskMergeResource>(
+            world,
+            "SolidMaskMergePipeline",
+            embedded_path!("shaders/merge_solid_mask.wgsl"),
+            "merge_solid_mask",
+        );
+
+        Self { pipeline }
+    }
+}
+
+impl HasBindGroupLayout for SolidMaskMergePipeline {
+    fn bind_group_layout(&self) -> &bevy::render::render_resource::BindGroupLayoutDescriptor {
+        &self.pipeline.bind_group_layout
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct SolidMaskMergeBindGroup {
+    pub bind_group: BindGroup,
+}
+
+impl From<BindGroup> for SolidMaskMergeBindGroup {
+    fn from(bind_group: BindGroup) -> Self {
+        Self { bind_group }
+    }
+}
+
+fn setup_solid_masks(
+    mut commands: Commands,
+    query: Query<(
+        Entity,
+        &FluidSolidMask,
+        &ChildOf,
+        Option<&SolidMaskMergeResource>,
+    )>,
+    query_removed: Query<Entity, (With<SolidMaskMergeResource>, Without<FluidSolidMask>)>,
+    query_fluid: Query<&UpdateSolidResource>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for entity in &query_removed {
+        remove_solid_mask_resources(&mut commands.entity(entity));
+    }
+
+    for (entity, mask, child_of, resource) in &query {
+        let Ok(update_solid_resource) = query_fluid.get(child_of.parent()) else {
+            continue;
+        };
+        let source = (mask.image.id(), mask.encoding);
+        if resource.is_some_and(|resource| {
+            resource.source == source
+                && resource.levelset_solid == update_solid_resource.levelset_solid
+        }) {
+            continue;
+        }
+        // Retried in the next frames until the image is loaded.
+        let Some(mask_size) = images.get(&mask.image).map(Image::size) else {
+            continue;
+        };
+
+        let mut entity_commands = commands.entity(entity);
+        remove_solid_mask_resources(&mut entity_commands);
+
+        let levelset_mask = match mask.encoding {
+            SolidMaskEncoding::Binary | SolidMaskEncoding::SignedDistance => mask.image.clone(),
+            SolidMaskEncoding::BinaryToSdf => {
+                let levelset_binary =
+                    images.new_texture_storage(mask_size, TextureFormat::R32Float);
+                let levelset_sdf = images.new_texture_storage(mask_size, TextureFormat::R32Float);
+                let seeds0 = images.new_texture_storage(mask_size, TextureFormat::Rg32Float);
+                let seeds1 = images.new_texture_storage(mask_size, TextureFormat::Rg32Float);
+
+                entity_commands.insert((
+                    SolidMaskInitializeResource {
+                        mask: mask.image.clone(),
+                        levelset_binary: levelset_binary.clone(),
+                        size: mask_size,
+                    },
+                    JumpFloodingInitializeSeedsResource {
+                        levelset_air1: levelset_binary.clone(),
+                    },
+                    JumpFloodingCalculateSdfResource {
+                        levelset_air0: levelset_sdf.clone(),
+                        levelset_air1: levelset_binary,
+                    },
+                    JumpFloodingSeedsTextures([seeds0, seeds1]),
+                    // Default boundary conditions, so that jump flooding of the mask does not wrap around.
+                    SimulationUniform::default(),
+                ));
+                levelset_sdf
+            }
+        };
+
+        entity_commands.insert(SolidMaskMergeResource {
+            u_solid: update_solid_resource.u_solid.clone(),
+            v_solid: update_solid_resource.v_solid.clone(),
+            levelset_solid: update_solid_resource.levelset_solid.clone(),
+            levelset_mask,
+            uniform: SolidMaskUniform::default(),
+            mask_size,
+            source,
+        });
+    }
+}
+
+fn remove_solid_mask_resources(entity_commands: &mut EntityCommands) {
+    entity_commands.remove::<(
+        SolidMaskMergeResource,
+        SolidMaskInitializeResource,
+        JumpFloodingInitializeSeedsResource,
+        JumpFloodingCalculateSdfResource,
+        JumpFloodingSeedsTextures,
+        SimulationUniform,
+    )>();
+}
+
+fn update_solid_mask_uniforms(
+    mut query: Query<(&FluidSolidMask, &Transform, &mut SolidMaskMergeResource)>,
+) {
+    for (mask, transform, mut resource) in &mut query {
+        let mask_size = resource.mask_size.as_vec2();
+        // [-0.5 * size, 0.5 * size] -> [0, mask_size] with y-axis pointing down
+        let local_to_texel = Mat4::from_translation((0.5 * mask_size).extend(0.0))
+            * Mat4::from_scale(Vec3::new(
+                mask_size.x / mask.size.x,
+                -mask_size.y / mask.size.y,
+                1.0,
+            ))
+            * transform.to_matrix().inverse();
+
+        resource.uniform = SolidMaskUniform {
+            local_to_texel,
+            texel_length: mask.size.x * transform.scale.x / mask_size.x,
+            encoding: match mask.encoding {
+                SolidMaskEncoding::Binary => ENCODING_BINARY,
+                SolidMaskEncoding::BinaryToSdf | SolidMaskEncoding::SignedDistance => {
+                    ENCODING_SIGNED_DISTANCE
+                }
+            },
+            linear_velocity: mask.linear_velocity,
+        };
+    }
+}
@@ -31,6 +31,8 @@ pub(crate) struct JacobiIterationResource {
     pub levelset_air0: Handle<Image>,
     #[storage_texture(4, image_format = R32Float, access = ReadOnly)]
     pub levelset_solid: Handle<Image>,
+    #[storage_texture(5, image_format = R32Float, access = ReadOnly)]
+    pub curvature: Handle<Image>,
 }
 
 #[derive(Component, Clone, ExtractComponent, AsBindGroup)]
@@ -45,6 +47,8 @@ pub(crate) struct JacobiIterationReverseResource {
     pub levelset_air0: Handle<Image>,
     #[storage_texture(4, image_format = R32Float, access = ReadOnly)]
     pub levelset_solid: Handle<Image>,
+    #[storage_texture(5, image_format = R32Float, access = ReadOnly)]
+    pub curvature: Handle<Image>,
 }
 
 #[derive(Resource)]
@@ -33,6 +33,8 @@ pub(crate) struct SolveUResource {
     pub levelset_air0: Handle<Image>,
     #[storage_texture(5, image_format = Rgba32Float, access = ReadOnly)]
     pub area_fraction_solid: Handle<Image>,
+    #[storage_texture(6, image_format = R32Float, access = ReadOnly)]
+    pub curvature: Handle<Image>,
 }
 
 #[derive(Component, Clone, ExtractComponent, AsBindGroup)]
@@ -49,6 +51,8 @@ pub(crate) struct SolveVResource {
     pub levelset_air0: Handle<Image>,
     #[storage_texture(5, image_format = Rgba32Float, access = ReadOnly)]
     pub area_fraction_solid: Handle<Image>,
+    #[storage_texture(6, image_format = R32Float, access = ReadOnly)]
+    pub curvature: Handle<Image>,
 }
 
 #[derive(Resource)]
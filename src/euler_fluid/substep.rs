@@ -0,0 +1,97 @@
+use bevy::{
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        gpu_readback::{Readback, ReadbackComplete},
+        storage::ShaderStorageBuffer,
+    },
+};
+
+use crate::{
+    diagnostics::{
+        component::{FluidMaxVelocityMagnitude, GridSize},
+        max_velocity::{MaxVelocityPass, MaxVelocityResource},
+    },
+    plugin::FluidComputePassPlugin,
+    settings::{FluidSettings, FluidTextures},
+};
+
+/// Upper limit of the number of substeps in one physics step.
+pub const MAX_SUBSTEPS: u32 = 8;
+
+pub(crate) struct SubstepPlugin;
+
+impl Plugin for SubstepPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            FluidComputePassPlugin::<MaxVelocityPass>::default(),
+            ExtractComponentPlugin::<GridSize>::default(),
+            ExtractComponentPlugin::<FluidSubsteps>::default(),
+        ))
+        .add_systems(Update, setup_max_velocity);
+    }
+}
+
+/// Number of substeps the fluid is advanced in one physics step.
+/// It is updated every step from the maximum velocity of the fluid so that the CFL number does not exceed
+/// `cfl` in [`FluidSettings`]. The maximum velocity is read back from GPU, so it lags behind by a few frames.
+#[derive(Component, ExtractComponent, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FluidSubsteps(pub u32);
+
+impl Default for FluidSubsteps {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl FluidSubsteps {
+    /// Number of substeps required to keep `max_velocity * dt / substeps` (in cells) below `cfl`.
+    pub(crate) fn from_cfl(max_velocity: f32, dt: f32, cfl: f32) -> Self {
+        if cfl <= 0.0 {
+            return Self::default();
+        }
+
+        let substeps = (max_velocity * dt / cfl).ceil() as u32;
+        Self(substeps.clamp(1, MAX_SUBSTEPS))
+    }
+}
+
+fn setup_max_velocity(
+    mut commands: Commands,
+    mut buffers: ResMut<Assets<ShaderStorageBuffer>>,
+    query: Query<(Entity, &FluidTextures, &FluidSettings), Added<FluidTextures>>,
+) {
+    for (entity, fluid_textures, settings) in &query {
+        let max_velocity_resource =
+            MaxVelocityResource::new(&mut buffers, fluid_textures, settings.size);
+        let max_velocity_entity = commands
+            .spawn((
+                max_velocity_resource.clone(),
+                Readback::buffer(max_velocity_resource.sum.clone()),
+                GridSize(settings.size),
+            ))
+            .observe(max_velocity_readback)
+            .id();
+
+        commands
+            .entity(entity)
+            .insert(FluidMaxVelocityMagnitude(0.0))
+            .add_child(max_velocity_entity);
+    }
+}
+
+fn max_velocity_readback(
+    trigger: On<ReadbackComplete>,
+    mut fluid_query: Query<&mut FluidMaxVelocityMagnitude, With<FluidSettings>>,
+    query: Query<&ChildOf, With<MaxVelocityResource>>,
+) {
+    let child = query.get(trigger.entity);
+    let Ok(child) = child else {
+        return;
+    };
+
+    let Ok(mut max_velocity) = fluid_query.get_mut(child.parent()) else {
+        return;
+    };
+    max_velocity.0 = trigger.event().to_shader_type();
+}
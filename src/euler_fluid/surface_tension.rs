@@ -0,0 +1,79 @@
+use bevy::{
+    asset::{embedded_asset, embedded_path},
+    prelude::*,
+    render::{
+        extract_component::ExtractComponent,
+        render_resource::{AsBindGroup, BindGroup, BindGroupLayoutDescriptor},
+    },
+};
+
+use crate::{
+    pipeline::{HasBindGroupLayout, SingleComputePipeline},
+    plugin::FluidComputePass,
+};
+
+/// Computes the curvature of the free surface from `levelset_air`.
+/// The curvature is used by the pressure solvers to impose the pressure jump due to surface tension.
+pub(crate) struct CurvaturePass;
+
+impl FluidComputePass for CurvaturePass {
+    type Pipeline = CurvaturePipeline;
+    type Resource = CurvatureResource;
+    type BG = CurvatureBindGroup;
+
+    fn register_assets(app: &mut App) {
+        embedded_asset!(app, "shaders/curvature.wgsl");
+    }
+}
+
+#[derive(Component, Clone, ExtractComponent, AsBindGroup)]
+pub(crate) struct CurvatureResource {
+    #[storage_texture(0, image_format = R32Float, access = ReadOnly)]
+    pub levelset_air: Handle<Image>,
+    #[storage_texture(1, image_format = R32Float, access = WriteOnly)]
+    pub curvature: Handle<Image>,
+}
+
+impl CurvatureResource {
+    pub fn new(levelset_air: &Handle<Image>, curvature: &Handle<Image>) -> Self {
+        Self {
+            levelset_air: levelset_air.clone(),
+            curvature: curvature.clone(),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct CurvaturePipeline {
+    pub pipeline: SingleComputePipeline,
+}
+
+impl FromWorld for CurvaturePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let pipeline = SingleComputePipeline::new_with_uniform::<CurvatureResource>(
+            world,
+            "CurvaturePipeline",
+            embedded_path!("shaders/curvature.wgsl"),
+            "calculate_curvature",
+        );
+
+        Self { pipeline }
+    }
+}
+
+impl HasBindGroupLayout for CurvaturePipeline {
+    fn bind_group_layout(&self) -> &BindGroupLayoutDescriptor {
+        &self.pipeline.bind_group_layout
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct CurvatureBindGroup {
+    pub bind_group: BindGroup,
+}
+
+impl From<BindGroup> for CurvatureBindGroup {
+    fn from(bind_group: BindGroup) -> Self {
+        Self { bind_group }
+    }
+}
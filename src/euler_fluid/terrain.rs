@@ -0,0 +1,274 @@
+#[cfg(feature = "avian2d")]
+use avian2d::prelude::Collider;
+use bevy::{platform::collections::HashMap, prelude::*, render::render_resource::TextureFormat};
+
+#[cfg(feature = "avian2d")]
+use crate::obstacle::FluidIgnore;
+use crate::{settings::FluidSettings, texture::ImageForCS, update_solid::UpdateSolidResource};
+
+/// Distance in cells from the terrain boundary within which the signed distance is evaluated.
+/// Cells farther than this are filled with `LARGE_LEVEL`, keeping only the sign.
+const NARROW_BAND: i32 = 16;
+/// Same as `LARGE_FLOAT` in update_solid.wgsl.
+const LARGE_LEVEL: f32 = 1.0e6;
+
+pub(crate) struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, rasterize_terrain);
+
+        #[cfg(feature = "avian2d")]
+        app.add_systems(
+            Update,
+            insert_terrain_from_colliders.before(rasterize_terrain),
+        );
+    }
+}
+
+/// Static level geometry, such as the ground of a level, over which the fluid flows.
+/// Unlike obstacles, terrain is rasterized once into a cached signed distance texture for each fluid,
+/// and rasterized again only when the terrain, its transform or the transform of the fluid changes.
+/// Terrain neither moves the fluid nor receives forces from it.
+///
+/// With the `avian2d` feature, this component is inserted to entities with a trimesh or heightfield `Collider`.
+#[derive(Component, Clone, Debug, Default)]
+#[require(Transform)]
+pub struct FluidTerrain {
+    /// Line segments enclosing the solid region, in the local space.
+    /// The enclosed regions must not overlap each other.
+    pub boundary: Vec<[Vec2; 2]>,
+}
+
+impl FluidTerrain {
+    /// Terrain filled with triangles. The boundary consists of the edges not shared by two triangles.
+    pub fn from_trimesh(vertices: &[Vec2], indices: &[[u32; 3]]) -> Self {
+        let mut edges = HashMap::<(u32, u32), u32>::default();
+        for [a, b, c] in indices {
+            for (i, j) in [(*a, *b), (*b, *c), (*c, *a)] {
+                *edges.entry((i.min(j), i.max(j))).or_default() += 1;
+            }
+        }
+
+        let boundary = edges
+            .into_iter()
+            .filter(|(_, count)| *count == 1)
+            .map(|((i, j), _)| [vertices[i as usize], vertices[j as usize]])
+            .collect();
+        Self { boundary }
+    }
+
+    /// Terrain below a heightfield, following the convention of parry2d `HeightField`:
+    /// heights are evenly spaced in x over `[-0.5, 0.5]`, and both axes are multiplied by `scale`.
+    pub fn from_heightfield(heights: &[f32], scale: Vec2) -> Self {
+        if heights.len() < 2 {
+            return Self::default();
+        }
+
+        let step = 1.0 / (heights.len() - 1) as f32;
+        let points = heights
+            .iter()
+            .enumerate()
+            .map(|(i, height)| Vec2::new(-0.5 + step * i as f32, *height) * scale)
+            .collect::<Vec<_>>();
+
+        // Close the surface far below so that everything under the heightfield is solid.
+        let bottom = points.iter().map(|p| p.y).fold(f32::INFINITY, f32::min) - LARGE_LEVEL;
+        let first = points[0];
+        let last = points[points.len() - 1];
+        let mut boundary = points.windows(2).map(|w| [w[0], w[1]]).collect::<Vec<_>>();
+        boundary.push([last, Vec2::new(last.x, bottom)]);
+        boundary.push([Vec2::new(last.x, bottom), Vec2::new(first.x, bottom)]);
+        boundary.push([Vec2::new(first.x, bottom), first]);
+        Self { boundary }
+    }
+}
+
+/// Initial terrain level set of a fluid, which has no terrain.
+pub(crate) fn new_levelset_terrain(images: &mut Assets<Image>, size: UVec2) -> Handle<Image> {
+    let levels = vec![LARGE_LEVEL; size.element_product() as usize];
+    images.add(levelset_image(size, &levels))
+}
+
+fn levelset_image(size: UVec2, levels: &[f32]) -> Image {
+    let mut image = Image::new_texture_storage(size, TextureFormat::R32Float);
+    image.data = Some(
+        levels
+            .iter()
+            .flat_map(|level| level.to_le_bytes())
+            .collect(),
+    );
+    image
+}
+
+#[cfg(feature = "avian2d")]
+fn insert_terrain_from_colliders(
+    mut commands: Commands,
+    query: Query<(Entity, &Collider, Has<FluidIgnore>), Changed<Collider>>,
+) {
+    for (entity, collider, is_ignored) in &query {
+        if is_ignored {
+            continue;
+        }
+        let terrain = if let Some(trimesh) = collider.shape().as_trimesh() {
+            FluidTerrain::from_trimesh(trimesh.vertices(), trimesh.indices())
+        } else if let Some(heightfield) = collider.shape().as_heightfield() {
+            FluidTerrain::from_heightfield(heightfield.heights(), heightfield.scale())
+        } else {
+            continue;
+        };
+        commands.entity(entity).insert(terrain);
+    }
+}
+
+fn rasterize_terrain(
+    query_terrain: Query<(Ref<FluidTerrain>, Ref<GlobalTransform>)>,
+    mut removed_terrain: RemovedComponents<FluidTerrain>,
+    query_fluid: Query<(Entity, &FluidSettings, Ref<UpdateSolidResource>)>,
+    query_fluid_transform: Query<Ref<Transform>, With<FluidSettings>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let terrain_changed = removed_terrain.read().count() > 0
+        || query_terrain
+            .iter()
+            .any(|(terrain, transform)| terrain.is_changed() || transform.is_changed());
+
+    let mut boundary = None;
+    for (entity, settings, update_solid_resource) in &query_fluid {
+        let transform = query_fluid_transform.get(entity).ok();
+        let fluid_changed =
+            update_solid_resource.is_added() || transform.as_ref().is_some_and(|t| t.is_changed());
+        if !terrain_changed && !fluid_changed {
+            continue;
+        }
+
+        let boundary = boundary.get_or_insert_with(|| {
+            query_terrain
+                .iter()
+                .flat_map(|(terrain, transform)| {
+                    let transform = *transform;
+                    terrain.into_inner().boundary.iter().map(move |[a, b]| {
+                        [
+                            transform.transform_point(a.extend(0.0)).truncate(),
+                            transform.transform_point(b.extend(0.0)).truncate(),
+                        ]
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let fluid_transform = transform.map_or(Transform::IDENTITY, |t| *t);
+        let levels = rasterize_levelset(boundary, &fluid_transform, settings.size);
+        let _ = images.insert(
+            &update_solid_resource.levelset_terrain,
+            levelset_image(settings.size, &levels),
+        );
+    }
+}
+
+/// Signed distance from the cell centers to the `boundary` given in world space. Negative inside.
+fn rasterize_levelset(
+    boundary: &[[Vec2; 2]],
+    fluid_transform: &Transform,
+    size: UVec2,
+) -> Vec<f32> {
+    let num_cells = size.element_product() as usize;
+    if boundary.is_empty() {
+        return vec![LARGE_LEVEL; num_cells];
+    }
+
+    // Same mapping between cells and world as `to_world` in update_solid.wgsl.
+    let to_world = fluid_transform.to_matrix();
+    let to_cell = to_world.inverse();
+    let half_size = size.as_vec2() * 0.5;
+    let cell_to_world = |x: Vec2| {
+        to_world
+            .transform_point3(Vec3::new(x.x - half_size.x, half_size.y - x.y, 0.0))
+            .truncate()
+    };
+    let world_to_cell = |p: Vec2| {
+        let local = to_cell.transform_point3(p.extend(0.0));
+        Vec2::new(local.x + half_size.x, half_size.y - local.y)
+    };
+    let boundary_cell = boundary
+        .iter()
+        .map(|[a, b]| [world_to_cell(*a), world_to_cell(*b)])
+        .collect::<Vec<_>>();
+
+    // Cells enclosed by the boundary, by the parity of crossings along each row.
+    let mut inside = vec![false; num_cells];
+    let mut crossings = vec![];
+    for j in 0..size.y {
+        let y = j as f32;
+        crossings.clear();
+        for [a, b] in &boundary_cell {
+            if (a.y > y) != (b.y > y) {
+                crossings.push(a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y));
+            }
+        }
+        crossings.sort_by(f32::total_cmp);
+        for pair in crossings.chunks_exact(2) {
+            let start = (pair[0].ceil().max(0.0) as u32).min(size.x);
+            let end = (pair[1].ceil().max(0.0) as u32).min(size.x);
+            for i in start..end {
+                inside[(j * size.x + i) as usize] = true;
+            }
+        }
+    }
+
+    // Distance to the boundary within the narrow band around each segment.
+    let mut distance = vec![LARGE_LEVEL; num_cells];
+    let last_cell = size.as_ivec2() - 1;
+    for ([a, b], [a_cell, b_cell]) in boundary.iter().zip(&boundary_cell) {
+        let min = (a_cell.min(*b_cell).floor().as_ivec2() - NARROW_BAND).max(IVec2::ZERO);
+        let max = (a_cell.max(*b_cell).ceil().as_ivec2() + NARROW_BAND).min(last_cell);
+        for j in min.y..=max.y {
+            for i in min.x..=max.x {
+                let idx = (j * size.x as i32 + i) as usize;
+                let x = cell_to_world(Vec2::new(i as f32, j as f32));
+                distance[idx] = distance[idx].min(distance_to_line_segment(x, *a, *b));
+            }
+        }
+    }
+
+    inside
+        .into_iter()
+        .zip(distance)
+        .map(|(inside, distance)| if inside { -distance } else { distance })
+        .collect()
+}
+
+fn distance_to_line_segment(x: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    if length_squared == 0.0 {
+        return x.distance(a);
+    }
+    let t = ((x - a).dot(ab) / length_squared).clamp(0.0, 1.0);
+    x.distance(a + t * ab)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{rasterize_levelset, FluidTerrain, LARGE_LEVEL};
+
+    #[test]
+    fn rasterize_heightfield() {
+        let size = UVec2::splat(64);
+        // Flat ground at y = -8 across the whole domain.
+        let terrain = FluidTerrain::from_heightfield(&[-8.0, -8.0], Vec2::new(128.0, 1.0));
+        let levels = rasterize_levelset(&terrain.boundary, &Transform::IDENTITY, size);
+
+        let level_at = |x: Vec2| {
+            // world to cell
+            let cell = Vec2::new(x.x + 32.0, 32.0 - x.y).as_uvec2();
+            levels[(cell.y * size.x + cell.x) as usize]
+        };
+        assert_eq!(level_at(Vec2::new(0.0, 0.0)), 8.0);
+        assert_eq!(level_at(Vec2::new(10.0, -10.0)), -2.0);
+        assert_eq!(level_at(Vec2::new(0.0, 30.0)), LARGE_LEVEL);
+        assert_eq!(level_at(Vec2::new(0.0, -30.0)), -LARGE_LEVEL);
+    }
+}
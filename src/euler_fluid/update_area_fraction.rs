@@ -47,7 +47,7 @@ pub(crate) struct UpdateAreaFractionPipeline {
 
 impl FromWorld for UpdateAreaFractionPipeline {
     fn from_world(world: &mut World) -> Self {
-        let pipeline = SingleComputePipeline::new::<UpdateAreaFractionResource>(
+        let pipeline = SingleComputePipeline::new_with_uniform::<UpdateAreaFractionResource>(
             world,
             "UpdateAreaFractionPipeline",
             embedded_path!("shaders/update_area_fraction.wgsl"),
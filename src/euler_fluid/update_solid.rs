@@ -32,6 +32,10 @@ pub(crate) struct UpdateSolidResource {
     pub levelset_solid: Handle<Image>,
     #[storage_texture(3, image_format = R32Sint, access = WriteOnly)]
     pub solid_id: Handle<Image>,
+    #[storage_texture(4, image_format = R32Float, access = ReadOnly)]
+    pub levelset_terrain: Handle<Image>,
+    #[storage_texture(5, image_format = Rg32Sint, access = WriteOnly)]
+    pub solid_id_face: Handle<Image>,
 }
 
 #[derive(Resource)]
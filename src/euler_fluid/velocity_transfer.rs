@@ -0,0 +1,394 @@
+use std::fmt::Display;
+
+use bevy::{
+    asset::{embedded_asset, embedded_path},
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_asset::RenderAssets,
+        render_resource::{
+            AsBindGroup, BindGroup, BindGroupLayoutDescriptor, CachedComputePipelineId,
+            ComputePass, PipelineCache, ShaderType, TextureFormat,
+        },
+        renderer::RenderDevice,
+        storage::{GpuShaderStorageBuffer, ShaderStorageBuffer},
+        texture::{FallbackImage, GpuImage},
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+use crate::{
+    fluid_uniform::{uniform_bind_group_layout_desc, SimulationUniformBindGroup},
+    pipeline::{is_pipeline_loaded, queue_compute_pipeline, DispatchFluidPass},
+    texture::NewTexture,
+};
+
+const WORKGROUP_SIZE_PARTICLE: u32 = 256;
+const MAX_PARTICLES_PER_CELL: usize = 8;
+
+pub(crate) struct VelocityTransferPlugin;
+
+impl Plugin for VelocityTransferPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/velocity_transfer.wgsl");
+
+        app.add_plugins((
+            ExtractComponentPlugin::<VelocityTransferMethod>::default(),
+            ExtractComponentPlugin::<VelocityTransferResource>::default(),
+        ));
+
+        let render_app = app.sub_app_mut(RenderApp);
+        render_app.add_systems(
+            Render,
+            prepare_bind_groups.in_set(RenderSystems::PrepareBindGroups),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let render_app = app.sub_app_mut(RenderApp);
+        render_app.init_resource::<VelocityTransferPipelines>();
+    }
+}
+
+/// Carries the velocity on particles instead of advecting it on the grid.
+/// Each step, the particle velocities are splatted to the grid before the forces are applied,
+/// and the grid velocity after the pressure projection is transferred back to the particles, which are then advected.
+/// Particles are seeded in the liquid cells and deleted once they leave the liquid.
+/// Faces without particles around them keep the velocity advected with [`crate::advection::AdvectionMethod`].
+///
+/// The particles are allocated only if the method is other than [`VelocityTransferMethod::Grid`] when the fluid is spawned.
+#[derive(Component, ExtractComponent, Clone, Copy, Debug, Default, PartialEq)]
+pub enum VelocityTransferMethod {
+    /// The velocity is advected on the grid only.
+    #[default]
+    Grid,
+    /// Particle-In-Cell. Particles take the grid velocity. As dissipative as the semi-Lagrangian advection.
+    PIC,
+    /// Fluid-Implicit-Particle. Particles are updated by the change of the grid velocity, which keeps the small-scale motion
+    /// but gets noisy. The result is blended with PIC by `flip_ratio`, typically around 0.95.
+    FLIP { flip_ratio: f32 },
+    /// Affine Particle-In-Cell (Jiang et al. 2015). Particles carry the velocity gradient as well,
+    /// which preserves the rotation without the noise of FLIP.
+    APIC,
+}
+
+impl VelocityTransferMethod {
+    pub(crate) fn as_u32(&self) -> u32 {
+        match self {
+            VelocityTransferMethod::Grid => 0,
+            VelocityTransferMethod::PIC => 1,
+            VelocityTransferMethod::FLIP { .. } => 2,
+            VelocityTransferMethod::APIC => 3,
+        }
+    }
+
+    pub(crate) fn flip_ratio(&self) -> f32 {
+        match self {
+            VelocityTransferMethod::FLIP { flip_ratio } => *flip_ratio,
+            _ => 0.0,
+        }
+    }
+}
+
+impl Display for VelocityTransferMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VelocityTransferMethod::Grid => {
+                write!(f, "Grid")
+            }
+            VelocityTransferMethod::PIC => {
+                write!(f, "PIC")
+            }
+            VelocityTransferMethod::FLIP { flip_ratio } => {
+                write!(f, "FLIP ({flip_ratio})")
+            }
+            VelocityTransferMethod::APIC => {
+                write!(f, "APIC")
+            }
+        }
+    }
+}
+
+#[derive(Clone, ShaderType)]
+pub(crate) struct TransferParticle {
+    position: Vec2,
+    velocity: Vec2,
+    c_u: Vec2,
+    c_v: Vec2,
+}
+
+impl TransferParticle {
+    const ZERO: TransferParticle = TransferParticle {
+        position: Vec2::ZERO,
+        velocity: Vec2::ZERO,
+        c_u: Vec2::ZERO,
+        c_v: Vec2::ZERO,
+    };
+}
+
+/// Inserted only if [`VelocityTransferMethod`] is other than [`VelocityTransferMethod::Grid`].
+#[derive(Component, Clone, ExtractComponent, AsBindGroup)]
+pub(crate) struct VelocityTransferResource {
+    #[storage(0, visibility(compute))]
+    pub particles_count: Handle<ShaderStorageBuffer>,
+    #[storage(1, visibility(compute))]
+    pub particles: Handle<ShaderStorageBuffer>,
+    #[storage(2, visibility(compute))]
+    pub next_particles_count: Handle<ShaderStorageBuffer>,
+    #[storage(3, visibility(compute))]
+    pub next_particles: Handle<ShaderStorageBuffer>,
+    #[storage(4, visibility(compute))]
+    pub particles_in_cell: Handle<ShaderStorageBuffer>,
+    #[storage(5, visibility(compute))]
+    pub face_weights: Handle<ShaderStorageBuffer>,
+    #[storage_texture(6, image_format = R32Float, access = ReadOnly)]
+    pub u0: Handle<Image>,
+    #[storage_texture(7, image_format = R32Float, access = ReadOnly)]
+    pub v0: Handle<Image>,
+    #[storage_texture(8, image_format = R32Float, access = ReadWrite)]
+    pub u1: Handle<Image>,
+    #[storage_texture(9, image_format = R32Float, access = ReadWrite)]
+    pub v1: Handle<Image>,
+    #[storage_texture(10, image_format = R32Float, access = ReadWrite)]
+    pub u_saved: Handle<Image>,
+    #[storage_texture(11, image_format = R32Float, access = ReadWrite)]
+    pub v_saved: Handle<Image>,
+    #[storage_texture(12, image_format = R32Float, access = ReadOnly)]
+    pub levelset_air: Handle<Image>,
+    #[storage_texture(13, image_format = R32Float, access = ReadOnly)]
+    pub levelset_solid: Handle<Image>,
+}
+
+impl VelocityTransferResource {
+    pub fn new(
+        images: &mut ResMut<Assets<Image>>,
+        buffers: &mut ResMut<Assets<ShaderStorageBuffer>>,
+        size: UVec2,
+        u: [&Handle<Image>; 2],
+        v: [&Handle<Image>; 2],
+        levelset_air: &Handle<Image>,
+        levelset_solid: &Handle<Image>,
+    ) -> Self {
+        let [u0, u1] = u;
+        let [v0, v1] = v;
+        let size_u = size + UVec2::new(1, 0);
+        let size_v = size + UVec2::new(0, 1);
+        let num_cells = size.element_product() as usize;
+        let num_faces = (size_u.element_product() + size_v.element_product()) as usize;
+
+        let particles_data = ShaderStorageBuffer::from(vec![
+            TransferParticle::ZERO;
+            num_cells * MAX_PARTICLES_PER_CELL
+        ]);
+
+        Self {
+            particles_count: buffers.add(ShaderStorageBuffer::from(0u32)),
+            particles: buffers.add(particles_data.clone()),
+            next_particles_count: buffers.add(ShaderStorageBuffer::from(0u32)),
+            next_particles: buffers.add(particles_data),
+            particles_in_cell: buffers.add(ShaderStorageBuffer::from(vec![0u32; num_cells])),
+            face_weights: buffers.add(ShaderStorageBuffer::from(vec![0i32; 2 * num_faces])),
+            u0: u0.clone(),
+            v0: v0.clone(),
+            u1: u1.clone(),
+            v1: v1.clone(),
+            u_saved: images.new_texture_storage(size_u, TextureFormat::R32Float),
+            v_saved: images.new_texture_storage(size_v, TextureFormat::R32Float),
+            levelset_air: levelset_air.clone(),
+            levelset_solid: levelset_solid.clone(),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct VelocityTransferPipelines {
+    clear_particles_pipeline: CachedComputePipelineId,
+    particle_to_grid_pipeline: CachedComputePipelineId,
+    grid_velocity_u_pipeline: CachedComputePipelineId,
+    grid_velocity_v_pipeline: CachedComputePipelineId,
+    update_particles_pipeline: CachedComputePipelineId,
+    reseed_particles_pipeline: CachedComputePipelineId,
+    copy_particles_pipeline: CachedComputePipelineId,
+    bind_group_layout: BindGroupLayoutDescriptor,
+}
+
+impl FromWorld for VelocityTransferPipelines {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let bind_group_layout =
+            VelocityTransferResource::bind_group_layout_descriptor(render_device);
+        let layouts = vec![bind_group_layout.clone(), uniform_bind_group_layout_desc()];
+
+        let mut queue = |label: &'static str, entry_point: &'static str| {
+            queue_compute_pipeline(
+                world,
+                label,
+                embedded_path!("shaders/velocity_transfer.wgsl"),
+                entry_point,
+                layouts.clone(),
+            )
+        };
+
+        Self {
+            clear_particles_pipeline: queue("ClearTransferParticlesPipeline", "clear_particles"),
+            particle_to_grid_pipeline: queue("ParticleToGridPipeline", "particle_to_grid"),
+            grid_velocity_u_pipeline: queue("GridVelocityUPipeline", "grid_velocity_u"),
+            grid_velocity_v_pipeline: queue("GridVelocityVPipeline", "grid_velocity_v"),
+            update_particles_pipeline: queue("UpdateTransferParticlesPipeline", "update_particles"),
+            reseed_particles_pipeline: queue("ReseedTransferParticlesPipeline", "reseed_particles"),
+            copy_particles_pipeline: queue("CopyTransferParticlesPipeline", "copy_particles"),
+            bind_group_layout,
+        }
+    }
+}
+
+impl VelocityTransferPipelines {
+    pub fn is_ready(&self, pipeline_cache: &PipelineCache) -> bool {
+        [
+            self.clear_particles_pipeline,
+            self.particle_to_grid_pipeline,
+            self.grid_velocity_u_pipeline,
+            self.grid_velocity_v_pipeline,
+            self.update_particles_pipeline,
+            self.reseed_particles_pipeline,
+            self.copy_particles_pipeline,
+        ]
+        .into_iter()
+        .all(|pipeline| is_pipeline_loaded(pipeline_cache, pipeline))
+    }
+
+    /// Removes all the particles. They are seeded again at the end of the first step.
+    pub fn dispatch_initialize(
+        &self,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        bind_group: &VelocityTransferBindGroup,
+        uniform_bind_group: &SimulationUniformBindGroup,
+    ) {
+        let clear_particles_pipeline = pipeline_cache
+            .get_compute_pipeline(self.clear_particles_pipeline)
+            .unwrap();
+
+        pass.push_debug_group("Clear velocity transfer particles");
+        pass.set_bind_group(0, &bind_group.bind_group, &[]);
+        pass.set_bind_group(
+            1,
+            &uniform_bind_group.bind_group,
+            &[uniform_bind_group.index],
+        );
+        pass.set_pipeline(clear_particles_pipeline);
+        pass.dispatch_workgroups(1, 1, 1);
+        pass.pop_debug_group();
+    }
+
+    /// Overwrites the advected velocity (u1, v1) by the particle velocities, and saves it for FLIP.
+    pub fn dispatch_particle_to_grid(
+        &self,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        bind_group: &VelocityTransferBindGroup,
+        uniform_bind_group: &SimulationUniformBindGroup,
+        size: UVec2,
+    ) {
+        let particle_to_grid_pipeline = pipeline_cache
+            .get_compute_pipeline(self.particle_to_grid_pipeline)
+            .unwrap();
+        let grid_velocity_u_pipeline = pipeline_cache
+            .get_compute_pipeline(self.grid_velocity_u_pipeline)
+            .unwrap();
+        let grid_velocity_v_pipeline = pipeline_cache
+            .get_compute_pipeline(self.grid_velocity_v_pipeline)
+            .unwrap();
+
+        pass.push_debug_group("Particle to grid");
+        pass.set_bind_group(0, &bind_group.bind_group, &[]);
+        pass.set_bind_group(
+            1,
+            &uniform_bind_group.bind_group,
+            &[uniform_bind_group.index],
+        );
+        pass.set_pipeline(particle_to_grid_pipeline);
+        pass.dispatch_workgroups(num_workgroups_particle(size), 1, 1);
+
+        pass.set_pipeline(grid_velocity_u_pipeline);
+        pass.dispatch_x_major(size);
+        pass.set_pipeline(grid_velocity_v_pipeline);
+        pass.dispatch_y_major(size);
+        pass.pop_debug_group();
+    }
+
+    /// Transfers the projected velocity (u0, v0) back to the particles and advects them.
+    pub fn dispatch_grid_to_particle(
+        &self,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        bind_group: &VelocityTransferBindGroup,
+        uniform_bind_group: &SimulationUniformBindGroup,
+        size: UVec2,
+    ) {
+        let update_particles_pipeline = pipeline_cache
+            .get_compute_pipeline(self.update_particles_pipeline)
+            .unwrap();
+        let reseed_particles_pipeline = pipeline_cache
+            .get_compute_pipeline(self.reseed_particles_pipeline)
+            .unwrap();
+        let copy_particles_pipeline = pipeline_cache
+            .get_compute_pipeline(self.copy_particles_pipeline)
+            .unwrap();
+
+        pass.push_debug_group("Grid to particle");
+        pass.set_bind_group(0, &bind_group.bind_group, &[]);
+        pass.set_bind_group(
+            1,
+            &uniform_bind_group.bind_group,
+            &[uniform_bind_group.index],
+        );
+        pass.set_pipeline(update_particles_pipeline);
+        pass.dispatch_workgroups(num_workgroups_particle(size), 1, 1);
+
+        pass.set_pipeline(reseed_particles_pipeline);
+        pass.dispatch_center(size);
+
+        pass.set_pipeline(copy_particles_pipeline);
+        pass.dispatch_workgroups(num_workgroups_particle(size), 1, 1);
+        pass.pop_debug_group();
+    }
+}
+
+fn num_workgroups_particle(size: UVec2) -> u32 {
+    (size.element_product() * MAX_PARTICLES_PER_CELL as u32).div_ceil(WORKGROUP_SIZE_PARTICLE)
+}
+
+#[derive(Component)]
+pub(crate) struct VelocityTransferBindGroup {
+    pub bind_group: BindGroup,
+}
+
+fn prepare_bind_groups<'a>(
+    mut commands: Commands,
+    pipelines: Res<VelocityTransferPipelines>,
+    query: Query<(Entity, &VelocityTransferResource)>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    mut param: (
+        Res<'a, RenderAssets<GpuImage>>,
+        Res<'a, FallbackImage>,
+        Res<'a, RenderAssets<GpuShaderStorageBuffer>>,
+    ),
+) {
+    for (entity, resource) in &query {
+        let bind_group = resource
+            .as_bind_group(
+                &pipelines.bind_group_layout,
+                &render_device,
+                &pipeline_cache,
+                &mut param,
+            )
+            .unwrap()
+            .bind_group;
+
+        commands
+            .entity(entity)
+            .insert(VelocityTransferBindGroup { bind_group });
+    }
+}
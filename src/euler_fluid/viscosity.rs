@@ -0,0 +1,219 @@
+use bevy::{
+    asset::{embedded_asset, embedded_path},
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_resource::{
+            AsBindGroup, BindGroup, BindGroupLayoutDescriptor, CachedComputePipelineId,
+            ComputePass, PipelineCache,
+        },
+        renderer::RenderDevice,
+    },
+};
+
+use crate::{
+    fluid_uniform::{uniform_bind_group_layout_desc, SimulationUniformBindGroup},
+    pipeline::{is_pipeline_loaded, queue_compute_pipeline, DispatchFluidPass, HasBindGroupLayout},
+    plugin::{FluidComputePass, FluidComputePassPlugin},
+    projection::gauss_seidel::GaussSeidelConfig,
+};
+
+pub(crate) struct ViscosityPlugin;
+
+impl Plugin for ViscosityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            FluidComputePassPlugin::<ViscosityPass>::default(),
+            ExtractComponentPlugin::<ViscositySolver>::default(),
+        ));
+    }
+}
+
+pub(crate) struct ViscosityPass;
+
+impl FluidComputePass for ViscosityPass {
+    type Pipeline = ViscosityPipeline;
+    type Resource = ViscosityResource;
+    type BG = ViscosityBindGroup;
+
+    fn register_assets(app: &mut App) {
+        embedded_asset!(app, "shaders/viscosity.wgsl");
+    }
+}
+
+/// Iterative solver used for the implicit viscous diffusion.
+/// The diffusion is solved with Red-Black Gauss-Seidel iterations on each velocity component.
+/// Highly viscous fluids (e.g. large [`crate::settings::FluidSettings::viscosity`]) may require more iterations to converge.
+#[derive(Component, ExtractComponent, Clone, Debug, Default)]
+pub struct ViscositySolver(pub GaussSeidelConfig);
+
+/// Resources for implicit viscosity solve.
+/// `u0` and `v0` are used as the right hand side of the linear system, since they are not used
+/// between advection and velocity update by pressure.
+#[derive(Component, ExtractComponent, Clone, AsBindGroup)]
+pub(crate) struct ViscosityResource {
+    #[storage_texture(0, image_format = R32Float, access = ReadWrite)]
+    pub u0: Handle<Image>,
+    #[storage_texture(1, image_format = R32Float, access = ReadWrite)]
+    pub v0: Handle<Image>,
+    #[storage_texture(2, image_format = R32Float, access = ReadWrite)]
+    pub u1: Handle<Image>,
+    #[storage_texture(3, image_format = R32Float, access = ReadWrite)]
+    pub v1: Handle<Image>,
+    #[storage_texture(4, image_format = R32Float, access = ReadOnly)]
+    pub u_solid: Handle<Image>,
+    #[storage_texture(5, image_format = R32Float, access = ReadOnly)]
+    pub v_solid: Handle<Image>,
+    #[storage_texture(6, image_format = R32Float, access = ReadOnly)]
+    pub levelset_air0: Handle<Image>,
+    #[storage_texture(7, image_format = Rgba32Float, access = ReadOnly)]
+    pub area_fraction_solid: Handle<Image>,
+}
+
+#[derive(Resource)]
+pub(crate) struct ViscosityPipeline {
+    copy_u_pipeline: CachedComputePipelineId,
+    copy_v_pipeline: CachedComputePipelineId,
+    diffuse_u_pipelines: [CachedComputePipelineId; 2],
+    diffuse_v_pipelines: [CachedComputePipelineId; 2],
+    bind_group_layout: BindGroupLayoutDescriptor,
+}
+
+impl FromWorld for ViscosityPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let bind_group_layout = ViscosityResource::bind_group_layout_descriptor(render_device);
+        let uniform_bind_group_layout = uniform_bind_group_layout_desc();
+        let layouts = vec![bind_group_layout.clone(), uniform_bind_group_layout];
+
+        let copy_u_pipeline = queue_compute_pipeline(
+            world,
+            "ViscosityCopyUPipeline",
+            embedded_path!("shaders/viscosity.wgsl"),
+            "copy_u",
+            layouts.clone(),
+        );
+        let copy_v_pipeline = queue_compute_pipeline(
+            world,
+            "ViscosityCopyVPipeline",
+            embedded_path!("shaders/viscosity.wgsl"),
+            "copy_v",
+            layouts.clone(),
+        );
+        let diffuse_u_red_pipeline = queue_compute_pipeline(
+            world,
+            "ViscosityDiffuseURedPipeline",
+            embedded_path!("shaders/viscosity.wgsl"),
+            "diffuse_u_red",
+            layouts.clone(),
+        );
+        let diffuse_u_black_pipeline = queue_compute_pipeline(
+            world,
+            "ViscosityDiffuseUBlackPipeline",
+            embedded_path!("shaders/viscosity.wgsl"),
+            "diffuse_u_black",
+            layouts.clone(),
+        );
+        let diffuse_v_red_pipeline = queue_compute_pipeline(
+            world,
+            "ViscosityDiffuseVRedPipeline",
+            embedded_path!("shaders/viscosity.wgsl"),
+            "diffuse_v_red",
+            layouts.clone(),
+        );
+        let diffuse_v_black_pipeline = queue_compute_pipeline(
+            world,
+            "ViscosityDiffuseVBlackPipeline",
+            embedded_path!("shaders/viscosity.wgsl"),
+            "diffuse_v_black",
+            layouts,
+        );
+
+        Self {
+            copy_u_pipeline,
+            copy_v_pipeline,
+            diffuse_u_pipelines: [diffuse_u_red_pipeline, diffuse_u_black_pipeline],
+            diffuse_v_pipelines: [diffuse_v_red_pipeline, diffuse_v_black_pipeline],
+            bind_group_layout,
+        }
+    }
+}
+
+impl ViscosityPipeline {
+    pub fn is_ready(&self, pipeline_cache: &PipelineCache) -> bool {
+        is_pipeline_loaded(pipeline_cache, self.copy_u_pipeline)
+            && is_pipeline_loaded(pipeline_cache, self.copy_v_pipeline)
+            && self
+                .diffuse_u_pipelines
+                .iter()
+                .chain(self.diffuse_v_pipelines.iter())
+                .all(|pipeline| is_pipeline_loaded(pipeline_cache, *pipeline))
+    }
+
+    pub fn dispatch(
+        &self,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        bind_group: &BindGroup,
+        uniform_bind_group: &SimulationUniformBindGroup,
+        size: UVec2,
+        solver: &ViscositySolver,
+    ) {
+        let copy_u_pipeline = pipeline_cache
+            .get_compute_pipeline(self.copy_u_pipeline)
+            .unwrap();
+        let copy_v_pipeline = pipeline_cache
+            .get_compute_pipeline(self.copy_v_pipeline)
+            .unwrap();
+        let [diffuse_u_red_pipeline, diffuse_u_black_pipeline] = self
+            .diffuse_u_pipelines
+            .map(|pipeline| pipeline_cache.get_compute_pipeline(pipeline).unwrap());
+        let [diffuse_v_red_pipeline, diffuse_v_black_pipeline] = self
+            .diffuse_v_pipelines
+            .map(|pipeline| pipeline_cache.get_compute_pipeline(pipeline).unwrap());
+
+        pass.push_debug_group("Viscosity");
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.set_bind_group(
+            1,
+            &uniform_bind_group.bind_group,
+            &[uniform_bind_group.index],
+        );
+
+        pass.set_pipeline(copy_u_pipeline);
+        pass.dispatch_x_major(size);
+        pass.set_pipeline(copy_v_pipeline);
+        pass.dispatch_y_major(size);
+
+        for _ in 0..solver.0.num_iterations {
+            pass.set_pipeline(diffuse_u_red_pipeline);
+            pass.dispatch_x_major(size);
+            pass.set_pipeline(diffuse_u_black_pipeline);
+            pass.dispatch_x_major(size);
+
+            pass.set_pipeline(diffuse_v_red_pipeline);
+            pass.dispatch_y_major(size);
+            pass.set_pipeline(diffuse_v_black_pipeline);
+            pass.dispatch_y_major(size);
+        }
+        pass.pop_debug_group();
+    }
+}
+
+impl HasBindGroupLayout for ViscosityPipeline {
+    fn bind_group_layout(&self) -> &BindGroupLayoutDescriptor {
+        &self.bind_group_layout
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct ViscosityBindGroup {
+    pub bind_group: BindGroup,
+}
+
+impl From<BindGroup> for ViscosityBindGroup {
+    fn from(bind_group: BindGroup) -> Self {
+        Self { bind_group }
+    }
+}
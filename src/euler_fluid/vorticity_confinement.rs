@@ -0,0 +1,180 @@
+use bevy::{
+    asset::{embedded_asset, embedded_path},
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_resource::{
+            AsBindGroup, BindGroup, BindGroupLayoutDescriptor, CachedComputePipelineId,
+            ComputePass, PipelineCache,
+        },
+        renderer::RenderDevice,
+    },
+};
+
+use crate::{
+    fluid_uniform::{uniform_bind_group_layout_desc, SimulationUniformBindGroup},
+    pipeline::{is_pipeline_loaded, queue_compute_pipeline, DispatchFluidPass, HasBindGroupLayout},
+    plugin::{FluidComputePass, FluidComputePassPlugin},
+};
+
+pub(crate) struct VorticityConfinementPlugin;
+
+impl Plugin for VorticityConfinementPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            FluidComputePassPlugin::<VorticityConfinementPass>::default(),
+            ExtractComponentPlugin::<VorticityConfinement>::default(),
+        ));
+    }
+}
+
+/// Vorticity confinement force to restore small vortices smeared out by numerical dissipation.
+/// Insert this component to the entity with [`crate::settings::FluidSettings`] to enable it.
+/// The force is applied only inside the liquid.
+/// # Fields
+/// * `strength`: Dimensionless strength of the confinement force. Typical value is in the range of 0.1 to 1.0.
+#[derive(Component, ExtractComponent, Clone, Debug)]
+pub struct VorticityConfinement {
+    pub strength: f32,
+}
+
+impl Default for VorticityConfinement {
+    fn default() -> Self {
+        Self { strength: 0.3 }
+    }
+}
+
+pub(crate) struct VorticityConfinementPass;
+
+impl FluidComputePass for VorticityConfinementPass {
+    type Pipeline = VorticityConfinementPipeline;
+    type Resource = VorticityConfinementResource;
+    type BG = VorticityConfinementBindGroup;
+
+    fn register_assets(app: &mut App) {
+        embedded_asset!(app, "shaders/vorticity_confinement.wgsl");
+    }
+}
+
+#[derive(Component, ExtractComponent, Clone, AsBindGroup)]
+pub(crate) struct VorticityConfinementResource {
+    #[storage_texture(0, image_format = R32Float, access = ReadWrite)]
+    pub u1: Handle<Image>,
+    #[storage_texture(1, image_format = R32Float, access = ReadWrite)]
+    pub v1: Handle<Image>,
+    #[storage_texture(2, image_format = R32Float, access = ReadOnly)]
+    pub levelset_air0: Handle<Image>,
+    #[storage_texture(3, image_format = Rgba32Float, access = ReadOnly)]
+    pub area_fraction_solid: Handle<Image>,
+    #[storage_texture(4, image_format = R32Float, access = ReadWrite)]
+    pub vorticity: Handle<Image>,
+}
+
+#[derive(Resource)]
+pub(crate) struct VorticityConfinementPipeline {
+    vorticity_pipeline: CachedComputePipelineId,
+    confinement_u_pipeline: CachedComputePipelineId,
+    confinement_v_pipeline: CachedComputePipelineId,
+    bind_group_layout: BindGroupLayoutDescriptor,
+}
+
+impl FromWorld for VorticityConfinementPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let bind_group_layout =
+            VorticityConfinementResource::bind_group_layout_descriptor(render_device);
+        let uniform_bind_group_layout = uniform_bind_group_layout_desc();
+        let layouts = vec![bind_group_layout.clone(), uniform_bind_group_layout];
+
+        let vorticity_pipeline = queue_compute_pipeline(
+            world,
+            "CalculateVorticityPipeline",
+            embedded_path!("shaders/vorticity_confinement.wgsl"),
+            "calculate_vorticity",
+            layouts.clone(),
+        );
+        let confinement_u_pipeline = queue_compute_pipeline(
+            world,
+            "VorticityConfinementUPipeline",
+            embedded_path!("shaders/vorticity_confinement.wgsl"),
+            "vorticity_confinement_u",
+            layouts.clone(),
+        );
+        let confinement_v_pipeline = queue_compute_pipeline(
+            world,
+            "VorticityConfinementVPipeline",
+            embedded_path!("shaders/vorticity_confinement.wgsl"),
+            "vorticity_confinement_v",
+            layouts,
+        );
+
+        Self {
+            vorticity_pipeline,
+            confinement_u_pipeline,
+            confinement_v_pipeline,
+            bind_group_layout,
+        }
+    }
+}
+
+impl VorticityConfinementPipeline {
+    pub fn is_ready(&self, pipeline_cache: &PipelineCache) -> bool {
+        is_pipeline_loaded(pipeline_cache, self.vorticity_pipeline)
+            && is_pipeline_loaded(pipeline_cache, self.confinement_u_pipeline)
+            && is_pipeline_loaded(pipeline_cache, self.confinement_v_pipeline)
+    }
+
+    pub fn dispatch(
+        &self,
+        pipeline_cache: &PipelineCache,
+        pass: &mut ComputePass,
+        bind_group: &BindGroup,
+        uniform_bind_group: &SimulationUniformBindGroup,
+        size: UVec2,
+    ) {
+        let vorticity_pipeline = pipeline_cache
+            .get_compute_pipeline(self.vorticity_pipeline)
+            .unwrap();
+        let confinement_u_pipeline = pipeline_cache
+            .get_compute_pipeline(self.confinement_u_pipeline)
+            .unwrap();
+        let confinement_v_pipeline = pipeline_cache
+            .get_compute_pipeline(self.confinement_v_pipeline)
+            .unwrap();
+
+        pass.push_debug_group("Vorticity confinement");
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.set_bind_group(
+            1,
+            &uniform_bind_group.bind_group,
+            &[uniform_bind_group.index],
+        );
+
+        pass.set_pipeline(vorticity_pipeline);
+        pass.dispatch_center(size);
+
+        pass.set_pipeline(confinement_u_pipeline);
+        pass.dispatch_x_major(size);
+        pass.set_pipeline(confinement_v_pipeline);
+        pass.dispatch_y_major(size);
+        pass.pop_debug_group();
+    }
+}
+
+impl HasBindGroupLayout for VorticityConfinementPipeline {
+    fn bind_group_layout(&self) -> &BindGroupLayoutDescriptor {
+        &self.bind_group_layout
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct VorticityConfinementBindGroup {
+    pub bind_group: BindGroup,
+}
+
+impl From<BindGroup> for VorticityConfinementBindGroup {
+    fn from(bind_group: BindGroup) -> Self {
+        Self { bind_group }
+    }
+}
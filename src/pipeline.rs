@@ -5,7 +5,7 @@ use bevy::{
     prelude::*,
     render::{
         render_resource::{
-            AsBindGroup, BindGroup, BindGroupLayoutDescriptor, CachedComputePipelineId,
+            AsBindGroup, BindGroup, BindGroupLayoutDescriptor, Buffer, CachedComputePipelineId,
             CachedPipelineState, ComputePass, ComputePipelineDescriptor, PipelineCache,
         },
         renderer::RenderDevice,
@@ -164,6 +164,26 @@ pub(crate) fn queue_compute_pipeline(
     })
 }
 
+/// Number of workgroups given on CPU, or read from a buffer on GPU at the byte offset.
+#[derive(Clone, Copy)]
+pub(crate) enum NumWorkgroups<'a> {
+    Direct(UVec3),
+    Indirect(&'a Buffer, u64),
+}
+
+impl NumWorkgroups<'_> {
+    pub fn dispatch(self, pass: &mut ComputePass) {
+        match self {
+            NumWorkgroups::Direct(num_workgroups) => {
+                pass.dispatch_workgroups(num_workgroups.x, num_workgroups.y, num_workgroups.z);
+            }
+            NumWorkgroups::Indirect(buffer, offset) => {
+                pass.dispatch_workgroups_indirect(buffer, offset);
+            }
+        }
+    }
+}
+
 pub trait DispatchFluidPass {
     fn dispatch_center(&mut self, size: UVec2);
 
@@ -0,0 +1,78 @@
+//! Runs the fluid headless on a GPU and checks the force by the boundary quadrature of the pressure,
+//! as sampled by sample_forces.wgsl, on a fixed obstacle and on a floating rigid body.
+//! A GPU adapter is required, so it is ignored by default:
+//! `cargo test --test boundary_quadrature -- --ignored`
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_eulerian_fluid::{
+    fluid_to_solid::SolidCouplingMethod,
+    obstacle::{FluidObstacle, FluidObstacleForces},
+    physics_time::PhysicsFrameInfo,
+    projection::{conjugate_gradient::ConjugateGradientConfig, ProjectionMethod},
+    settings::{FluidMode, FluidSettings},
+};
+use common::{new_app, GRAVITY, LENGTH_UNIT, NUM_FRAMES, RHO};
+
+#[test]
+#[ignore = "requires a GPU adapter"]
+fn immersed_box_gets_buoyancy_of_its_area() {
+    let mut app = new_app();
+
+    // A closed domain of a single fluid at rest, whose pressure is hydrostatic.
+    app.world_mut().spawn((
+        FluidSettings {
+            rho: RHO,
+            gravity: Vec2::Y * GRAVITY,
+            size: UVec2::splat(64),
+            mode: FluidMode::SinglePhase,
+            ..default()
+        },
+        ProjectionMethod::ConjugateGradient(ConjugateGradientConfig::default()),
+        SolidCouplingMethod::BoundaryQuadrature,
+    ));
+    let half_size = Vec2::new(6.0, 3.0);
+    let obstacle = app
+        .world_mut()
+        .spawn((
+            FluidObstacle::new(Rectangle { half_size }),
+            Transform::from_xyz(0.3, -4.7, 0.0),
+        ))
+        .id();
+
+    let mut forces = Vec::with_capacity(NUM_FRAMES);
+    for _ in 0..NUM_FRAMES {
+        app.update();
+        forces.push(
+            app.world()
+                .get::<FluidObstacleForces>(obstacle)
+                .unwrap()
+                .force,
+        );
+    }
+
+    // Forces are handed over multiplied by the time step and divided by the grid length.
+    let delta_secs = app.world().resource::<PhysicsFrameInfo>().delta_secs;
+    let grid_length = 1.0 / LENGTH_UNIT;
+    let area = 4.0 * half_size.x * half_size.y;
+    let expected = RHO * GRAVITY * area * grid_length * delta_secs;
+    let force = forces[NUM_FRAMES - 10..].iter().sum::<Vec2>() / 10.0;
+    assert!(
+        (force.y - expected).abs() < 0.05 * expected && force.x.abs() < 0.05 * expected,
+        "force: {force}, expected buoyancy: {expected}"
+    );
+}
+
+/// A dynamic box of half the density of the liquid floats with half of its height under the free surface.
+#[cfg(feature = "avian2d")]
+#[test]
+#[ignore = "requires a GPU adapter"]
+fn half_dense_box_floats_at_half_immersion() {
+    let (draft, height) = common::floating_box_draft(SolidCouplingMethod::BoundaryQuadrature);
+    assert!(
+        (draft - 0.5 * height).abs() < 0.1 * height,
+        "draft: {draft}, expected half the height: {}",
+        0.5 * height
+    );
+}
@@ -0,0 +1,110 @@
+//! Headless app and scenes shared by the tests running the fluid on a GPU.
+
+use std::time::Duration;
+
+#[cfg(feature = "avian2d")]
+use avian2d::prelude::{
+    Collider, ColliderDensity, Gravity, LinearDamping, LockedAxes, PhysicsPlugins, Position,
+    RigidBody,
+};
+use bevy::{
+    prelude::*, render::pipelined_rendering::PipelinedRenderingPlugin, time::TimeUpdateStrategy,
+    window::ExitCondition, winit::WinitPlugin,
+};
+use bevy_eulerian_fluid::FluidPlugin;
+#[cfg(feature = "avian2d")]
+use bevy_eulerian_fluid::{
+    fluid_source::{FluidSource, FluidSourceMode, FluidSourceOneshot, FluidSourceShape},
+    fluid_to_solid::SolidCouplingMethod,
+    projection::{conjugate_gradient::ConjugateGradientConfig, ProjectionMethod},
+    settings::{FluidMode, FluidSettings},
+};
+
+pub const LENGTH_UNIT: f32 = 1.0;
+pub const RHO: f32 = 1.0;
+pub const GRAVITY: f32 = 9.8;
+pub const NUM_FRAMES: usize = 300;
+
+pub fn new_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .build()
+            .disable::<WinitPlugin>()
+            .disable::<PipelinedRenderingPlugin>()
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                ..default()
+            }),
+    )
+    .add_plugins(FluidPlugin::new(LENGTH_UNIT))
+    // A physics step per frame.
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1.0 / 64.0,
+    )));
+    #[cfg(feature = "avian2d")]
+    app.add_plugins(PhysicsPlugins::default().with_length_unit(LENGTH_UNIT))
+        .insert_resource(Gravity(Vec2::NEG_Y * GRAVITY));
+    app.finish();
+    app.cleanup();
+    app
+}
+
+/// Drops a dynamic box of half the density of the liquid on its free surface,
+/// and returns the draft at rest together with the height of the box.
+#[cfg(feature = "avian2d")]
+pub fn floating_box_draft(method: SolidCouplingMethod) -> (f32, f32) {
+    let mut app = new_app();
+
+    // Liquid filling the lower half of the domain, whose free surface is at y = 0.
+    let size = UVec2::splat(64);
+    app.world_mut()
+        .spawn((
+            FluidSettings {
+                rho: RHO,
+                gravity: Vec2::Y * GRAVITY,
+                size,
+                mode: FluidMode::FreeSurface,
+                ..default()
+            },
+            ProjectionMethod::ConjugateGradient(ConjugateGradientConfig::default()),
+            method,
+        ))
+        .with_child((
+            FluidSource {
+                active: true,
+                mode: FluidSourceMode::Source,
+            },
+            Transform::from_xyz(0.0, -0.25 * size.y as f32, 0.0),
+            FluidSourceShape::Aabb {
+                half_size: Vec2::new(0.5, 0.25) * size.as_vec2(),
+            },
+            FluidSourceOneshot,
+        ));
+
+    let half_size = Vec2::new(6.0, 4.0);
+    let body = app
+        .world_mut()
+        .spawn((
+            RigidBody::Dynamic,
+            Collider::rectangle(2.0 * half_size.x, 2.0 * half_size.y),
+            ColliderDensity(0.5 * RHO),
+            // Damps the oscillation around the equilibrium.
+            LinearDamping(2.0),
+            LockedAxes::ROTATION_LOCKED,
+            Transform::from_xyz(0.3, 2.0, 0.0),
+        ))
+        .id();
+
+    let mut heights = Vec::with_capacity(2 * NUM_FRAMES);
+    for _ in 0..2 * NUM_FRAMES {
+        app.update();
+        heights.push(app.world().get::<Position>(body).unwrap().y);
+    }
+
+    // The surface rises by the area displaced at half immersion over the width of the domain.
+    let surface = 2.0 * half_size.x * half_size.y / size.x as f32;
+    let height = heights[2 * NUM_FRAMES - 30..].iter().sum::<f32>() / 30.0;
+    (surface - (height - half_size.y), 2.0 * half_size.y)
+}
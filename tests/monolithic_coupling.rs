@@ -0,0 +1,22 @@
+//! Runs the fluid headless on a GPU with the monolithic coupling, where the velocity of a body is solved
+//! together with the pressure by conjugate_gradient.wgsl and handed back to the rigid body backend.
+//! A GPU adapter is required, so it is ignored by default:
+//! `cargo test --test monolithic_coupling -- --ignored`
+
+#![cfg(feature = "avian2d")]
+
+mod common;
+
+use bevy_eulerian_fluid::fluid_to_solid::SolidCouplingMethod;
+
+/// A dynamic box of half the density of the liquid floats with half of its height under the free surface.
+#[test]
+#[ignore = "requires a GPU adapter"]
+fn half_dense_box_floats_at_half_immersion() {
+    let (draft, height) = common::floating_box_draft(SolidCouplingMethod::Monolithic);
+    assert!(
+        (draft - 0.5 * height).abs() < 0.1 * height,
+        "draft: {draft}, expected half the height: {}",
+        0.5 * height
+    );
+}